@@ -0,0 +1,84 @@
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+/// How the outside temperature evolves over a run.
+///
+/// Times are seconds on the profile clock: seconds since midnight of day 1 when
+/// the simulation has a start datetime, otherwise seconds since the start.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum AmbientProfile {
+    /// Outside temperature stays at its initial value.
+    #[default]
+    Constant,
+    /// Day/night cycle: `mean + amplitude * cos(2*pi*(t - peak_time)/period)`.
+    Sinusoidal {
+        mean: f64,
+        amplitude: f64,
+        period: f64,
+        peak_time: f64,
+    },
+    /// Holds each temperature from its time until the next entry, e.g. moving the
+    /// bottle into a fridge. Entries are `(time, temp)` sorted by time.
+    Steps(Vec<(f64, f64)>),
+    /// Piecewise-linear table of `(time, temp)` sorted by time, held flat
+    /// before the first and after the last point.
+    Table(Vec<(f64, f64)>),
+}
+
+impl AmbientProfile {
+    /// 24 h cycle around `mean` peaking at 15:00.
+    pub fn day_night(mean: f64, amplitude: f64) -> Self {
+        AmbientProfile::Sinusoidal {
+            mean,
+            amplitude,
+            period: 86_400.0,
+            peak_time: 15.0 * 3600.0,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AmbientProfile::Constant => "Constant",
+            AmbientProfile::Sinusoidal { .. } => "Day/night",
+            AmbientProfile::Steps(_) => "Steps",
+            AmbientProfile::Table(_) => "Table",
+        }
+    }
+
+    /// Outside temperature at profile time `t`; `base` is returned for `Constant`
+    /// and for empty schedules.
+    pub fn temperature_at(&self, t: f64, base: f64) -> f64 {
+        match self {
+            AmbientProfile::Constant => base,
+            AmbientProfile::Sinusoidal {
+                mean,
+                amplitude,
+                period,
+                peak_time,
+            } => mean + amplitude * (TAU * (t - peak_time) / period.max(1.0)).cos(),
+            AmbientProfile::Steps(steps) => steps
+                .iter()
+                .take_while(|(time, _)| *time <= t)
+                .last()
+                .or(steps.first())
+                .map_or(base, |&(_, temp)| temp),
+            AmbientProfile::Table(points) => {
+                let Some(&(t0, first)) = points.first() else {
+                    return base;
+                };
+                if t <= t0 {
+                    return first;
+                }
+                for pair in points.windows(2) {
+                    let ((ta, va), (tb, vb)) = (pair[0], pair[1]);
+                    if t <= tb {
+                        let span = tb - ta;
+                        return if span <= 0.0 { vb } else { va + (vb - va) * (t - ta) / span };
+                    }
+                }
+                points.last().map_or(base, |&(_, temp)| temp)
+            }
+        }
+    }
+}
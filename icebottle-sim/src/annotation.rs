@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// A mark a scenario's author puts on the run's temperature chart, such as
+/// the expected plateau while the ice melts. What it is follows from the
+/// fields given: `time_s` alone is a vertical line, `temp_c` alone a
+/// horizontal one and both a label at that point; `from_s`..`to_s` shades a
+/// stretch of time, `low_c`..`high_c` a range of temperatures, and both a box.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Annotation {
+    pub label: String,
+    pub time_s: Option<f64>,
+    pub temp_c: Option<f64>,
+    pub from_s: Option<f64>,
+    pub to_s: Option<f64>,
+    pub low_c: Option<f64>,
+    pub high_c: Option<f64>,
+}
+
+impl Annotation {
+    /// The stretch of time shaded, when both ends are given.
+    pub fn times(&self) -> Option<(f64, f64)> {
+        self.from_s.zip(self.to_s)
+    }
+
+    /// The range of temperatures shaded, when both ends are given.
+    pub fn temps(&self) -> Option<(f64, f64)> {
+        self.low_c.zip(self.high_c)
+    }
+
+    /// What is wrong with it as a mark, if anything.
+    pub fn problem(&self) -> Option<String> {
+        let spans = [("from_s", "to_s", self.from_s, self.to_s), ("low_c", "high_c", self.low_c, self.high_c)];
+        for (low_key, high_key, low, high) in spans {
+            match (low, high) {
+                (Some(low), Some(high)) if low >= high => return Some(format!("{low_key} ({low}) must be below {high_key} ({high})")),
+                (Some(_), None) => return Some(format!("{low_key} needs {high_key}")),
+                (None, Some(_)) => return Some(format!("{high_key} needs {low_key}")),
+                _ => {}
+            }
+        }
+        let point = self.time_s.is_some() || self.temp_c.is_some();
+        let span = self.times().is_some() || self.temps().is_some();
+        match (point, span) {
+            (false, false) => Some("needs time_s, temp_c, from_s/to_s or low_c/high_c".to_string()),
+            (true, true) => Some("is either a line or label (time_s, temp_c) or a shaded span (from_s/to_s, low_c/high_c), not both".to_string()),
+            _ if self.time_s.is_some_and(|t| t < 0.0) || self.from_s.is_some_and(|t| t < 0.0) => Some("starts at a negative time".to_string()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::{Simulation, SAMPLE_COLUMNS};
+
+/// Times (s) a scenario has the run's state exported at, so an unattended run
+/// leaves intermediate results behind; passing one is logged as a
+/// `Checkpoint` event. `screenshot` asks for a picture of the window as well.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Checkpoints {
+    times: Vec<f64>,
+    pub screenshot: bool,
+}
+
+impl Checkpoints {
+    pub fn new(mut times: Vec<f64>, screenshot: bool) -> Self {
+        times.retain(|t| t.is_finite());
+        times.sort_by(f64::total_cmp);
+        times.dedup();
+        Self { times, screenshot }
+    }
+
+    /// In time order.
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    /// The checkpoints after `from` and up to `to` s.
+    pub fn between(&self, from: f64, to: f64) -> impl Iterator<Item = f64> + '_ {
+        self.times.iter().copied().filter(move |&t| t > from && t <= to)
+    }
+}
+
+/// Writes the header of a checkpoint summary CSV: the run's name and notes as
+/// comment lines, then the `SAMPLE_COLUMNS`, the hold time, the steam lost,
+/// the ice sublimed, the number of events and `sim`'s time-temperature
+/// integrals.
+pub fn write_checkpoint_header<W: Write>(out: &mut W, sim: &Simulation) -> io::Result<()> {
+    sim.experiment().write_header(out)?;
+    let mut columns: Vec<String> = SAMPLE_COLUMNS.iter().map(|c| c.to_string()).collect();
+    columns.extend(["hold_h", "vapor_lost_kg", "ice_sublimed_kg", "events"].map(String::from));
+    columns.extend(sim.doses().iter().map(|dose| dose.kind.column()));
+    writeln!(out, "{}", columns.join(","))
+}
+
+/// Writes one summary row of `sim` as it is now, under `write_checkpoint_header`.
+pub fn write_checkpoint_row<W: Write>(out: &mut W, sim: &Simulation) -> io::Result<()> {
+    let mut cells: Vec<String> = sim.sample().iter().map(f64::to_string).collect();
+    cells.push(sim.hold_time().hours().to_string());
+    cells.push(sim.vapor_lost().to_string());
+    cells.push(sim.ice_sublimed().to_string());
+    cells.push(sim.events().len().to_string());
+    cells.extend(sim.doses().iter().map(|dose| dose.total().to_string()));
+    writeln!(out, "{}", cells.join(","))
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::CP_WATER;
+
+/// An immersion chiller: a coil in the contents with tap water running through
+/// it, as brewers use to cool wort. The coil is a counter-flow exchanger whose
+/// effectiveness comes from its conductance and the coolant flow, and the
+/// coolant is shut off once the contents reach the target.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Chiller {
+    pub flow: f64,       // kg/s of coolant; 0 means no chiller
+    pub inlet_temp: f64, // °C of the coolant going in
+    pub ua: f64,         // W/K, coil wall and both films
+    pub target: f64,     // °C at which the coolant is shut off
+    coolant_used: f64,   // kg since the reset
+    chill_time: Option<f64>, // s when the contents first reached the target
+}
+
+impl Default for Chiller {
+    fn default() -> Self {
+        Self {
+            flow: 0.0,
+            inlet_temp: 15.0,
+            ua: 300.0,
+            target: 20.0,
+            coolant_used: 0.0,
+            chill_time: None,
+        }
+    }
+}
+
+impl Chiller {
+    /// Coolant runs while there is a flow and the target hasn't been reached.
+    pub fn is_running(&self) -> bool {
+        self.flow > 0.0 && self.chill_time.is_none()
+    }
+
+    /// Heat (W) the coil puts into contents at `temp`, negative when chilling.
+    pub fn power(&self, temp: f64) -> f64 {
+        if !self.is_running() {
+            return 0.0;
+        }
+        let capacity = self.flow * CP_WATER; // W/K
+        let effectiveness = 1.0 - (-self.ua / capacity).exp();
+        effectiveness * capacity * (self.inlet_temp - temp)
+    }
+
+    /// Counts the coolant that ran over `dt` and notes the time the contents,
+    /// now at `temp`, reach the target. Returns true on that step.
+    pub fn advance(&mut self, temp: f64, time: f64, dt: f64) -> bool {
+        if !self.is_running() {
+            return false;
+        }
+        self.coolant_used += self.flow * dt;
+        if temp <= self.target {
+            self.chill_time = Some(time);
+            return true;
+        }
+        false
+    }
+
+    pub fn reset(&mut self) {
+        self.coolant_used = 0.0;
+        self.chill_time = None;
+    }
+
+    /// Coolant (kg, about a litre each) run through the coil since the reset.
+    pub fn coolant_used(&self) -> f64 {
+        self.coolant_used
+    }
+
+    /// Simulated time (s) at which the contents reached the target, if they have.
+    pub fn chill_time(&self) -> Option<f64> {
+        self.chill_time
+    }
+}
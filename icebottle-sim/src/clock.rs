@@ -0,0 +1,57 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Simulated date and time with no time zone or calendar: a day counter
+/// (starting at 1) and the time of day. Lets schedules be written in clock
+/// time ("starts 09:00, sunny until 17:00") without touching the host clock.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SimDateTime {
+    pub day: u32,
+    pub seconds_of_day: f64,
+}
+
+impl SimDateTime {
+    pub fn from_hms(day: u32, hours: u32, minutes: u32, seconds: u32) -> Self {
+        Self {
+            day,
+            seconds_of_day: 0.0,
+        }
+        .add_seconds((hours * 3600 + minutes * 60 + seconds) as f64)
+    }
+
+    /// Parses `HH:MM` or `HH:MM:SS` on day 1.
+    pub fn parse_time_of_day(text: &str) -> Option<Self> {
+        let mut parts = text.trim().split(':').map(|p| p.parse::<u32>().ok());
+        let hours = parts.next()??;
+        let minutes = parts.next()??;
+        let seconds = parts.next().unwrap_or(Some(0))?;
+        if parts.next().is_some() || hours >= 24 || minutes >= 60 || seconds >= 60 {
+            return None;
+        }
+        Some(Self::from_hms(1, hours, minutes, seconds))
+    }
+
+    pub fn add_seconds(self, seconds: f64) -> Self {
+        let total = self.seconds_of_day + seconds;
+        let days = (total / SECONDS_PER_DAY).floor();
+        Self {
+            day: (self.day as i64 + days as i64).max(1) as u32,
+            seconds_of_day: total - days * SECONDS_PER_DAY,
+        }
+    }
+
+    /// Fractional hour of the day, 0 <= h < 24.
+    pub fn hour_of_day(&self) -> f64 {
+        self.seconds_of_day / 3600.0
+    }
+}
+
+impl fmt::Display for SimDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = self.seconds_of_day as u64;
+        write!(f, "Day {} {:02}:{:02}:{:02}", self.day, s / 3600, (s / 60) % 60, s % 60)
+    }
+}
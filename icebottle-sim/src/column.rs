@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+    DENSITY_WATER, H_INSIDE, MIXING_TIME, UA_AIR_SURFACE,
+};
+use crate::reduce;
+use crate::state::SystemState;
+
+/// Optional vertical discretisation of the water: `layers[0]` is the bottom.
+///
+/// The lumped model still owns the energy: each step the layers are nudged by
+/// conduction, the ice or headspace at the top and buoyant mixing, then shifted
+/// together so their mean matches the lumped water temperature again.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct WaterColumn {
+    layers: Vec<f64>,
+}
+
+impl WaterColumn {
+    /// `count` layers at `temp`; fewer than two means the water stays lumped.
+    pub fn new(count: usize, temp: f64) -> Self {
+        Self {
+            layers: if count >= 2 { vec![temp; count] } else { Vec::new() },
+        }
+    }
+
+    /// Layer temperatures from the bottom up (°C); empty when lumped.
+    pub fn layers(&self) -> &[f64] {
+        &self.layers
+    }
+
+    pub fn reset(&mut self, temp: f64) {
+        self.layers.iter_mut().for_each(|t| *t = temp);
+    }
+
+    /// Steps the layers over `dt`; without `convection` the top layer only
+    /// conducts to the ice and the layers don't mix, however they stack. An
+    /// `agitation` from a shake stirs them all towards their mean.
+    pub(crate) fn step(&mut self, state: &SystemState, cross_section: f64, convection: bool, agitation: f64, dt: f64) {
+        let n = self.layers.len();
+        if n < 2 {
+            return;
+        }
+        if state.mass_water <= 0.0 {
+            self.reset(state.temp_water);
+            return;
+        }
+        let layer_cap = state.liquid_heat_capacity() / n as f64;
+        let liquid = state.liquid.properties();
+        let height = state.mass_water / liquid.density / cross_section;
+        let ua_layers = liquid.conductivity * cross_section / (height / n as f64);
+
+        // conduction between neighbours
+        let mut flow = vec![0.0; n];
+        for i in 0..n - 1 {
+            let q = ua_layers * (self.layers[i + 1] - self.layers[i]);
+            flow[i] += q;
+            flow[i + 1] -= q;
+        }
+        // the top layer touches the floating ice, or the headspace air
+        let top = n - 1;
+        flow[top] += if state.mass_ice > 0.0 {
+            let ua = if convection { H_INSIDE * cross_section } else { 2.0 * ua_layers };
+            ua * (state.temp_ice - self.layers[top])
+        } else {
+            UA_AIR_SURFACE * (state.temp_air - self.layers[top])
+        };
+        for (t, q) in self.layers.iter_mut().zip(&flow) {
+            *t += q * dt / layer_cap;
+        }
+
+        // buoyancy: a layer denser than the one below sinks, trading places with it
+        // over MIXING_TIME; below 4 °C colder water is lighter and stays on top
+        let mix = if convection { (dt / MIXING_TIME).min(1.0) } else { 0.0 };
+        for i in 0..n - 1 {
+            if water_density(self.layers[i + 1]) > water_density(self.layers[i]) {
+                let exchange = mix * (self.layers[i + 1] - self.layers[i]);
+                self.layers[i] += exchange;
+                self.layers[i + 1] -= exchange;
+            }
+        }
+        if agitation > 0.0 {
+            let mean = reduce::ordered_sum(self.layers.iter().copied()) / n as f64;
+            let stir = (agitation * dt / MIXING_TIME).min(1.0);
+            for t in &mut self.layers {
+                *t += stir * (mean - *t);
+            }
+        }
+
+        // keep the mean on the lumped temperature, which carries the energy balance
+        let mean = reduce::ordered_sum(self.layers.iter().copied()) / n as f64;
+        let shift = state.temp_water - mean;
+        let floor = state.freezing_point().min(state.temp_water);
+        for t in &mut self.layers {
+            *t = (*t + shift).max(floor);
+        }
+    }
+}
+
+/// Fresh water density (kg/m^3), peaking at 4 °C; enough to get the
+/// buoyancy ordering right between 0 and 30 °C.
+fn water_density(temp: f64) -> f64 {
+    DENSITY_WATER * (1.0 - 6.8e-6 * (temp - 4.0).powi(2))
+}
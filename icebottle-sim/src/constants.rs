@@ -0,0 +1,78 @@
+// Physical constants
+pub const CP_WATER: f64 = 4186.0; // J/(kg*K)
+pub const CP_ICE: f64 = 2100.0;   // J/(kg*K)
+pub const CV_AIR: f64 = 718.0;    // J/(kg*K), constant volume: the headspace is sealed
+pub const LATENT_FUSION: f64 = 334_000.0; // J/kg
+pub const LATENT_VAPORIZATION: f64 = 2_256_000.0; // J/kg at 100 °C
+pub const LATENT_VAPORIZATION_0C: f64 = 2_501_000.0; // J/kg at 0 °C
+pub const BOILING_POINT: f64 = 100.0; // °C at 1 atm
+
+// Heat transfer
+pub const H_INSIDE: f64 = 200.0; // W/(m^2*K), natural convection of water on the inner wall
+pub const H_OUTSIDE: f64 = 5.0;  // W/(m^2*K), natural convection in still air (radiation is separate)
+pub const H_WIND_PER_MS: f64 = 3.8; // W/(m^2*K) per m/s of wind (McAdams-style linear fit)
+pub const H_WATER_BATH: f64 = 500.0; // W/(m^2*K), natural convection of a water bath on the outer wall
+pub const AIR_WALL_FRACTION: f64 = 0.15; // share of the wall conductance touching the headspace
+pub const UA_AIR_SURFACE: f64 = 0.05; // W/K, headspace air <-> water surface
+pub const H_ICE_WATER: f64 = 300.0; // W/(m^2*K), water melting separate ice cubes
+pub const H_SHAKEN: f64 = 1500.0; // W/(m^2*K), water sloshed over the wall and the ice by a shake
+pub const SHAKE_SETTLE: f64 = 20.0; // s for the sloshing of a shake to die down to 1/e
+
+// Radiation
+pub const KELVIN: f64 = 273.15;
+pub const STEFAN_BOLTZMANN: f64 = 5.670_374e-8; // W/(m^2*K^4)
+pub const RADIATION_REF_TEMP: f64 = 283.15; // K, linearisation point for radiative exchange
+
+// Dissolved salt (NaCl)
+pub const CP_SALT: f64 = 864.0; // J/(kg·K), solid NaCl
+pub const MOLAR_MASS_SALT: f64 = 0.058_44; // kg/mol
+pub const VANT_HOFF_SALT: f64 = 2.0; // Na+ and Cl- ions
+pub const CRYOSCOPIC_WATER: f64 = 1.86; // K·kg/mol
+pub const EBULLIOSCOPIC_WATER: f64 = 0.512; // K·kg/mol
+pub const EUTECTIC_TEMP: f64 = -21.1; // °C, lowest brine freezing point
+
+// Supercooling
+pub const NUCLEATION_RATE: f64 = 1e-5; // 1/(s·K³), heterogeneous nucleation in a smooth bottle
+pub const HOMOGENEOUS_UNDERCOOLING: f64 = 40.0; // K, pure water always freezes by about -40 °C
+pub const DEGASSED_NUCLEATION: f64 = 0.1; // share of NUCLEATION_RATE left once the dissolved air is boiled out
+pub const BOILED_DISSOLVED_AIR: f64 = 0.2; // of saturation, boiled water cooled with a lid on
+
+// Headspace gas
+pub const ATMOSPHERIC_PRESSURE: f64 = 101_325.0; // Pa
+pub const R_AIR: f64 = 287.05; // J/(kg·K), specific gas constant of dry air
+pub const R_VAPOR: f64 = 461.5; // J/(kg·K), specific gas constant of steam
+pub const DENSITY_WATER: f64 = 1000.0; // kg/m^3
+pub const DENSITY_ICE: f64 = 917.0; // kg/m^3, ice takes ~9% more room than the water it froze from
+// K/Pa the melting point moves with pressure (Clausius–Clapeyron, T·Δv/L): about −0.0074 K per bar
+pub const MELTING_SLOPE: f64 = KELVIN * (1.0 / DENSITY_WATER - 1.0 / DENSITY_ICE) / LATENT_FUSION;
+
+// Open neck (cap off)
+pub const NECK_AREA: f64 = 4.9e-4; // m^2, 2.5 cm opening
+pub const UA_NECK: f64 = 0.03; // W/K, headspace air exchanged with the outside through the neck
+pub const MASS_TRANSFER_NECK: f64 = 0.005; // m/s, vapour carried off the neck in still air
+pub const AMBIENT_HUMIDITY: f64 = 0.5; // default relative humidity of the outside air
+pub const CAP_SEAL_LEAK: f64 = 0.002; // share of the open neck's vapour transfer that gets past a closed cap
+pub const LATENT_SUBLIMATION: f64 = LATENT_FUSION + LATENT_VAPORIZATION_0C; // J/kg, ice straight to vapour
+
+// Condensation on the outside of the wall
+pub const RHO_CP_AIR: f64 = 1206.0; // J/(m^3·K), for the heat/mass transfer analogy
+pub const CONDENSATE_FILM: f64 = 0.08; // kg/m^2 the wall holds before drops run off
+
+// Peltier cooler (negative heater power)
+pub const PELTIER_CARNOT_FRACTION: f64 = 0.08; // of the Carnot COP for the lift from the contents to the outside
+pub const PELTIER_MAX_COP: f64 = 2.0; // with little or no lift
+
+// Water column (stratified mode)
+pub const CONDUCTIVITY_WATER: f64 = 0.6; // W/(m·K)
+pub const CONDUCTIVITY_AIR: f64 = 0.026; // W/(m·K), still air, for the conduction-only mode
+pub const MIXING_TIME: f64 = 2.0; // s for a buoyantly unstable pair of layers to mix
+
+// 2D field mode: a vertical slice through the bottle
+pub const FIELD_NX: usize = 16; // columns, including the wall on each side
+pub const FIELD_NZ: usize = 46; // rows, including the bottom and the cap
+pub const FIELD_CELL: f64 = 0.0045; // m, square cells
+pub const CONDUCTIVITY_ICE: f64 = 2.2; // W/(m·K)
+pub const FIELD_WATER_CONDUCTIVITY: f64 = 6.0; // W/(m·K), effective: conduction plus natural convection
+pub const FIELD_AIR_CONDUCTIVITY: f64 = 0.05; // W/(m·K), effective
+pub const FIELD_AIR_RHO_C: f64 = 1206.0; // J/(m^3·K)
+pub const FIELD_WALL_RHO_C: f64 = 1.5e6; // J/(m^3·K), typical solid
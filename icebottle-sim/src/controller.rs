@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+/// How the heater is driven.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ControlMode {
+    /// The heater runs at its set power.
+    Off,
+    /// Thermostat: full power below the setpoint, nothing above it, with a
+    /// hysteresis band so it doesn't chatter.
+    BangBang,
+    Pid,
+}
+
+impl ControlMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ControlMode::Off => "off",
+            ControlMode::BangBang => "bang-bang",
+            ControlMode::Pid => "PID",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ControlMode::Off => ControlMode::BangBang,
+            ControlMode::BangBang => ControlMode::Pid,
+            ControlMode::Pid => ControlMode::Off,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        self.next().next()
+    }
+}
+
+/// Closed-loop control of the water temperature with the heater as actuator.
+///
+/// The heater's set power is the rating: a positive one heats from 0 W up to
+/// it, a negative one (a cooler) cools from 0 W down to it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Controller {
+    pub mode: ControlMode,
+    pub setpoint: f64,   // °C
+    pub hysteresis: f64, // K, half-width of the bang-bang band
+    pub kp: f64,         // W/K
+    pub ki: f64,         // W/(K·s)
+    pub kd: f64,         // W·s/K
+    integral: f64,       // K·s
+    prev_error: Option<f64>,
+    on: bool,
+}
+
+impl Default for Controller {
+    fn default() -> Self {
+        Self {
+            mode: ControlMode::Off,
+            setpoint: 4.0,
+            hysteresis: 0.5,
+            kp: 20.0,
+            ki: 0.05,
+            kd: 0.0,
+            integral: 0.0,
+            prev_error: None,
+            on: false,
+        }
+    }
+}
+
+impl Controller {
+    /// Forgets the integral, the last error and the thermostat state.
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.prev_error = None;
+        self.on = false;
+    }
+
+    /// Heater power (W) for the next `dt` seconds at water temperature `temp`,
+    /// for a heater rated at `rating` W.
+    pub fn output(&mut self, temp: f64, rating: f64, dt: f64) -> f64 {
+        if self.mode == ControlMode::Off || rating == 0.0 {
+            return rating;
+        }
+        // a cooler works the other way round: it comes on when too warm
+        let sign = rating.signum();
+        let error = (self.setpoint - temp) * sign;
+        let limit = rating.abs();
+        let power = match self.mode {
+            ControlMode::Off => limit,
+            ControlMode::BangBang => {
+                if error > self.hysteresis {
+                    self.on = true;
+                } else if error < -self.hysteresis {
+                    self.on = false;
+                }
+                if self.on { limit } else { 0.0 }
+            }
+            ControlMode::Pid => {
+                let derivative = match self.prev_error {
+                    Some(prev) if dt > 0.0 => (error - prev) / dt,
+                    _ => 0.0,
+                };
+                self.prev_error = Some(error);
+                let unclamped = self.kp * error + self.ki * (self.integral + error * dt) + self.kd * derivative;
+                // anti-windup: stop integrating while the output is pinned at a limit,
+                // unless the error is pulling it back
+                if (0.0..=limit).contains(&unclamped) || (unclamped > limit) == (error < 0.0) {
+                    self.integral += error * dt;
+                }
+                unclamped.clamp(0.0, limit)
+            }
+        };
+        if power > 0.0 { power * sign } else { 0.0 }
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{CP_ICE, CP_WATER, DENSITY_WATER, LATENT_FUSION, RHO_CP_AIR};
+use crate::simulation::Simulation;
+
+/// Several bottles packed into one cool box. They share its air, which each
+/// one warms or cools through its wall and which leaks heat to the outside
+/// through the box's walls, and bottles standing against each other also
+/// conduct heat through the contact. A box packed full of cold bottles keeps
+/// its air cold, so each bottle in it lasts longer than it would on its own.
+///
+/// Loose ice can be tipped in around the bottles. Its melt water either
+/// drains away as it forms or stays in the box as ice water the bottles stand
+/// in, which cools them far better than air does and keeps the box cold after
+/// the last of the ice is gone.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Cooler {
+    pub bottles: Vec<Simulation>,
+    pub volume: f64,       // m^3 of air in the box
+    pub thermal_mass: f64, // J/K of liner that follows the air
+    pub ua: f64,           // W/K through the box's walls and lid
+    pub outside_temp: f64, // °C around the box
+    pub contact_ua: f64,   // W/K between two bottles that touch
+    /// Pairs of bottles (indices into `bottles`) standing against each other.
+    pub touching: Vec<(usize, usize)>,
+    pub ice: f64,     // kg of loose ice put in at the start, at 0 °C
+    pub drain: bool,  // melt water runs out of the box as it forms
+    pub ice_ua: f64,  // W/K between the loose ice, or the water it left, and the air
+    pub bath_ua: f64, // W/K from the melt water to a bottle standing fully in it
+    air_temp: f64,    // °C
+    // loose ice and melt water still in the box: mass (kg) and enthalpy (J,
+    // relative to water at 0 °C)
+    slush: f64,
+    slush_enthalpy: f64,
+    drained: f64, // kg of melt water let out
+}
+
+impl Cooler {
+    /// An empty box of `volume` m³ with walls of `ua` W/K, its air at `outside_temp`.
+    pub fn new(volume: f64, ua: f64, outside_temp: f64) -> Self {
+        Self {
+            bottles: Vec::new(),
+            volume,
+            thermal_mass: 0.0,
+            ua,
+            outside_temp,
+            contact_ua: 0.5,
+            touching: Vec::new(),
+            ice: 0.0,
+            drain: false,
+            ice_ua: 5.0,
+            bath_ua: 5.0,
+            air_temp: outside_temp,
+            slush: 0.0,
+            slush_enthalpy: 0.0,
+            drained: 0.0,
+        }
+    }
+
+    /// Stands the bottles in a row, each against the next.
+    pub fn pack_in_a_row(&mut self) {
+        self.touching = (1..self.bottles.len()).map(|i| (i - 1, i)).collect();
+    }
+
+    /// Air temperature in the box (°C).
+    pub fn air_temp(&self) -> f64 {
+        self.air_temp
+    }
+
+    /// Loose ice left in the box (kg).
+    pub fn loose_ice(&self) -> f64 {
+        if self.slush_enthalpy >= 0.0 {
+            0.0
+        } else {
+            (-self.slush_enthalpy / LATENT_FUSION).min(self.slush)
+        }
+    }
+
+    /// Melt water standing in the box (kg).
+    pub fn melt_water(&self) -> f64 {
+        self.slush - self.loose_ice()
+    }
+
+    /// Temperature (°C) of the loose ice and melt water: 0 °C while both are
+    /// there, colder once it has all frozen, warmer once it has all melted.
+    pub fn melt_water_temp(&self) -> f64 {
+        let (mass, h) = (self.slush, self.slush_enthalpy);
+        if mass <= 0.0 {
+            self.air_temp
+        } else if h >= 0.0 {
+            h / (mass * CP_WATER)
+        } else if h < -mass * LATENT_FUSION {
+            (h + mass * LATENT_FUSION) / (mass * CP_ICE)
+        } else {
+            0.0
+        }
+    }
+
+    /// Melt water let out of the box since the start (kg).
+    pub fn drained(&self) -> f64 {
+        self.drained
+    }
+
+    /// Heat capacity (J/K) of the air and the liner.
+    pub fn heat_capacity(&self) -> f64 {
+        self.volume * RHO_CP_AIR + self.thermal_mass
+    }
+
+    /// Closes the lid on the bottles as they are: the air starts at the
+    /// outside temperature, the loose ice goes in at 0 °C and every bottle
+    /// that isn't running is started.
+    pub fn start(&mut self) {
+        self.air_temp = self.outside_temp;
+        self.slush = self.ice;
+        self.slush_enthalpy = -self.ice * LATENT_FUSION;
+        self.drained = 0.0;
+        for sim in self.bottles.iter_mut().filter(|s| !s.is_running()) {
+            sim.toggle_running();
+        }
+    }
+
+    /// Advances the box and every bottle in it by `dt` seconds.
+    pub fn step(&mut self, dt: f64) {
+        let mut contact = vec![0.0; self.bottles.len()];
+        for &(a, b) in &self.touching {
+            let (ta, tb) = (self.bottles[a].state().system_temperature_equivalent(), self.bottles[b].state().system_temperature_equivalent());
+            let q = self.contact_ua * (tb - ta);
+            contact[a] += q;
+            contact[b] -= q;
+        }
+        // the melt water comes up the bottles as it fills the box
+        let slush_temp = self.melt_water_temp();
+        let wet = (self.melt_water() / DENSITY_WATER / self.volume).min(1.0);
+        let mut to_bath = 0.0;
+        for (sim, q) in self.bottles.iter().zip(&mut contact).filter(|(s, _)| s.is_running()) {
+            let bath = self.bath_ua * wet * (slush_temp - sim.state().system_temperature_equivalent());
+            *q += bath;
+            to_bath += bath;
+        }
+        // what the bottles took from the air: through the wall, plus vented steam
+        let mut from_air = 0.0;
+        for (sim, &q) in self.bottles.iter_mut().zip(&contact) {
+            if !sim.is_running() {
+                continue;
+            }
+            sim.step_in(dt, self.air_temp, q);
+            let last = sim.energy().last;
+            from_air += (last.wall + last.neck) * dt;
+        }
+        let to_slush = if self.slush > 0.0 { self.ice_ua * (self.air_temp - slush_temp) } else { 0.0 };
+        self.slush_enthalpy += (to_slush - to_bath) * dt;
+        if self.drain {
+            // water at 0 °C carries no enthalpy away; the last of it may have
+            // warmed a little in its final step, and that goes with it
+            let water = self.melt_water();
+            self.slush -= water;
+            self.drained += water;
+            self.slush_enthalpy = self.slush_enthalpy.min(0.0);
+        }
+        let gained = self.ua * (self.outside_temp - self.air_temp) * dt - from_air - to_slush * dt;
+        self.air_temp += gained / self.heat_capacity();
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{CP_ICE, DENSITY_ICE};
+use crate::reduce::ordered_sum;
+use crate::state::SystemState;
+
+// pieces below this are considered melted
+const MIN_PIECE_MASS: f64 = 1e-7;
+
+/// Ice as separate cubes instead of one lumped mass. Each cube exchanges heat
+/// with the water through its own surface, so the same mass of crushed ice
+/// melts faster than one block.
+///
+/// `SystemState::mass_ice` stays the total; ice frozen onto or melted from the
+/// lumped total elsewhere is spread over the cubes by surface area.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct IceCubes {
+    masses: Vec<f64>,
+}
+
+impl IceCubes {
+    /// `count` equal cubes sharing `total` kg; zero cubes keeps the ice lumped.
+    pub fn split(total: f64, count: usize) -> Self {
+        let masses = if count == 0 || total <= 0.0 { Vec::new() } else { vec![total / count as f64; count] };
+        Self { masses }
+    }
+
+    /// Mass of each cube (kg).
+    pub fn masses(&self) -> &[f64] {
+        &self.masses
+    }
+
+    /// Edge length of a cube of `mass` kg (m).
+    pub fn edge(mass: f64) -> f64 {
+        (mass.max(0.0) / DENSITY_ICE).cbrt()
+    }
+
+    /// A new cube of `mass` kg, dropped in beside the others.
+    pub(crate) fn add(&mut self, mass: f64) {
+        self.masses.push(mass);
+    }
+
+    fn area(mass: f64) -> f64 {
+        let edge = Self::edge(mass);
+        6.0 * edge * edge
+    }
+
+    fn total(&self) -> f64 {
+        ordered_sum(self.masses.iter().copied())
+    }
+
+    /// Spreads a change in the lumped ice mass over the cubes by area.
+    fn sync(&mut self, mass_ice: f64) {
+        let delta = mass_ice - self.total();
+        if delta > 0.0 && self.masses.is_empty() {
+            // fresh ice with nothing to grow on forms one new piece
+            self.masses.push(delta);
+        } else {
+            self.distribute(delta);
+        }
+        self.masses.retain(|&m| m > MIN_PIECE_MASS);
+    }
+
+    fn distribute(&mut self, delta: f64) {
+        let total_area = ordered_sum(self.masses.iter().map(|&m| Self::area(m)));
+        if total_area <= 0.0 {
+            return;
+        }
+        for m in &mut self.masses {
+            *m = (*m + delta * Self::area(*m) / total_area).max(0.0);
+        }
+    }
+
+    /// Heat exchange between the water and the cubes over `dt`: the water
+    /// warms the ice to its freezing point, then melts it, each in proportion
+    /// to the cubes' areas. Melted ice joins the water at the freezing point.
+    /// `film` gives the film coefficient (W/(m^2*K)) on a cube of an edge.
+    pub(crate) fn exchange(&mut self, state: &mut SystemState, dt: f64, film: impl Fn(f64) -> f64) {
+        self.sync(state.mass_ice);
+        if self.masses.is_empty() || state.mass_water <= 0.0 {
+            return;
+        }
+        let t_freeze = state.freezing_point();
+        let ha = ordered_sum(self.masses.iter().map(|&m| film(Self::edge(m)) * Self::area(m)));
+        let mut q = ha * (state.temp_water - state.temp_ice) * dt;
+        if q <= 0.0 {
+            return;
+        }
+
+        // 1) warm the ice to the freezing point; below that the water freezes onto it
+        let need = state.mass_ice * CP_ICE * (t_freeze - state.temp_ice);
+        if need > 0.0 {
+            let warm = q.min(need);
+            state.temp_ice += warm / (state.mass_ice * CP_ICE);
+            let from_water = warm.min(state.liquid_heat_capacity() * (state.temp_water - t_freeze).max(0.0));
+            state.temp_water -= from_water / state.liquid_heat_capacity();
+            let freeze_mass = ((warm - from_water) / state.latent_fusion()).min(state.mass_water);
+            state.mass_water -= freeze_mass;
+            state.mass_ice += freeze_mass;
+            self.distribute(freeze_mass);
+            q -= warm;
+        }
+
+        // 2) melt, as far as the water's heat above the freezing point allows
+        let available = state.liquid_heat_capacity() * (state.temp_water - t_freeze).max(0.0);
+        let latent = state.latent_fusion();
+        let melt_mass = (q.min(available) / latent).min(state.mass_ice);
+        if melt_mass > 0.0 {
+            let cap = state.liquid_heat_capacity();
+            let cp = state.liquid.properties().cp;
+            let temp_after = state.temp_water - melt_mass * latent / cap;
+            state.temp_water = (cap * temp_after + melt_mass * cp * t_freeze) / (cap + melt_mass * cp);
+            state.mass_water += melt_mass;
+            state.mass_ice -= melt_mass;
+            self.distribute(-melt_mass);
+        }
+        self.masses.retain(|&m| m > MIN_PIECE_MASS);
+        if self.masses.is_empty() {
+            // last sliver melted
+            state.mass_water += state.mass_ice;
+            state.mass_ice = 0.0;
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use std::io::{self, Write};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::constants;
+use crate::controller::ControlMode;
+use crate::geometry::Geometry;
+use crate::liquid::Liquid;
+use crate::simulation::Simulation;
+use crate::wall::InsulationPreset;
+
+/// What the model of a simulation consists of as it is set up: the nodes that
+/// carry state, the parameters with their current values, the equations that
+/// are switched on and the constants they use. Built from the simulation
+/// itself, so it goes with an export and still says how the numbers were made
+/// after the model has changed.
+#[derive(Clone, Debug, Serialize)]
+pub struct ModelDescription {
+    pub model: &'static str,
+    pub version: &'static str,
+    pub nodes: Vec<Node>,
+    pub parameters: Vec<Parameter>,
+    pub equations: Vec<Equation>,
+    pub constants: Vec<Constant>,
+}
+
+/// A part of the model with state of its own.
+#[derive(Clone, Debug, Serialize)]
+pub struct Node {
+    pub name: &'static str,
+    pub state: &'static str,
+    pub description: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Parameter {
+    pub name: &'static str,
+    pub value: Value,
+    pub unit: &'static str,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Equation {
+    pub name: &'static str,
+    pub formula: &'static str,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Constant {
+    pub name: &'static str,
+    pub value: f64,
+    pub unit: &'static str,
+}
+
+// The constants by their names in `constants`, so the list can't drift from the values
+macro_rules! constants {
+    ($($name:ident: $unit:literal),* $(,)?) => {
+        vec![$(Constant { name: stringify!($name), value: constants::$name as f64, unit: $unit }),*]
+    };
+}
+
+impl ModelDescription {
+    pub fn of(sim: &Simulation) -> Self {
+        Self {
+            model: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            nodes: nodes(sim),
+            parameters: parameters(sim),
+            equations: equations(sim),
+            constants: constants![
+                CP_WATER: "J/(kg·K)",
+                CP_ICE: "J/(kg·K)",
+                CV_AIR: "J/(kg·K)",
+                CP_SALT: "J/(kg·K)",
+                LATENT_FUSION: "J/kg",
+                LATENT_VAPORIZATION: "J/kg",
+                LATENT_VAPORIZATION_0C: "J/kg",
+                BOILING_POINT: "°C",
+                H_INSIDE: "W/(m²·K)",
+                H_OUTSIDE: "W/(m²·K)",
+                H_WIND_PER_MS: "W/(m²·K) per m/s",
+                H_WATER_BATH: "W/(m²·K)",
+                AIR_WALL_FRACTION: "",
+                UA_AIR_SURFACE: "W/K",
+                H_ICE_WATER: "W/(m²·K)",
+                STEFAN_BOLTZMANN: "W/(m²·K⁴)",
+                RADIATION_REF_TEMP: "K",
+                MOLAR_MASS_SALT: "kg/mol",
+                VANT_HOFF_SALT: "",
+                CRYOSCOPIC_WATER: "K·kg/mol",
+                EBULLIOSCOPIC_WATER: "K·kg/mol",
+                EUTECTIC_TEMP: "°C",
+                NUCLEATION_RATE: "1/(s·K³)",
+                HOMOGENEOUS_UNDERCOOLING: "K",
+                DEGASSED_NUCLEATION: "",
+                BOILED_DISSOLVED_AIR: "",
+                ATMOSPHERIC_PRESSURE: "Pa",
+                R_AIR: "J/(kg·K)",
+                R_VAPOR: "J/(kg·K)",
+                DENSITY_WATER: "kg/m³",
+                DENSITY_ICE: "kg/m³",
+                MELTING_SLOPE: "K/Pa",
+                NECK_AREA: "m²",
+                UA_NECK: "W/K",
+                MASS_TRANSFER_NECK: "m/s",
+                CAP_SEAL_LEAK: "",
+                LATENT_SUBLIMATION: "J/kg",
+                RHO_CP_AIR: "J/(m³·K)",
+                CONDENSATE_FILM: "kg/m²",
+                PELTIER_CARNOT_FRACTION: "",
+                PELTIER_MAX_COP: "",
+                CONDUCTIVITY_WATER: "W/(m·K)",
+                CONDUCTIVITY_AIR: "W/(m·K)",
+                MIXING_TIME: "s",
+                CONDUCTIVITY_ICE: "W/(m·K)",
+                FIELD_CELL: "m",
+                FIELD_WATER_CONDUCTIVITY: "W/(m·K)",
+                FIELD_AIR_CONDUCTIVITY: "W/(m·K)",
+            ],
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("plain data always serializes")
+    }
+
+    /// Writes the description as a Markdown document.
+    pub fn write_markdown<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "# Model: {} {}", self.model, self.version)?;
+        writeln!(out, "\n## Nodes\n")?;
+        writeln!(out, "| Node | State | Description |\n| --- | --- | --- |")?;
+        for node in &self.nodes {
+            writeln!(out, "| {} | {} | {} |", node.name, node.state, node.description)?;
+        }
+        writeln!(out, "\n## Parameters\n")?;
+        writeln!(out, "| Parameter | Value | Unit |\n| --- | --- | --- |")?;
+        for p in &self.parameters {
+            let value = match &p.value {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            writeln!(out, "| {} | {value} | {} |", p.name, p.unit)?;
+        }
+        writeln!(out, "\n## Equations\n")?;
+        for eq in &self.equations {
+            writeln!(out, "- **{}:** `{}`", eq.name, eq.formula)?;
+        }
+        writeln!(out, "\n## Constants\n")?;
+        writeln!(out, "| Constant | Value | Unit |\n| --- | --- | --- |")?;
+        for c in &self.constants {
+            writeln!(out, "| `{}` | {} | {} |", c.name, c.value, c.unit)?;
+        }
+        Ok(())
+    }
+}
+
+fn nodes(sim: &Simulation) -> Vec<Node> {
+    let mut nodes = vec![Node {
+        name: "contents",
+        state: "m_w, m_i, m_s, T_w, T_i",
+        description: "water, ice and dissolved salt, lumped; the ice melts or freezes at the freezing point".to_string(),
+    }];
+    if sim.init().air > 0.0 {
+        nodes.push(Node {
+            name: "headspace",
+            state: "m_a, m_v, T_a",
+            description: "air and steam above the contents, an ideal gas".to_string(),
+        });
+    }
+    if sim.ice_pieces() > 0 {
+        nodes.push(Node {
+            name: "ice cubes",
+            state: "m_i per cube",
+            description: format!("the ice as {} separate cubes, each melting through its own surface", sim.ice_pieces()),
+        });
+    }
+    if sim.water_layers().len() > 1 {
+        nodes.push(Node {
+            name: "water column",
+            state: "T per layer",
+            description: format!("{} stacked water layers, shifted together to the lumped water temperature", sim.water_layers().len()),
+        });
+    }
+    if sim.field().is_some() {
+        nodes.push(Node {
+            name: "2D field",
+            state: "h per cell",
+            description: format!("{} × {} cells of {} mm over a vertical slice, separate from the lumped model", constants::FIELD_NX, constants::FIELD_NZ, constants::FIELD_CELL * 1000.0),
+        });
+    }
+    if let Some(room) = sim.room() {
+        nodes.push(Node {
+            name: "room",
+            state: "T_room",
+            description: format!("{} m³ of air and {:.0} kJ/K of thermal mass around the bottle, warmed or cooled by it", room.volume, room.thermal_mass / 1000.0),
+        });
+    }
+    nodes.push(Node {
+        name: "wall",
+        state: "none",
+        description: "quasi-steady: the outer surface temperature is solved every step".to_string(),
+    });
+    nodes
+}
+
+fn parameters(sim: &Simulation) -> Vec<Parameter> {
+    let init = sim.init();
+    let wall = sim.wall();
+    let p = |name, value: Value, unit| Parameter { name, value, unit };
+    let mut params = vec![
+        p("initial water", json!(init.water), "kg"),
+        p("initial ice", json!(init.ice), "kg"),
+        p("initial air", json!(init.air), "kg"),
+        p("initial salt", json!(init.salt), "kg"),
+        p("liquid", json!(sim.liquid().label()), ""),
+        p("initial contents temperature", json!(init.system_temp), "°C"),
+        p("outside temperature", json!(init.outside_temp), "°C"),
+        p("outside profile", json!(sim.ambient().label()), ""),
+        p(
+            "bottle shape",
+            json!(match sim.geometry() {
+                Geometry::Cylinder { .. } => "cylinder",
+                Geometry::Sphere { .. } => "sphere",
+                Geometry::Custom { .. } => "custom",
+                Geometry::Outline { .. } => "outline",
+            }),
+            "",
+        ),
+        p("bottle volume", json!(sim.geometry().volume()), "m³"),
+        p("wall area", json!(wall.area), "m²"),
+        p("wall material", json!(wall.material.label()), ""),
+        p("wall thickness", json!(wall.thickness), "m"),
+        p("wall emissivity", json!(wall.emissivity), ""),
+        p("insulation", json!(InsulationPreset::of_layers(&wall.layers).map_or("custom", |i| i.label())), ""),
+        p("gap gas", json!(wall.gap_gas().map_or("none", |g| g.label())), ""),
+        p("wall conductance", json!(wall.conductance()), "W/K"),
+        p("water bath", json!(sim.in_bath()), ""),
+        p("room", json!(sim.room().is_some()), ""),
+        p("wind speed", json!(sim.wind_speed()), "m/s"),
+        p("humidity", json!(sim.humidity()), ""),
+        p("sun irradiance", json!(sim.solar().irradiance), "W/m²"),
+        p("sun absorptivity", json!(sim.solar().absorptivity), ""),
+        p("shaded", json!(sim.solar().shaded), ""),
+        p("capped", json!(sim.is_capped()), ""),
+        p("heater power", json!(sim.heater_power()), "W"),
+        p("control", json!(sim.controller().mode.label()), ""),
+    ];
+    // water's are the constants below
+    if sim.liquid() != Liquid::Water {
+        let liquid = sim.liquid().properties();
+        params.extend([
+            p("liquid specific heat", json!(liquid.cp), "J/(kg·K)"),
+            p("liquid density", json!(liquid.density), "kg/m³"),
+            p("liquid latent heat", json!(liquid.latent_fusion), "J/kg"),
+            p("liquid conductivity", json!(liquid.conductivity), "W/(m·K)"),
+            p("liquid freezing point", json!(liquid.freezing_point), "°C"),
+        ]);
+    }
+    if sim.heater_power() != 0.0 {
+        let electricity = sim.electricity();
+        params.push(p("electricity tariff", json!(electricity.tariff), "per kWh"));
+        if sim.heater_power() < 0.0 {
+            params.extend([p("cooler Carnot fraction", json!(electricity.carnot_fraction), ""), p("cooler max COP", json!(electricity.max_cop), "")]);
+        }
+    }
+    if sim.controller().mode != ControlMode::Off {
+        let c = sim.controller();
+        params.push(p("setpoint", json!(c.setpoint), "°C"));
+        match c.mode {
+            ControlMode::BangBang => params.push(p("hysteresis", json!(c.hysteresis), "K")),
+            _ => params.extend([p("Kp", json!(c.kp), "W/K"), p("Ki", json!(c.ki), "W/(K·s)"), p("Kd", json!(c.kd), "W·s/K")]),
+        }
+    }
+    if let Some(room) = sim.room() {
+        params.extend([
+            p("room volume", json!(room.volume), "m³"),
+            p("room thermal mass", json!(room.thermal_mass), "J/K"),
+            p("room UA", json!(room.ua), "W/K"),
+            p("room load", json!(room.load), "W"),
+        ]);
+    }
+    let chiller = sim.chiller();
+    if chiller.flow > 0.0 {
+        params.extend([
+            p("chiller flow", json!(chiller.flow), "kg/s"),
+            p("chiller inlet", json!(chiller.inlet_temp), "°C"),
+            p("chiller UA", json!(chiller.ua), "W/K"),
+            p("chiller target", json!(chiller.target), "°C"),
+        ]);
+    }
+    params.extend([
+        p("supercooling", json!(sim.nucleation().enabled), ""),
+        p("dissolved air", json!(sim.nucleation().dissolved_air), ""),
+        p("nucleation rate", json!(sim.nucleation().rate()), "1/(s·K³)"),
+        p("pressure melting", json!(sim.pressure_melting()), ""),
+        p("conduction only", json!(sim.conduction_only()), ""),
+        p("ice pieces", json!(sim.ice_pieces()), ""),
+        p("water layers", json!(sim.water_layers().len()), ""),
+        p("2D field", json!(sim.field().is_some()), ""),
+        p("integrator", json!(sim.integrator().label()), ""),
+        p("time step", json!(sim.step_size()), "s"),
+        p("stop after", json!(sim.duration()), "s"),
+    ]);
+    params
+}
+
+fn equations(sim: &Simulation) -> Vec<Equation> {
+    let eq = |name, formula| Equation { name, formula };
+    let mut eqs = vec![
+        eq("energy balance", "dH/dt = Q_wall + Q_heater + Q_chiller"),
+        eq("enthalpy", "H = (m_w c_w + m_s c_s) T_w + m_i (c_i T_i − L_f) + m_v L_v + m_a c_v T_a"),
+        eq("phase change", "ice melts or freezes at T_f while the contents would pass it; T_f = −i K_f m_s / (M_s m_w), no lower than the eutectic"),
+        eq("wall conduction", "Q_wall = (T_in − T_s) / R, R = 1/(h_in A) + t/(k A) + Σ R_layer"),
+    ];
+    if sim.in_bath() {
+        eqs.push(eq("outer surface", "(T_in − T_s) / R = h_bath A (T_s − T_∞)"));
+    } else {
+        eqs.push(eq("outer surface", "(T_in − T_s) / R = h_out A (T_s − T_∞) + ε σ A (T_s⁴ − T_∞⁴) − α G A_p − Q_condensation"));
+        if !sim.conduction_only() {
+            eqs.push(eq("outside convection", "h_out = 5 + 3.8 v"));
+        }
+        eqs.push(eq("condensation", "ṁ = (h_out / ρc_p,air) A (φ ρ_sat(T_∞) − ρ_sat(T_s)), the Lewis analogy"));
+    }
+    if sim.conduction_only() {
+        eqs.push(eq("conduction only", "h_out = 2 k_air / D (k_water in a bath), D = √(A/π); h_in = 2 k_l / r; UA_surf = 2 k_air A_x² / V_head; h_cube = 2 k_l / a; no wind, neck exchange or layer mixing"));
+    }
+    if sim.room().is_some() {
+        eqs.push(eq("room", "(ρc_p V + C) dT_room/dt = UA_room (T_outdoor − T_room) + P_load − Q_wall − Q_neck; the bottle sees T_∞ = T_room"));
+    }
+    if sim.pressure_melting() {
+        eqs.push(eq("pressure melting", "ΔT_f = T Δv / L_f (p − p_atm) ≈ −7.4e-8 K/Pa, L = L_f + (c_w − c_i) ΔT_f; a few bar move T_f by hundredths of a kelvin"));
+    }
+    if sim.init().air > 0.0 {
+        eqs.push(eq("headspace", "Q_air = f Q_wall + UA_surf (T_w − T_a), p = (m_a R_a + m_v R_v) T_a / V"));
+    }
+    if !sim.is_capped() {
+        eqs.push(eq("evaporation", "ṁ = k_m (h_out / h_still) A_neck (ρ_sat(T_w) − φ ρ_sat(T_∞)), taking L_v(T_w) per kg"));
+    }
+    if sim.init().outside_temp < 0.0 {
+        eqs.push(eq("sublimation", "frozen solid: ṁ = k_m (h_out / h_still) s A_neck (ρ_sat,ice(T_i) − φ ρ_sat(T_∞)), s = 1 open or s_cap capped, taking L_s = L_f + L_v(0 °C) per kg"));
+    }
+    if sim.ice_pieces() > 0 {
+        eqs.push(eq("ice cubes", "Q_cube = h_iw 6 a² (T_w − T_f), a = (m / ρ_i)^(1/3)"));
+    }
+    if sim.water_layers().len() > 1 {
+        eqs.push(eq("water column", "conduction k ΔT / Δz between layers; a layer denser than the one below mixes in τ_mix"));
+    }
+    match sim.controller().mode {
+        ControlMode::Off => {}
+        ControlMode::BangBang => eqs.push(eq("thermostat", "P = rating below setpoint − hysteresis, 0 above setpoint + hysteresis")),
+        ControlMode::Pid => eqs.push(eq("PID", "P = clamp(Kp e + Ki ∫e dt + Kd de/dt, 0, rating), e = setpoint − T_w")),
+    }
+    if sim.heater_power() < 0.0 {
+        eqs.push(eq("Peltier cooler", "P_el = −Q_heater / COP, COP = min(η T_w / (T_∞ − T_w), COP_max) in K; a heater draws P_el = Q_heater"));
+    }
+    if sim.chiller().flow > 0.0 {
+        eqs.push(eq("immersion chiller", "Q_chiller = ε ṁ c_w (T_in − T_w), ε = 1 − exp(−UA / ṁ c_w), off once T_w ≤ target"));
+    }
+    if sim.nucleation().enabled {
+        eqs.push(eq("nucleation", "rate = J ΔT³ with no ice present, J = J_0 (f + (1 − f) a) for dissolved air a, certain at ΔT = 40 K; recalescence to T_f"));
+    }
+    eqs
+}
@@ -0,0 +1,80 @@
+use crate::ambient::AmbientProfile;
+use crate::simulation::{Simulation, FIXED_DT};
+
+/// Differential-scanning-calorimetry style analysis: the outside temperature is
+/// ramped slowly and the heat flow into the contents is recorded against it.
+/// Melting shows up as a peak, like the endotherm on a real DSC trace.
+#[derive(Clone, Copy, Debug)]
+pub struct DscSweep {
+    pub start_temp: f64,      // °C
+    pub end_temp: f64,        // °C
+    pub rate: f64,            // K/min
+    pub sample_mass: f64,     // kg of contents; small so the sample can follow the ramp
+    pub sample_interval: f64, // s of simulated time between points
+}
+
+impl Default for DscSweep {
+    fn default() -> Self {
+        Self {
+            start_temp: -20.0,
+            end_temp: 20.0,
+            rate: 0.5,
+            sample_mass: 0.01,
+            sample_interval: 10.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DscPoint {
+    pub time: f64,         // s since the start of the sweep
+    pub program_temp: f64, // °C, the ramped outside temperature
+    pub sample_temp: f64,  // °C, sensible-weighted contents temperature
+    pub heat_flow: f64,    // W into the contents through the wall
+}
+
+impl DscSweep {
+    pub fn duration(&self) -> f64 {
+        (self.end_temp - self.start_temp).abs() / self.rate.max(1e-6) * 60.0
+    }
+
+    /// Runs the sweep on a copy of `sim`, keeping its wall, insulation, wind and
+    /// solar settings. The contents are scaled down to `sample_mass` (salt and
+    /// air in proportion) and start frozen solid at `start_temp`, so heating
+    /// sweeps pass through the melting peak.
+    pub fn run(&self, sim: &Simulation) -> Vec<DscPoint> {
+        let mut sweep = sim.clone();
+        let duration = self.duration();
+        let init = sweep.init_mut();
+        let contents = init.ice + init.water;
+        let scale = if contents > 0.0 { self.sample_mass / contents } else { 0.0 };
+        init.ice = contents * scale;
+        init.water = 0.0;
+        init.air *= scale;
+        init.salt *= scale;
+        init.system_temp = self.start_temp;
+        init.outside_temp = self.start_temp;
+        sweep.set_start_datetime(None);
+        sweep.set_ambient(AmbientProfile::Table(vec![(0.0, self.start_temp), (duration, self.end_temp)]));
+        sweep.reset_from_init();
+        sweep.toggle_running();
+
+        let steps_per_sample = (self.sample_interval / FIXED_DT).round().max(1.0) as usize;
+        let mut points = Vec::new();
+        loop {
+            points.push(DscPoint {
+                time: sweep.time_seconds(),
+                program_temp: sweep.outside_temp(),
+                sample_temp: sweep.state().system_temperature_equivalent(),
+                heat_flow: sweep.wall_exchange().conduction,
+            });
+            if sweep.time_seconds() >= duration {
+                break;
+            }
+            for _ in 0..steps_per_sample {
+                sweep.step(FIXED_DT);
+            }
+        }
+        points
+    }
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{KELVIN, PELTIER_CARNOT_FRACTION, PELTIER_MAX_COP};
+
+/// What the heater draws from the mains, and what that costs. An immersion
+/// heater turns each joule it draws into a joule of heat. A Peltier cooler
+/// pumps heat out at a coefficient of performance (COP) that falls as the
+/// contents get colder than the outside: a fixed fraction of the Carnot COP
+/// T_cold / (T_hot − T_cold), capped where the lift is small.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Electricity {
+    pub carnot_fraction: f64,
+    pub max_cop: f64,
+    pub tariff: f64, // price of a kWh, in whatever currency
+    used: f64,       // J drawn since the reset
+}
+
+impl Default for Electricity {
+    fn default() -> Self {
+        Self {
+            carnot_fraction: PELTIER_CARNOT_FRACTION,
+            max_cop: PELTIER_MAX_COP,
+            tariff: 0.30,
+            used: 0.0,
+        }
+    }
+}
+
+impl Electricity {
+    /// COP of cooling contents at `cold` °C with the heat rejected to `hot` °C.
+    pub fn cop(&self, cold: f64, hot: f64) -> f64 {
+        let lift = hot - cold;
+        if lift <= 0.0 {
+            return self.max_cop;
+        }
+        (self.carnot_fraction * (cold + KELVIN) / lift).min(self.max_cop)
+    }
+
+    /// Electricity (J, or W) drawn to put `heat` into contents at `cold` °C
+    /// with the outside at `hot` °C; `heat` is negative when cooling.
+    pub fn drawn(&self, heat: f64, cold: f64, hot: f64) -> f64 {
+        if heat >= 0.0 {
+            heat
+        } else {
+            -heat / self.cop(cold, hot)
+        }
+    }
+
+    /// Adds what was drawn to put `heat` (J) in over a step.
+    pub fn advance(&mut self, heat: f64, cold: f64, hot: f64) {
+        self.used += self.drawn(heat, cold, hot);
+    }
+
+    pub fn reset(&mut self) {
+        self.used = 0.0;
+    }
+
+    /// kWh drawn since the reset.
+    pub fn kwh(&self) -> f64 {
+        self.used / 3.6e6
+    }
+
+    /// What the kWh since the reset cost at the tariff.
+    pub fn cost(&self) -> f64 {
+        self.kwh() * self.tariff
+    }
+}
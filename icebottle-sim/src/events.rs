@@ -0,0 +1,73 @@
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::experiment::Experiment;
+
+/// Discrete things that happen to the bottle outside the continuous heat flow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EventKind {
+    /// Run started; the edited initial conditions were applied.
+    Started,
+    Paused,
+    /// Cap opened; steam in the headspace escapes at once.
+    CapOpened,
+    CapClosed,
+    /// Supercooled water nucleated and partly froze.
+    Nucleation,
+    /// Run stopped on reaching its simulated duration.
+    Finished,
+    /// The contents reached the chiller's target and its coolant was shut off.
+    Chilled,
+    /// The contents came into the target band.
+    InBand,
+    /// The contents went above the target band.
+    Overshoot,
+    /// The run reached one of its scheduled checkpoints.
+    Checkpoint,
+    /// An ice cube was dropped in through the neck.
+    IceAdded,
+    /// Liquid was poured in through the neck, or out of it.
+    PouredIn,
+    PouredOut,
+}
+
+impl EventKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            EventKind::Started => "started",
+            EventKind::Paused => "paused",
+            EventKind::CapOpened => "cap_opened",
+            EventKind::CapClosed => "cap_closed",
+            EventKind::Nucleation => "nucleation",
+            EventKind::Finished => "finished",
+            EventKind::Chilled => "chilled",
+            EventKind::InBand => "in_band",
+            EventKind::Overshoot => "overshoot",
+            EventKind::Checkpoint => "checkpoint",
+            EventKind::IceAdded => "ice_added",
+            EventKind::PouredIn => "poured_in",
+            EventKind::PouredOut => "poured_out",
+        }
+    }
+}
+
+/// One logged event and the jump in contents enthalpy (J) it caused, so the
+/// continuous wall heat flow plus these jumps reconcile with the state.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Event {
+    pub time: f64,
+    pub kind: EventKind,
+    pub enthalpy_change: f64,
+}
+
+/// Writes events as CSV: `time_s,event,enthalpy_change_J`, after the run's
+/// name and notes as comment lines.
+pub fn write_events_csv<W: Write>(out: &mut W, experiment: &Experiment, events: &[Event]) -> io::Result<()> {
+    experiment.write_header(out)?;
+    writeln!(out, "time_s,event,enthalpy_change_J")?;
+    for e in events {
+        writeln!(out, "{},{},{}", e.time, e.kind.label(), e.enthalpy_change)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+/// Name and free-text notes attached to a run, carried into everything
+/// exported from it so the data does not depend on a file name.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Experiment {
+    pub name: String,
+    pub notes: String,
+}
+
+impl Experiment {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_empty() && self.notes.is_empty()
+    }
+
+    /// Writes `# name: ...` and `# notes: ...` comment lines for the top of a
+    /// CSV export; multi-line notes get one comment line each.
+    pub fn write_header<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        if !self.name.is_empty() {
+            writeln!(out, "# name: {}", self.name)?;
+        }
+        for line in self.notes.lines() {
+            writeln!(out, "# notes: {line}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+    CONDUCTIVITY_ICE, CP_ICE, CP_WATER, DENSITY_WATER, FIELD_AIR_CONDUCTIVITY, FIELD_AIR_RHO_C,
+    FIELD_CELL, FIELD_NX, FIELD_NZ, FIELD_WALL_RHO_C, FIELD_WATER_CONDUCTIVITY, LATENT_FUSION,
+};
+use crate::state::SystemState;
+use crate::wall::WallConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CellKind {
+    Wall,
+    /// Water or ice; which one follows from the cell's enthalpy.
+    Contents,
+    Air,
+}
+
+/// 2D finite-difference temperature field over a vertical slice through the
+/// bottle: a one-cell wall (with the cap on top) around water, floating ice
+/// and headspace air. Water and ice cells use an enthalpy formulation, so they
+/// melt and freeze cell by cell.
+///
+/// This is a separate, finer model run alongside the lumped one for the
+/// heatmap; it is seeded from the lumped state when a run starts and does not
+/// feed back into it. Convection inside is folded into effective
+/// conductivities, and sunlight is not included. The contents are always
+/// fresh water here, whatever the lumped model's liquid.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TemperatureField {
+    kind: Vec<CellKind>,
+    // wall and air cells; contents cells depend on their liquid fraction
+    conductivity: Vec<f64>,
+    // sensible heat capacity per volume for wall/air cells, J/(m^3*K)
+    rho_c: Vec<f64>,
+    // J/m^3; for contents cells 0 is ice at 0 °C and rho*L is water at 0 °C
+    enthalpy: Vec<f64>,
+    h_outside: f64,
+    dt_stable: f64,
+    pending: f64,
+}
+
+impl TemperatureField {
+    pub fn new(state: &SystemState, wall: &WallConfig, cross_section: f64, h_outside: f64) -> Self {
+        let n = FIELD_NX * FIELD_NZ;
+        let mut field = Self {
+            kind: vec![CellKind::Air; n],
+            conductivity: vec![FIELD_AIR_CONDUCTIVITY; n],
+            rho_c: vec![FIELD_AIR_RHO_C; n],
+            enthalpy: vec![0.0; n],
+            h_outside,
+            dt_stable: 0.0,
+            pending: 0.0,
+        };
+
+        // the wall cell's conductivity reproduces the lumped wall resistance per area
+        let wall_k = FIELD_CELL / (wall.conduction_resistance() * wall.area);
+        // fill heights of the real bottle, in rows
+        let rows = |mass: f64| (mass / DENSITY_WATER / cross_section / FIELD_CELL).round() as usize;
+        let water_rows = rows(state.mass_water).min(FIELD_NZ - 2);
+        let ice_rows = rows(state.mass_ice).min(FIELD_NZ - 2 - water_rows);
+
+        for j in 0..FIELD_NZ {
+            for i in 0..FIELD_NX {
+                let c = j * FIELD_NX + i;
+                let row = j.wrapping_sub(1); // interior row, 0 at the bottom
+                if i == 0 || i == FIELD_NX - 1 || j == 0 || j == FIELD_NZ - 1 {
+                    field.kind[c] = CellKind::Wall;
+                    field.conductivity[c] = wall_k;
+                    field.rho_c[c] = FIELD_WALL_RHO_C;
+                    field.enthalpy[c] = FIELD_WALL_RHO_C * state.temp_water;
+                } else if row < water_rows {
+                    field.kind[c] = CellKind::Contents;
+                    field.enthalpy[c] = DENSITY_WATER * (LATENT_FUSION + CP_WATER * state.temp_water.max(0.0));
+                } else if row < water_rows + ice_rows {
+                    field.kind[c] = CellKind::Contents;
+                    field.enthalpy[c] = DENSITY_WATER * CP_ICE * state.temp_ice.min(0.0);
+                } else {
+                    field.enthalpy[c] = FIELD_AIR_RHO_C * state.temp_air;
+                }
+            }
+        }
+
+        // explicit scheme: keep every cell's Fourier number well under 1/4
+        let slowest = (0..n)
+            .map(|c| match field.kind[c] {
+                CellKind::Contents => DENSITY_WATER * CP_ICE.min(CP_WATER) / FIELD_WATER_CONDUCTIVITY.max(CONDUCTIVITY_ICE),
+                _ => field.rho_c[c] / field.conductivity[c],
+            })
+            .fold(f64::MAX, f64::min);
+        field.dt_stable = 0.2 * FIELD_CELL * FIELD_CELL * slowest;
+        field
+    }
+
+    pub fn nx(&self) -> usize {
+        FIELD_NX
+    }
+
+    pub fn nz(&self) -> usize {
+        FIELD_NZ
+    }
+
+    /// Cell at column `i`, row `j` (row 0 is the bottom).
+    pub fn kind(&self, i: usize, j: usize) -> CellKind {
+        self.kind[j * FIELD_NX + i]
+    }
+
+    /// Temperature (°C) of cell `i`, `j`.
+    pub fn temperature(&self, i: usize, j: usize) -> f64 {
+        self.temp_of(j * FIELD_NX + i)
+    }
+
+    /// Liquid fraction of a contents cell (0 = ice, 1 = water); 0 for others.
+    pub fn liquid_fraction(&self, i: usize, j: usize) -> f64 {
+        let c = j * FIELD_NX + i;
+        if self.kind[c] != CellKind::Contents {
+            return 0.0;
+        }
+        (self.enthalpy[c] / (DENSITY_WATER * LATENT_FUSION)).clamp(0.0, 1.0)
+    }
+
+    /// Advances by `dt` seconds, in stable sub-steps, with the outside air at `outside_temp`.
+    pub fn advance(&mut self, dt: f64, outside_temp: f64) {
+        self.pending += dt;
+        while self.pending >= self.dt_stable {
+            self.step(self.dt_stable, outside_temp);
+            self.pending -= self.dt_stable;
+        }
+    }
+
+    fn step(&mut self, dt: f64, outside_temp: f64) {
+        let temps: Vec<f64> = (0..self.kind.len()).map(|c| self.temp_of(c)).collect();
+        let conductivity: Vec<f64> = (0..self.kind.len()).map(|c| self.conductivity_of(c)).collect();
+        let face = FIELD_CELL * FIELD_CELL;
+        for j in 0..FIELD_NZ {
+            for i in 0..FIELD_NX {
+                let c = j * FIELD_NX + i;
+                let mut q = 0.0; // W/m^3
+                let neighbours = [
+                    (i > 0).then(|| c - 1),
+                    (i + 1 < FIELD_NX).then(|| c + 1),
+                    (j > 0).then(|| c - FIELD_NX),
+                    (j + 1 < FIELD_NZ).then(|| c + FIELD_NX),
+                ];
+                for neighbour in neighbours {
+                    match neighbour {
+                        Some(o) => {
+                            let (k1, k2) = (conductivity[c], conductivity[o]);
+                            let k = 2.0 * k1 * k2 / (k1 + k2);
+                            q += k * (temps[o] - temps[c]) / face;
+                        }
+                        // outer face of the wall
+                        None => q += self.h_outside * (outside_temp - temps[c]) / FIELD_CELL,
+                    }
+                }
+                self.enthalpy[c] += q * dt;
+            }
+        }
+    }
+
+    // contents cells blend ice and (convecting) water by liquid fraction
+    fn conductivity_of(&self, c: usize) -> f64 {
+        match self.kind[c] {
+            CellKind::Contents => {
+                let liquid = (self.enthalpy[c] / (DENSITY_WATER * LATENT_FUSION)).clamp(0.0, 1.0);
+                liquid * FIELD_WATER_CONDUCTIVITY + (1.0 - liquid) * CONDUCTIVITY_ICE
+            }
+            _ => self.conductivity[c],
+        }
+    }
+
+    fn temp_of(&self, c: usize) -> f64 {
+        let h = self.enthalpy[c];
+        match self.kind[c] {
+            CellKind::Contents => {
+                let latent = DENSITY_WATER * LATENT_FUSION;
+                if h < 0.0 {
+                    h / (DENSITY_WATER * CP_ICE)
+                } else if h > latent {
+                    (h - latent) / (DENSITY_WATER * CP_WATER)
+                } else {
+                    0.0
+                }
+            }
+            _ => h / self.rho_c[c],
+        }
+    }
+}
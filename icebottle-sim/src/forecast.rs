@@ -0,0 +1,97 @@
+use crate::simulation::Simulation;
+
+/// Looks ahead by running a copy of the simulation, with the same model and
+/// settings, until the ice is gone and the water has come to the outside
+/// temperature, or the horizon runs out.
+#[derive(Clone, Copy, Debug)]
+pub struct Predictor {
+    pub horizon: f64, // s of simulated time to look ahead
+    pub step: f64,    // s, coarser than the live step so a forecast stays cheap
+    pub band: f64,    // K, "at the outside temperature" once within this
+}
+
+impl Default for Predictor {
+    fn default() -> Self {
+        Self {
+            horizon: 24.0 * 3600.0,
+            step: 2.0,
+            band: 0.5,
+        }
+    }
+}
+
+/// Simulated times (s, on the run's clock) at which things are expected to
+/// happen; `None` when they don't within the horizon.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Prediction {
+    pub from: f64,
+    /// All ice melted; `None` also when there is no ice to begin with.
+    pub ice_gone: Option<f64>,
+    /// Contents within the band of the outside temperature.
+    pub near_ambient: Option<f64>,
+}
+
+impl Predictor {
+    /// Forecast from the current state of `sim`, or from its initial conditions
+    /// while paused (that is where Start picks up), run to the end at once.
+    pub fn predict(&self, sim: &Simulation) -> Prediction {
+        let mut forecast = self.start(sim);
+        loop {
+            if let Some(prediction) = forecast.advance(usize::MAX) {
+                return prediction;
+            }
+        }
+    }
+
+    /// A forecast from where `predict` would start, to be run a few steps at
+    /// a time so a front-end can spread the work over its frames. The 2D
+    /// field, which doesn't feed back into the contents, is left out of the copy.
+    pub fn start(&self, sim: &Simulation) -> Forecast {
+        let mut ahead = sim.clone();
+        ahead.set_field_mode(false);
+        ahead.set_duration(None);
+        if !ahead.is_running() {
+            ahead.toggle_running();
+        }
+        let from = ahead.time_seconds();
+        Forecast {
+            predictor: *self,
+            had_ice: ahead.state().mass_ice > 0.0,
+            ahead,
+            prediction: Prediction { from, ..Prediction::default() },
+        }
+    }
+}
+
+/// A forecast under way; see `Predictor::start`.
+#[derive(Clone, Debug)]
+pub struct Forecast {
+    predictor: Predictor,
+    ahead: Simulation,
+    had_ice: bool,
+    prediction: Prediction,
+}
+
+impl Forecast {
+    /// Runs up to `steps` more steps of the copy; the prediction once it is
+    /// complete, `None` while there is more to run.
+    pub fn advance(&mut self, steps: usize) -> Option<Prediction> {
+        let Predictor { horizon, step, band } = self.predictor;
+        let (ahead, prediction) = (&mut self.ahead, &mut self.prediction);
+        for _ in 0..steps {
+            if ahead.time_seconds() >= prediction.from + horizon {
+                return Some(*prediction);
+            }
+            let now = ahead.time_seconds();
+            if self.had_ice && prediction.ice_gone.is_none() && ahead.state().mass_ice <= 0.0 {
+                prediction.ice_gone = Some(now);
+            }
+            if (ahead.state().system_temperature_equivalent() - ahead.outside_temp()).abs() <= band {
+                prediction.near_ambient = Some(now);
+                return Some(*prediction);
+            }
+            ahead.step(step);
+        }
+        None
+    }
+}
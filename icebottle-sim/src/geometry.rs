@@ -0,0 +1,167 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::outline::{self, OUTLINE_SAMPLES};
+
+/// Shape of the bottle, for the wall area that exchanges heat, the area it
+/// shows the sun and how high a given volume of contents fills it.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Geometry {
+    /// Upright cylinder, ends included in the area (m).
+    Cylinder { radius: f64, height: f64 },
+    /// Round flask (m).
+    Sphere { radius: f64 },
+    /// Any shape given by its outer area (m^2) and volume (m^3); filled as if
+    /// straight-sided.
+    Custom { area: f64, volume: f64 },
+    /// Turned shape as read from an outline: the inner radius (m) at
+    /// `OUTLINE_SAMPLES` evenly spaced heights from the base to the rim,
+    /// joined by straight lines, and the height (m).
+    Outline { radii: [f64; OUTLINE_SAMPLES], height: f64 },
+}
+
+impl Default for Geometry {
+    /// ~0.77 L drinks bottle, r = 3.5 cm, h = 20 cm.
+    fn default() -> Self {
+        Geometry::Cylinder { radius: 0.035, height: 0.2 }
+    }
+}
+
+impl Geometry {
+    /// The shape turned from the outline in an SVG document's first path; see
+    /// `from_svg_path`.
+    pub fn from_svg(svg: &str, height: Option<f64>) -> Result<Geometry, String> {
+        let d = outline::path_data(svg).ok_or("no <path> with a d attribute in the SVG")?;
+        Geometry::from_svg_path(d, height)
+    }
+
+    /// The shape an SVG path outline `d` makes turned about its vertical
+    /// centre line, e.g. a bottle's silhouette. The outline is scaled to
+    /// `height` (m) or, without one, taken as drawn in millimetres.
+    pub fn from_svg_path(d: &str, height: Option<f64>) -> Result<Geometry, String> {
+        let (radii, height) = outline::profile(d, height)?;
+        let geometry = Geometry::Outline { radii, height };
+        if geometry.volume() > 0.0 {
+            Ok(geometry)
+        } else {
+            Err("the outline encloses no volume".to_string())
+        }
+    }
+
+    /// Outer wall area (m^2).
+    pub fn area(&self) -> f64 {
+        match *self {
+            Geometry::Cylinder { radius, height } => 2.0 * PI * radius * (height + radius),
+            Geometry::Sphere { radius } => 4.0 * PI * radius * radius,
+            Geometry::Custom { area, .. } => area,
+            Geometry::Outline { radii, height } => {
+                // base, rim and the slanted band between each pair of heights
+                let dz = height / (OUTLINE_SAMPLES - 1) as f64;
+                let ends = PI * (radii[0] * radii[0] + radii[OUTLINE_SAMPLES - 1] * radii[OUTLINE_SAMPLES - 1]);
+                ends + radii.windows(2).map(|r| PI * (r[0] + r[1]) * (r[1] - r[0]).hypot(dz)).sum::<f64>()
+            }
+        }
+    }
+
+    /// Inner volume (m^3).
+    pub fn volume(&self) -> f64 {
+        match *self {
+            Geometry::Cylinder { radius, height } => PI * radius * radius * height,
+            Geometry::Sphere { radius } => 4.0 / 3.0 * PI * radius * radius * radius,
+            Geometry::Custom { volume, .. } => volume,
+            Geometry::Outline { height, .. } => self.volume_below(height),
+        }
+    }
+
+    /// Area presented to a beam of sunlight (m^2): an upright cylinder seen
+    /// from the side, or a quarter of the area for other convex shapes.
+    pub fn projected_area(&self) -> f64 {
+        match *self {
+            Geometry::Cylinder { radius, height } => 2.0 * radius * height,
+            Geometry::Sphere { radius } => PI * radius * radius,
+            Geometry::Custom { area, .. } => area / 4.0,
+            Geometry::Outline { radii, height } => {
+                let dz = height / (OUTLINE_SAMPLES - 1) as f64;
+                radii.windows(2).map(|r| (r[0] + r[1]) * dz).sum()
+            }
+        }
+    }
+
+    /// Inner height (m); a custom shape is taken as a cube of its volume.
+    pub fn height(&self) -> f64 {
+        match *self {
+            Geometry::Cylinder { height, .. } => height,
+            Geometry::Sphere { radius } => 2.0 * radius,
+            Geometry::Custom { volume, .. } => volume.cbrt(),
+            Geometry::Outline { height, .. } => height,
+        }
+    }
+
+    /// Mean horizontal cross-section (m^2): volume over height.
+    pub fn cross_section(&self) -> f64 {
+        match *self {
+            Geometry::Cylinder { radius, .. } => PI * radius * radius,
+            Geometry::Sphere { radius } => 2.0 / 3.0 * PI * radius * radius,
+            Geometry::Custom { .. } | Geometry::Outline { .. } => self.volume() / self.height(),
+        }
+    }
+
+    /// Fraction of the height (0..1) filled by `volume` m^3 of contents.
+    pub fn fill_fraction(&self, volume: f64) -> f64 {
+        let full = self.volume();
+        if full <= 0.0 {
+            return 0.0;
+        }
+        let fraction = (volume / full).clamp(0.0, 1.0);
+        match *self {
+            Geometry::Sphere { radius } => {
+                // spherical cap V = pi h^2 (3r - h) / 3 grows monotonically in h: bisect
+                let target = fraction * full;
+                let (mut lo, mut hi) = (0.0, 2.0 * radius);
+                for _ in 0..50 {
+                    let h = 0.5 * (lo + hi);
+                    if PI * h * h * (3.0 * radius - h) / 3.0 < target {
+                        lo = h;
+                    } else {
+                        hi = h;
+                    }
+                }
+                0.5 * (lo + hi) / (2.0 * radius)
+            }
+            Geometry::Outline { height, .. } => {
+                let target = fraction * full;
+                let (mut lo, mut hi) = (0.0, height);
+                for _ in 0..50 {
+                    let h = 0.5 * (lo + hi);
+                    if self.volume_below(h) < target {
+                        lo = h;
+                    } else {
+                        hi = h;
+                    }
+                }
+                0.5 * (lo + hi) / height
+            }
+            _ => fraction,
+        }
+    }
+
+    // Volume (m^3) of an outline up to `z` m above its base, frustum by frustum
+    fn volume_below(&self, z: f64) -> f64 {
+        let Geometry::Outline { radii, height } = *self else {
+            return 0.0;
+        };
+        let dz = height / (OUTLINE_SAMPLES - 1) as f64;
+        let frustum = |r0: f64, r1: f64, h: f64| PI * h * (r0 * r0 + r0 * r1 + r1 * r1) / 3.0;
+        let mut volume = 0.0;
+        for (i, r) in radii.windows(2).enumerate() {
+            let bottom = i as f64 * dz;
+            if z <= bottom {
+                break;
+            }
+            let h = (z - bottom).min(dz);
+            volume += frustum(r[0], r[0] + (r[1] - r[0]) * h / dz, h);
+        }
+        volume
+    }
+}
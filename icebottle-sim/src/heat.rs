@@ -0,0 +1,176 @@
+use std::ops::{Add, Mul, Sub};
+
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{CP_ICE, CV_AIR};
+use crate::state::SystemState;
+
+/// Heat flows into each thermal node (W), or energies (J) once multiplied by a time step.
+///
+/// The rest is the same flow by where it comes from, for the ledger: the
+/// integrator combines it with the same weights as the nodes', so it adds up
+/// to `liquid + air` of whatever was applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HeatFlow {
+    /// Into the ice/water contents.
+    pub liquid: f64,
+    /// Into the headspace air.
+    pub air: f64,
+    pub wall: f64,
+    pub heater: f64,
+    pub chiller: f64,
+    /// From bottles touching this one, and a cooler's bath.
+    pub contact: f64,
+    /// Headspace air swapped with the outside through an open neck.
+    pub neck: f64,
+}
+
+impl Add for HeatFlow {
+    type Output = HeatFlow;
+
+    fn add(self, rhs: HeatFlow) -> HeatFlow {
+        HeatFlow {
+            liquid: self.liquid + rhs.liquid,
+            air: self.air + rhs.air,
+            wall: self.wall + rhs.wall,
+            heater: self.heater + rhs.heater,
+            chiller: self.chiller + rhs.chiller,
+            contact: self.contact + rhs.contact,
+            neck: self.neck + rhs.neck,
+        }
+    }
+}
+
+impl Mul<f64> for HeatFlow {
+    type Output = HeatFlow;
+
+    fn mul(self, rhs: f64) -> HeatFlow {
+        HeatFlow {
+            liquid: self.liquid * rhs,
+            air: self.air * rhs,
+            wall: self.wall * rhs,
+            heater: self.heater * rhs,
+            chiller: self.chiller * rhs,
+            contact: self.contact * rhs,
+            neck: self.neck * rhs,
+        }
+    }
+}
+
+/// Energy of the contents by where it comes from and where it goes. Sources
+/// are positive into the contents; stores are positive when they take energy
+/// up, and together they add up to the change in `SystemState::enthalpy`,
+/// less the jumps logged as events.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct EnergySplit {
+    /// Through the wall: convection, radiation and sun outside, condensation.
+    pub wall: f64,
+    pub heater: f64,
+    pub chiller: f64,
+    /// Conducted in from bottles touching this one, and from a cooler's bath.
+    pub contact: f64,
+    /// Steam vented and water evaporated through an open neck, and ice
+    /// sublimed out of frozen contents.
+    pub neck: f64,
+    pub ice_sensible: f64,
+    /// Latent heat taken up melting ice; negative while water freezes.
+    pub melting: f64,
+    pub water_sensible: f64,
+    /// Heat of the steam in the headspace: what boiled it off the water.
+    pub steam: f64,
+    pub air: f64,
+}
+
+impl EnergySplit {
+    /// The stores of `state`, each term of its enthalpy on its own.
+    pub fn stored(state: &SystemState) -> Self {
+        Self {
+            ice_sensible: state.mass_ice * CP_ICE * state.temp_ice,
+            melting: -state.mass_ice * state.liquid.properties().latent_fusion,
+            water_sensible: state.liquid_heat_capacity() * state.temp_water,
+            steam: state.mass_vapor * state.steam_enthalpy(),
+            air: state.mass_air * CV_AIR * state.temp_air,
+            ..Self::default()
+        }
+    }
+
+    /// Everything the sources brought in; it makes up the change in the stores.
+    pub fn sources(&self) -> f64 {
+        self.wall + self.heater + self.chiller + self.contact + self.neck
+    }
+
+    /// Every category with its label, sources first.
+    pub fn entries(&self) -> [(&'static str, f64); 10] {
+        [
+            ("Wall", self.wall),
+            ("Heater", self.heater),
+            ("Chiller", self.chiller),
+            ("Contact", self.contact),
+            ("Neck", self.neck),
+            ("Ice, sensible", self.ice_sensible),
+            ("Melting", self.melting),
+            ("Water, sensible", self.water_sensible),
+            ("Steam", self.steam),
+            ("Headspace air", self.air),
+        ]
+    }
+
+    fn map2(self, rhs: Self, f: impl Fn(f64, f64) -> f64) -> Self {
+        Self {
+            wall: f(self.wall, rhs.wall),
+            heater: f(self.heater, rhs.heater),
+            chiller: f(self.chiller, rhs.chiller),
+            contact: f(self.contact, rhs.contact),
+            neck: f(self.neck, rhs.neck),
+            ice_sensible: f(self.ice_sensible, rhs.ice_sensible),
+            melting: f(self.melting, rhs.melting),
+            water_sensible: f(self.water_sensible, rhs.water_sensible),
+            steam: f(self.steam, rhs.steam),
+            air: f(self.air, rhs.air),
+        }
+    }
+}
+
+impl Add for EnergySplit {
+    type Output = EnergySplit;
+
+    fn add(self, rhs: EnergySplit) -> EnergySplit {
+        self.map2(rhs, |a, b| a + b)
+    }
+}
+
+impl Sub for EnergySplit {
+    type Output = EnergySplit;
+
+    fn sub(self, rhs: EnergySplit) -> EnergySplit {
+        self.map2(rhs, |a, b| a - b)
+    }
+}
+
+impl Mul<f64> for EnergySplit {
+    type Output = EnergySplit;
+
+    fn mul(self, rhs: f64) -> EnergySplit {
+        self.map2(self, |a, _| a * rhs)
+    }
+}
+
+/// The energy ledger kept by `Simulation::step`: the split of the last step as
+/// flows (W) and of the whole run since the reset (J).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct EnergyLedger {
+    pub last: EnergySplit,
+    pub total: EnergySplit,
+}
+
+impl EnergyLedger {
+    /// Books the energies (J) of a step of `dt` seconds.
+    pub fn record(&mut self, step: EnergySplit, dt: f64) {
+        self.last = step * (1.0 / dt);
+        self.total = self.total + step;
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
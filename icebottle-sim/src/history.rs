@@ -0,0 +1,157 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::experiment::Experiment;
+use crate::simulation::SAMPLE_COLUMNS;
+
+static NEXT_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// Run history with bounded memory: fixed-width samples (one `f64` per
+/// column) are kept in RAM up to `ram_limit`; beyond that the oldest half is
+/// appended to a temporary file. Reads see the whole run either way.
+///
+/// The spill file holds raw little-endian `f64`s, so sample `i` sits at a
+/// fixed offset. It is deleted when the history is cleared or dropped.
+#[derive(Debug)]
+pub struct History {
+    columns: usize,
+    ram_limit: usize,
+    recent: Vec<f64>,
+    spilled: usize,
+    file: Option<File>,
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(columns: usize, ram_limit: usize) -> Self {
+        let id = NEXT_FILE.fetch_add(1, Ordering::Relaxed);
+        let name = format!("icebottle-history-{}-{id}.bin", std::process::id());
+        Self {
+            columns: columns.max(1),
+            ram_limit: ram_limit.max(2),
+            recent: Vec::new(),
+            spilled: 0,
+            file: None,
+            path: std::env::temp_dir().join(name),
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn len(&self) -> usize {
+        self.spilled + self.recent.len() / self.columns
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Samples currently on disk rather than in memory.
+    pub fn spilled(&self) -> usize {
+        self.spilled
+    }
+
+    /// Appends a sample; missing columns are stored as NaN, extra ones dropped.
+    pub fn push(&mut self, sample: &[f64]) -> io::Result<()> {
+        if self.recent.len() / self.columns >= self.ram_limit {
+            self.spill(self.ram_limit / 2)?;
+        }
+        for c in 0..self.columns {
+            self.recent.push(sample.get(c).copied().unwrap_or(f64::NAN));
+        }
+        Ok(())
+    }
+
+    /// Sample `index` (0 = oldest), from disk or memory.
+    pub fn get(&mut self, index: usize) -> io::Result<Option<Vec<f64>>> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+        if index >= self.spilled {
+            let start = (index - self.spilled) * self.columns;
+            return Ok(Some(self.recent[start..start + self.columns].to_vec()));
+        }
+        let record = self.columns * 8;
+        let file = self.file.as_mut().expect("spilled samples have a file");
+        file.seek(SeekFrom::Start((index * record) as u64))?;
+        let mut bytes = vec![0u8; record];
+        file.read_exact(&mut bytes)?;
+        Ok(Some(decode(&bytes)))
+    }
+
+    /// Calls `f` with every sample in order, oldest first.
+    pub fn for_each(&mut self, mut f: impl FnMut(&[f64])) -> io::Result<()> {
+        if let Some(file) = self.file.as_mut() {
+            file.seek(SeekFrom::Start(0))?;
+            let mut reader = BufReader::new(&*file);
+            let mut bytes = vec![0u8; self.columns * 8];
+            for _ in 0..self.spilled {
+                reader.read_exact(&mut bytes)?;
+                f(&decode(&bytes));
+            }
+        }
+        self.recent.chunks(self.columns).for_each(f);
+        Ok(())
+    }
+
+    /// Forgets every sample and removes the spill file.
+    pub fn clear(&mut self) {
+        self.recent.clear();
+        self.spilled = 0;
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    fn spill(&mut self, samples: usize) -> io::Result<()> {
+        if self.file.is_none() {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&self.path)?;
+            self.file = Some(file);
+        }
+        let file = self.file.as_mut().expect("just opened");
+        file.seek(SeekFrom::End(0))?;
+        let values = samples * self.columns;
+        let bytes: Vec<u8> = self.recent[..values].iter().flat_map(|v| v.to_le_bytes()).collect();
+        file.write_all(&bytes)?;
+        self.recent.drain(..values);
+        self.spilled += samples;
+        Ok(())
+    }
+}
+
+impl Drop for History {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Writes the history as CSV with a `SAMPLE_COLUMNS` header, after the run's
+/// name and notes as comment lines.
+pub fn write_history_csv<W: Write>(out: &mut W, experiment: &Experiment, history: &mut History) -> io::Result<()> {
+    experiment.write_header(out)?;
+    writeln!(out, "{}", SAMPLE_COLUMNS.join(","))?;
+    let mut result = Ok(());
+    history.for_each(|row| {
+        if result.is_ok() {
+            let cells: Vec<String> = row.iter().map(f64::to_string).collect();
+            result = writeln!(out, "{}", cells.join(","));
+        }
+    })?;
+    result
+}
+
+fn decode(bytes: &[u8]) -> Vec<f64> {
+    bytes
+        .chunks_exact(8)
+        .map(|b| f64::from_le_bytes(b.try_into().expect("8-byte chunk")))
+        .collect()
+}
@@ -0,0 +1,104 @@
+use std::ops::{Add, Mul};
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::SystemState;
+
+// Adaptive mode: max temperature disagreement (K) between one step and two half steps
+const ADAPTIVE_TOLERANCE: f64 = 1e-4;
+const ADAPTIVE_MAX_DEPTH: u32 = 8;
+
+/// Anything the integrators can sample and combine: heat flows per node.
+pub(crate) trait Flow: Copy + Add<Output = Self> + Mul<f64, Output = Self> {}
+
+impl<T: Copy + Add<Output = T> + Mul<f64, Output = T>> Flow for T {}
+
+/// Time integration scheme for the heat ODE.
+///
+/// The model is written as `dH/dt = rate(state)`, where the rate is the heat
+/// flow into each node in watts and `apply` turns amounts of energy into
+/// temperature and phase changes. Integrators only decide where the rate is sampled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Integrator {
+    #[default]
+    Euler,
+    Rk4,
+    /// RK4 with step doubling; halves the step until the error is below tolerance.
+    Adaptive,
+}
+
+impl Integrator {
+    pub fn label(self) -> &'static str {
+        match self {
+            Integrator::Euler => "Euler",
+            Integrator::Rk4 => "RK4",
+            Integrator::Adaptive => "Adaptive RK4",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Integrator::Euler => Integrator::Rk4,
+            Integrator::Rk4 => Integrator::Adaptive,
+            Integrator::Adaptive => Integrator::Euler,
+        }
+    }
+
+    /// Advances `state` by `dt` and returns it with the energy applied on
+    /// the way, the rate times `dt` as the scheme combined it.
+    pub(crate) fn integrate<F, R, A>(self, state: SystemState, dt: f64, rate: &R, apply: &A) -> (SystemState, F)
+    where
+        F: Flow,
+        R: Fn(&SystemState) -> F,
+        A: Fn(&mut SystemState, F),
+    {
+        match self {
+            Integrator::Euler => {
+                let mut next = state;
+                let q = rate(&state) * dt;
+                apply(&mut next, q);
+                (next, q)
+            }
+            Integrator::Rk4 => rk4(state, dt, rate, apply),
+            Integrator::Adaptive => adaptive(state, dt, rate, apply, 0),
+        }
+    }
+}
+
+fn rk4<F, R, A>(state: SystemState, dt: f64, rate: &R, apply: &A) -> (SystemState, F)
+where
+    F: Flow,
+    R: Fn(&SystemState) -> F,
+    A: Fn(&mut SystemState, F),
+{
+    let trial = |q: F| {
+        let mut s = state;
+        apply(&mut s, q);
+        s
+    };
+    let k1 = rate(&state);
+    let k2 = rate(&trial(k1 * dt * 0.5));
+    let k3 = rate(&trial(k2 * dt * 0.5));
+    let k4 = rate(&trial(k3 * dt));
+    let q = (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (dt / 6.0);
+    (trial(q), q)
+}
+
+fn adaptive<F, R, A>(state: SystemState, dt: f64, rate: &R, apply: &A, depth: u32) -> (SystemState, F)
+where
+    F: Flow,
+    R: Fn(&SystemState) -> F,
+    A: Fn(&mut SystemState, F),
+{
+    let (full, _) = rk4(state, dt, rate, apply);
+    let (half, first) = rk4(state, dt * 0.5, rate, apply);
+    let (halves, second) = rk4(half, dt * 0.5, rate, apply);
+    let err = (full.system_temperature_equivalent() - halves.system_temperature_equivalent()).abs();
+    if err <= ADAPTIVE_TOLERANCE || depth >= ADAPTIVE_MAX_DEPTH {
+        (halves, first + second)
+    } else {
+        let (mid, first) = adaptive(state, dt * 0.5, rate, apply, depth + 1);
+        let (end, second) = adaptive(mid, dt * 0.5, rate, apply, depth + 1);
+        (end, first + second)
+    }
+}
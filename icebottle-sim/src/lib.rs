@@ -0,0 +1,81 @@
+//! Lumped thermal model of a sealed bottle holding ice, water and air.
+//!
+//! This crate holds the physics only and has no graphics dependencies, so it
+//! can be embedded in other tools or driven from tests. The macroquad
+//! front-end lives in the `IceBottle-Simulation` binary.
+
+mod ambient;
+mod annotation;
+mod checkpoint;
+mod chiller;
+mod clock;
+mod column;
+pub mod constants;
+mod controller;
+mod cooler;
+mod cubes;
+mod describe;
+mod dsc;
+mod electricity;
+mod events;
+mod experiment;
+mod field2d;
+mod forecast;
+mod geometry;
+mod heat;
+mod history;
+mod integrator;
+mod liquid;
+mod metrics;
+mod nucleation;
+mod outline;
+pub mod reduce;
+mod replay;
+mod rng;
+mod room;
+mod scenario;
+mod solar;
+mod simulation;
+mod state;
+pub mod vapor;
+mod wall;
+
+pub use ambient::AmbientProfile;
+pub use annotation::Annotation;
+pub use checkpoint::{write_checkpoint_header, write_checkpoint_row, Checkpoints};
+pub use chiller::Chiller;
+pub use clock::SimDateTime;
+pub use column::WaterColumn;
+pub use controller::{ControlMode, Controller};
+pub use cooler::Cooler;
+pub use describe::{Constant, Equation, ModelDescription, Node, Parameter};
+pub use cubes::IceCubes;
+pub use dsc::{DscPoint, DscSweep};
+pub use electricity::Electricity;
+pub use events::{write_events_csv, Event, EventKind};
+pub use experiment::Experiment;
+pub use field2d::{CellKind, TemperatureField};
+pub use forecast::{Forecast, Prediction, Predictor};
+pub use geometry::Geometry;
+pub use heat::{EnergyLedger, EnergySplit, HeatFlow};
+pub use history::{write_history_csv, History};
+pub use integrator::Integrator;
+pub use liquid::{Liquid, LiquidProperties};
+pub use metrics::{BandCrossing, Dose, DoseKind, Envelope, HoldTime, TargetBand};
+pub use nucleation::Nucleation;
+pub use outline::OUTLINE_SAMPLES;
+pub use replay::{Change, Player, Recorder};
+pub use rng::Rng;
+pub use room::Room;
+pub use scenario::{Scenario, ScenarioBottle, ScenarioCheckpoints, ScenarioChiller, ScenarioElectricity, ScenarioError, ScenarioInitial, ScenarioIntegrals, ScenarioOutside, ScenarioRoom, ScenarioTarget, ScenarioVariant};
+pub use simulation::{InitialConditions, Simulation, DEFAULT_SPAN, FIXED_DT, SAMPLE_COLUMNS, TICK_RATE_RANGE, TIME_SCALES};
+pub use solar::SolarConfig;
+pub use state::SystemState;
+pub use wall::{GapGas, InsulationLayer, InsulationPreset, Surroundings, WallConfig, WallExchange, WallMaterial};
+
+// Batch runners such as the app's `--sweep` move simulations onto worker
+// threads, so the model must stay `Send`.
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<Simulation>();
+};
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{CONDUCTIVITY_WATER, CP_WATER, DENSITY_WATER, LATENT_FUSION};
+
+/// Thermal properties of a liquid the bottle can hold. What freezes out of
+/// it is taken to be ice, so the latent heat is per kg of the liquid frozen:
+/// a drink that is partly sugar, fat or alcohol gives up less than water does.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LiquidProperties {
+    pub cp: f64,            // J/(kg·K)
+    pub density: f64,       // kg/m^3
+    pub latent_fusion: f64, // J/kg
+    pub conductivity: f64,  // W/(m·K)
+    /// °C where it starts to freeze. Held fixed, where the real one falls as
+    /// ice freezes out and leaves the rest stronger; only added salt is
+    /// followed that way.
+    pub freezing_point: f64,
+}
+
+/// What the bottle holds, from the property table below.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Liquid {
+    #[default]
+    Water,
+    /// Sea water, 3.5 % salt.
+    Saltwater,
+    /// Whole milk.
+    Milk,
+    /// Apple or orange juice.
+    Juice,
+    /// Water with 12 % ethanol by volume, as in wine.
+    EthanolMix,
+}
+
+// By `Liquid`, in order; water's are the model's own constants
+const PROPERTIES: [LiquidProperties; 5] = [
+    LiquidProperties { cp: CP_WATER, density: DENSITY_WATER, latent_fusion: LATENT_FUSION, conductivity: CONDUCTIVITY_WATER, freezing_point: 0.0 },
+    LiquidProperties { cp: 3993.0, density: 1025.0, latent_fusion: 322_000.0, conductivity: 0.596, freezing_point: -1.9 },
+    LiquidProperties { cp: 3930.0, density: 1030.0, latent_fusion: 290_000.0, conductivity: 0.53, freezing_point: -0.54 },
+    LiquidProperties { cp: 3850.0, density: 1045.0, latent_fusion: 293_000.0, conductivity: 0.55, freezing_point: -1.4 },
+    LiquidProperties { cp: 4150.0, density: 985.0, latent_fusion: 300_000.0, conductivity: 0.5, freezing_point: -4.5 },
+];
+
+impl Liquid {
+    pub const ALL: [Liquid; 5] = [Liquid::Water, Liquid::Saltwater, Liquid::Milk, Liquid::Juice, Liquid::EthanolMix];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Liquid::Water => "Water",
+            Liquid::Saltwater => "Saltwater",
+            Liquid::Milk => "Milk",
+            Liquid::Juice => "Juice",
+            Liquid::EthanolMix => "Ethanol",
+        }
+    }
+
+    pub fn properties(self) -> LiquidProperties {
+        PROPERTIES[self as usize]
+    }
+}
@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+
+/// Time the drink spends at or below a target temperature ("hours below 8 °C").
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct HoldTime {
+    pub threshold: f64, // Celsius
+    seconds: f64,
+}
+
+impl Default for HoldTime {
+    fn default() -> Self {
+        Self::new(8.0)
+    }
+}
+
+impl HoldTime {
+    pub fn new(threshold: f64) -> Self {
+        Self { threshold, seconds: 0.0 }
+    }
+
+    pub fn accumulate(&mut self, temp: f64, dt: f64) {
+        if temp <= self.threshold {
+            self.seconds += dt;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.seconds = 0.0;
+    }
+
+    pub fn seconds(&self) -> f64 {
+        self.seconds
+    }
+
+    pub fn hours(&self) -> f64 {
+        self.seconds / 3600.0
+    }
+}
+
+/// What a [`Dose`] adds up over the run, from the contents temperature.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum DoseKind {
+    /// Hours above a temperature (°C), e.g. 5 °C for the food-safety danger zone.
+    HoursAbove(f64),
+    /// Hours at or below a temperature (°C).
+    HoursBelow(f64),
+    /// Pasteurization units as brewers count them: a minute at 60 °C is one
+    /// unit, and each degree above multiplies the rate by 1.393.
+    PasteurizationUnits,
+    /// Utah-model chill units: an hour at 2.5–9.1 °C is one unit, the bands
+    /// either side count half, and an hour above 16 °C takes some back.
+    ChillUnits,
+}
+
+impl DoseKind {
+    /// Units gained per second at `temp` (°C).
+    pub fn rate(self, temp: f64) -> f64 {
+        match self {
+            Self::HoursAbove(threshold) if temp > threshold => 1.0 / 3600.0,
+            Self::HoursBelow(threshold) if temp <= threshold => 1.0 / 3600.0,
+            Self::HoursAbove(_) | Self::HoursBelow(_) => 0.0,
+            Self::PasteurizationUnits => 1.393f64.powf(temp - 60.0) / 60.0,
+            Self::ChillUnits => {
+                let per_hour = match temp {
+                    t if t < 1.5 => 0.0,
+                    t if t < 2.5 => 0.5,
+                    t if t < 9.2 => 1.0,
+                    t if t < 12.5 => 0.5,
+                    t if t < 16.0 => 0.0,
+                    t if t <= 18.0 => -0.5,
+                    _ => -1.0,
+                };
+                per_hour / 3600.0
+            }
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Self::HoursAbove(threshold) => format!("Hours above {threshold} °C"),
+            Self::HoursBelow(threshold) => format!("Hours below {threshold} °C"),
+            Self::PasteurizationUnits => "Pasteurization units".to_string(),
+            Self::ChillUnits => "Chill units".to_string(),
+        }
+    }
+
+    /// Name of its column in a CSV.
+    pub fn column(self) -> String {
+        match self {
+            Self::HoursAbove(threshold) => format!("hours_above_{threshold}_c"),
+            Self::HoursBelow(threshold) => format!("hours_below_{threshold}_c"),
+            Self::PasteurizationUnits => "pasteurization_units".to_string(),
+            Self::ChillUnits => "chill_units".to_string(),
+        }
+    }
+}
+
+/// A time-temperature integral over the run, chosen per scenario.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Dose {
+    pub kind: DoseKind,
+    total: f64,
+}
+
+impl Dose {
+    pub fn new(kind: DoseKind) -> Self {
+        Self { kind, total: 0.0 }
+    }
+
+    pub fn accumulate(&mut self, temp: f64, dt: f64) {
+        self.total += self.kind.rate(temp) * dt;
+    }
+
+    pub fn reset(&mut self) {
+        self.total = 0.0;
+    }
+
+    pub fn total(&self) -> f64 {
+        self.total
+    }
+}
+
+/// A range the contents are meant to be brought into, such as 36–38 °C for a
+/// baby's bottle, watched so the front-end can say when they get there and
+/// warn when they go past it.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct TargetBand {
+    pub low: f64,  // Celsius
+    pub high: f64, // Celsius
+    inside: bool,
+    above: bool,
+    // contents first seen above the band are being brought down into it, not
+    // past it, until they get there
+    #[serde(default)]
+    followed: bool,
+    #[serde(default)]
+    from_above: bool,
+}
+
+/// What the contents just did relative to a [`TargetBand`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BandCrossing {
+    Entered,
+    Overshot,
+}
+
+impl TargetBand {
+    pub fn new(low: f64, high: f64) -> Self {
+        Self { low, high, inside: false, above: false, followed: false, from_above: false }
+    }
+
+    pub fn contains(&self, temp: f64) -> bool {
+        (self.low..=self.high).contains(&temp)
+    }
+
+    /// Follows the contents to `temp`; returns the crossing, if any, on the
+    /// step it happens.
+    pub fn update(&mut self, temp: f64) -> Option<BandCrossing> {
+        let (was_inside, was_above) = (self.inside, self.above);
+        self.inside = self.contains(temp);
+        self.above = temp > self.high;
+        if !self.followed {
+            self.followed = true;
+            self.from_above = self.above;
+        }
+        self.from_above &= !self.inside;
+        if self.inside && !was_inside {
+            Some(BandCrossing::Entered)
+        } else if self.above && !was_above && !self.from_above {
+            Some(BandCrossing::Overshot)
+        } else {
+            None
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.inside = false;
+        self.above = false;
+        self.followed = false;
+        self.from_above = false;
+    }
+
+    /// Whether the contents have gone past the band, rather than still
+    /// coming down into it from above.
+    pub fn overshot(&self) -> bool {
+        self.above && !self.from_above
+    }
+}
+
+/// Running minimum and maximum of each sample column, updated every physics
+/// step so short transients are caught even when the display skips frames.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Envelope {
+    min: Vec<f64>,
+    max: Vec<f64>,
+}
+
+impl Envelope {
+    pub fn record(&mut self, sample: &[f64]) {
+        if self.min.is_empty() {
+            self.min = sample.to_vec();
+            self.max = sample.to_vec();
+            return;
+        }
+        for (i, &v) in sample.iter().enumerate() {
+            self.min[i] = self.min[i].min(v);
+            self.max[i] = self.max[i].max(v);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.min.clear();
+        self.max.clear();
+    }
+
+    /// `(min, max)` of `column`, or None before the first step.
+    pub fn range(&self, column: usize) -> Option<(f64, f64)> {
+        Some((*self.min.get(column)?, *self.max.get(column)?))
+    }
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{DEGASSED_NUCLEATION, HOMOGENEOUS_UNDERCOOLING, NUCLEATION_RATE};
+use crate::rng::Rng;
+
+const SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Optional supercooling: without any ice to grow on, water can cool below its
+/// freezing point until a nucleation event (random, or a shake) starts the freeze.
+///
+/// Dissolved air coming out of solution gives ice somewhere to start, so water
+/// that has been boiled and cooled, with most of its air driven off, supercools
+/// deeper than tap water. Dissolved salt only counts through the freezing point
+/// the undercooling is measured from.
+///
+/// The random draws are seeded so runs stay reproducible.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Nucleation {
+    pub enabled: bool,
+    /// Air dissolved in the water as a fraction of saturation: 1 for tap water,
+    /// about `BOILED_DISSOLVED_AIR` once boiled and cooled.
+    pub dissolved_air: f64,
+    shaken: bool,
+    rng: Rng,
+}
+
+impl Default for Nucleation {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dissolved_air: 1.0,
+            shaken: false,
+            rng: Rng::new(SEED),
+        }
+    }
+}
+
+impl Nucleation {
+    /// Clears a pending shake and restarts the random sequence, so a reset run
+    /// repeats exactly.
+    pub fn reset(&mut self) {
+        self.shaken = false;
+        self.rng = Rng::new(SEED);
+    }
+
+    /// Requests a nucleation event at the next step, like knocking the bottle.
+    pub fn shake(&mut self) {
+        self.shaken = true;
+    }
+
+    /// The nucleation rate coefficient (1/(s·K³)) for the dissolved air: the full
+    /// `NUCLEATION_RATE` for saturated water, down to `DEGASSED_NUCLEATION` of it
+    /// with none.
+    pub fn rate(&self) -> f64 {
+        let air = self.dissolved_air.clamp(0.0, 1.0);
+        NUCLEATION_RATE * (DEGASSED_NUCLEATION + (1.0 - DEGASSED_NUCLEATION) * air)
+    }
+
+    /// Whether freezing starts during a step of `dt` with the liquid `undercooling`
+    /// kelvin below its freezing point. The rate grows steeply with undercooling
+    /// (`rate() * ΔT³` per second) and is certain past the homogeneous limit.
+    pub fn triggers(&mut self, undercooling: f64, dt: f64) -> bool {
+        if undercooling <= 0.0 {
+            self.shaken = false;
+            return false;
+        }
+        if std::mem::take(&mut self.shaken) || undercooling >= HOMOGENEOUS_UNDERCOOLING {
+            return true;
+        }
+        let probability = 1.0 - (-self.rate() * undercooling.powi(3) * dt).exp();
+        self.rng.next_uniform() < probability
+    }
+}
@@ -0,0 +1,215 @@
+// Bottle outlines drawn in a vector editor: the `d` of an SVG path, flattened
+// to straight lines and measured across at evenly spaced heights to give the
+// radius of the turned shape. Lines (M, L, H, V, Z) and Bézier curves (C, S,
+// Q, T) are read, absolute or relative; arcs are not.
+
+// Heights the outline is measured at, bottom first; few enough that a
+// `Geometry` stays a small `Copy` value
+pub const OUTLINE_SAMPLES: usize = 24;
+
+// Straight pieces each curve is flattened into
+const CURVE_PIECES: usize = 16;
+
+// m per SVG user unit when no height is given: drawn in millimetres
+const UNIT: f64 = 0.001;
+
+type Point = (f64, f64);
+
+/// The `d` attribute of the first `<path>` in an SVG document.
+pub fn path_data(svg: &str) -> Option<&str> {
+    let path = &svg[svg.find("<path")?..];
+    let path = &path[..path.find('>')?];
+    // ` d=` and not the end of another attribute's name such as `id=`
+    let start = path.match_indices("d=").map(|(i, _)| i).find(|&i| path[..i].ends_with(char::is_whitespace))? + 2;
+    let quote = path[start..].chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let rest = &path[start + 1..];
+    Some(&rest[..rest.find(quote)?])
+}
+
+/// Radius (m) of the shape the outline `d` turns about its vertical centre
+/// line, at `OUTLINE_SAMPLES` heights from its lowest point to its highest,
+/// and that height (m). The outline is scaled to `height` when given and
+/// taken as drawn in millimetres otherwise.
+pub fn profile(d: &str, height: Option<f64>) -> Result<([f64; OUTLINE_SAMPLES], f64), String> {
+    let shapes = flatten(d)?;
+    let points = shapes.iter().flatten();
+    let (mut left, mut right, mut top, mut bottom) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+    for &(x, y) in points {
+        left = left.min(x);
+        right = right.max(x);
+        top = top.min(y);
+        bottom = bottom.max(y);
+    }
+    if !(bottom > top && right > left) {
+        return Err("the outline has no height or no width".to_string());
+    }
+    let scale = height.map_or(UNIT, |h| h / (bottom - top));
+    let mut radii = [0.0; OUTLINE_SAMPLES];
+    for (i, radius) in radii.iter_mut().enumerate() {
+        // just inside the ends, where a flat base or rim would lie along the line
+        let f = (i as f64 / (OUTLINE_SAMPLES - 1) as f64).clamp(1e-6, 1.0 - 1e-6);
+        // SVG's y runs down the page
+        let y = bottom - f * (bottom - top);
+        let (lo, hi) = crossings(&shapes, y).fold((f64::MAX, f64::MIN), |(lo, hi), x| (lo.min(x), hi.max(x)));
+        if hi > lo {
+            *radius = 0.5 * (hi - lo) * scale;
+        }
+    }
+    Ok((radii, (bottom - top) * scale))
+}
+
+// x where the closed outlines cross the line at height `y`
+fn crossings(shapes: &[Vec<Point>], y: f64) -> impl Iterator<Item = f64> + '_ {
+    shapes.iter().flat_map(move |shape| {
+        let closing = shape.last().copied().into_iter().zip(shape.first().copied());
+        shape.windows(2).map(|w| (w[0], w[1])).chain(closing).filter_map(move |((x0, y0), (x1, y1))| {
+            let spans = (y0 <= y && y < y1) || (y1 <= y && y < y0);
+            spans.then(|| x0 + (y - y0) / (y1 - y0) * (x1 - x0))
+        })
+    })
+}
+
+// The path's subpaths as lists of points, curves flattened
+fn flatten(d: &str) -> Result<Vec<Vec<Point>>, String> {
+    let tokens = tokenize(d)?;
+    let mut shapes: Vec<Vec<Point>> = Vec::new();
+    let mut at: Point = (0.0, 0.0);
+    let mut start: Point = (0.0, 0.0);
+    // last control point, for the smooth curves that mirror it
+    let mut control: Option<Point> = None;
+    let mut command = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Token::Command(c) = tokens[i] {
+            command = Some(c);
+            i += 1;
+            if c.eq_ignore_ascii_case(&'z') {
+                shapes.last_mut().filter(|s| !s.is_empty()).ok_or("Z before any point")?;
+                at = start;
+                control = None;
+                continue;
+            }
+        }
+        let c = command.ok_or("the path must start with a command")?;
+        let relative = c.is_ascii_lowercase();
+        let count = match c.to_ascii_uppercase() {
+            'M' | 'L' | 'T' => 2,
+            'H' | 'V' => 1,
+            'S' | 'Q' => 4,
+            'C' => 6,
+            'A' => return Err("arcs (A) aren't supported; convert them to curves first".to_string()),
+            // a bare number after Z
+            'Z' => return Err("a number after Z".to_string()),
+            other => return Err(format!("unknown path command '{other}'")),
+        };
+        let mut numbers = [0.0; 6];
+        for n in numbers.iter_mut().take(count) {
+            match tokens.get(i) {
+                Some(&Token::Number(v)) => *n = v,
+                _ => return Err(format!("'{c}' needs {count} numbers")),
+            }
+            i += 1;
+        }
+        let origin = if relative { at } else { (0.0, 0.0) };
+        let point = |k: usize| (origin.0 + numbers[k], origin.1 + numbers[k + 1]);
+        let mirrored = control.map_or(at, |(cx, cy)| (2.0 * at.0 - cx, 2.0 * at.1 - cy));
+        match c.to_ascii_uppercase() {
+            'M' => {
+                at = point(0);
+                start = at;
+                shapes.push(vec![at]);
+                // further pairs are lines
+                command = Some(if relative { 'l' } else { 'L' });
+                control = None;
+            }
+            'L' | 'H' | 'V' => {
+                at = match c.to_ascii_uppercase() {
+                    'H' => (origin.0 + numbers[0], at.1),
+                    'V' => (at.0, origin.1 + numbers[0]),
+                    _ => point(0),
+                };
+                current(&mut shapes)?.push(at);
+                control = None;
+            }
+            'C' | 'S' => {
+                let (c1, c2, end) = if c.eq_ignore_ascii_case(&'c') { (point(0), point(2), point(4)) } else { (mirrored, point(0), point(2)) };
+                let from = at;
+                current(&mut shapes)?.extend((1..=CURVE_PIECES).map(|k| cubic(from, c1, c2, end, k as f64 / CURVE_PIECES as f64)));
+                at = end;
+                control = Some(c2);
+            }
+            _ => {
+                // Q and T: a quadratic curve is a cubic with its control point two thirds of the way out
+                let (q, end) = if c.eq_ignore_ascii_case(&'q') { (point(0), point(2)) } else { (mirrored, point(0)) };
+                let from = at;
+                let toward = |p: Point| (p.0 + 2.0 / 3.0 * (q.0 - p.0), p.1 + 2.0 / 3.0 * (q.1 - p.1));
+                let (c1, c2) = (toward(from), toward(end));
+                current(&mut shapes)?.extend((1..=CURVE_PIECES).map(|k| cubic(from, c1, c2, end, k as f64 / CURVE_PIECES as f64)));
+                at = end;
+                control = Some(q);
+            }
+        }
+    }
+    if shapes.iter().map(Vec::len).sum::<usize>() < 3 {
+        return Err("the outline needs at least three points".to_string());
+    }
+    Ok(shapes)
+}
+
+fn current(shapes: &mut [Vec<Point>]) -> Result<&mut Vec<Point>, String> {
+    shapes.last_mut().ok_or_else(|| "the path must start with M".to_string())
+}
+
+fn cubic(p0: Point, p1: Point, p2: Point, p3: Point, t: f64) -> Point {
+    let u = 1.0 - t;
+    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+    (a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0, a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1)
+}
+
+enum Token {
+    Command(char),
+    Number(f64),
+}
+
+// Commands and numbers; numbers may run together as in `10-5.5.5`
+fn tokenize(d: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = d.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() || c == ',' {
+            i += 1;
+        } else if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            tokens.push(Token::Command(c));
+            i += 1;
+        } else {
+            let begin = i;
+            let mut seen_point = false;
+            let mut seen_exponent = false;
+            if chars[i] == '-' || chars[i] == '+' {
+                i += 1;
+            }
+            while i < chars.len() {
+                match chars[i] {
+                    '0'..='9' => {}
+                    '.' if !seen_point && !seen_exponent => seen_point = true,
+                    'e' | 'E' if !seen_exponent => {
+                        seen_exponent = true;
+                        if matches!(chars.get(i + 1), Some('-' | '+')) {
+                            i += 1;
+                        }
+                    }
+                    _ => break,
+                }
+                i += 1;
+            }
+            let text: String = chars[begin..i].iter().collect();
+            match text.parse() {
+                Ok(v) => tokens.push(Token::Number(v)),
+                Err(_) => return Err(format!("can't read '{}' in the path", if text.is_empty() { c.to_string() } else { text })),
+            }
+        }
+    }
+    Ok(tokens)
+}
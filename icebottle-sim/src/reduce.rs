@@ -0,0 +1,28 @@
+//! Float reductions with a fixed evaluation order.
+//!
+//! Floating-point addition is not associative, so a sum that is split across
+//! threads or reordered by an iterator adapter can differ in the last bits
+//! between platforms. Every reduction in the model goes through these helpers,
+//! which always accumulate left to right in index order.
+
+/// Sum in iteration order, starting from +0.0.
+pub fn ordered_sum<I: IntoIterator<Item = f64>>(values: I) -> f64 {
+    let mut total = 0.0;
+    for v in values {
+        total += v;
+    }
+    total
+}
+
+/// 64-bit FNV-1a over the exact bit patterns of `values`; equal fingerprints
+/// mean bit-identical inputs.
+pub fn fingerprint<I: IntoIterator<Item = f64>>(values: I) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for v in values {
+        for byte in v.to_bits().to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    hash
+}
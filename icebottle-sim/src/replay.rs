@@ -0,0 +1,138 @@
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::simulation::Simulation;
+
+/// One line of a replay file, which is JSON Lines: the simulation as the
+/// session started, then each change made to it, then the step it ended on.
+/// Written as it goes, so a session that crashes still leaves a file that
+/// replays up to the crash.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Line {
+    Start(Box<Simulation>),
+    Change(Change),
+    End(u64),
+}
+
+/// The fields of the simulation a user changed between two physics steps,
+/// with their new values.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Change {
+    /// Physics steps taken since the start before the change.
+    pub step: u64,
+    /// Simulated time (s) at the change, for reading the file.
+    pub time: f64,
+    pub set: Map<String, Value>,
+}
+
+/// Records an interactive session. The model is deterministic, so only what
+/// the user did needs keeping: the front-end reports the simulation after
+/// each batch of physics steps and again after handling input, and whatever
+/// differs between the two is written down with the step count.
+pub struct Recorder<W: Write> {
+    out: W,
+    last: Map<String, Value>,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Starts a recording of `sim` as it is now.
+    pub fn new(mut out: W, sim: &Simulation) -> io::Result<Self> {
+        write_line(&mut out, &Line::Start(Box::new(sim.clone())))?;
+        Ok(Self { out, last: fields(sim) })
+    }
+
+    /// Takes `sim` as it came out of the physics steps, which replay themselves.
+    pub fn after_steps(&mut self, sim: &Simulation) {
+        self.last = fields(sim);
+    }
+
+    /// Writes down what input changed in `sim` since `after_steps`, with
+    /// `step` the physics steps taken so far.
+    pub fn after_input(&mut self, step: u64, sim: &Simulation) -> io::Result<()> {
+        let now = fields(sim);
+        let set: Map<String, Value> = now.iter().filter(|(key, value)| self.last.get(*key) != Some(value)).map(|(k, v)| (k.clone(), v.clone())).collect();
+        self.last = now;
+        if set.is_empty() {
+            return Ok(());
+        }
+        write_line(&mut self.out, &Line::Change(Change { step, time: sim.time_seconds(), set }))
+    }
+
+    /// Ends the recording at `step`.
+    pub fn finish(mut self, step: u64) -> io::Result<()> {
+        write_line(&mut self.out, &Line::End(step))
+    }
+}
+
+/// Plays a recording back: the front-end steps the simulation as usual and
+/// calls `apply` before every step, which puts in the changes due there.
+#[derive(Clone, Debug)]
+pub struct Player {
+    changes: Vec<Change>,
+    next: usize,
+    end: Option<u64>,
+}
+
+impl Player {
+    /// Reads a replay file; returns the simulation to start from and the player.
+    pub fn read<R: BufRead>(input: R) -> Result<(Simulation, Player), String> {
+        let mut start = None;
+        let mut player = Player { changes: Vec::new(), next: 0, end: None };
+        for (i, line) in input.lines().enumerate() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line).map_err(|e| format!("line {}: {e}", i + 1))? {
+                Line::Start(sim) => start = Some(*sim),
+                Line::Change(change) => player.changes.push(change),
+                Line::End(step) => player.end = Some(step),
+            }
+        }
+        let sim: Simulation = start.ok_or("no start line: not a replay file")?;
+        // every change has to read back onto the simulation, so `apply` can't fail later
+        let mut all = fields(&sim);
+        for change in &player.changes {
+            all.extend(change.set.clone());
+            serde_json::from_value::<Simulation>(Value::Object(all.clone())).map_err(|e| format!("change at step {}: {e}", change.step))?;
+        }
+        Ok((sim, player))
+    }
+
+    /// Puts in the changes recorded before physics step `step`.
+    pub fn apply(&mut self, step: u64, sim: &mut Simulation) {
+        while let Some(change) = self.changes.get(self.next).filter(|c| c.step <= step) {
+            let mut all = fields(sim);
+            all.extend(change.set.clone());
+            *sim = serde_json::from_value(Value::Object(all)).expect("checked when the file was read");
+            self.next += 1;
+        }
+    }
+
+    /// Step the recording ended on; a session that crashed has none, and its
+    /// last change is as far as it goes.
+    pub fn end(&self) -> u64 {
+        self.end.unwrap_or_else(|| self.changes.last().map_or(0, |c| c.step))
+    }
+
+    /// True once every change has been put in.
+    pub fn is_done(&self) -> bool {
+        self.next == self.changes.len()
+    }
+}
+
+fn fields(sim: &Simulation) -> Map<String, Value> {
+    match serde_json::to_value(sim).expect("plain data always serializes") {
+        Value::Object(map) => map,
+        _ => unreachable!("a struct serializes to an object"),
+    }
+}
+
+fn write_line<W: Write>(out: &mut W, line: &Line) -> io::Result<()> {
+    serde_json::to_writer(&mut *out, line)?;
+    writeln!(out)?;
+    out.flush()
+}
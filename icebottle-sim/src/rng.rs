@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// Small seeded generator (xorshift64*) for the model's random draws, so runs
+/// with the same seed repeat exactly. Front-ends use it for their own draws,
+/// such as an ensemble's inputs, to repeat just as the model does.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift never leaves zero
+        Self(if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed })
+    }
+
+    /// Uniform in [0, 1).
+    pub fn next_uniform(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        let bits = self.0.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        (bits >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in [lo, hi).
+    pub fn range(&mut self, lo: f64, hi: f64) -> f64 {
+        lo + (hi - lo) * self.next_uniform()
+    }
+
+    /// Standard normal, from two uniforms by Box-Muller.
+    pub fn normal(&mut self) -> f64 {
+        // in (0, 1], so the logarithm is finite
+        let u = 1.0 - self.next_uniform();
+        let v = self.next_uniform();
+        (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos()
+    }
+}
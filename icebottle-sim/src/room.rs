@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::RHO_CP_AIR;
+
+/// A closed room around the bottle, in place of outside air that never
+/// changes temperature. The room's air and furnishings store heat, it leaks
+/// heat to the outdoors (the ambient profile) through its walls and windows,
+/// and whatever the bottle takes from it or gives it moves its temperature.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Room {
+    pub volume: f64,       // m^3 of air
+    pub thermal_mass: f64, // J/K of walls, floor and furniture that follow the air
+    pub ua: f64,           // W/K through the envelope to the outdoors
+    pub load: f64,         // W of other heat sources in the room (people, a fridge's back)
+    temp: f64,             // °C
+}
+
+impl Room {
+    pub fn new(volume: f64, thermal_mass: f64, ua: f64, load: f64) -> Self {
+        Self { volume, thermal_mass, ua, load, temp: 0.0 }
+    }
+
+    /// Air temperature (°C), which is what the bottle sees.
+    pub fn temp(&self) -> f64 {
+        self.temp
+    }
+
+    /// Heat capacity (J/K) of the air and the thermal mass together.
+    pub fn heat_capacity(&self) -> f64 {
+        self.volume * RHO_CP_AIR + self.thermal_mass
+    }
+
+    pub fn reset(&mut self, temp: f64) {
+        self.temp = temp;
+    }
+
+    /// Advances the room over `dt` with the outdoors at `outdoor` °C, having
+    /// lost `to_bottle` J to the bottle over the step.
+    pub fn advance(&mut self, outdoor: f64, to_bottle: f64, dt: f64) {
+        let gained = (self.ua * (outdoor - self.temp) + self.load) * dt - to_bottle;
+        self.temp += gained / self.heat_capacity();
+    }
+}
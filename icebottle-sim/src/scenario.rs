@@ -0,0 +1,841 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::constants::{DENSITY_ICE, KELVIN};
+use crate::ambient::AmbientProfile;
+use crate::annotation::Annotation;
+use crate::clock::SimDateTime;
+use crate::checkpoint::Checkpoints;
+use crate::geometry::Geometry;
+use crate::liquid::Liquid;
+use crate::metrics::DoseKind;
+use crate::room::Room;
+use crate::simulation::{Simulation, TIME_SCALES};
+use crate::wall::{GapGas, InsulationLayer, InsulationPreset, WallMaterial};
+
+// kg of NaCl that dissolves in 1 kg of water at room temperature
+const SALT_SOLUBILITY: f64 = 0.36;
+
+/// A ready-made experiment as written in a TOML scenario file: the initial
+/// contents, the bottle, the outside conditions and the run settings. Anything
+/// left out keeps the app's default.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub notes: String,
+    pub duration_h: Option<f64>,
+    pub time_scale: Option<f64>,
+    /// Physics steps per simulated second; a slow tick lets a run of days
+    /// keep up with a high `time_scale`.
+    pub tick_rate_hz: Option<f64>,
+    /// Clock time the run starts at, `HH:MM` or `HH:MM:SS` on day 1. With it
+    /// the outside schedules and the sun's hours are in clock time.
+    pub start: Option<String>,
+    /// Fields of the app's controls panel shown but not editable, named as
+    /// for `--lock`; the app checks the names, which the library doesn't know.
+    #[serde(default)]
+    pub locked: Vec<String>,
+    #[serde(default)]
+    pub initial: ScenarioInitial,
+    #[serde(default)]
+    pub bottle: ScenarioBottle,
+    #[serde(default)]
+    pub outside: ScenarioOutside,
+    #[serde(default)]
+    pub integrals: ScenarioIntegrals,
+    #[serde(default)]
+    pub chiller: ScenarioChiller,
+    #[serde(default)]
+    pub electricity: ScenarioElectricity,
+    pub target: Option<ScenarioTarget>,
+    pub room: Option<ScenarioRoom>,
+    /// `[[annotations]]`: marks drawn on the temperature chart.
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    #[serde(default)]
+    pub checkpoints: ScenarioCheckpoints,
+    /// `[[variants]]`: runs of this setup with some of the initial contents
+    /// or the bottle changed, for `--compare` to set side by side.
+    #[serde(default)]
+    pub variants: Vec<ScenarioVariant>,
+}
+
+/// `[initial]`: what is in the bottle when the run starts.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioInitial {
+    pub water_kg: Option<f64>,
+    pub ice_kg: Option<f64>,
+    pub air_kg: Option<f64>,
+    pub salt_kg: Option<f64>,
+    /// What the water is: Water, Saltwater, Milk, Juice or Ethanol; the
+    /// masses above are of it.
+    pub liquid: Option<String>,
+    pub contents_c: Option<f64>,
+    /// Let water with no ice in it cool below freezing until it nucleates.
+    pub supercooling: Option<bool>,
+    /// Air dissolved in the water as a fraction of saturation: 1 for tap
+    /// water, about 0.2 for water boiled and cooled with a lid on.
+    pub dissolved_air: Option<f64>,
+}
+
+/// `[bottle]`: shape, wall and cap.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioBottle {
+    /// `cylinder` (radius_m, height_m), `sphere` (radius_m), `custom`
+    /// (area_m2, volume_m3) or `outline` (outline, and height_m to scale it).
+    pub shape: Option<String>,
+    pub radius_m: Option<f64>,
+    pub height_m: Option<f64>,
+    pub area_m2: Option<f64>,
+    pub volume_m3: Option<f64>,
+    /// The bottle's silhouette as SVG path data, in millimetres unless height_m is given.
+    pub outline: Option<String>,
+    pub material: Option<String>,
+    pub insulation: Option<String>,
+    /// `vacuum`, `argon` or `air` in the gap of a double-walled insulation.
+    pub gap_gas: Option<String>,
+    pub capped: Option<bool>,
+    /// An immersion heater in the contents (W); negative for a Peltier cooler.
+    pub heater_w: Option<f64>,
+    /// Heat only conducts, in the bottle and around it: no convection.
+    pub conduction_only: Option<bool>,
+}
+
+/// `[outside]`: the air around the bottle.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioOutside {
+    pub temp_c: Option<f64>,
+    /// `constant`, `day_night` (amplitude_c), `steps` or `table` (schedule).
+    pub profile: Option<String>,
+    pub amplitude_c: Option<f64>,
+    /// `[time_s, temp_c]` pairs in time order.
+    pub schedule: Option<Vec<[f64; 2]>>,
+    pub wind_m_s: Option<f64>,
+    pub humidity: Option<f64>,
+    pub sun_w_m2: Option<f64>,
+    /// Clock times (`HH:MM`) the sun shines from and until; needs `start`.
+    pub sun_from: Option<String>,
+    pub sun_until: Option<String>,
+    /// The bottle stands in a water bath at `temp_c` instead of in air.
+    pub bath: Option<bool>,
+}
+
+/// `[integrals]`: the time-temperature integrals to keep over the run.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScenarioIntegrals {
+    /// Hours the contents spend above each of these temperatures (°C).
+    pub hours_above_c: Vec<f64>,
+    /// Hours the contents spend at or below each of these temperatures (°C).
+    /// The first is the run's hold threshold, kept as its hold time (8 °C
+    /// when there is none); the rest are kept as integrals.
+    pub hours_below_c: Vec<f64>,
+    pub pasteurization_units: bool,
+    pub chill_units: bool,
+}
+
+/// `[chiller]`: an immersion chiller coil in the contents.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioChiller {
+    pub flow_l_min: Option<f64>,
+    pub inlet_c: Option<f64>,
+    pub ua_w_k: Option<f64>,
+    pub target_c: Option<f64>,
+}
+
+/// `[electricity]`: what the heater's electricity costs, and how well a
+/// Peltier cooler pumps.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioElectricity {
+    /// Price of a kWh, in whatever currency.
+    pub tariff_per_kwh: Option<f64>,
+    /// A cooler's COP as a fraction of the Carnot COP, and the most it gets to.
+    pub carnot_fraction: Option<f64>,
+    pub max_cop: Option<f64>,
+}
+
+/// `[target]`: the range the contents are meant to be brought into.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioTarget {
+    pub low_c: f64,
+    pub high_c: f64,
+}
+
+/// `[room]`: a closed room around the bottle, warmed or cooled by it, with
+/// `[outside]` as the outdoors beyond its walls.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioRoom {
+    pub volume_m3: f64,
+    /// Walls, floor and furniture that follow the air (kJ/K); none by default.
+    pub thermal_mass_kj_k: Option<f64>,
+    /// Conductance of the walls and windows to the outdoors; 0, sealed, by default.
+    pub ua_w_k: Option<f64>,
+    /// Other heat sources in the room (W), negative for an air conditioner.
+    pub load_w: Option<f64>,
+}
+
+/// `[checkpoints]`: times the run's state is exported at, for runs nobody watches.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScenarioCheckpoints {
+    /// Hours into the run.
+    pub times_h: Vec<f64>,
+    /// Also every this many hours up to duration_h.
+    pub every_h: Option<f64>,
+    /// Save a picture of the window too; runs without one skip it.
+    pub screenshot: bool,
+}
+
+/// `[[variants]]`: one run of a comparison, named, with the `[initial]`,
+/// `[bottle]` and `[outside]` keys it changes from the scenario's.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioVariant {
+    pub name: String,
+    /// Variants in the same group are ranked against each other.
+    #[serde(default)]
+    pub group: String,
+    #[serde(default)]
+    pub initial: ScenarioInitial,
+    #[serde(default)]
+    pub bottle: ScenarioBottle,
+    #[serde(default)]
+    pub outside: ScenarioOutside,
+}
+
+impl ScenarioInitial {
+    // these values, with the ones left out taken from `base`
+    fn over(&self, base: &Self) -> Self {
+        Self {
+            water_kg: self.water_kg.or(base.water_kg),
+            ice_kg: self.ice_kg.or(base.ice_kg),
+            air_kg: self.air_kg.or(base.air_kg),
+            salt_kg: self.salt_kg.or(base.salt_kg),
+            liquid: self.liquid.clone().or_else(|| base.liquid.clone()),
+            contents_c: self.contents_c.or(base.contents_c),
+            supercooling: self.supercooling.or(base.supercooling),
+            dissolved_air: self.dissolved_air.or(base.dissolved_air),
+        }
+    }
+}
+
+impl ScenarioBottle {
+    // these values, with the ones left out taken from `base`
+    fn over(&self, base: &Self) -> Self {
+        Self {
+            shape: self.shape.clone().or_else(|| base.shape.clone()),
+            radius_m: self.radius_m.or(base.radius_m),
+            height_m: self.height_m.or(base.height_m),
+            area_m2: self.area_m2.or(base.area_m2),
+            volume_m3: self.volume_m3.or(base.volume_m3),
+            outline: self.outline.clone().or_else(|| base.outline.clone()),
+            material: self.material.clone().or_else(|| base.material.clone()),
+            insulation: self.insulation.clone().or_else(|| base.insulation.clone()),
+            gap_gas: self.gap_gas.clone().or_else(|| base.gap_gas.clone()),
+            capped: self.capped.or(base.capped),
+            heater_w: self.heater_w.or(base.heater_w),
+            conduction_only: self.conduction_only.or(base.conduction_only),
+        }
+    }
+}
+
+impl ScenarioOutside {
+    // these values, with the ones left out taken from `base`
+    fn over(&self, base: &Self) -> Self {
+        Self {
+            temp_c: self.temp_c.or(base.temp_c),
+            profile: self.profile.clone().or_else(|| base.profile.clone()),
+            amplitude_c: self.amplitude_c.or(base.amplitude_c),
+            schedule: self.schedule.clone().or_else(|| base.schedule.clone()),
+            wind_m_s: self.wind_m_s.or(base.wind_m_s),
+            humidity: self.humidity.or(base.humidity),
+            sun_w_m2: self.sun_w_m2.or(base.sun_w_m2),
+            sun_from: self.sun_from.clone().or_else(|| base.sun_from.clone()),
+            sun_until: self.sun_until.clone().or_else(|| base.sun_until.clone()),
+            bath: self.bath.or(base.bath),
+        }
+    }
+}
+
+/// One problem found in a scenario file, at a 1-based line when it can be
+/// pinned down.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScenarioError {
+    pub line: Option<usize>,
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.key.is_empty()) {
+            (Some(line), false) => write!(f, "line {line}: {}: {}", self.key, self.message),
+            (Some(line), true) => write!(f, "line {line}: {}", self.message),
+            (None, false) => write!(f, "{}: {}", self.key, self.message),
+            (None, true) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Scenario {
+    /// Parses and checks a scenario file: TOML syntax and known keys first,
+    /// then physical consistency and schedule ordering. Returns every problem
+    /// found, not just the first.
+    pub fn from_toml(source: &str) -> Result<Scenario, Vec<ScenarioError>> {
+        let scenario: Scenario = toml::from_str(source).map_err(|e| {
+            vec![ScenarioError {
+                line: e.span().map(|span| line_at(source, span.start)),
+                key: String::new(),
+                message: e.message().trim().to_string(),
+            }]
+        })?;
+        let errors = scenario.check(source);
+        if errors.is_empty() {
+            Ok(scenario)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Problems with the `locked` list: names not among `known`, the fields
+    /// the app offers.
+    pub fn check_locked(&self, source: &str, known: &[&str]) -> Vec<ScenarioError> {
+        self.locked
+            .iter()
+            .filter(|name| !known.contains(&name.as_str()))
+            .map(|name| ScenarioError {
+                line: line_of(source, None, "locked"),
+                key: "locked".to_string(),
+                message: format!("'{name}' is not a field that can be locked"),
+            })
+            .collect()
+    }
+
+    /// The scenario variant `index` runs: its initial contents, bottle and
+    /// outside over this one's, and no variants of its own.
+    pub fn variant(&self, index: usize) -> Scenario {
+        let variant = &self.variants[index];
+        Scenario {
+            initial: variant.initial.over(&self.initial),
+            bottle: variant.bottle.over(&self.bottle),
+            outside: variant.outside.over(&self.outside),
+            variants: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// The checkpoint times (s) listed and repeated, in time order.
+    pub fn checkpoints(&self) -> Checkpoints {
+        let c = &self.checkpoints;
+        let mut times: Vec<f64> = c.times_h.iter().map(|h| h * 3600.0).collect();
+        if let (Some(every), Some(duration)) = (c.every_h.filter(|e| *e > 0.0), self.duration_h) {
+            times.extend((1..).map(|n| n as f64 * every).take_while(|h| *h <= duration).map(|h| h * 3600.0));
+        }
+        Checkpoints::new(times, c.screenshot)
+    }
+
+    /// The bottle shape, `None` when the file leaves it to the default.
+    pub fn geometry(&self) -> Result<Option<Geometry>, String> {
+        let b = &self.bottle;
+        let need = |value: Option<f64>, key: &str| value.ok_or(format!("shape '{}' needs {key}", b.shape.as_deref().unwrap_or_default()));
+        match b.shape.as_deref() {
+            None => Ok(None),
+            Some("cylinder") => Ok(Some(Geometry::Cylinder {
+                radius: need(b.radius_m, "radius_m")?,
+                height: need(b.height_m, "height_m")?,
+            })),
+            Some("sphere") => Ok(Some(Geometry::Sphere { radius: need(b.radius_m, "radius_m")? })),
+            Some("custom") => Ok(Some(Geometry::Custom {
+                area: need(b.area_m2, "area_m2")?,
+                volume: need(b.volume_m3, "volume_m3")?,
+            })),
+            Some("outline") => {
+                let d = b.outline.as_deref().ok_or("shape 'outline' needs outline")?;
+                Geometry::from_svg_path(d, b.height_m).map(Some).map_err(|e| format!("outline: {e}"))
+            }
+            Some(other) => Err(format!("unknown shape '{other}' (expected cylinder, sphere, custom or outline)")),
+        }
+    }
+
+    /// The hours of the day (from, until) the sun shines, when they are set.
+    pub fn sun_hours(&self) -> Result<Option<(f64, f64)>, String> {
+        let hour = |text: &Option<String>| match text {
+            None => Ok(None),
+            Some(text) => SimDateTime::parse_time_of_day(text).map(|t| Some(t.hour_of_day())).ok_or(format!("'{text}' is not a clock time like 09:00")),
+        };
+        match (hour(&self.outside.sun_from)?, hour(&self.outside.sun_until)?) {
+            (None, None) => Ok(None),
+            (Some(from), Some(until)) if from < until => Ok(Some((from, until))),
+            (Some(_), Some(_)) => Err("sun_from must be before sun_until".to_string()),
+            _ => Err("needs both sun_from and sun_until".to_string()),
+        }
+    }
+
+    pub fn liquid(&self) -> Result<Option<Liquid>, String> {
+        let Some(name) = self.initial.liquid.as_deref() else {
+            return Ok(None);
+        };
+        match Liquid::ALL.iter().find(|l| l.label().eq_ignore_ascii_case(name)) {
+            Some(&l) => Ok(Some(l)),
+            None => Err(format!("unknown liquid '{name}' (expected {})", labels(Liquid::ALL.iter().map(|l| l.label())))),
+        }
+    }
+
+    pub fn material(&self) -> Result<Option<WallMaterial>, String> {
+        let Some(name) = self.bottle.material.as_deref() else {
+            return Ok(None);
+        };
+        match WallMaterial::ALL.iter().find(|m| m.label().eq_ignore_ascii_case(name)) {
+            Some(&m) => Ok(Some(m)),
+            None => Err(format!("unknown material '{name}' (expected {})", labels(WallMaterial::ALL.iter().map(|m| m.label())))),
+        }
+    }
+
+    pub fn insulation(&self) -> Result<Option<InsulationPreset>, String> {
+        let Some(name) = self.bottle.insulation.as_deref() else {
+            return Ok(None);
+        };
+        match InsulationPreset::ALL.iter().find(|p| p.label().eq_ignore_ascii_case(name)) {
+            Some(&p) => Ok(Some(p)),
+            None => Err(format!("unknown insulation '{name}' (expected {})", labels(InsulationPreset::ALL.iter().map(|p| p.label())))),
+        }
+    }
+
+    pub fn gap_gas(&self) -> Result<Option<GapGas>, String> {
+        let Some(name) = self.bottle.gap_gas.as_deref() else {
+            return Ok(None);
+        };
+        match GapGas::ALL.iter().find(|g| g.label().eq_ignore_ascii_case(name)) {
+            Some(&g) => Ok(Some(g)),
+            None => Err(format!("unknown gap gas '{name}' (expected {})", labels(GapGas::ALL.iter().map(|g| g.label())))),
+        }
+    }
+
+    /// The outside temperature profile around `outside_temp`, `None` when the
+    /// file leaves it to the default.
+    pub fn ambient(&self, outside_temp: f64) -> Option<AmbientProfile> {
+        let outside = &self.outside;
+        let schedule = || outside.schedule.iter().flatten().map(|&[t, temp]| (t, temp)).collect();
+        match outside.profile.as_deref()? {
+            "constant" => Some(AmbientProfile::Constant),
+            "day_night" => Some(AmbientProfile::day_night(outside_temp, outside.amplitude_c.unwrap_or(6.0))),
+            "steps" => Some(AmbientProfile::Steps(schedule())),
+            "table" => Some(AmbientProfile::Table(schedule())),
+            _ => None,
+        }
+    }
+
+    /// The integrals chosen under `[integrals]`, in a fixed order.
+    pub fn doses(&self) -> Vec<DoseKind> {
+        let integrals = &self.integrals;
+        let mut kinds: Vec<DoseKind> = integrals.hours_above_c.iter().map(|&t| DoseKind::HoursAbove(t)).collect();
+        // the first is the hold time, kept apart
+        kinds.extend(integrals.hours_below_c.iter().skip(1).map(|&t| DoseKind::HoursBelow(t)));
+        if integrals.pasteurization_units {
+            kinds.push(DoseKind::PasteurizationUnits);
+        }
+        if integrals.chill_units {
+            kinds.push(DoseKind::ChillUnits);
+        }
+        kinds
+    }
+
+    /// Sets `sim` up for this scenario and resets it, ready to start. Meant for
+    /// a scenario that passed the check; what the file leaves out keeps the
+    /// value `sim` already has.
+    pub fn apply(&self, sim: &mut Simulation) {
+        let (initial, outside) = (&self.initial, &self.outside);
+        let init = sim.init_mut();
+        for (value, target) in [
+            (initial.water_kg, &mut init.water),
+            (initial.ice_kg, &mut init.ice),
+            (initial.air_kg, &mut init.air),
+            (initial.salt_kg, &mut init.salt),
+            (initial.contents_c, &mut init.system_temp),
+            (outside.temp_c, &mut init.outside_temp),
+        ] {
+            if let Some(v) = value {
+                *target = v;
+            }
+        }
+        if let Ok(Some(liquid)) = self.liquid() {
+            sim.set_liquid(liquid);
+        }
+        if let Ok(Some(geometry)) = self.geometry() {
+            sim.set_geometry(geometry);
+        }
+        if let Ok(Some(material)) = self.material() {
+            sim.wall_mut().set_material(material);
+        }
+        if let Ok(Some(insulation)) = self.insulation() {
+            sim.wall_mut().layers = insulation.layers();
+        }
+        if let Ok(Some(gas)) = self.gap_gas() {
+            sim.wall_mut().set_gap_gas(gas);
+        }
+        if let Some(capped) = self.bottle.capped {
+            sim.set_capped(capped);
+        }
+        if let Some(power) = self.bottle.heater_w {
+            sim.set_heater_power(power);
+        }
+        if let Some(on) = self.bottle.conduction_only {
+            sim.set_conduction_only(on);
+        }
+        if let Some(on) = initial.supercooling {
+            sim.nucleation_mut().enabled = on;
+        }
+        if let Some(air) = initial.dissolved_air {
+            sim.nucleation_mut().dissolved_air = air;
+        }
+        if let Some(profile) = self.ambient(sim.init().outside_temp) {
+            sim.set_ambient(profile);
+        }
+        if let Some(wind) = outside.wind_m_s {
+            sim.set_wind_speed(wind);
+        }
+        if let Some(humidity) = outside.humidity {
+            sim.set_humidity(humidity);
+        }
+        if let Some(sun) = outside.sun_w_m2 {
+            sim.solar_mut().irradiance = sun;
+        }
+        if let Some(start) = self.start.as_deref().and_then(SimDateTime::parse_time_of_day) {
+            sim.set_start_datetime(Some(start));
+        }
+        if let Ok(Some(hours)) = self.sun_hours() {
+            sim.solar_mut().hours = Some(hours);
+        }
+        if let Some(bath) = outside.bath {
+            sim.set_bath(bath);
+        }
+        if let Some(target) = &self.target {
+            sim.set_target_band(Some((target.low_c, target.high_c)));
+        }
+        sim.set_annotations(self.annotations.clone());
+        sim.set_checkpoints(self.checkpoints());
+        if let Some(room) = &self.room {
+            let thermal_mass = room.thermal_mass_kj_k.unwrap_or(0.0) * 1000.0;
+            sim.set_room(Some(Room::new(room.volume_m3, thermal_mass, room.ua_w_k.unwrap_or(0.0), room.load_w.unwrap_or(0.0))));
+        }
+        let chiller = sim.chiller_mut();
+        for (value, target) in [
+            (self.chiller.flow_l_min.map(|f| f / 60.0), &mut chiller.flow),
+            (self.chiller.inlet_c, &mut chiller.inlet_temp),
+            (self.chiller.ua_w_k, &mut chiller.ua),
+            (self.chiller.target_c, &mut chiller.target),
+        ] {
+            if let Some(v) = value {
+                *target = v;
+            }
+        }
+        let electricity = sim.electricity_mut();
+        for (value, target) in [
+            (self.electricity.tariff_per_kwh, &mut electricity.tariff),
+            (self.electricity.carnot_fraction, &mut electricity.carnot_fraction),
+            (self.electricity.max_cop, &mut electricity.max_cop),
+        ] {
+            if let Some(v) = value {
+                *target = v;
+            }
+        }
+        if let Some(&threshold) = self.integrals.hours_below_c.first() {
+            sim.set_hold_threshold(threshold);
+        }
+        let doses = self.doses();
+        if !doses.is_empty() {
+            sim.set_doses(&doses);
+        }
+        if let Some(hours) = self.duration_h {
+            sim.set_duration(Some(hours * 3600.0));
+        }
+        // a rate out of range is reported by the check and changes nothing here
+        if let Some(hz) = self.tick_rate_hz {
+            let _ = sim.set_tick_rate(hz);
+        }
+        let experiment = sim.experiment_mut();
+        if !self.name.is_empty() {
+            experiment.name = self.name.clone();
+        }
+        if !self.notes.is_empty() {
+            experiment.notes = self.notes.clone();
+        }
+        sim.reset_from_init();
+        if let Some(scale) = self.time_scale.filter(|s| TIME_SCALES.contains(s)) {
+            while sim.time_scale() != scale {
+                sim.cycle_time_scale();
+            }
+        }
+    }
+
+    fn check(&self, source: &str) -> Vec<ScenarioError> {
+        let mut errors = Vec::new();
+        let mut report = |section: Option<&str>, key: &str, message: String| {
+            errors.push(ScenarioError {
+                line: line_of(source, section, key),
+                key: match section {
+                    Some(s) => format!("{s}.{key}"),
+                    None => key.to_string(),
+                },
+                message,
+            });
+        };
+
+        if let Some(h) = self.duration_h.filter(|h| *h <= 0.0) {
+            report(None, "duration_h", format!("must be positive, got {h}"));
+        }
+        if let Some(s) = self.time_scale.filter(|s| !TIME_SCALES.contains(s)) {
+            let offered: Vec<String> = TIME_SCALES.iter().map(|s| s.to_string()).collect();
+            report(None, "time_scale", format!("{s} is not one of {}", offered.join(", ")));
+        }
+        if let Some(hz) = self.tick_rate_hz {
+            if let Err(message) = Simulation::check_tick_rate(hz) {
+                report(None, "tick_rate_hz", message);
+            }
+        }
+        if let Some(start) = self.start.as_deref().filter(|s| SimDateTime::parse_time_of_day(s).is_none()) {
+            report(None, "start", format!("'{start}' is not a clock time like 09:00"));
+        }
+
+        let init = &self.initial;
+        for (key, value) in [("water_kg", init.water_kg), ("ice_kg", init.ice_kg), ("air_kg", init.air_kg), ("salt_kg", init.salt_kg)] {
+            if let Some(v) = value.filter(|v| *v < 0.0) {
+                report(Some("initial"), key, format!("mass can't be negative, got {v}"));
+            }
+        }
+        if let (Some(salt), Some(water)) = (init.salt_kg, init.water_kg) {
+            if salt > SALT_SOLUBILITY * water && salt > 0.0 {
+                report(Some("initial"), "salt_kg", format!("{salt} kg of salt won't dissolve in {water} kg of water (at most {:.3} kg)", SALT_SOLUBILITY * water));
+            }
+        }
+        if let Err(message) = self.liquid() {
+            report(Some("initial"), "liquid", message);
+        }
+        if let Some(air) = init.dissolved_air.filter(|a| !(0.0..=1.0).contains(a)) {
+            report(Some("initial"), "dissolved_air", format!("must be from 0 (none) to 1 (saturated), got {air}"));
+        }
+        for (section, key, value) in [("initial", "contents_c", init.contents_c), ("outside", "temp_c", self.outside.temp_c)] {
+            if let Some(t) = value.filter(|t| *t <= -KELVIN) {
+                report(Some(section), key, format!("{t} °C is below absolute zero"));
+            }
+        }
+
+        let geometry = match self.geometry() {
+            Ok(g) => g,
+            Err(message) => {
+                report(Some("bottle"), "shape", message);
+                None
+            }
+        };
+        for (key, value) in [("radius_m", self.bottle.radius_m), ("height_m", self.bottle.height_m), ("area_m2", self.bottle.area_m2), ("volume_m3", self.bottle.volume_m3)] {
+            if let Some(v) = value.filter(|v| *v <= 0.0) {
+                report(Some("bottle"), key, format!("must be positive, got {v}"));
+            }
+        }
+        // the water and ice have to fit in the bottle; the air is squeezed into what's left
+        if let Some(g) = geometry.filter(|g| g.volume() > 0.0) {
+            let defaults = crate::simulation::InitialConditions::default();
+            let water = init.water_kg.unwrap_or(defaults.water).max(0.0);
+            let ice = init.ice_kg.unwrap_or(defaults.ice).max(0.0);
+            let density = self.liquid().ok().flatten().unwrap_or_default().properties().density;
+            let volume = water / density + ice / DENSITY_ICE;
+            if volume > g.volume() {
+                report(
+                    Some("initial"),
+                    "water_kg",
+                    format!("the water and ice take {:.3} L but the bottle holds {:.3} L", volume * 1000.0, g.volume() * 1000.0),
+                );
+            }
+        }
+        if let Err(message) = self.material() {
+            report(Some("bottle"), "material", message);
+        }
+        if let Err(message) = self.insulation() {
+            report(Some("bottle"), "insulation", message);
+        }
+        let double_walled = self.insulation().ok().flatten().is_some_and(|p| p.layers().iter().any(|l| matches!(l, InsulationLayer::Gap { .. })));
+        match self.gap_gas() {
+            Err(message) => report(Some("bottle"), "gap_gas", message),
+            Ok(Some(_)) if !double_walled => report(Some("bottle"), "gap_gas", "needs a double-walled insulation (Vacuum or Vac+sleeve)".to_string()),
+            _ => {}
+        }
+
+        let outside = &self.outside;
+        let scheduled = matches!(outside.profile.as_deref(), Some("steps" | "table"));
+        match outside.profile.as_deref() {
+            None | Some("constant" | "day_night" | "steps" | "table") => {}
+            Some(other) => report(Some("outside"), "profile", format!("unknown profile '{other}' (expected constant, day_night, steps or table)")),
+        }
+        if let Some(a) = outside.amplitude_c.filter(|a| *a < 0.0) {
+            report(Some("outside"), "amplitude_c", format!("must not be negative, got {a}"));
+        }
+        match &outside.schedule {
+            Some(_) if !scheduled => report(Some("outside"), "schedule", "is only used by the steps and table profiles".to_string()),
+            None if scheduled => report(Some("outside"), "profile", "needs a schedule of [time_s, temp_c] pairs".to_string()),
+            Some(schedule) if schedule.is_empty() => report(Some("outside"), "schedule", "is empty".to_string()),
+            Some(schedule) => {
+                if schedule[0][0] < 0.0 {
+                    report(Some("outside"), "schedule", format!("entry 1 starts at a negative time ({} s)", schedule[0][0]));
+                }
+                for (i, pair) in schedule.windows(2).enumerate() {
+                    if pair[1][0] <= pair[0][0] {
+                        report(
+                            Some("outside"),
+                            "schedule",
+                            format!("entry {} ({} s) is not after entry {} ({} s); times must increase", i + 2, pair[1][0], i + 1, pair[0][0]),
+                        );
+                    }
+                }
+                for (i, entry) in schedule.iter().enumerate().filter(|(_, e)| e[1] <= -KELVIN) {
+                    report(Some("outside"), "schedule", format!("entry {} ({} °C) is below absolute zero", i + 1, entry[1]));
+                }
+            }
+            None => {}
+        }
+        if let Some(w) = outside.wind_m_s.filter(|w| *w < 0.0) {
+            report(Some("outside"), "wind_m_s", format!("must not be negative, got {w}"));
+        }
+        if let Some(h) = outside.humidity.filter(|h| !(0.0..=1.0).contains(h)) {
+            report(Some("outside"), "humidity", format!("is a fraction between 0 and 1, got {h}"));
+        }
+        if let Some(s) = outside.sun_w_m2.filter(|s| *s < 0.0) {
+            report(Some("outside"), "sun_w_m2", format!("must not be negative, got {s}"));
+        }
+        match self.sun_hours() {
+            Err(message) => report(Some("outside"), "sun_from", message),
+            Ok(Some(_)) if self.start.is_none() => report(Some("outside"), "sun_from", "needs start to know the time of day".to_string()),
+            Ok(_) => {}
+        }
+        if let Some(target) = &self.target {
+            for (key, t) in [("low_c", target.low_c), ("high_c", target.high_c)] {
+                if t <= -KELVIN {
+                    report(Some("target"), key, format!("{t} °C is below absolute zero"));
+                }
+            }
+            if target.high_c <= target.low_c {
+                report(Some("target"), "high_c", format!("must be above low_c ({} °C), got {}", target.low_c, target.high_c));
+            }
+        }
+        if let Some(room) = &self.room {
+            if room.volume_m3 <= 0.0 {
+                report(Some("room"), "volume_m3", format!("must be positive, got {}", room.volume_m3));
+            }
+            for (key, value) in [("thermal_mass_kj_k", room.thermal_mass_kj_k), ("ua_w_k", room.ua_w_k)] {
+                if let Some(v) = value.filter(|v| *v < 0.0) {
+                    report(Some("room"), key, format!("must not be negative, got {v}"));
+                }
+            }
+        }
+        let chiller = &self.chiller;
+        if let Some(f) = chiller.flow_l_min.filter(|f| *f < 0.0) {
+            report(Some("chiller"), "flow_l_min", format!("must not be negative, got {f}"));
+        }
+        if let Some(ua) = chiller.ua_w_k.filter(|ua| *ua <= 0.0) {
+            report(Some("chiller"), "ua_w_k", format!("must be positive, got {ua}"));
+        }
+        for (key, value) in [("inlet_c", chiller.inlet_c), ("target_c", chiller.target_c)] {
+            if let Some(t) = value.filter(|t| *t <= -KELVIN) {
+                report(Some("chiller"), key, format!("{t} °C is below absolute zero"));
+            }
+        }
+        let electricity = &self.electricity;
+        if let Some(tariff) = electricity.tariff_per_kwh.filter(|t| *t < 0.0) {
+            report(Some("electricity"), "tariff_per_kwh", format!("must not be negative, got {tariff}"));
+        }
+        for (key, value) in [("carnot_fraction", electricity.carnot_fraction), ("max_cop", electricity.max_cop)] {
+            if let Some(v) = value.filter(|v| *v <= 0.0) {
+                report(Some("electricity"), key, format!("must be positive, got {v}"));
+            }
+        }
+        if let Some(f) = electricity.carnot_fraction.filter(|f| *f > 1.0) {
+            report(Some("electricity"), "carnot_fraction", format!("can't beat the Carnot COP, got {f}"));
+        }
+        for (key, temps) in [("hours_above_c", &self.integrals.hours_above_c), ("hours_below_c", &self.integrals.hours_below_c)] {
+            for t in temps.iter().filter(|t| **t <= -KELVIN) {
+                report(Some("integrals"), key, format!("{t} °C is below absolute zero"));
+            }
+        }
+        let checkpoints = &self.checkpoints;
+        for h in checkpoints.times_h.iter().filter(|h| **h <= 0.0) {
+            report(Some("checkpoints"), "times_h", format!("times must be positive, got {h}"));
+        }
+        if let Some(duration) = self.duration_h {
+            for h in checkpoints.times_h.iter().filter(|h| **h > duration) {
+                report(Some("checkpoints"), "times_h", format!("{h} h is after the run ends (duration_h = {duration})"));
+            }
+        }
+        match checkpoints.every_h {
+            Some(e) if e <= 0.0 => report(Some("checkpoints"), "every_h", format!("must be positive, got {e}")),
+            Some(_) if self.duration_h.is_none() => report(Some("checkpoints"), "every_h", "needs duration_h to know when to stop".to_string()),
+            _ => {}
+        }
+        for (i, annotation) in self.annotations.iter().enumerate() {
+            if let Some(message) = annotation.problem() {
+                errors.push(ScenarioError { line: entry_line(source, "annotations", i), key: "annotations".to_string(), message: format!("entry {} {message}", i + 1) });
+            }
+        }
+        for (i, variant) in self.variants.iter().enumerate() {
+            let mut problems = Vec::new();
+            if variant.name.trim().is_empty() {
+                problems.push("needs a name".to_string());
+            } else if self.variants[..i].iter().any(|v| v.name == variant.name) {
+                problems.push(format!("has the same name as an earlier one, '{}'", variant.name));
+            }
+            // the problems of the setup it runs, less those already reported for the scenario's own
+            for e in self.variant(i).check("") {
+                if !errors.iter().any(|b| b.key == e.key && b.message == e.message) {
+                    problems.push(format!("{}: {}", e.key, e.message));
+                }
+            }
+            let line = entry_line(source, "variants", i);
+            errors.extend(problems.into_iter().map(|p| ScenarioError { line, key: "variants".to_string(), message: format!("entry {} {p}", i + 1) }));
+        }
+        errors
+    }
+}
+
+fn labels<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    names.collect::<Vec<_>>().join(", ")
+}
+
+// 1-based line holding byte `offset`
+fn line_at(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+// 1-based line of the header of entry `index` of the array of tables `[[name]]`
+fn entry_line(source: &str, name: &str, index: usize) -> Option<usize> {
+    let header = format!("[[{name}]]");
+    source.lines().enumerate().filter(|(_, line)| line.trim() == header).nth(index).map(|(i, _)| i + 1)
+}
+
+// 1-based line where `key` is set, inside `[section]` or at the top level
+fn line_of(source: &str, section: Option<&str>, key: &str) -> Option<usize> {
+    let mut current: Option<&str> = None;
+    for (i, line) in source.lines().enumerate() {
+        let line = line.trim();
+        // a table header, `[[name]]` for an entry of an array of tables, not a
+        // row of an array spread over several lines
+        let header = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')).map(|h| h.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(h).trim());
+        if let Some(name) = header.filter(|h| h.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.')) {
+            current = Some(name);
+            continue;
+        }
+        let sets_key = line.strip_prefix(key).is_some_and(|rest| rest.trim_start().starts_with('='));
+        if sets_key && current == section {
+            return Some(i + 1);
+        }
+    }
+    None
+}
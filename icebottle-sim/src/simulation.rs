@@ -0,0 +1,1445 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ambient::AmbientProfile;
+use crate::annotation::Annotation;
+use crate::checkpoint::Checkpoints;
+use crate::chiller::Chiller;
+use crate::clock::SimDateTime;
+use crate::column::WaterColumn;
+use crate::controller::Controller;
+use crate::constants::{
+    AIR_WALL_FRACTION, AMBIENT_HUMIDITY, ATMOSPHERIC_PRESSURE, BOILING_POINT, CAP_SEAL_LEAK, CONDENSATE_FILM, CONDUCTIVITY_AIR, CONDUCTIVITY_WATER, CP_ICE, CV_AIR,
+    DENSITY_ICE, H_ICE_WATER, H_INSIDE, H_OUTSIDE, H_SHAKEN, H_WATER_BATH, KELVIN,
+    LATENT_SUBLIMATION, MASS_TRANSFER_NECK, MELTING_SLOPE, NECK_AREA, R_AIR, RADIATION_REF_TEMP, RHO_CP_AIR, STEFAN_BOLTZMANN,
+    SHAKE_SETTLE, UA_AIR_SURFACE, UA_NECK,
+};
+use crate::cubes::IceCubes;
+use crate::electricity::Electricity;
+use crate::events::{Event, EventKind};
+use crate::experiment::Experiment;
+use crate::field2d::TemperatureField;
+use crate::geometry::Geometry;
+use crate::heat::{EnergyLedger, EnergySplit, HeatFlow};
+use crate::integrator::Integrator;
+use crate::liquid::Liquid;
+use crate::metrics::{BandCrossing, Dose, DoseKind, Envelope, HoldTime, TargetBand};
+use crate::nucleation::Nucleation;
+use crate::reduce;
+use crate::rng::Rng;
+use crate::room::Room;
+use crate::state::SystemState;
+use crate::vapor;
+use crate::solar::SolarConfig;
+use crate::wall::{Surroundings, WallConfig, WallExchange};
+
+/// Internal physics step in seconds of simulated time, a 100 Hz tick, unless
+/// changed with `set_tick_rate`. Front-ends accumulate real frame time (times
+/// `time_scale`) and call `step` in slices of `step_size` so results don't
+/// depend on the frame rate.
+pub const FIXED_DT: f64 = 0.01;
+
+/// Simulated seconds a run without a duration of its own is taken to last
+/// where it has to end: headless, in sweeps, comparisons and thumbnails.
+pub const DEFAULT_SPAN: f64 = 24.0 * 3600.0;
+
+/// Simulated seconds per real second the front-end offers, in the order the
+/// speed button cycles through them. The top two suit slow systems such as an
+/// aquarium, where an hour passes in a second.
+pub const TIME_SCALES: [f64; 7] = [1.0, 2.0, 5.0, 10.0, 100.0, 1000.0, 3600.0];
+
+/// Physics steps per simulated second a run may take: from one every 100 s,
+/// which the headspace air still integrates in pieces, to 10 kHz.
+pub const TICK_RATE_RANGE: std::ops::RangeInclusive<f64> = 0.01..=10_000.0;
+
+// Agitation below which a shake has settled
+const SETTLED: f64 = 1e-3;
+// Most pieces a step is integrated in for the headspace air to keep up
+const MAX_PIECES: f64 = 100.0;
+
+/// Values the user edits before a run; applied to the state on start/reset.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct InitialConditions {
+    pub water: f64,
+    pub ice: f64,
+    pub air: f64,
+    pub salt: f64,
+    pub system_temp: f64,
+    pub outside_temp: f64,
+}
+
+impl Default for InitialConditions {
+    fn default() -> Self {
+        Self {
+            water: 0.5,
+            ice: 0.1,
+            air: 0.02,
+            salt: 0.0,
+            system_temp: 5.0,
+            outside_temp: 25.0,
+        }
+    }
+}
+
+impl InitialConditions {
+    /// Random but physically plausible starting values, the same for the same
+    /// seed: 0.1-0.9 kg water, up to 0.3 kg ice, 5-30 g air, salt in one run
+    /// of four (up to 20 g), contents at -5..25 °C and outside air at -20..40 °C.
+    pub fn randomized(seed: u64) -> Self {
+        // spread small consecutive seeds over the generator's state
+        let mut rng = Rng::new((seed ^ 0x5851_F42D_4C95_7F2D).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        // rounded so the values read well as exercise questions
+        let round = |value: f64, step: f64| (value / step).round() * step;
+        let water = round(rng.range(0.1, 0.9), 0.01);
+        let ice = round(rng.range(0.0, 0.3), 0.01);
+        let air = round(rng.range(0.005, 0.03), 0.001);
+        let salty = rng.next_uniform() < 0.25;
+        let salt = round(rng.range(0.0, 0.02), 0.001);
+        Self {
+            water,
+            ice,
+            air,
+            salt: if salty { salt } else { 0.0 },
+            system_temp: round(rng.range(-5.0, 25.0), 0.5),
+            outside_temp: round(rng.range(-20.0, 40.0), 0.5),
+        }
+    }
+
+    /// Bottle volume that holds the contents with the air at atmospheric
+    /// pressure and the initial temperature, i.e. the bottle was capped then.
+    fn bottle_volume(self, liquid: Liquid) -> f64 {
+        let state = self.to_state(liquid);
+        let air_volume = self.air * R_AIR * (self.system_temp + KELVIN) / ATMOSPHERIC_PRESSURE;
+        state.condensed_volume() + air_volume
+    }
+
+    fn to_state(self, liquid: Liquid) -> SystemState {
+        let mut state = SystemState {
+            mass_water: self.water,
+            mass_ice: self.ice,
+            mass_air: self.air,
+            mass_solute: self.salt,
+            mass_vapor: 0.0,
+            temp_water: self.system_temp,
+            temp_ice: self.system_temp,
+            temp_air: self.system_temp,
+            pressure_shift: 0.0,
+            liquid,
+        };
+        state.temp_ice = self.system_temp.min(state.freezing_point());
+        state
+    }
+}
+
+/// Column names of `Simulation::sample`, in order.
+pub const SAMPLE_COLUMNS: [&str; 8] = [
+    "time_s",
+    "water_kg",
+    "ice_kg",
+    "t_water_c",
+    "t_ice_c",
+    "t_air_c",
+    "outside_c",
+    "wall_heat_w",
+];
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Simulation {
+    state: SystemState,
+    outside_temp: f64,
+    time_seconds: f64,
+    running: bool,
+    time_scale: f64, // one of TIME_SCALES
+    step_size: f64,  // s of simulated time per physics step
+    duration: Option<f64>, // s of simulated time after which the run stops
+
+    // initial GUI-editable values
+    init: InitialConditions,
+    liquid: Liquid, // what the water is, filled in with the initial conditions
+
+    wall: WallConfig,
+    geometry: Geometry,
+    ambient: AmbientProfile,
+    solar: SolarConfig,
+    wind_speed: f64, // m/s
+    bath: bool, // the bottle stands in a water bath at the outside temperature
+    room: Option<Room>, // a closed room between the bottle and the outdoors
+    contact_power: f64, // W conducted in from bottles touching this one, this step
+    heater_power: f64, // W into the contents, negative for a cooler; the rating under control
+    heater_output: f64, // W actually delivered this step
+    controller: Controller,
+    chiller: Chiller,
+    heater_energy: f64, // J delivered by the heater since the reset
+    electricity: Electricity,
+    energy: EnergyLedger,
+    capped: bool,
+    bottle_volume: f64, // m^3, fixed when the initial conditions are applied
+    vapor_lost: f64, // kg of steam that escaped through the open neck
+    ice_sublimed: f64, // kg of ice lost straight to vapour, kept apart from `vapor_lost`
+    humidity: f64,   // relative humidity of the outside air, 0..1
+    condensate: f64, // kg of water clinging to the outside of the wall
+    condensate_dripped: f64,
+    condensation_rate: f64, // kg/s onto the outside, from the last step
+    start_datetime: Option<SimDateTime>,
+    hold_time: HoldTime,
+    doses: Vec<Dose>,
+    target_band: Option<TargetBand>,
+    annotations: Vec<Annotation>,
+    checkpoints: Checkpoints,
+    envelope: Envelope,
+    column: WaterColumn,
+    ice_pieces: usize, // 0 keeps the ice lumped
+    cubes: IceCubes,
+    field: Option<TemperatureField>,
+    events: Vec<Event>,
+    experiment: Experiment,
+    nucleation: Nucleation,
+    pressure_melting: bool, // the melting point follows the headspace pressure
+    conduction_only: bool,  // no convection anywhere, for teaching
+    agitation: f64, // 0..1, how hard the contents still slosh from a shake
+    integrator: Integrator,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Self::with_initial(InitialConditions::default())
+    }
+
+    pub fn with_initial(init: InitialConditions) -> Self {
+        Self {
+            state: init.to_state(Liquid::default()),
+            outside_temp: init.outside_temp,
+            time_seconds: 0.0,
+            running: false,
+            time_scale: 1.0,
+            step_size: FIXED_DT,
+            duration: None,
+            init,
+            liquid: Liquid::default(),
+            wall: WallConfig::default(),
+            geometry: Geometry::default(),
+            ambient: AmbientProfile::default(),
+            solar: SolarConfig::default(),
+            wind_speed: 0.0,
+            bath: false,
+            room: None,
+            contact_power: 0.0,
+            heater_power: 0.0,
+            heater_output: 0.0,
+            controller: Controller::default(),
+            chiller: Chiller::default(),
+            heater_energy: 0.0,
+            electricity: Electricity::default(),
+            energy: EnergyLedger::default(),
+            capped: true,
+            bottle_volume: init.bottle_volume(Liquid::default()),
+            vapor_lost: 0.0,
+            ice_sublimed: 0.0,
+            humidity: AMBIENT_HUMIDITY,
+            condensate: 0.0,
+            condensate_dripped: 0.0,
+            condensation_rate: 0.0,
+            start_datetime: None,
+            hold_time: HoldTime::default(),
+            doses: Vec::new(),
+            target_band: None,
+            annotations: Vec::new(),
+            checkpoints: Checkpoints::default(),
+            envelope: Envelope::default(),
+            column: WaterColumn::default(),
+            ice_pieces: 0,
+            cubes: IceCubes::default(),
+            field: None,
+            events: Vec::new(),
+            experiment: Experiment::default(),
+            nucleation: Nucleation::default(),
+            pressure_melting: false,
+            conduction_only: false,
+            agitation: 0.0,
+            integrator: Integrator::default(),
+        }
+    }
+
+    pub fn state(&self) -> &SystemState {
+        &self.state
+    }
+
+    pub fn outside_temp(&self) -> f64 {
+        self.outside_temp
+    }
+
+    pub fn time_seconds(&self) -> f64 {
+        self.time_seconds
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Simulated time (s) the front-ends advance the model by per `step`.
+    pub fn step_size(&self) -> f64 {
+        self.step_size
+    }
+
+    /// Physics steps per simulated second.
+    pub fn tick_rate(&self) -> f64 {
+        1.0 / self.step_size
+    }
+
+    /// Sets the physics steps per simulated second. A slower tick costs less
+    /// CPU at high speeds and is a little less accurate; it is kept in
+    /// snapshots and replay files, so a run always repeats at its own tick.
+    /// A rate outside `TICK_RATE_RANGE` (or not a number) is refused and the
+    /// tick left as it was.
+    pub fn set_tick_rate(&mut self, hz: f64) -> Result<(), String> {
+        Self::check_tick_rate(hz)?;
+        self.step_size = 1.0 / hz;
+        Ok(())
+    }
+
+    /// What is wrong with `hz` as a tick rate, if anything.
+    pub fn check_tick_rate(hz: f64) -> Result<(), String> {
+        if TICK_RATE_RANGE.contains(&hz) {
+            Ok(())
+        } else {
+            Err(format!("{hz} Hz is not between {} and {} Hz", TICK_RATE_RANGE.start(), TICK_RATE_RANGE.end()))
+        }
+    }
+
+    pub fn duration(&self) -> Option<f64> {
+        self.duration
+    }
+
+    /// How long to run where the run has to end: the duration, or
+    /// `DEFAULT_SPAN` for a run that would go on until paused.
+    pub fn span(&self) -> f64 {
+        self.duration.unwrap_or(DEFAULT_SPAN)
+    }
+
+    /// Sets the simulated time (s) after which the run stops by itself; `None` runs until paused.
+    pub fn set_duration(&mut self, duration: Option<f64>) {
+        self.duration = duration.filter(|d| *d > 0.0);
+    }
+
+    /// True once the clock has reached the duration.
+    pub fn is_finished(&self) -> bool {
+        self.duration.is_some_and(|d| self.time_seconds >= d - 1e-9)
+    }
+
+    pub fn wall(&self) -> &WallConfig {
+        &self.wall
+    }
+
+    pub fn wall_mut(&mut self) -> &mut WallConfig {
+        &mut self.wall
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    /// Changes the bottle shape; the wall's heat-exchange area follows it.
+    pub fn set_geometry(&mut self, geometry: Geometry) {
+        self.geometry = geometry;
+        self.wall.area = geometry.area();
+    }
+
+    pub fn ambient(&self) -> &AmbientProfile {
+        &self.ambient
+    }
+
+    pub fn set_ambient(&mut self, profile: AmbientProfile) {
+        self.ambient = profile;
+        self.outside_temp = self.surrounding_temp();
+    }
+
+    /// Outside temperature the ambient profile gives at simulation time `t`.
+    pub fn ambient_temp_at(&self, t: f64) -> f64 {
+        let clock = match self.start_datetime {
+            Some(start) => start.seconds_of_day + t,
+            None => t,
+        };
+        self.ambient.temperature_at(clock, self.init.outside_temp)
+    }
+
+    pub fn solar(&self) -> &SolarConfig {
+        &self.solar
+    }
+
+    pub fn solar_mut(&mut self) -> &mut SolarConfig {
+        &mut self.solar
+    }
+
+    pub fn wind_speed(&self) -> f64 {
+        self.wind_speed
+    }
+
+    pub fn set_wind_speed(&mut self, speed: f64) {
+        self.wind_speed = speed.max(0.0);
+    }
+
+    /// Whether the bottle stands in a water bath, as when warming a baby's
+    /// bottle. The outside temperature is then the bath's, held there as by a
+    /// bottle warmer; the wind, sun and humidity don't reach the wall.
+    pub fn in_bath(&self) -> bool {
+        self.bath
+    }
+
+    pub fn set_bath(&mut self, bath: bool) {
+        self.bath = bath;
+    }
+
+    /// The closed room around the bottle, if there is one. Without a room the
+    /// outside air is the ambient profile and nothing the bottle does changes it.
+    pub fn room(&self) -> Option<&Room> {
+        self.room.as_ref()
+    }
+
+    /// Puts the bottle in a room, starting at the outdoor temperature, or takes it out.
+    pub fn set_room(&mut self, room: Option<Room>) {
+        self.room = room;
+        self.reset_surroundings();
+    }
+
+    // Outside convection coefficient (W/(m^2*K)) at the wall; without
+    // convection, conduction into the still air or bath around a sphere of the
+    // bottle's area (Nu = 2), which no wind stirs
+    fn h_outside(&self) -> f64 {
+        if self.conduction_only {
+            let k = if self.bath { CONDUCTIVITY_WATER } else { CONDUCTIVITY_AIR };
+            2.0 * k / (self.wall.area / std::f64::consts::PI).sqrt()
+        } else if self.bath {
+            H_WATER_BATH
+        } else {
+            Surroundings::h_for_wind(self.wind_speed)
+        }
+    }
+
+    // Film of the contents on the inner wall (W/(m^2*K)); without convection,
+    // conduction through the liquid over half the bottle's radius. A shake
+    // raises either towards forced convection while it lasts
+    fn h_inside(&self) -> f64 {
+        let still = if self.conduction_only {
+            let radius = (self.geometry.cross_section() / std::f64::consts::PI).sqrt();
+            2.0 * self.state.liquid.properties().conductivity / radius
+        } else {
+            H_INSIDE
+        };
+        still + (H_SHAKEN - still) * self.agitation
+    }
+
+    pub fn heater_power(&self) -> f64 {
+        self.heater_power
+    }
+
+    /// Power (W) of an immersion heater in the contents; negative values make
+    /// it a Peltier cooler. With a controller on this is the heater's rating.
+    pub fn set_heater_power(&mut self, power: f64) {
+        self.heater_power = power;
+    }
+
+    /// Power (W) the heater delivered in the last step, after the controller.
+    pub fn heater_output(&self) -> f64 {
+        self.heater_output
+    }
+
+    pub fn controller(&self) -> &Controller {
+        &self.controller
+    }
+
+    pub fn controller_mut(&mut self) -> &mut Controller {
+        &mut self.controller
+    }
+
+    pub fn chiller(&self) -> &Chiller {
+        &self.chiller
+    }
+
+    pub fn chiller_mut(&mut self) -> &mut Chiller {
+        &mut self.chiller
+    }
+
+    /// Energy (J) the heater has put in since the reset, negative when cooling.
+    pub fn heater_energy(&self) -> f64 {
+        self.heater_energy
+    }
+
+    /// The heater's electricity use since the reset, with its COP model and tariff.
+    pub fn electricity(&self) -> &Electricity {
+        &self.electricity
+    }
+
+    pub fn electricity_mut(&mut self) -> &mut Electricity {
+        &mut self.electricity
+    }
+
+    /// Where the energy went, in the last step and since the reset.
+    pub fn energy(&self) -> &EnergyLedger {
+        &self.energy
+    }
+
+    /// A capped bottle keeps its steam in the headspace; an open one loses it,
+    /// evaporates from the surface and swaps headspace air with the outside.
+    pub fn is_capped(&self) -> bool {
+        self.capped
+    }
+
+    pub fn set_capped(&mut self, capped: bool) {
+        if capped == self.capped {
+            return;
+        }
+        let before = self.state.enthalpy();
+        self.capped = capped;
+        if capped {
+            self.log_event(EventKind::CapClosed, before);
+        } else {
+            self.vapor_lost += std::mem::take(&mut self.state.mass_vapor);
+            self.log_event(EventKind::CapOpened, before);
+        }
+    }
+
+    /// Inner volume of the bottle (m^3).
+    pub fn bottle_volume(&self) -> f64 {
+        self.bottle_volume
+    }
+
+    /// Headspace volume left over by the water and ice (m^3).
+    pub fn headspace_volume(&self) -> f64 {
+        (self.bottle_volume - self.state.condensed_volume()).max(1e-6)
+    }
+
+    /// Absolute headspace pressure (Pa). An open bottle stays at atmospheric pressure.
+    pub fn headspace_pressure(&self) -> f64 {
+        if self.capped {
+            self.state.gas_pressure(self.headspace_volume())
+        } else {
+            ATMOSPHERIC_PRESSURE
+        }
+    }
+
+    /// Whether the gauge pressure exceeds what the wall material can hold.
+    pub fn burst_risk(&self) -> bool {
+        self.headspace_pressure() - ATMOSPHERIC_PRESSURE > self.wall.material.burst_pressure()
+    }
+
+    /// Water evaporating out of the open neck (kg/s); zero while capped.
+    ///
+    /// Vapour diffuses from saturated air at the water surface to the outside
+    /// air at the set humidity; wind strengthens the mass transfer in
+    /// proportion to the outside convection coefficient.
+    pub fn evaporation_rate(&self) -> f64 {
+        if self.capped || self.state.mass_water <= 0.0 {
+            return 0.0;
+        }
+        let wind_factor = Surroundings::h_for_wind(self.wind_speed) / H_OUTSIDE;
+        let surface = vapor::saturation_density(self.state.temp_water);
+        let outside = self.humidity * vapor::saturation_density(self.outside_temp);
+        (MASS_TRANSFER_NECK * wind_factor * NECK_AREA * (surface - outside)).max(0.0)
+    }
+
+    /// Ice subliming out of the bottle (kg/s), freezer burn in long frozen
+    /// storage. Only contents frozen solid sublime: while there is water, its
+    /// evaporation is what leaves. Vapour diffuses from air saturated over the
+    /// ice to the outside air as evaporation does, through the open neck, or
+    /// past a closed cap at `CAP_SEAL_LEAK` of that; a drier freezer takes
+    /// more. Frost settling from air wetter than the ice isn't followed.
+    pub fn sublimation_rate(&self) -> f64 {
+        if self.state.mass_water > 0.0 || self.state.mass_ice <= 0.0 {
+            return 0.0;
+        }
+        let wind_factor = Surroundings::h_for_wind(self.wind_speed) / H_OUTSIDE;
+        let seal = if self.capped { CAP_SEAL_LEAK } else { 1.0 };
+        let surface = vapor::saturation_density_ice(self.state.temp_ice);
+        let outside = self.humidity * vapor::saturation_density(self.outside_temp);
+        (MASS_TRANSFER_NECK * wind_factor * seal * NECK_AREA * (surface - outside)).max(0.0)
+    }
+
+    /// Ice that has sublimed out of the bottle since the last reset (kg).
+    pub fn ice_sublimed(&self) -> f64 {
+        self.ice_sublimed
+    }
+
+    /// Relative humidity of the outside air (0..1).
+    pub fn humidity(&self) -> f64 {
+        self.humidity
+    }
+
+    pub fn set_humidity(&mut self, humidity: f64) {
+        self.humidity = humidity.clamp(0.0, 1.0);
+    }
+
+    /// Dew point of the outside air (°C); the wall sweats when its surface is colder.
+    pub fn dew_point(&self) -> f64 {
+        vapor::dew_point(self.outside_temp, self.humidity)
+    }
+
+    /// Condensate clinging to the outside of the wall (kg).
+    pub fn condensate(&self) -> f64 {
+        self.condensate
+    }
+
+    /// Condensate that has run off the wall since the last reset (kg).
+    pub fn condensate_dripped(&self) -> f64 {
+        self.condensate_dripped
+    }
+
+    /// Vapour condensing on the outside (kg/s); negative while the film dries.
+    pub fn condensation_rate(&self) -> f64 {
+        self.condensation_rate
+    }
+
+    /// Steam and evaporated water that have left the open bottle since the last reset (kg).
+    pub fn vapor_lost(&self) -> f64 {
+        self.vapor_lost
+    }
+
+    /// Simulated clock time at t = 0, if the run is anchored to a time of day.
+    pub fn start_datetime(&self) -> Option<SimDateTime> {
+        self.start_datetime
+    }
+
+    pub fn set_start_datetime(&mut self, start: Option<SimDateTime>) {
+        self.start_datetime = start;
+    }
+
+    /// Current simulated date and time, if a start datetime is set.
+    pub fn datetime(&self) -> Option<SimDateTime> {
+        self.start_datetime.map(|start| start.add_seconds(self.time_seconds))
+    }
+
+    pub fn hold_time(&self) -> &HoldTime {
+        &self.hold_time
+    }
+
+    /// Changes the hold-time target; the accumulated time restarts from zero.
+    pub fn set_hold_threshold(&mut self, threshold: f64) {
+        self.hold_time = HoldTime::new(threshold);
+    }
+
+    /// The time-temperature integrals kept for this run, in the order chosen.
+    pub fn doses(&self) -> &[Dose] {
+        &self.doses
+    }
+
+    pub fn target_band(&self) -> Option<&TargetBand> {
+        self.target_band.as_ref()
+    }
+
+    /// Sets the range (°C) the contents are meant to reach; entering it and
+    /// going above it are logged as events.
+    pub fn set_target_band(&mut self, band: Option<(f64, f64)>) {
+        self.target_band = band.map(|(low, high)| TargetBand::new(low, high));
+    }
+
+    /// Marks the scenario's author put on the temperature chart.
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.annotations
+    }
+
+    pub fn set_annotations(&mut self, annotations: Vec<Annotation>) {
+        self.annotations = annotations;
+    }
+
+    /// Times the run's state is to be exported at.
+    pub fn checkpoints(&self) -> &Checkpoints {
+        &self.checkpoints
+    }
+
+    pub fn set_checkpoints(&mut self, checkpoints: Checkpoints) {
+        self.checkpoints = checkpoints;
+    }
+
+    /// Chooses the time-temperature integrals to keep; they start from zero.
+    pub fn set_doses(&mut self, kinds: &[DoseKind]) {
+        self.doses = kinds.iter().map(|&kind| Dose::new(kind)).collect();
+    }
+
+    /// Water layer temperatures from the bottom up; empty when the water is lumped.
+    pub fn water_layers(&self) -> &[f64] {
+        self.column.layers()
+    }
+
+    /// Splits the water into `count` stacked layers (0 or 1 keeps it lumped).
+    pub fn set_water_layers(&mut self, count: usize) {
+        self.column = WaterColumn::new(count, self.state.temp_water);
+    }
+
+    /// Number of separate ice cubes the initial ice is split into; 0 when lumped.
+    pub fn ice_pieces(&self) -> usize {
+        self.ice_pieces
+    }
+
+    /// Splits the ice into `count` equal cubes (0 keeps it lumped).
+    pub fn set_ice_pieces(&mut self, count: usize) {
+        self.ice_pieces = count;
+        self.cubes = IceCubes::split(self.state.mass_ice, count);
+    }
+
+    /// The separate ice cubes, if the ice is not lumped.
+    pub fn ice_cubes(&self) -> &IceCubes {
+        &self.cubes
+    }
+
+    /// Drops a cube of `mass` kg of ice at `temp` °C into the running bottle
+    /// through its open neck; ice can't be warmer than the freezing point, so
+    /// a warmer `temp` is taken as that. It mixes with the ice already in, or
+    /// floats as a cube of its own when the ice is in separate cubes, and the
+    /// jump in enthalpy is logged with the event. The 2D field, seeded at the
+    /// start, doesn't get it. False, and nothing added, when the run is
+    /// paused (starting would refill the bottle), the cap is on or the cube
+    /// doesn't fit in the headspace.
+    pub fn drop_ice(&mut self, mass: f64, temp: f64) -> bool {
+        if !self.running || self.capped || mass <= 0.0 || mass / DENSITY_ICE >= self.headspace_volume() {
+            return false;
+        }
+        let before = self.state.enthalpy();
+        let temp = temp.min(self.state.freezing_point());
+        let state = &mut self.state;
+        state.temp_ice = (state.mass_ice * state.temp_ice + mass * temp) / (state.mass_ice + mass);
+        state.mass_ice += mass;
+        if self.ice_pieces > 0 {
+            self.cubes.add(mass);
+        }
+        self.update_pressure_shift();
+        self.log_event(EventKind::IceAdded, before);
+        true
+    }
+
+    /// Pours `mass` kg of the bottle's liquid at `temp` °C in through the
+    /// open neck, its enthalpy mixed into the liquid already there; it comes
+    /// in liquid, so `temp` is taken between its own freezing and boiling
+    /// points. Its heat above the freezing point goes first into the ice, as
+    /// the wall's does, warming and melting it at once; what is left, with
+    /// the water melted, mixes into the liquid. Ice in separate cubes is left
+    /// to melt by its own exchange with the warmer liquid. The jump in
+    /// enthalpy is logged with the event. Stratified layers all move to the
+    /// new mean, and the 2D field doesn't get it. False, and nothing added,
+    /// when the run is paused, the cap is on or the liquid doesn't fit.
+    pub fn pour_in(&mut self, mass: f64, temp: f64) -> bool {
+        let liquid = self.state.liquid.properties();
+        if !self.running || self.capped || mass <= 0.0 || mass / liquid.density >= self.headspace_volume() {
+            return false;
+        }
+        let before = self.state.enthalpy();
+        let temp = temp.clamp(liquid.freezing_point, BOILING_POINT);
+        let (capacity, separate_ice) = (self.state.liquid_heat_capacity(), self.ice_pieces > 0);
+        let state = &mut self.state;
+        let old_temp = state.temp_water;
+        // what comes in dilutes any salt, raising the freezing point
+        let t_freeze = SystemState { mass_water: state.mass_water + mass, ..*state }.freezing_point();
+        let mut heat = mass * liquid.cp * (temp - t_freeze);
+        let mut melted = 0.0;
+        if !separate_ice && state.mass_ice > 0.0 {
+            let warm = (state.mass_ice * CP_ICE * (t_freeze - state.temp_ice)).min(heat).max(0.0);
+            state.temp_ice += warm / (state.mass_ice * CP_ICE);
+            heat -= warm;
+            melted = (heat / state.latent_fusion()).clamp(0.0, state.mass_ice);
+            state.mass_ice -= melted;
+            heat -= melted * state.latent_fusion();
+        }
+        // the poured liquid and the meltwater join at the freezing point, with the heat left over
+        let joined = mass + melted;
+        state.mass_water += joined;
+        state.temp_water = (capacity * old_temp + joined * liquid.cp * t_freeze + heat) / (capacity + joined * liquid.cp);
+        self.update_pressure_shift();
+        self.log_event(EventKind::PouredIn, before);
+        true
+    }
+
+    /// Pours `fraction` (0 to 1) of the liquid out of the open neck, with the
+    /// salt in it; the ice stays behind, too big to pass the neck. The
+    /// enthalpy it takes is logged with the event. False, and nothing poured,
+    /// when the run is paused, the cap is on or there is no liquid.
+    pub fn pour_out(&mut self, fraction: f64) -> bool {
+        if !self.running || self.capped || self.state.mass_water <= 0.0 || !(fraction > 0.0 && fraction <= 1.0) {
+            return false;
+        }
+        let before = self.state.enthalpy();
+        self.state.mass_water *= 1.0 - fraction;
+        self.state.mass_solute *= 1.0 - fraction;
+        self.update_pressure_shift();
+        self.log_event(EventKind::PouredOut, before);
+        true
+    }
+
+    /// 2D temperature field, when the field mode is on.
+    pub fn field(&self) -> Option<&TemperatureField> {
+        self.field.as_ref()
+    }
+
+    /// Turns the 2D field mode on (seeded from the current state) or off.
+    pub fn set_field_mode(&mut self, on: bool) {
+        self.field = on.then(|| self.new_field());
+    }
+
+    fn new_field(&self) -> TemperatureField {
+        let t = RADIATION_REF_TEMP;
+        let emissivity = if self.bath { 0.0 } else { self.wall.emissivity };
+        let h_rad = 4.0 * emissivity * STEFAN_BOLTZMANN * t * t * t;
+        let h_outside = self.h_outside() + h_rad;
+        TemperatureField::new(&self.state, &self.wall, self.geometry.cross_section(), h_outside)
+    }
+
+    pub fn nucleation(&self) -> &Nucleation {
+        &self.nucleation
+    }
+
+    pub fn nucleation_mut(&mut self) -> &mut Nucleation {
+        &mut self.nucleation
+    }
+
+    /// Whether the melting point and the latent heat follow the headspace
+    /// pressure. A sealed bottle reaches a few bar at most, which moves the
+    /// melting point by hundredths of a kelvin: this is here to show that it
+    /// can be left out.
+    pub fn pressure_melting(&self) -> bool {
+        self.pressure_melting
+    }
+
+    pub fn set_pressure_melting(&mut self, on: bool) {
+        self.pressure_melting = on;
+        self.update_pressure_shift();
+    }
+
+    /// A teaching switch that takes convection out of the model: heat only
+    /// conducts, into still air (or a still bath) outside, through still
+    /// contents to the wall, across the headspace and into the ice cubes. The
+    /// wind, the air swapped through an open neck and the mixing of the water
+    /// layers stop. Evaporation, radiation and sunlight go on, and the 2D
+    /// field keeps its effective conductivities.
+    pub fn conduction_only(&self) -> bool {
+        self.conduction_only
+    }
+
+    pub fn set_conduction_only(&mut self, on: bool) {
+        self.conduction_only = on;
+    }
+
+    /// Shakes the bottle: the contents slosh over the wall and the ice, which
+    /// film coefficients take towards `H_SHAKEN` and which stirs the water
+    /// layers together, then settle over `SHAKE_SETTLE`. Lumped ice, which
+    /// otherwise only takes heat through the wall, melts from the water's heat
+    /// above its freezing point while the sloshing lasts. Supercooled water
+    /// nucleates.
+    pub fn shake(&mut self) {
+        self.agitation = 1.0;
+        self.nucleation.shake();
+    }
+
+    /// How hard the contents still slosh from the last shake, 1 just after
+    /// it and 0 once settled.
+    pub fn agitation(&self) -> f64 {
+        self.agitation
+    }
+
+    // The melting point shift (K) for the current headspace pressure
+    fn update_pressure_shift(&mut self) {
+        self.state.pressure_shift = if self.pressure_melting {
+            MELTING_SLOPE * (self.headspace_pressure() - ATMOSPHERIC_PRESSURE)
+        } else {
+            0.0
+        };
+    }
+
+    /// Whether the liquid is currently below its freezing point with no ice to grow on.
+    pub fn is_supercooled(&self) -> bool {
+        self.state.mass_ice <= 0.0 && self.state.temp_water < self.state.freezing_point()
+    }
+
+    pub fn integrator(&self) -> Integrator {
+        self.integrator
+    }
+
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    /// The whole simulation as JSON: state, initial values, settings, clock and
+    /// event log, so a run can be saved and picked up later exactly where it was.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("plain data always serializes")
+    }
+
+    /// Reads a simulation written by `to_json`.
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn init(&self) -> &InitialConditions {
+        &self.init
+    }
+
+    pub fn init_mut(&mut self) -> &mut InitialConditions {
+        &mut self.init
+    }
+
+    /// What the bottle is filled with. The live state takes it up with the
+    /// initial conditions, on the next start or reset.
+    pub fn liquid(&self) -> Liquid {
+        self.liquid
+    }
+
+    pub fn set_liquid(&mut self, liquid: Liquid) {
+        self.liquid = liquid;
+    }
+
+    /// Copies the initial conditions into the live state without touching the clock.
+    pub fn apply_init(&mut self) {
+        self.state = self.init.to_state(self.liquid);
+        self.bottle_volume = self.init.bottle_volume(self.liquid);
+        self.column.reset(self.state.temp_water);
+        self.cubes = IceCubes::split(self.state.mass_ice, self.ice_pieces);
+        if self.field.is_some() {
+            self.field = Some(self.new_field());
+        }
+        self.reset_surroundings();
+        self.update_pressure_shift();
+    }
+
+    // A room starts out at the outdoor temperature
+    fn reset_surroundings(&mut self) {
+        let outdoor = self.ambient_temp_at(self.time_seconds);
+        if let Some(room) = &mut self.room {
+            room.reset(outdoor);
+        }
+        self.outside_temp = self.surrounding_temp();
+    }
+
+    // What the bottle sees: the room's air, or the outdoors without a room
+    fn surrounding_temp(&self) -> f64 {
+        match &self.room {
+            Some(room) => room.temp(),
+            None => self.ambient_temp_at(self.time_seconds),
+        }
+    }
+
+    /// Start/Pause toggle; a paused simulation picks up the edited inits first.
+    /// Starting a finished run starts it over.
+    pub fn toggle_running(&mut self) {
+        if !self.running && self.is_finished() {
+            self.reset_from_init();
+        }
+        let before = self.state.enthalpy();
+        if !self.running {
+            self.apply_init();
+        }
+        self.running = !self.running;
+        let kind = if self.running { EventKind::Started } else { EventKind::Paused };
+        self.log_event(kind, before);
+    }
+
+    /// Events since the last reset, oldest first.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Name and notes of the run; kept across resets.
+    pub fn experiment(&self) -> &Experiment {
+        &self.experiment
+    }
+
+    pub fn experiment_mut(&mut self) -> &mut Experiment {
+        &mut self.experiment
+    }
+
+    fn log_event(&mut self, kind: EventKind, enthalpy_before: f64) {
+        self.events.push(Event {
+            time: self.time_seconds,
+            kind,
+            enthalpy_change: self.state.enthalpy() - enthalpy_before,
+        });
+    }
+
+    pub fn cycle_time_scale(&mut self) {
+        let current = TIME_SCALES.iter().position(|&s| s == self.time_scale);
+        self.time_scale = match current {
+            Some(i) if i + 1 < TIME_SCALES.len() => TIME_SCALES[i + 1],
+            _ => TIME_SCALES[0],
+        };
+    }
+
+    pub fn reset_from_init(&mut self) {
+        self.time_seconds = 0.0;
+        self.apply_init();
+        self.running = false;
+        self.time_scale = 1.0;
+        self.hold_time.reset();
+        self.doses.iter_mut().for_each(Dose::reset);
+        self.target_band.iter_mut().for_each(TargetBand::reset);
+        self.envelope.reset();
+        self.nucleation.reset();
+        self.agitation = 0.0;
+        self.vapor_lost = 0.0;
+        self.ice_sublimed = 0.0;
+        self.heater_energy = 0.0;
+        self.electricity.reset();
+        self.energy.reset();
+        self.heater_output = 0.0;
+        self.controller.reset();
+        self.chiller.reset();
+        self.condensate = 0.0;
+        self.condensate_dripped = 0.0;
+        self.condensation_rate = 0.0;
+        self.events.clear();
+    }
+
+    /// One row of run history, matching `SAMPLE_COLUMNS`.
+    pub fn sample(&self) -> [f64; SAMPLE_COLUMNS.len()] {
+        let s = &self.state;
+        [
+            self.time_seconds,
+            s.mass_water,
+            s.mass_ice,
+            s.temp_water,
+            s.temp_ice,
+            s.temp_air,
+            self.outside_temp,
+            self.wall_exchange().conduction,
+        ]
+    }
+
+    /// Lowest and highest value of each `SAMPLE_COLUMNS` quantity since the last reset.
+    pub fn envelope(&self) -> &Envelope {
+        &self.envelope
+    }
+
+    /// Bit-exact fingerprint of the clock and state, for comparing runs across
+    /// machines. Runs on different platforms may differ in the last bits, since
+    /// `exp`, `ln` and `cos` come from each platform's maths library.
+    pub fn fingerprint(&self) -> u64 {
+        let s = &self.state;
+        reduce::fingerprint([
+            self.time_seconds,
+            s.mass_water,
+            s.mass_ice,
+            s.mass_air,
+            s.temp_water,
+            s.temp_ice,
+            s.temp_air,
+            self.hold_time.seconds(),
+        ])
+    }
+
+    /// Advances the model by `dt` seconds of simulated time (`time_scale` is not applied here).
+    pub fn step(&mut self, dt: f64) {
+        let surroundings = self.surrounding_temp();
+        self.step_in(dt, surroundings, 0.0);
+    }
+
+    // One step with the outside air at `air_temp` and `contact` W conducted in
+    // through the wall from touching bottles; a cooler drives its bottles so
+    pub(crate) fn step_in(&mut self, dt: f64, air_temp: f64, contact: f64) {
+        if !self.running {
+            return;
+        }
+
+        self.outside_temp = air_temp;
+        self.contact_power = contact;
+        self.update_pressure_shift();
+        let supercooling = self.nucleation.enabled;
+        let separate_ice = self.ice_pieces > 0;
+        self.heater_output = self.controller.output(self.state.temp_water, self.heater_power, dt);
+        let start = self.state;
+        // the headspace air holds little heat and settles within a second or
+        // so; a coarser tick integrates in pieces no longer than that, or the
+        // air would overshoot and swing ever wider
+        let pieces = (dt / self.air_time_constant()).ceil().clamp(1.0, MAX_PIECES);
+        let mut applied = HeatFlow::default();
+        for _ in 0..pieces as usize {
+            let (state, q) = self.integrator.integrate(
+                self.state,
+                dt / pieces,
+                &|s| self.heat_rate(s),
+                &|s, q| apply_heat(s, q, supercooling, separate_ice),
+            );
+            self.state = state;
+            applied = applied + q;
+        }
+        let (conduction_only, agitation) = (self.conduction_only, self.agitation);
+        if separate_ice {
+            let conductivity = self.state.liquid.properties().conductivity;
+            self.cubes.exchange(&mut self.state, dt, |edge| {
+                // still water conducts to a cube as to a sphere (Nu = 2)
+                let still = if conduction_only { 2.0 * conductivity / edge } else { H_ICE_WATER };
+                still + (H_SHAKEN - still) * agitation
+            });
+        } else if agitation > 0.0 {
+            // sloshing water washes over the lumped ice as over one block
+            IceCubes::split(self.state.mass_ice, 1).exchange(&mut self.state, dt, |_| H_SHAKEN * agitation);
+        }
+        let HeatFlow { wall, heater, chiller, contact, neck: swapped, .. } = applied;
+        self.heater_energy += heater;
+        self.electricity.advance(heater, start.temp_water, self.outside_temp);
+        if supercooling {
+            let undercooling = if self.state.mass_ice <= 0.0 {
+                self.state.freezing_point() - self.state.temp_water
+            } else {
+                0.0
+            };
+            if self.nucleation.triggers(undercooling, dt) {
+                let before = self.state.enthalpy();
+                recalesce(&mut self.state);
+                self.log_event(EventKind::Nucleation, before);
+            }
+        }
+
+        let sealed = self.state.enthalpy();
+        if !self.capped {
+            self.vapor_lost += std::mem::take(&mut self.state.mass_vapor);
+            self.evaporate(dt);
+        }
+        self.sublimate(dt);
+        let neck = swapped + self.state.enthalpy() - sealed;
+        let stored = EnergySplit::stored(&self.state) - EnergySplit::stored(&start);
+        self.energy.record(EnergySplit { wall, heater, chiller, contact, neck, ..stored }, dt);
+        // the room gives the bottle what went through the wall and takes in the vented steam
+        let outdoor = self.ambient_temp_at(self.time_seconds);
+        if let Some(room) = &mut self.room {
+            room.advance(outdoor, wall + neck, dt);
+        }
+        self.column.step(&self.state, self.geometry.cross_section(), !self.conduction_only, agitation, dt);
+        self.agitation *= (-dt / SHAKE_SETTLE).exp();
+        if self.agitation < SETTLED {
+            self.agitation = 0.0;
+        }
+        if let Some(field) = &mut self.field {
+            field.advance(dt, self.outside_temp);
+        }
+
+        let drink = self.state.drink_temperature();
+        self.hold_time.accumulate(drink, dt);
+        for dose in &mut self.doses {
+            dose.accumulate(drink, dt);
+        }
+        let previous = self.time_seconds;
+        self.time_seconds += dt;
+        let passed = self.checkpoints.between(previous, self.time_seconds).count();
+        for _ in 0..passed {
+            self.log_event(EventKind::Checkpoint, self.state.enthalpy());
+        }
+        if self.chiller.advance(self.state.temp_water, self.time_seconds, dt) {
+            self.log_event(EventKind::Chilled, self.state.enthalpy());
+        }
+        let crossing = self.target_band.as_mut().and_then(|band| band.update(drink));
+        match crossing {
+            Some(BandCrossing::Entered) => self.log_event(EventKind::InBand, self.state.enthalpy()),
+            Some(BandCrossing::Overshot) => self.log_event(EventKind::Overshoot, self.state.enthalpy()),
+            None => {}
+        }
+        self.update_condensation(dt);
+        let sample = self.sample();
+        self.envelope.record(&sample);
+
+        if self.is_finished() {
+            self.running = false;
+            self.log_event(EventKind::Finished, self.state.enthalpy());
+        }
+    }
+
+    // Evaporation through the open neck: the water loses the mass and the latent heat.
+    fn evaporate(&mut self, dt: f64) {
+        let mass = (self.evaporation_rate() * dt).min(self.state.mass_water);
+        if mass <= 0.0 {
+            return;
+        }
+        let latent = mass * vapor::latent_heat(self.state.temp_water);
+        self.state.mass_water -= mass;
+        let cap = self.state.liquid_heat_capacity();
+        if cap > 0.0 {
+            self.state.temp_water -= latent / cap;
+        }
+        self.vapor_lost += mass;
+    }
+
+    // Sublimation out of frozen contents: the ice loses the mass and the latent
+    // heat of sublimation, as water does evaporating.
+    fn sublimate(&mut self, dt: f64) {
+        let mass = (self.sublimation_rate() * dt).min(self.state.mass_ice);
+        if mass <= 0.0 {
+            return;
+        }
+        self.state.mass_ice -= mass;
+        if self.state.mass_ice > 0.0 {
+            self.state.temp_ice -= mass * LATENT_SUBLIMATION / (self.state.mass_ice * CP_ICE);
+        }
+        self.ice_sublimed += mass;
+    }
+
+    // Outside air condenses on a wall colder than its dew point, and the film
+    // dries again once the wall warms; mass transfer follows the convection
+    // coefficient (Lewis analogy). The latent heat reaches the wall balance
+    // through `surroundings` from the next step on.
+    fn update_condensation(&mut self, dt: f64) {
+        if self.bath {
+            self.condensation_rate = 0.0;
+            return;
+        }
+        let surface = self.wall_exchange().surface_temp;
+        let h_mass = self.h_outside() / RHO_CP_AIR;
+        let ambient = self.humidity * vapor::saturation_density(self.outside_temp);
+        let rate = h_mass * self.wall.area * (ambient - vapor::saturation_density(surface));
+        // only an existing film can dry
+        let rate = rate.max(-self.condensate / dt);
+        self.condensate += rate * dt;
+        let film = CONDENSATE_FILM * self.wall.area;
+        if self.condensate > film {
+            self.condensate_dripped += self.condensate - film;
+            self.condensate = film;
+        }
+        self.condensation_rate = rate;
+    }
+
+    /// Wall heat flow for the current state, split into convection, radiation and conduction.
+    pub fn wall_exchange(&self) -> WallExchange {
+        self.wall.exchange(self.wall_inner_temp(&self.state), &self.surroundings())
+    }
+
+    fn surroundings(&self) -> Surroundings {
+        Surroundings {
+            air_temp: self.outside_temp,
+            h_convection: self.h_outside(),
+            h_inside: self.h_inside(),
+            absorbed_solar: if self.bath { 0.0 } else { self.solar.absorbed(self.geometry.projected_area(), self.datetime().map(|t| t.hour_of_day())) },
+            latent: self.condensation_rate * vapor::latent_heat(self.outside_temp),
+            immersed: self.bath,
+        }
+    }
+
+    // Temperature seen by the inner wall: mostly liquid, partly headspace air.
+    fn wall_inner_temp(&self, state: &SystemState) -> f64 {
+        let sys_temp = state.system_temperature_equivalent();
+        if state.mass_air <= 0.0 {
+            sys_temp
+        } else {
+            (1.0 - AIR_WALL_FRACTION) * sys_temp + AIR_WALL_FRACTION * state.temp_air
+        }
+    }
+
+    /// Heat flow into the contents and the headspace air (W, positive => heating).
+    fn heat_rate(&self, state: &SystemState) -> HeatFlow {
+        // Equivalent system temp (sensible)
+        let sys_temp = state.system_temperature_equivalent();
+        let wall = self.wall.exchange(self.wall_inner_temp(state), &self.surroundings());
+        let heater = Self::heater_share(state, self.heater_output);
+        let chiller = Self::heater_share(state, self.chiller.power(state.temp_water));
+        let contact = Self::heater_share(state, self.contact_power);
+        let inside = HeatFlow { heater, chiller, contact, ..HeatFlow::default() };
+        if state.mass_air <= 0.0 {
+            return HeatFlow { liquid: wall.conduction + heater + chiller + contact, wall: wall.conduction, ..inside };
+        }
+        // the headspace is a third node: it gets its share of the wall conduction
+        // and exchanges with the surface
+        let ua_cond = 1.0 / wall.resistance;
+        let wall_to_liquid = ua_cond * (1.0 - AIR_WALL_FRACTION) * (wall.surface_temp - sys_temp);
+        let wall_to_air = ua_cond * AIR_WALL_FRACTION * (wall.surface_temp - state.temp_air);
+        let air_to_liquid = self.ua_air_surface() * (state.temp_air - sys_temp);
+        let neck_to_air = self.ua_neck() * (self.outside_temp - state.temp_air);
+        HeatFlow {
+            liquid: wall_to_liquid + air_to_liquid + heater + chiller + contact,
+            air: wall_to_air - air_to_liquid + neck_to_air,
+            wall: wall_to_liquid + wall_to_air,
+            neck: neck_to_air,
+            ..inside
+        }
+    }
+
+    // Headspace air to the water surface (W/K); without convection the surface
+    // takes heat by conduction across half the headspace
+    fn ua_air_surface(&self) -> f64 {
+        if self.conduction_only {
+            let area = self.geometry.cross_section();
+            2.0 * CONDUCTIVITY_AIR * area * area / self.headspace_volume()
+        } else {
+            UA_AIR_SURFACE
+        }
+    }
+
+    // Headspace air swapped with the outside through an open neck (W/K)
+    fn ua_neck(&self) -> f64 {
+        if self.capped || self.conduction_only { 0.0 } else { UA_NECK }
+    }
+
+    // s the headspace air takes to settle towards what it exchanges with
+    fn air_time_constant(&self) -> f64 {
+        if self.state.mass_air <= 0.0 {
+            return f64::INFINITY;
+        }
+        let wall = self.wall.exchange(self.wall_inner_temp(&self.state), &self.surroundings());
+        let ua = AIR_WALL_FRACTION / wall.resistance + self.ua_air_surface() + self.ua_neck();
+        self.state.mass_air * CV_AIR / ua
+    }
+
+    // The heater and the chiller coil sit in the contents; with nothing left
+    // around them, they do nothing.
+    fn heater_share(state: &SystemState, power: f64) -> f64 {
+        if state.mass_water + state.mass_ice > 0.0 { power } else { 0.0 }
+    }
+}
+
+/// Distributes the energy (J) over the nodes: air is purely sensible, the
+/// contents take sensible heat and then melt/freeze at the freezing point of
+/// the (possibly salty) liquid. With `supercooling` and no ice present the
+/// water keeps cooling below the freezing point instead of freezing. With
+/// `separate_ice` the wall heats the water only; the cubes melt by their own
+/// exchange with it. Whatever the contents cannot take, with the water all
+/// boiled off or nothing left to cool, goes to the headspace air.
+fn apply_heat(state: &mut SystemState, q: HeatFlow, supercooling: bool, separate_ice: bool) {
+    let mut to_air = q.air;
+    let mut q = q.liquid;
+    let t_freeze = state.freezing_point();
+    let supercooled = supercooling && state.mass_ice <= 0.0;
+
+    // HEATING (q > 0): raise ice temp to the freezing point, melt, heat water, then boil
+    let heats_ice = !separate_ice || state.mass_water <= 0.0;
+    if q > 0.0 {
+        // 1) warm ice to the freezing point
+        if heats_ice && state.mass_ice > 0.0 && state.temp_ice < t_freeze {
+            let need = state.mass_ice * CP_ICE * (t_freeze - state.temp_ice);
+            if q >= need {
+                state.temp_ice = t_freeze;
+                q -= need;
+            } else {
+                state.temp_ice += q / (state.mass_ice * CP_ICE);
+                q = 0.0;
+            }
+        }
+
+        // 2) melt ice at the freezing point
+        if heats_ice && q > 0.0 && state.mass_ice > 0.0 {
+            q = melt(state, q);
+        }
+
+        // 3) raise water temperature (mixed water) up to the boiling point
+        if q > 0.0 && state.mass_water > 0.0 {
+            let room = (state.liquid_heat_capacity() * (state.boiling_point() - state.temp_water)).max(0.0);
+            let sensible = q.min(room);
+            state.temp_water += sensible / state.liquid_heat_capacity();
+            q -= sensible;
+        }
+
+        // 4) boil off water into the headspace
+        if q > 0.0 && state.mass_water > 0.0 {
+            let per_kg = state.steam_enthalpy() - state.liquid.properties().cp * state.temp_water;
+            let boil_mass = (q / per_kg).min(state.mass_water);
+            state.mass_water -= boil_mass;
+            state.mass_vapor += boil_mass;
+            q -= boil_mass * per_kg;
+        }
+        to_air += q;
+    } else if q < 0.0 {
+        // COOLING: remove energy from water down to the freezing point, freeze, then cool ice
+        let mut q_abs = -q;
+
+        // 0) steam in a closed headspace condenses back first, into the water as it is
+        if state.mass_vapor > 0.0 {
+            let per_kg = state.steam_enthalpy() - state.liquid.properties().cp * state.temp_water;
+            let condense_mass = (q_abs / per_kg).min(state.mass_vapor);
+            state.mass_vapor -= condense_mass;
+            state.mass_water += condense_mass;
+            q_abs -= condense_mass * per_kg;
+        }
+
+        // 0b) no ice to grow on: the water just gets colder until it nucleates
+        if supercooled && state.mass_water > 0.0 {
+            state.temp_water -= q_abs / state.liquid_heat_capacity();
+            q_abs = 0.0;
+        }
+
+        // 1) cool water to the freezing point
+        if state.mass_water > 0.0 && state.temp_water > t_freeze {
+            let need = state.liquid_heat_capacity() * (state.temp_water - t_freeze);
+            let take = need.min(q_abs);
+            state.temp_water -= take / state.liquid_heat_capacity();
+            q_abs -= take;
+        }
+
+        // 2) freeze some water at the freezing point
+        if q_abs > 0.0 && state.mass_water > 0.0 && (state.temp_water - t_freeze).abs() < 1e-3 {
+            q_abs = freeze(state, q_abs);
+        }
+
+        // 3) lower ice temperature
+        if q_abs > 0.0 && state.mass_ice > 0.0 {
+            state.temp_ice -= q_abs / (state.mass_ice * CP_ICE);
+            q_abs = 0.0;
+        }
+        to_air -= q_abs;
+    }
+    if state.mass_air > 0.0 {
+        state.temp_air += to_air / (state.mass_air * CV_AIR);
+    }
+
+    // The salt moves the freezing point as water melts or freezes, and
+    // evaporation can take the water under it: water left below it freezes
+    // and ice left above it melts, as much as brings them back to it, so the
+    // energy stays where it was
+    let t_freeze = state.freezing_point();
+    if state.mass_water > 0.0 && !supercooled && state.temp_water < t_freeze {
+        let deficit = state.liquid_heat_capacity() * (t_freeze - state.temp_water);
+        state.temp_water = t_freeze;
+        freeze(state, deficit);
+    }
+    if state.mass_ice > 0.0 && state.temp_ice > t_freeze {
+        let excess = state.mass_ice * CP_ICE * (state.temp_ice - t_freeze);
+        state.temp_ice = t_freeze;
+        melt(state, excess);
+    }
+
+    // Ensure temp bounds and mass sanity
+    if state.mass_ice <= 0.0 {
+        state.temp_ice = state.freezing_point();
+    }
+    if state.mass_water > 0.0 {
+        state.temp_water = state.temp_water.min(state.boiling_point());
+    } else {
+        // if no water, keep temp at the freezing point (degenerate)
+        state.temp_water = state.freezing_point();
+    }
+}
+
+// Melts ice at the freezing point with `q` J, the melt water mixing in from
+// there; returns what is left once the ice is gone.
+fn melt(state: &mut SystemState, q: f64) -> f64 {
+    let (t_freeze, latent) = (state.freezing_point(), state.latent_fusion());
+    let melt_mass = (q / latent).min(state.mass_ice);
+    let cap = state.liquid_heat_capacity();
+    let melt_cap = melt_mass * state.liquid.properties().cp;
+    state.mass_ice -= melt_mass;
+    state.mass_water += melt_mass;
+    state.temp_water = (cap * state.temp_water + melt_cap * t_freeze) / (cap + melt_cap);
+    q - melt_mass * latent
+}
+
+// Freezes water at the freezing point, taking `q` J out, the new ice mixing in
+// with what is there; the salt stays in the brine. Returns what is left once
+// the water is gone.
+fn freeze(state: &mut SystemState, q: f64) -> f64 {
+    let (t_freeze, latent) = (state.freezing_point(), state.latent_fusion());
+    let freeze_mass = (q / latent).min(state.mass_water);
+    let ice_temp = if state.mass_ice > 0.0 { state.temp_ice } else { t_freeze };
+    state.temp_ice = (state.mass_ice * ice_temp + freeze_mass * t_freeze) / (state.mass_ice + freeze_mass);
+    state.mass_water -= freeze_mass;
+    state.mass_ice += freeze_mass;
+    q - freeze_mass * latent
+}
+
+/// Sudden freeze after nucleation: ice forms until the latent heat released has
+/// warmed the supercooled liquid back to its freezing point.
+fn recalesce(state: &mut SystemState) {
+    let t_freeze = state.freezing_point();
+    let deficit = state.liquid_heat_capacity() * (t_freeze - state.temp_water);
+    let freeze_mass = (deficit / state.latent_fusion()).clamp(0.0, state.mass_water);
+    state.mass_water -= freeze_mass;
+    state.mass_ice += freeze_mass;
+    state.temp_water = state.freezing_point();
+    state.temp_ice = state.temp_water;
+}
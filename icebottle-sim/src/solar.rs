@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+/// Sunlight falling on the bottle.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SolarConfig {
+    pub irradiance: f64,   // W/m^2 on a surface facing the sun; ~1000 at noon on a clear day
+    pub absorptivity: f64, // fraction of the incident flux absorbed by the outer surface
+    pub shaded: bool,
+    /// Hours of the day (from, until) the sun shines, on a run with a clock;
+    /// `None` shines all the time.
+    #[serde(default)]
+    pub hours: Option<(f64, f64)>,
+}
+
+impl Default for SolarConfig {
+    fn default() -> Self {
+        Self {
+            irradiance: 0.0,
+            absorptivity: 0.6,
+            shaded: false,
+            hours: None,
+        }
+    }
+}
+
+impl SolarConfig {
+    /// Power absorbed (W) by a body presenting `projected_area` m^2 to the sun,
+    /// at `hour` of the day when the run has a clock.
+    pub fn absorbed(&self, projected_area: f64, hour: Option<f64>) -> f64 {
+        let set = matches!((self.hours, hour), (Some((from, until)), Some(h)) if !(from..until).contains(&h));
+        if self.shaded || set {
+            0.0
+        } else {
+            self.absorptivity * self.irradiance.max(0.0) * projected_area
+        }
+    }
+}
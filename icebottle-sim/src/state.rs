@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+    BOILING_POINT, CP_ICE, CP_SALT, CRYOSCOPIC_WATER, CV_AIR, DENSITY_ICE, EBULLIOSCOPIC_WATER, EUTECTIC_TEMP, KELVIN,
+    LATENT_VAPORIZATION, MOLAR_MASS_SALT, R_AIR, R_VAPOR, VANT_HOFF_SALT,
+};
+use crate::liquid::Liquid;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct SystemState {
+    pub mass_water: f64,
+    pub mass_ice: f64,
+    pub mass_air: f64,
+    pub mass_solute: f64, // salt dissolved in the water
+    pub mass_vapor: f64,  // steam in the headspace
+    pub temp_water: f64, // Celsius
+    pub temp_ice: f64,   // Celsius
+    pub temp_air: f64,   // Celsius, headspace
+    pub pressure_shift: f64, // K the headspace pressure moves the melting point; 0 unless pressure melting is on
+    pub liquid: Liquid, // what `mass_water` is; the ice is ice whatever it froze out of
+}
+
+impl SystemState {
+    // fn total_mass(&self) -> f64 {
+    //     self.mass_water + self.mass_ice + self.mass_air
+    // }
+
+    /// Temperature of what you would drink: the water, or the ice once it has all frozen.
+    pub fn drink_temperature(&self) -> f64 {
+        if self.mass_water > 0.0 {
+            self.temp_water
+        } else {
+            self.temp_ice
+        }
+    }
+
+    /// Freezing point of the liquid (°C): the liquid's own, 0 for fresh water,
+    /// depressed by the dissolved salt (ΔT = i·Kf·molality) down to the
+    /// eutectic. Freezing out ice concentrates the brine, so this drops as the
+    /// contents freeze. Pressure melting moves it by `pressure_shift` on top.
+    pub fn freezing_point(&self) -> f64 {
+        let own = self.liquid.properties().freezing_point;
+        let depressed = if self.mass_solute <= 0.0 {
+            own
+        } else if self.mass_water <= 0.0 {
+            EUTECTIC_TEMP
+        } else {
+            let molality = self.mass_solute / MOLAR_MASS_SALT / self.mass_water;
+            (own - VANT_HOFF_SALT * CRYOSCOPIC_WATER * molality).max(EUTECTIC_TEMP)
+        };
+        depressed + self.pressure_shift
+    }
+
+    /// Latent heat of fusion (J/kg) at the melting point. `enthalpy` counts
+    /// the liquid's latent heat at 0 °C; a melting point moved off it by the
+    /// liquid, salt or pressure takes it along by the difference in heat
+    /// capacity of liquid and ice (Kirchhoff), so the energy still balances.
+    pub fn latent_fusion(&self) -> f64 {
+        let liquid = self.liquid.properties();
+        liquid.latent_fusion + (liquid.cp - CP_ICE) * self.freezing_point()
+    }
+
+    /// Boiling point of the liquid at 1 atm (°C), raised by dissolved salt.
+    pub fn boiling_point(&self) -> f64 {
+        if self.mass_solute <= 0.0 || self.mass_water <= 0.0 {
+            return BOILING_POINT;
+        }
+        let molality = self.mass_solute / MOLAR_MASS_SALT / self.mass_water;
+        BOILING_POINT + VANT_HOFF_SALT * EBULLIOSCOPIC_WATER * molality
+    }
+
+    /// Heat a kg of steam carries (J/kg) relative to the liquid at 0 °C: its
+    /// sensible heat up to the boiling point and the latent heat boiling it.
+    pub fn steam_enthalpy(&self) -> f64 {
+        self.liquid.properties().cp * BOILING_POINT + LATENT_VAPORIZATION
+    }
+
+    /// Heat capacity of the liquid (J/K): the water plus its dissolved salt.
+    pub fn liquid_heat_capacity(&self) -> f64 {
+        self.mass_water * self.liquid.properties().cp + self.mass_solute * CP_SALT
+    }
+
+    /// Salt mass fraction of the liquid brine (kg/kg). Rises as ice freezes out
+    /// because the salt is rejected into the remaining water.
+    pub fn salinity(&self) -> f64 {
+        let brine = self.mass_water + self.mass_solute;
+        if self.mass_solute <= 0.0 || brine <= 0.0 {
+            0.0
+        } else {
+            self.mass_solute / brine
+        }
+    }
+
+    /// Mixture specific heat of the brine (J/(kg·K)), by mass-weighting water and salt.
+    pub fn liquid_specific_heat(&self) -> f64 {
+        let brine = self.mass_water + self.mass_solute;
+        if brine <= 0.0 {
+            self.liquid.properties().cp
+        } else {
+            self.liquid_heat_capacity() / brine
+        }
+    }
+
+    /// Enthalpy of the contents (J) relative to liquid water and air at 0 °C:
+    /// sensible heat of every node, minus the latent heat of the ice, plus the
+    /// heat of the steam.
+    pub fn enthalpy(&self) -> f64 {
+        self.liquid_heat_capacity() * self.temp_water
+            + self.mass_ice * (CP_ICE * self.temp_ice - self.liquid.properties().latent_fusion)
+            + self.mass_vapor * self.steam_enthalpy()
+            + self.mass_air * CV_AIR * self.temp_air
+    }
+
+    /// Volume taken by the water and ice (m^3); grows as water freezes.
+    pub fn condensed_volume(&self) -> f64 {
+        self.mass_water / self.liquid.properties().density + self.mass_ice / DENSITY_ICE
+    }
+
+    /// Absolute pressure (Pa) of the headspace air and steam in `volume` m^3,
+    /// by the ideal gas law at the headspace temperature.
+    pub fn gas_pressure(&self, volume: f64) -> f64 {
+        (self.mass_air * R_AIR + self.mass_vapor * R_VAPOR) * (self.temp_air + KELVIN) / volume
+    }
+
+    pub fn system_temperature_equivalent(&self) -> f64 {
+        // sensible heat weighted temperature relative to 0 °C:
+        let sensible_ice = self.mass_ice * CP_ICE * self.temp_ice;
+        let sensible_water = self.liquid_heat_capacity() * self.temp_water;
+        let c_eff = self.mass_ice * CP_ICE + self.liquid_heat_capacity();
+        if c_eff.abs() < 1e-9 {
+            0.0
+        } else {
+            (sensible_ice + sensible_water) / c_eff
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::constants::{KELVIN, LATENT_VAPORIZATION, LATENT_VAPORIZATION_0C, R_VAPOR};
+
+/// Saturation vapour pressure over water (Pa), Magnus formula.
+pub fn saturation_pressure(temp: f64) -> f64 {
+    610.94 * (17.625 * temp / (temp + 243.04)).exp()
+}
+
+/// Mass of water vapour per volume of saturated air (kg/m^3).
+pub fn saturation_density(temp: f64) -> f64 {
+    saturation_pressure(temp) / (R_VAPOR * (temp + KELVIN))
+}
+
+/// Saturation vapour pressure over ice (Pa), Magnus formula; below the one
+/// over water at the same temperature, so ice keeps in air that water dries in.
+pub fn saturation_pressure_ice(temp: f64) -> f64 {
+    611.15 * (22.452 * temp / (temp + 272.55)).exp()
+}
+
+/// Mass of water vapour per volume of air saturated over ice (kg/m^3).
+pub fn saturation_density_ice(temp: f64) -> f64 {
+    saturation_pressure_ice(temp) / (R_VAPOR * (temp + KELVIN))
+}
+
+/// Dew point (°C) of air at `temp` and relative humidity `humidity` (0..1),
+/// the Magnus formula inverted.
+pub fn dew_point(temp: f64, humidity: f64) -> f64 {
+    let gamma = humidity.max(1e-6).ln() + 17.625 * temp / (temp + 243.04);
+    243.04 * gamma / (17.625 - gamma)
+}
+
+/// Latent heat of vaporization (J/kg) at `temp`, interpolated between 0 and 100 °C.
+pub fn latent_heat(temp: f64) -> f64 {
+    LATENT_VAPORIZATION_0C + (LATENT_VAPORIZATION - LATENT_VAPORIZATION_0C) * temp / 100.0
+}
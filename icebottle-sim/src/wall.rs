@@ -0,0 +1,402 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{H_INSIDE, H_OUTSIDE, H_WIND_PER_MS, KELVIN, RADIATION_REF_TEMP, STEFAN_BOLTZMANN};
+use crate::geometry::Geometry;
+use crate::reduce::ordered_sum;
+
+/// What the bottle wall is made of.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum WallMaterial {
+    Glass,
+    #[default]
+    Pet,
+    StainlessSteel,
+    Foam,
+}
+
+impl WallMaterial {
+    pub const ALL: [WallMaterial; 4] = [
+        WallMaterial::Glass,
+        WallMaterial::Pet,
+        WallMaterial::StainlessSteel,
+        WallMaterial::Foam,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WallMaterial::Glass => "Glass",
+            WallMaterial::Pet => "PET",
+            WallMaterial::StainlessSteel => "Steel",
+            WallMaterial::Foam => "Foam",
+        }
+    }
+
+    /// Thermal conductivity in W/(m*K).
+    pub fn conductivity(self) -> f64 {
+        match self {
+            WallMaterial::Glass => 1.0,
+            WallMaterial::Pet => 0.2,
+            WallMaterial::StainlessSteel => 16.0,
+            WallMaterial::Foam => 0.035,
+        }
+    }
+
+    /// Typical wall thickness for a drinks bottle of this material, in m.
+    pub fn typical_thickness(self) -> f64 {
+        match self {
+            WallMaterial::Glass => 0.003,
+            WallMaterial::Pet => 0.0004,
+            WallMaterial::StainlessSteel => 0.0008,
+            WallMaterial::Foam => 0.005,
+        }
+    }
+
+    /// Emissivity of the outer surface.
+    pub fn emissivity(self) -> f64 {
+        match self {
+            WallMaterial::Glass => 0.92,
+            WallMaterial::Pet => 0.9,
+            WallMaterial::StainlessSteel => 0.15,
+            WallMaterial::Foam => 0.9,
+        }
+    }
+
+    /// Gauge pressure (Pa) above which a sealed bottle of this material may burst.
+    pub fn burst_pressure(self) -> f64 {
+        match self {
+            WallMaterial::Glass => 1.0e6,
+            WallMaterial::Pet => 1.2e6,
+            WallMaterial::StainlessSteel => 5.0e6,
+            WallMaterial::Foam => 0.2e6,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&m| m == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Self {
+        let i = Self::ALL.iter().position(|&m| m == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// What fills the gap of a double-walled bottle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum GapGas {
+    #[default]
+    Vacuum,
+    Argon,
+    Air,
+}
+
+impl GapGas {
+    pub const ALL: [GapGas; 3] = [GapGas::Vacuum, GapGas::Argon, GapGas::Air];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GapGas::Vacuum => "Vacuum",
+            GapGas::Argon => "Argon",
+            GapGas::Air => "Air",
+        }
+    }
+
+    /// Thermal conductivity near room temperature (W/(m*K)); nothing carries
+    /// heat across a vacuum.
+    pub fn conductivity(self) -> f64 {
+        match self {
+            GapGas::Vacuum => 0.0,
+            GapGas::Argon => 0.0177,
+            GapGas::Air => 0.0257,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&g| g == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Self {
+        let i = Self::ALL.iter().position(|&g| g == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// Extra layer outside the bottle wall, added in series to the thermal resistance.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum InsulationLayer {
+    /// Conducting layer such as a neoprene sleeve or foam jacket.
+    Solid { thickness: f64, conductivity: f64 },
+    /// Gap between the two walls of a double-walled bottle (width in m). A gas
+    /// in it conducts straight across, since a few millimetres is too narrow
+    /// for convection to start, and the facing surfaces exchange radiation
+    /// as two grey plates.
+    Gap { gas: GapGas, width: f64, emissivity: f64 },
+}
+
+impl InsulationLayer {
+    /// Thermal resistance in K/W for a layer spanning `area` m^2, with gap
+    /// radiation linearised about the reference temperature.
+    pub fn resistance(&self, area: f64) -> f64 {
+        self.resistance_at(area, RADIATION_REF_TEMP)
+    }
+
+    /// Thermal resistance in K/W with gap radiation linearised about
+    /// `gap_temp` (K), the mean temperature across the gap.
+    pub fn resistance_at(&self, area: f64, gap_temp: f64) -> f64 {
+        match *self {
+            InsulationLayer::Solid { thickness, conductivity } => thickness / (conductivity * area),
+            InsulationLayer::Gap { gas, width, emissivity } => {
+                // conduction through the gas and radiation side by side
+                let e = emissivity.clamp(1e-3, 1.0);
+                let exchange = 1.0 / (2.0 / e - 1.0);
+                let t = gap_temp.max(1.0);
+                let h_rad = 4.0 * STEFAN_BOLTZMANN * t * t * t * exchange;
+                let h_gas = gas.conductivity() / width.max(1e-4);
+                1.0 / ((h_rad + h_gas) * area)
+            }
+        }
+    }
+}
+
+/// Common insulation stacks for the GUI.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InsulationPreset {
+    #[default]
+    None,
+    FoamSleeve,
+    VacuumFlask,
+    VacuumFlaskWithSleeve,
+}
+
+impl InsulationPreset {
+    pub const ALL: [InsulationPreset; 4] = [
+        InsulationPreset::None,
+        InsulationPreset::FoamSleeve,
+        InsulationPreset::VacuumFlask,
+        InsulationPreset::VacuumFlaskWithSleeve,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InsulationPreset::None => "None",
+            InsulationPreset::FoamSleeve => "Sleeve",
+            InsulationPreset::VacuumFlask => "Vacuum",
+            InsulationPreset::VacuumFlaskWithSleeve => "Vac+sleeve",
+        }
+    }
+
+    pub fn layers(self) -> Vec<InsulationLayer> {
+        // 5 mm neoprene sleeve; 3 mm vacuum gap between polished (low-emissivity) steel walls
+        let sleeve = InsulationLayer::Solid { thickness: 0.005, conductivity: 0.05 };
+        let vacuum = InsulationLayer::Gap {
+            gas: GapGas::Vacuum,
+            width: 0.003,
+            emissivity: 0.05,
+        };
+        match self {
+            InsulationPreset::None => Vec::new(),
+            InsulationPreset::FoamSleeve => vec![sleeve],
+            InsulationPreset::VacuumFlask => vec![vacuum],
+            InsulationPreset::VacuumFlaskWithSleeve => vec![vacuum, sleeve],
+        }
+    }
+
+    /// The preset `layers` were made from, whatever fills the gap.
+    pub fn of_layers(layers: &[InsulationLayer]) -> Option<Self> {
+        let mut evacuated = layers.to_vec();
+        set_gap_gas(&mut evacuated, GapGas::Vacuum);
+        Self::ALL.into_iter().find(|p| p.layers() == evacuated)
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&p| p == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Self {
+        let i = Self::ALL.iter().position(|&p| p == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+fn set_gap_gas(layers: &mut [InsulationLayer], gas: GapGas) {
+    for layer in layers {
+        if let InsulationLayer::Gap { gas: g, .. } = layer {
+            *g = gas;
+        }
+    }
+}
+
+/// Heat through the wall split by mechanism (W, positive => into the bottle).
+///
+/// At the outer surface convection, radiation, absorbed sunlight and condensing
+/// vapour bring heat in, and conduction carries the same total on through the
+/// wall to the contents.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WallExchange {
+    pub convection: f64,
+    pub radiation: f64,
+    pub solar: f64,
+    pub latent: f64, // condensation on the outside (negative while condensate evaporates)
+    pub conduction: f64,
+    pub surface_temp: f64, // Celsius, outer surface
+    pub resistance: f64,   // K/W from the contents to the outer surface, as solved
+}
+
+/// Conditions at the outer surface of the bottle, and the film of the
+/// contents on the inner one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Surroundings {
+    pub air_temp: f64,       // Celsius, also the radiant temperature of the surroundings
+    pub h_convection: f64,   // W/(m^2*K)
+    pub h_inside: f64,       // W/(m^2*K), contents to the inner wall
+    pub absorbed_solar: f64, // W absorbed at the outer surface
+    pub latent: f64,         // W released at the outer surface by condensation
+    /// Standing in a liquid bath: the outer surface neither radiates nor gets sun.
+    pub immersed: bool,
+}
+
+impl Surroundings {
+    /// Still air at `air_temp`, no sun.
+    pub fn still_air(air_temp: f64) -> Self {
+        Self {
+            air_temp,
+            h_convection: H_OUTSIDE,
+            h_inside: H_INSIDE,
+            absorbed_solar: 0.0,
+            latent: 0.0,
+            immersed: false,
+        }
+    }
+
+    /// Outside convection coefficient for a wind of `speed` m/s: natural
+    /// convection plus a forced term growing linearly with the wind.
+    pub fn h_for_wind(speed: f64) -> f64 {
+        H_OUTSIDE + H_WIND_PER_MS * speed.max(0.0)
+    }
+}
+
+/// Bottle wall between the contents and the outside air.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct WallConfig {
+    pub material: WallMaterial,
+    pub thickness: f64, // m
+    pub area: f64,      // m^2, wetted + headspace wall
+    /// Insulation outside the wall, innermost first.
+    pub layers: Vec<InsulationLayer>,
+    /// Outer surface emissivity for radiative exchange with the surroundings.
+    pub emissivity: f64,
+}
+
+impl Default for WallConfig {
+    fn default() -> Self {
+        Self::for_material(WallMaterial::default())
+    }
+}
+
+impl WallConfig {
+    /// The default bottle geometry with the material's usual thickness.
+    pub fn for_material(material: WallMaterial) -> Self {
+        Self {
+            material,
+            thickness: material.typical_thickness(),
+            area: Geometry::default().area(),
+            layers: Vec::new(),
+            emissivity: material.emissivity(),
+        }
+    }
+
+    /// Switches material and adopts its typical thickness.
+    pub fn set_material(&mut self, material: WallMaterial) {
+        self.material = material;
+        self.thickness = material.typical_thickness();
+        self.emissivity = material.emissivity();
+    }
+
+    /// Gas in the gap of a double wall, `None` without one.
+    pub fn gap_gas(&self) -> Option<GapGas> {
+        self.layers.iter().find_map(|layer| match layer {
+            InsulationLayer::Gap { gas, .. } => Some(*gas),
+            InsulationLayer::Solid { .. } => None,
+        })
+    }
+
+    /// Fills every gap in the insulation with `gas`.
+    pub fn set_gap_gas(&mut self, gas: GapGas) {
+        set_gap_gas(&mut self.layers, gas);
+    }
+
+    /// Resistance from the contents to the outer surface (K/W): inside film,
+    /// wall conduction and insulation layers in series.
+    pub fn conduction_resistance(&self) -> f64 {
+        self.conduction_resistance_at(RADIATION_REF_TEMP)
+    }
+
+    /// `conduction_resistance` with gap radiation taken at `gap_temp` (K).
+    pub fn conduction_resistance_at(&self, gap_temp: f64) -> f64 {
+        self.resistance_with(H_INSIDE, gap_temp)
+    }
+
+    // The same with an inside film of `h_inside` W/(m^2*K)
+    fn resistance_with(&self, h_inside: f64, gap_temp: f64) -> f64 {
+        let r_inside = 1.0 / (h_inside * self.area);
+        let r_wall = self.thickness / (self.material.conductivity() * self.area);
+        let r_layers = ordered_sum(self.layers.iter().map(|l| l.resistance_at(self.area, gap_temp)));
+        r_inside + r_wall + r_layers
+    }
+
+    /// Overall conductance UA in W/K, with radiation linearised about the
+    /// reference temperature. For display; the model uses `exchange`.
+    pub fn conductance(&self) -> f64 {
+        let t = RADIATION_REF_TEMP;
+        let h_rad = 4.0 * self.emissivity * STEFAN_BOLTZMANN * t * t * t;
+        let r_outside = 1.0 / ((H_OUTSIDE + h_rad) * self.area);
+        1.0 / (self.conduction_resistance() + r_outside)
+    }
+
+    /// Solves the outer-surface balance for heat flowing from the surroundings
+    /// to contents at `inner_temp` (Celsius), with full T^4 radiation. Radiation
+    /// across a gap follows the temperatures on either side of it, so the
+    /// resistance is updated along with the surface temperature.
+    pub fn exchange(&self, inner_temp: f64, surroundings: &Surroundings) -> WallExchange {
+        let outside_temp = surroundings.air_temp;
+        let solar = surroundings.absorbed_solar;
+        let latent = surroundings.latent;
+        let h_inside = surroundings.h_inside;
+        let mut r_cond = self.resistance_with(h_inside, RADIATION_REF_TEMP);
+        let h_conv_a = surroundings.h_convection * self.area;
+        let emissivity = if surroundings.immersed { 0.0 } else { self.emissivity };
+        let rad_a = emissivity * STEFAN_BOLTZMANN * self.area;
+        let t_out_k = outside_temp + KELVIN;
+        let t_out4 = t_out_k * t_out_k * t_out_k * t_out_k;
+
+        // Newton on f(Ts) = conduction in - (convection + radiation + solar) out,
+        // from the linearised guess; a fixed iteration count keeps results deterministic.
+        let mut ts = inner_temp + self.conductance() * (outside_temp - inner_temp) * r_cond;
+        let has_gap = self.gap_gas().is_some();
+        for _ in 0..4 {
+            if has_gap {
+                r_cond = self.resistance_with(h_inside, (inner_temp + ts) / 2.0 + KELVIN);
+            }
+            let ts_k = ts + KELVIN;
+            let ts4 = ts_k * ts_k * ts_k * ts_k;
+            let f = (ts - inner_temp) / r_cond - h_conv_a * (outside_temp - ts) - rad_a * (t_out4 - ts4) - solar - latent;
+            let df = 1.0 / r_cond + h_conv_a + 4.0 * rad_a * ts_k * ts_k * ts_k;
+            ts -= f / df;
+        }
+
+        let ts_k = ts + KELVIN;
+        let ts4 = ts_k * ts_k * ts_k * ts_k;
+        WallExchange {
+            convection: h_conv_a * (outside_temp - ts),
+            radiation: rad_a * (t_out4 - ts4),
+            solar,
+            latent,
+            conduction: (ts - inner_temp) / r_cond,
+            surface_temp: ts,
+            resistance: r_cond,
+        }
+    }
+}
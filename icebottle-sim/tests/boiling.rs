@@ -0,0 +1,53 @@
+use icebottle_sim::constants::LATENT_VAPORIZATION;
+use icebottle_sim::{InitialConditions, Simulation, FIXED_DT};
+
+// Water just off the boil with a 1 kW immersion heater in it
+fn kettle(water: f64, capped: bool) -> Simulation {
+    let mut sim = Simulation::with_initial(InitialConditions {
+        water,
+        ice: 0.0,
+        air: 0.0005,
+        salt: 0.0,
+        system_temp: 100.0,
+        outside_temp: 20.0,
+    });
+    sim.set_heater_power(1000.0);
+    sim.toggle_running();
+    sim.set_capped(capped);
+    sim
+}
+
+#[test]
+fn water_at_the_boil_turns_to_steam_at_the_latent_heat() {
+    let mut sim = kettle(0.5, true);
+    // the heat lost through the wall, summed apart from the model
+    let mut through_wall = 0.0;
+    for _ in 0..(600.0 / FIXED_DT) as usize {
+        through_wall += sim.wall_exchange().conduction * FIXED_DT;
+        sim.step(FIXED_DT);
+        assert!(sim.state().temp_water <= 100.0, "{} °C", sim.state().temp_water);
+    }
+
+    // what the heater puts in past the wall's loss boils water at 2.26 MJ/kg
+    let steam = sim.state().mass_vapor;
+    let expected = (sim.heater_energy() + through_wall) / LATENT_VAPORIZATION;
+    assert!(steam > 0.2, "{steam} kg");
+    assert!((steam - expected).abs() < 1e-4 * expected, "{steam} kg of steam, {expected} kg expected");
+    assert!((sim.state().mass_water + steam - 0.5).abs() < 1e-12);
+}
+
+#[test]
+fn heat_left_once_the_water_has_boiled_off_goes_to_the_air() {
+    // 50 g takes some 113 kJ to boil away, under two minutes at 1 kW
+    let mut sim = kettle(0.05, false);
+    for _ in 0..(180.0 / FIXED_DT) as usize {
+        sim.step(FIXED_DT);
+    }
+
+    assert_eq!(sim.state().mass_water, 0.0);
+    assert!((sim.vapor_lost() - 0.05).abs() < 1e-3, "{} kg", sim.vapor_lost());
+    // with nothing left to boil, the heater's power is not lost but heats the headspace
+    assert!(sim.state().temp_air > 100.0, "{} °C", sim.state().temp_air);
+    let total = sim.energy().total;
+    assert!(total.air > 0.0, "{} J", total.air);
+}
@@ -0,0 +1,21 @@
+use icebottle_sim::{EventKind, Scenario, Simulation, FIXED_DT};
+
+#[test]
+fn wort_is_chilled_to_the_target_and_the_coolant_shut_off() {
+    let scenario = Scenario::from_toml(include_str!("../../scenarios/wort-immersion-chiller.toml")).unwrap();
+    let mut sim = Simulation::new();
+    scenario.apply(&mut sim);
+    sim.toggle_running();
+    while sim.is_running() {
+        sim.step(FIXED_DT);
+    }
+
+    let chiller = sim.chiller();
+    let chilled = chiller.chill_time().expect("the wort reaches 20 °C");
+    // 20 L from 95 °C with 12 °C tap water: a quarter of an hour or so, not minutes or hours
+    assert!((600.0..1800.0).contains(&chilled), "{chilled} s");
+    // the coolant ran at 8 L/min until then and not after
+    assert!((chiller.coolant_used() - 8.0 / 60.0 * chilled).abs() < 0.01, "{} kg", chiller.coolant_used());
+    assert_eq!(chiller.power(sim.state().temp_water), 0.0);
+    assert!(sim.events().iter().any(|e| e.kind == EventKind::Chilled && e.time == chilled));
+}
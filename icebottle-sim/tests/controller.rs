@@ -0,0 +1,52 @@
+use icebottle_sim::{ControlMode, InitialConditions, Simulation, FIXED_DT};
+
+// Half a litre at room temperature, a 200 W heater held to 40 °C
+fn warmer(ki: f64) -> Simulation {
+    let mut sim = Simulation::with_initial(InitialConditions {
+        water: 0.5,
+        ice: 0.0,
+        air: 0.02,
+        salt: 0.0,
+        system_temp: 20.0,
+        outside_temp: 20.0,
+    });
+    sim.set_heater_power(200.0);
+    let controller = sim.controller_mut();
+    controller.mode = ControlMode::Pid;
+    controller.setpoint = 40.0;
+    controller.ki = ki;
+    sim.toggle_running();
+    sim
+}
+
+fn run_for(sim: &mut Simulation, seconds: f64) -> f64 {
+    let mut peak = f64::MIN;
+    for _ in 0..(seconds / FIXED_DT) as usize {
+        sim.step(FIXED_DT);
+        peak = peak.max(sim.state().temp_water);
+    }
+    peak
+}
+
+#[test]
+fn pid_settles_on_the_setpoint_at_the_power_the_wall_loses() {
+    let mut sim = warmer(0.05);
+    let peak = run_for(&mut sim, 2.0 * 3600.0);
+    assert!(peak < 42.0, "overshot to {peak} °C");
+    assert!((sim.state().temp_water - 40.0).abs() < 0.01, "{} °C", sim.state().temp_water);
+    // held steady, the heater makes up exactly what leaks out through the wall
+    let loss = -sim.wall_exchange().conduction;
+    assert!(loss > 0.0);
+    assert!((sim.heater_output() - loss).abs() < 0.01 * loss, "{} W in, {loss} W out", sim.heater_output());
+}
+
+#[test]
+fn without_integral_action_it_settles_short_by_the_loss_over_the_gain() {
+    let mut sim = warmer(0.0);
+    run_for(&mut sim, 2.0 * 3600.0);
+    // proportional control alone needs an error to give any power at all
+    let offset = 40.0 - sim.state().temp_water;
+    let expected = -sim.wall_exchange().conduction / sim.controller().kp;
+    assert!(offset > 0.1, "{offset} K");
+    assert!((offset - expected).abs() < 0.01 * expected, "{offset} K short, {expected} K expected");
+}
@@ -0,0 +1,65 @@
+use icebottle_sim::{Cooler, InitialConditions, Simulation};
+
+fn bottle(ice: f64, water: f64, temp: f64) -> Simulation {
+    let init = InitialConditions { water, ice, system_temp: temp, outside_temp: 30.0, ..InitialConditions::default() };
+    Simulation::with_initial(init)
+}
+
+// a 30 L foam box on a 30 °C day, stepped for `hours`
+fn run(mut cooler: Cooler, hours: f64) -> Cooler {
+    cooler.thermal_mass = 2000.0;
+    cooler.start();
+    for _ in 0..(hours * 7200.0) as usize {
+        cooler.step(0.5);
+    }
+    cooler
+}
+
+#[test]
+fn a_packed_cooler_keeps_its_ice_longer() {
+    let mut alone = Cooler::new(0.03, 1.0, 30.0);
+    alone.bottles.push(bottle(0.6, 0.0, -10.0));
+    let mut packed = Cooler::new(0.03, 1.0, 30.0);
+    packed.bottles.extend((0..6).map(|_| bottle(0.6, 0.0, -10.0)));
+    let (alone, packed) = (run(alone, 3.0), run(packed, 3.0));
+
+    // six frozen bottles hold the air far colder than one does
+    assert!(packed.air_temp() < alone.air_temp() - 5.0, "{} vs {} °C", packed.air_temp(), alone.air_temp());
+    assert!(packed.bottles[0].state().mass_ice > alone.bottles[0].state().mass_ice + 0.1);
+}
+
+#[test]
+fn a_drink_against_ice_bottles_cools_through_the_contact() {
+    let setup = |touching| {
+        let mut cooler = Cooler::new(0.03, 1.0, 30.0);
+        cooler.bottles.push(bottle(0.0, 0.5, 25.0));
+        cooler.bottles.extend((0..2).map(|_| bottle(0.6, 0.0, -10.0)));
+        if touching {
+            cooler.touching = vec![(0, 1), (0, 2)];
+        }
+        run(cooler, 1.0)
+    };
+    let (apart, against) = (setup(false), setup(true));
+    let drink = |c: &Cooler| c.bottles[0].state().temp_water;
+    assert!(drink(&against) < 8.0, "{} °C", drink(&against));
+    assert!(drink(&apart) > 12.0, "{} °C", drink(&apart));
+}
+
+#[test]
+fn ice_water_left_in_the_box_keeps_the_drinks_cold_longer() {
+    let setup = |drain| {
+        let mut cooler = Cooler::new(0.03, 1.0, 30.0);
+        cooler.ice = 4.0;
+        cooler.drain = drain;
+        cooler.bottles.extend((0..6).map(|_| bottle(0.0, 0.5, 20.0)));
+        run(cooler, 24.0)
+    };
+    let (kept, drained) = (setup(false), setup(true));
+    assert_eq!(kept.drained(), 0.0);
+    assert!((drained.drained() - 4.0).abs() < 1e-9, "{} kg", drained.drained());
+    assert!(kept.loose_ice() == 0.0 && drained.loose_ice() == 0.0);
+
+    // the drinks stand in 0 °C water, and it stays cold for a while after the ice is gone
+    let hold = |c: &Cooler| c.bottles[0].hold_time().hours();
+    assert!(hold(&kept) > hold(&drained) + 1.5, "{:.1} h vs {:.1} h", hold(&kept), hold(&drained));
+}
@@ -0,0 +1,22 @@
+use icebottle_sim::constants::LATENT_FUSION;
+use icebottle_sim::{ModelDescription, Simulation};
+
+#[test]
+fn the_description_follows_the_setup() {
+    let mut sim = Simulation::new();
+    let plain = ModelDescription::of(&sim);
+    assert!(!plain.equations.iter().any(|e| e.name == "immersion chiller"));
+    let latent = plain.constants.iter().find(|c| c.name == "LATENT_FUSION").unwrap();
+    assert_eq!(latent.value, LATENT_FUSION);
+
+    sim.chiller_mut().flow = 0.1;
+    sim.set_capped(false);
+    let chilled = ModelDescription::of(&sim);
+    assert!(chilled.equations.iter().any(|e| e.name == "immersion chiller"));
+    assert!(chilled.equations.iter().any(|e| e.name == "evaporation"));
+    let json: serde_json::Value = serde_json::from_str(&chilled.to_json()).unwrap();
+    assert_eq!(json["parameters"].as_array().unwrap().len(), chilled.parameters.len());
+    let mut markdown = Vec::new();
+    chilled.write_markdown(&mut markdown).unwrap();
+    assert!(String::from_utf8(markdown).unwrap().contains("| chiller flow | 0.1 | kg/s |"));
+}
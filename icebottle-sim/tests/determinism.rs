@@ -0,0 +1,56 @@
+use icebottle_sim::{InitialConditions, InsulationPreset, Integrator, Simulation, FIXED_DT};
+
+// Fixed scenario: freezer-cold bottle with ice warming in a hot room.
+fn scenario(integrator: Integrator) -> Simulation {
+    let mut sim = Simulation::with_initial(InitialConditions {
+        water: 0.45,
+        ice: 0.15,
+        air: 0.01,
+        salt: 0.0,
+        system_temp: -6.0,
+        outside_temp: 32.0,
+    });
+    sim.set_integrator(integrator);
+    sim.wall_mut().layers = InsulationPreset::FoamSleeve.layers();
+    sim.toggle_running();
+    sim
+}
+
+// Samples a fingerprint every simulated minute over two hours, like an export would.
+fn run(integrator: Integrator) -> Vec<u64> {
+    let mut sim = scenario(integrator);
+    let steps_per_sample = (60.0 / FIXED_DT).round() as usize;
+    (0..120)
+        .map(|_| {
+            for _ in 0..steps_per_sample {
+                sim.step(FIXED_DT);
+            }
+            sim.fingerprint()
+        })
+        .collect()
+}
+
+#[test]
+fn repeated_runs_are_bit_identical() {
+    for integrator in [Integrator::Euler, Integrator::Rk4, Integrator::Adaptive] {
+        assert_eq!(run(integrator), run(integrator), "{integrator:?}");
+    }
+}
+
+#[test]
+fn runs_on_other_threads_are_bit_identical() {
+    let here = run(Integrator::Rk4);
+    let there = std::thread::spawn(|| run(Integrator::Rk4)).join().unwrap();
+    assert_eq!(here, there);
+}
+
+// Reference check: the final fingerprint of the fixed scenario, as taken on
+// x86_64 Linux. It guards against unintended changes to the model there; other
+// targets use their own maths library for exp, ln and cos, so it isn't a
+// cross-platform guarantee. If the model is changed on purpose, update the
+// constant from the assertion message.
+#[test]
+fn fixed_scenario_matches_reference_fingerprint() {
+    let last = *run(Integrator::Euler).last().unwrap();
+    assert_eq!(last, 0x373a_5f48_aec7_33e0, "reference fingerprint changed: got {last:#018x}");
+}
@@ -0,0 +1,209 @@
+use icebottle_sim::constants::{CAP_SEAL_LEAK, CP_ICE, CP_WATER, LATENT_FUSION};
+use icebottle_sim::{EventKind, InitialConditions, Liquid, Room, Simulation, FIXED_DT};
+
+#[test]
+fn the_ledger_books_every_joule_of_a_melt() {
+    let mut sim = Simulation::new();
+    let ice = sim.state().mass_ice;
+    let enthalpy = sim.state().enthalpy();
+    sim.toggle_running();
+    // the flux through the wall, summed apart from the ledger step by step
+    let mut through_wall = 0.0;
+    for _ in 0..(3600.0 / FIXED_DT) as usize {
+        through_wall += sim.wall_exchange().conduction * FIXED_DT;
+        sim.step(FIXED_DT);
+    }
+
+    let total = sim.energy().total;
+    // the warm room melts the ice through the wall and nothing else
+    assert!(through_wall > 0.0);
+    assert!((total.wall - through_wall).abs() < 1e-6 * through_wall, "{} J booked, {through_wall} J through the wall", total.wall);
+    assert_eq!(total.heater + total.chiller + total.contact + total.neck, 0.0);
+    // and the flows booked make up the change, with nothing left over to hide in the wall
+    let sources = total.sources();
+    let stored = total.ice_sensible + total.melting + total.water_sensible + total.steam + total.air;
+    let change = sim.state().enthalpy() - enthalpy;
+    assert!((sources - change).abs() < 1e-6 * change.abs().max(1.0), "{sources} J in, {change} J gained");
+    assert!((stored - change).abs() < 1e-6 * change.abs().max(1.0), "{stored} J stored, {change} J gained");
+    assert!((total.melting - (ice - sim.state().mass_ice) * LATENT_FUSION).abs() < 1e-6, "{} J", total.melting);
+}
+
+#[test]
+fn ice_in_milk_melts_with_milks_latent_heat() {
+    let mut sim = Simulation::new();
+    sim.set_liquid(Liquid::Milk);
+    sim.toggle_running();
+    let milk = Liquid::Milk.properties();
+    // the liquid is filled in on the start, ice at milk's freezing point
+    assert_eq!(sim.state().liquid, Liquid::Milk);
+    assert_eq!(sim.state().temp_ice, milk.freezing_point);
+    let (ice, enthalpy) = (sim.state().mass_ice, sim.state().enthalpy());
+    for _ in 0..(3600.0 / FIXED_DT) as usize {
+        sim.step(FIXED_DT);
+    }
+
+    let total = sim.energy().total;
+    let sources = total.sources();
+    let change = sim.state().enthalpy() - enthalpy;
+    assert!((sources - change).abs() < 1e-6 * change.abs().max(1.0), "{sources} J in, {change} J gained");
+    assert!((total.melting - (ice - sim.state().mass_ice) * milk.latent_fusion).abs() < 1e-6, "{} J", total.melting);
+}
+
+#[test]
+fn a_dropped_cube_is_logged_with_its_own_enthalpy() {
+    let mut sim = Simulation::new();
+    // not while paused, as starting refills the bottle, nor through the cap
+    assert!(!sim.drop_ice(0.02, -18.0));
+    sim.toggle_running();
+    assert!(!sim.drop_ice(0.02, -18.0));
+    sim.set_capped(false);
+    for _ in 0..(600.0 / FIXED_DT) as usize {
+        sim.step(FIXED_DT);
+    }
+    let (ice, enthalpy) = (sim.state().mass_ice, sim.state().enthalpy());
+    assert!(sim.drop_ice(0.02, -18.0));
+
+    assert!((sim.state().mass_ice - ice - 0.02).abs() < 1e-12);
+    let event = sim.events().last().unwrap();
+    assert_eq!(event.kind, EventKind::IceAdded);
+    let cube = 0.02 * (CP_ICE * -18.0 - LATENT_FUSION);
+    assert!((event.enthalpy_change - cube).abs() < 1e-6, "{} J", event.enthalpy_change);
+    assert!((sim.state().enthalpy() - enthalpy - cube).abs() < 1e-6);
+    // a cube warmer than ice can be goes in at the freezing point
+    let before = sim.state().enthalpy();
+    assert!(sim.drop_ice(0.01, 5.0));
+    let change = sim.state().enthalpy() - before;
+    let cube = 0.01 * (CP_ICE * sim.state().freezing_point() - LATENT_FUSION);
+    assert!((change - cube).abs() < 1e-6, "{change} J");
+}
+
+#[test]
+fn hot_water_poured_on_ice_mixes_in_by_its_enthalpy() {
+    let mut sim = Simulation::new();
+    sim.toggle_running();
+    assert!(!sim.pour_in(0.05, 80.0));
+    sim.set_capped(false);
+    for _ in 0..(600.0 / FIXED_DT) as usize {
+        sim.step(FIXED_DT);
+    }
+    let (water, ice, enthalpy) = (sim.state().mass_water, sim.state().mass_ice, sim.state().enthalpy());
+    let temp = sim.state().temp_water;
+    assert!(sim.pour_in(0.05, 80.0));
+
+    let event = *sim.events().last().unwrap();
+    assert_eq!(event.kind, EventKind::PouredIn);
+    assert!((event.enthalpy_change - 0.05 * CP_WATER * 80.0).abs() < 1e-6, "{} J", event.enthalpy_change);
+    // lumped ice takes the poured water's heat above 0 °C at once, like the wall's
+    let melted = 0.05 * CP_WATER * 80.0 / LATENT_FUSION;
+    assert!((sim.state().mass_ice - (ice - melted)).abs() < 1e-12, "{} kg of ice", sim.state().mass_ice);
+    assert!((sim.state().mass_water - (water + 0.05 + melted)).abs() < 1e-12);
+    let mixed = water * temp / (water + 0.05 + melted);
+    assert!((sim.state().temp_water - mixed).abs() < 1e-9, "{} °C", sim.state().temp_water);
+    // from there on the ledger and the event make up the change
+    let ledger = sim.energy().total;
+    let sources = ledger.sources();
+    for _ in 0..(60.0 / FIXED_DT) as usize {
+        sim.step(FIXED_DT);
+    }
+    let total = sim.energy().total;
+    let gained = total.sources() - sources + event.enthalpy_change;
+    let change = sim.state().enthalpy() - enthalpy;
+    assert!((gained - change).abs() < 1e-6 * change.abs(), "{gained} J booked, {change} J gained");
+
+    // half poured out takes half the liquid's enthalpy with it, and leaves the ice
+    let (water, ice, liquid) = (sim.state().mass_water, sim.state().mass_ice, sim.state().liquid_heat_capacity() * sim.state().temp_water);
+    assert!(sim.pour_out(0.5));
+    assert!((sim.state().mass_water - water / 2.0).abs() < 1e-12);
+    assert_eq!(sim.state().mass_ice, ice);
+    let event = sim.events().last().unwrap();
+    assert_eq!(event.kind, EventKind::PouredOut);
+    assert!((event.enthalpy_change + liquid / 2.0).abs() < 1e-6, "{} J", event.enthalpy_change);
+    assert!(!sim.pour_out(1.5));
+
+    // cubes are left to melt in the warm water by their own exchange
+    let mut sim = Simulation::new();
+    sim.set_ice_pieces(4);
+    sim.toggle_running();
+    sim.set_capped(false);
+    let (water, ice, temp) = (sim.state().mass_water, sim.state().mass_ice, sim.state().temp_water);
+    assert!(sim.pour_in(0.05, 80.0));
+    assert_eq!(sim.state().mass_ice, ice);
+    let mixed = (water * temp + 0.05 * 80.0) / (water + 0.05);
+    assert!((sim.state().temp_water - mixed).abs() < 1e-9, "{} °C", sim.state().temp_water);
+}
+
+#[test]
+fn a_sealed_room_gives_the_bottle_what_it_takes() {
+    let mut sim = Simulation::new();
+    sim.set_room(Some(Room::new(2.0, 0.0, 0.0, 0.0)));
+    let outdoor = sim.outside_temp();
+    sim.toggle_running();
+    for _ in 0..(3600.0 / FIXED_DT) as usize {
+        sim.step(FIXED_DT);
+    }
+
+    let room = sim.room().unwrap();
+    let taken = sim.energy().total.wall + sim.energy().total.neck;
+    let lost = (outdoor - room.temp()) * room.heat_capacity();
+    assert!(taken > 0.0);
+    assert!((lost - taken).abs() < 1e-6 * taken, "{lost} J lost, {taken} J taken");
+    // a small sealed room with nothing else in it does cool noticeably
+    assert!(room.temp() < outdoor - 1.0, "{} °C", room.temp());
+}
+
+#[test]
+fn ice_frozen_solid_sublimes_out_of_a_dry_freezer() {
+    // a day in a freezer, starting frozen solid, with the cap off, on and in humid air
+    let day = |capped: bool, humidity: f64| {
+        let mut sim = Simulation::new();
+        *sim.init_mut() = InitialConditions { water: 0.0, ice: 0.3, air: 0.0, salt: 0.0, system_temp: -18.0, outside_temp: -18.0 };
+        sim.set_capped(capped);
+        sim.set_humidity(humidity);
+        sim.set_tick_rate(1.0).unwrap();
+        sim.toggle_running();
+        let enthalpy = sim.state().enthalpy();
+        for _ in 0..86_400 {
+            sim.step(sim.step_size());
+        }
+        let total = sim.energy().total;
+        let change = sim.state().enthalpy() - enthalpy;
+        assert!((total.wall + total.neck - change).abs() < 1e-6 * change.abs().max(1.0), "{} J in, {change} J gained", total.wall + total.neck);
+        assert!((0.3 - sim.state().mass_ice - sim.ice_sublimed()).abs() < 1e-10);
+        assert_eq!(sim.vapor_lost(), 0.0);
+        sim.ice_sublimed()
+    };
+
+    let open = day(false, 0.3);
+    assert!(open > 1e-5 && open < 1e-3, "{open} kg");
+    let capped = day(true, 0.3);
+    assert!((capped / open - CAP_SEAL_LEAK).abs() < 1e-4, "{capped} kg");
+    // air wetter than the ice gives it nothing to lose to
+    assert_eq!(day(false, 0.95), 0.0);
+}
+
+#[test]
+fn a_shake_melts_ice_faster_for_a_while() {
+    let melt = |shaken: bool, pieces: usize| {
+        let mut sim = Simulation::new();
+        sim.set_ice_pieces(pieces);
+        sim.toggle_running();
+        let enthalpy = sim.state().enthalpy();
+        if shaken {
+            sim.shake();
+        }
+        for _ in 0..(300.0 / FIXED_DT) as usize {
+            sim.step(FIXED_DT);
+        }
+        // the sloshing moves heat about inside and the ledger still closes
+        let total = sim.energy().total;
+        let sources = total.sources();
+        let change = sim.state().enthalpy() - enthalpy;
+        assert!((sources - change).abs() < 1e-6 * change.abs(), "{sources} J in, {change} J gained");
+        assert_eq!(sim.agitation(), 0.0, "settled after five minutes");
+        sim.state().mass_ice
+    };
+    for pieces in [0, 4] {
+        let (still, shaken) = (melt(false, pieces), melt(true, pieces));
+        assert!(shaken < still - 1e-3, "{pieces} pieces: {shaken} kg of ice left shaken, {still} kg still");
+    }
+}
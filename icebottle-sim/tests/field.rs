@@ -0,0 +1,59 @@
+use icebottle_sim::{CellKind, Simulation, TemperatureField, FIXED_DT};
+
+// Ice left in the field, in cells' worth
+fn ice_cells(field: &TemperatureField) -> f64 {
+    let mut ice = 0.0;
+    for j in 0..field.nz() {
+        for i in 0..field.nx() {
+            if field.kind(i, j) == CellKind::Contents {
+                ice += 1.0 - field.liquid_fraction(i, j);
+            }
+        }
+    }
+    ice
+}
+
+#[test]
+fn the_field_melts_its_ice_from_the_warm_wall_inwards() {
+    // the default bottle: ice floating on cool water in a warm room
+    let mut sim = Simulation::new();
+    sim.toggle_running();
+    sim.set_field_mode(true);
+    let mut ice = ice_cells(sim.field().unwrap());
+    assert!(ice > 0.0);
+    for _ in 0..3 {
+        for _ in 0..(3600.0 / FIXED_DT) as usize {
+            sim.step(FIXED_DT);
+        }
+        let field = sim.field().unwrap();
+        let left = ice_cells(field);
+        assert!(left < ice || left == 0.0, "{left} cells of ice after {ice}");
+        ice = left;
+
+        // across a row of water the bottle is mirror-symmetric, warmest at the wall and coolest in the middle
+        let (nx, j) = (field.nx(), field.nz() / 3);
+        assert_eq!(field.kind(1, j), CellKind::Contents);
+        for i in 0..nx / 2 {
+            let (t, mirror) = (field.temperature(i, j), field.temperature(nx - 1 - i, j));
+            assert!((t - mirror).abs() < 1e-9, "{t} °C against {mirror} °C");
+            if i + 1 < nx / 2 {
+                assert!(t > field.temperature(i + 1, j), "column {i}");
+            }
+        }
+        assert!(field.temperature(0, j) < sim.outside_temp());
+    }
+    assert_eq!(ice, 0.0);
+}
+
+#[test]
+fn the_field_does_not_feed_back_into_the_lumped_model() {
+    let mut plain = Simulation::new();
+    plain.toggle_running();
+    let mut with_field = plain.clone();
+    with_field.set_field_mode(true);
+    for _ in 0..(1800.0 / FIXED_DT) as usize {
+        plain.step(FIXED_DT);
+        with_field.step(FIXED_DT);
+    }
+    assert_eq!(plain.fingerprint(), with_field.fingerprint());
+}
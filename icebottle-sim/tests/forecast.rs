@@ -0,0 +1,21 @@
+use icebottle_sim::{Predictor, Simulation};
+
+#[test]
+fn a_forecast_run_a_few_steps_at_a_time_predicts_what_one_run_at_once_does() {
+    let sim = Simulation::new();
+    let predictor = Predictor::default();
+    let at_once = predictor.predict(&sim);
+    assert!(at_once.ice_gone.is_some() && at_once.near_ambient.is_some(), "{at_once:?}");
+
+    let mut forecast = predictor.start(&sim);
+    let mut frames = 1;
+    let in_pieces = loop {
+        match forecast.advance(500) {
+            Some(prediction) => break prediction,
+            None => frames += 1,
+        }
+    };
+    assert_eq!(in_pieces, at_once);
+    // no single piece ran the whole forecast
+    assert!(frames > 1, "{frames}");
+}
@@ -0,0 +1,48 @@
+use icebottle_sim::{write_history_csv, Experiment, History, SAMPLE_COLUMNS};
+
+#[test]
+fn spilled_samples_read_back_in_order() {
+    let mut history = History::new(3, 10);
+    for i in 0..95 {
+        let x = i as f64;
+        history.push(&[x, x * 0.5, -x]).unwrap();
+    }
+    assert_eq!(history.len(), 95);
+    assert!(history.spilled() > 0);
+    assert!(history.len() - history.spilled() <= 10);
+
+    let mut seen = Vec::new();
+    history.for_each(|row| seen.push(row.to_vec())).unwrap();
+    assert_eq!(seen.len(), 95);
+    for (i, row) in seen.iter().enumerate() {
+        let x = i as f64;
+        assert_eq!(row, &[x, x * 0.5, -x]);
+    }
+
+    assert_eq!(history.get(3).unwrap(), Some(vec![3.0, 1.5, -3.0]));
+    assert_eq!(history.get(94).unwrap(), Some(vec![94.0, 47.0, -94.0]));
+    assert_eq!(history.get(95).unwrap(), None);
+
+    history.clear();
+    assert!(history.is_empty());
+}
+
+#[test]
+fn csv_has_header_and_every_sample() {
+    let mut history = History::new(SAMPLE_COLUMNS.len(), 4);
+    for i in 0..9 {
+        history.push(&[i as f64; SAMPLE_COLUMNS.len()]).unwrap();
+    }
+    let experiment = Experiment {
+        name: "fridge".to_string(),
+        notes: String::new(),
+    };
+    let mut out = Vec::new();
+    write_history_csv(&mut out, &experiment, &mut history).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "# name: fridge");
+    assert_eq!(lines[1], SAMPLE_COLUMNS.join(","));
+    assert_eq!(lines.len(), 2 + 9);
+    assert_eq!(lines[10], ["8"; SAMPLE_COLUMNS.len()].join(","));
+}
@@ -0,0 +1,56 @@
+use icebottle_sim::constants::{ATMOSPHERIC_PRESSURE, KELVIN};
+use icebottle_sim::{InitialConditions, Simulation, WallMaterial, FIXED_DT};
+
+fn sealed(system_temp: f64, outside_temp: f64) -> Simulation {
+    let mut sim = Simulation::with_initial(InitialConditions {
+        water: 0.5,
+        ice: 0.0,
+        air: 0.0005,
+        salt: 0.0,
+        system_temp,
+        outside_temp,
+    });
+    sim.toggle_running();
+    sim
+}
+
+#[test]
+fn sealed_air_warms_at_constant_volume() {
+    // a cold drink in a warm room: no ice, no steam, just the headspace warming
+    let mut sim = sealed(5.0, 30.0);
+    let (p0, t0) = (sim.headspace_pressure(), sim.state().temp_air);
+    for _ in 0..(1800.0 / FIXED_DT) as usize {
+        sim.step(FIXED_DT);
+    }
+
+    let (p, t) = (sim.headspace_pressure(), sim.state().temp_air);
+    assert!(t > t0 + 5.0, "{t0} °C to {t} °C");
+    let expected = p0 * (t + KELVIN) / (t0 + KELVIN);
+    assert!((p - expected).abs() < 1e-9 * expected, "{p} Pa, {expected} Pa by Gay-Lussac");
+}
+
+#[test]
+fn a_boiling_sealed_bottle_is_at_risk_once_past_its_rated_pressure() {
+    for material in [WallMaterial::Pet, WallMaterial::StainlessSteel] {
+        let mut sim = sealed(100.0, 20.0);
+        sim.wall_mut().material = material;
+        sim.set_heater_power(1000.0);
+        let rated = material.burst_pressure();
+        assert!(!sim.burst_risk());
+        // the steam builds the pressure up until the warning comes on, and only then
+        let mut steps = 0;
+        while !sim.burst_risk() {
+            assert!(sim.headspace_pressure() - ATMOSPHERIC_PRESSURE <= rated, "{material:?}");
+            sim.step(FIXED_DT);
+            steps += 1;
+            assert!(steps < (600.0 / FIXED_DT) as usize, "{material:?} never got to its rating");
+        }
+        assert!(sim.headspace_pressure() - ATMOSPHERIC_PRESSURE > rated);
+        assert!(sim.state().mass_vapor > 0.0);
+
+        // taking the cap off lets it all out
+        sim.set_capped(false);
+        assert_eq!(sim.headspace_pressure(), ATMOSPHERIC_PRESSURE);
+        assert!(!sim.burst_risk());
+    }
+}
@@ -0,0 +1,47 @@
+use icebottle_sim::{InsulationPreset, Player, Recorder, Simulation, FIXED_DT};
+
+#[test]
+fn a_recorded_session_replays_to_the_same_state() {
+    // a session as the app drives it: steps in frame-sized batches, with the
+    // user starting the run, changing the bottle, pausing, editing and resuming
+    let mut sim = Simulation::new();
+    let mut file = Vec::new();
+    let mut recorder = Recorder::new(&mut file, &sim).unwrap();
+    let mut steps = 0;
+    for frame in 0..600u64 {
+        for _ in 0..(1 + frame % 3) {
+            sim.step(FIXED_DT);
+            steps += 1;
+        }
+        recorder.after_steps(&sim);
+        match frame {
+            10 => sim.toggle_running(),
+            100 => sim.wall_mut().layers = InsulationPreset::VacuumFlask.layers(),
+            200 => sim.cycle_time_scale(),
+            300 => sim.toggle_running(),
+            310 => sim.init_mut().water = 0.8,
+            320 => sim.set_heater_power(25.0),
+            400 => sim.toggle_running(),
+            _ => {}
+        }
+        recorder.after_input(steps, &sim).unwrap();
+    }
+    recorder.finish(steps).unwrap();
+
+    let (mut replayed, mut player) = Player::read(file.as_slice()).expect("replay file reads back");
+    assert_eq!(player.end(), steps);
+    for step in 0..player.end() {
+        player.apply(step, &mut replayed);
+        replayed.step(FIXED_DT);
+    }
+    assert!(player.is_done());
+    assert_eq!(replayed.fingerprint(), sim.fingerprint());
+    assert_eq!(replayed.to_json(), sim.to_json());
+
+    // a session that crashed has no end line and replays up to its last change
+    let text = String::from_utf8(file).unwrap();
+    let cut: Vec<&str> = text.lines().filter(|line| !line.starts_with("{\"end\"")).collect();
+    let (_, player) = Player::read(cut.join("\n").as_bytes()).unwrap();
+    assert!(player.end() < steps);
+    assert!(Player::read("{\"change\": {}}".as_bytes()).is_err());
+}
@@ -0,0 +1,24 @@
+use icebottle_sim::Rng;
+
+#[test]
+fn a_seed_repeats_its_draws_and_normals_have_unit_spread() {
+    let draws = |seed| {
+        let mut rng = Rng::new(seed);
+        (0..20_000).map(|_| rng.normal()).collect::<Vec<f64>>()
+    };
+    let normals = draws(7);
+    assert_eq!(normals, draws(7));
+    assert_ne!(normals, draws(8));
+
+    let n = normals.len() as f64;
+    let mean = normals.iter().sum::<f64>() / n;
+    let sd = (normals.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    assert!(mean.abs() < 0.03, "{mean}");
+    assert!((sd - 1.0).abs() < 0.03, "{sd}");
+    // about 95 % within 1.96 standard deviations
+    let inside = normals.iter().filter(|x| x.abs() < 1.96).count() as f64 / n;
+    assert!((inside - 0.95).abs() < 0.01, "{inside}");
+
+    let mut rng = Rng::new(0);
+    assert!((0..1000).map(|_| rng.range(2.0, 3.0)).all(|x| (2.0..3.0).contains(&x)));
+}
@@ -0,0 +1,471 @@
+use icebottle_sim::{DoseKind, EventKind, Geometry, Scenario, Simulation, WallMaterial, FIXED_DT};
+
+#[test]
+fn shipped_scenarios_pass_the_check() {
+    for source in [
+        include_str!("../../scenarios/warm-car.toml"),
+        include_str!("../../scenarios/into-the-fridge.toml"),
+        include_str!("../../scenarios/milk-cooldown.toml"),
+        include_str!("../../scenarios/baby-bottle.toml"),
+        include_str!("../../scenarios/aquarium.toml"),
+        include_str!("../../scenarios/cold-bottle-room.toml"),
+        include_str!("../../scenarios/wort-immersion-chiller.toml"),
+        include_str!("../../scenarios/wort-small-batch.toml"),
+        include_str!("../../scenarios/wort-summer-tap.toml"),
+        include_str!("../../scenarios/wine-bottle.toml"),
+        include_str!("../../scenarios/supercooling-tap.toml"),
+        include_str!("../../scenarios/supercooling-boiled.toml"),
+        include_str!("../../scenarios/mpemba.toml"),
+        include_str!("../../scenarios/picnic.toml"),
+        include_str!("../../scenarios/ice-or-cooler.toml"),
+        include_str!("../../scenarios/freezer-burn.toml"),
+        include_str!("../../scenarios/conduction-only.toml"),
+        include_str!("../../scenarios/sweating-bottle.toml"),
+        include_str!("../../scenarios/ice-cream-churn.toml"),
+    ] {
+        let scenario = Scenario::from_toml(source).unwrap_or_else(|errors| panic!("{errors:?}"));
+        assert!(!scenario.name.is_empty());
+    }
+    let car = Scenario::from_toml(include_str!("../../scenarios/warm-car.toml")).unwrap();
+    assert_eq!(car.geometry(), Ok(Some(Geometry::Cylinder { radius: 0.035, height: 0.2 })));
+    assert_eq!(car.material(), Ok(Some(WallMaterial::Pet)));
+
+    let mut sim = Simulation::new();
+    car.apply(&mut sim);
+    assert_eq!(sim.init().water, 0.4);
+    assert_eq!(sim.init().ice, 0.15);
+    assert_eq!(sim.geometry(), Geometry::Cylinder { radius: 0.035, height: 0.2 });
+    assert_eq!(sim.duration(), Some(6.0 * 3600.0));
+    assert_eq!(sim.time_scale(), 100.0);
+    assert_eq!(sim.ambient_temp_at(7200.0), 45.0);
+    assert_eq!(sim.state().mass_ice, 0.15);
+    assert_eq!(sim.experiment().name, "Ice in a warm car");
+
+    let mut tank = Simulation::new();
+    Scenario::from_toml(include_str!("../../scenarios/aquarium.toml")).unwrap().apply(&mut tank);
+    assert_eq!(tank.time_scale(), 3600.0);
+    // at x3600 a frame of 1/60 s is a minute of the tank, six steps of its tick
+    assert_eq!(tank.step_size(), 10.0);
+    assert_eq!(tank.state().mass_water, 54.0);
+}
+
+#[test]
+fn a_coarse_tick_keeps_the_headspace_air_steady() {
+    // the tank's headspace air settles in about half a second, far inside its 10 s tick
+    let scenario = Scenario::from_toml(include_str!("../../scenarios/aquarium.toml")).unwrap();
+    let run = |hz: f64| {
+        let mut tank = Simulation::new();
+        scenario.apply(&mut tank);
+        tank.set_tick_rate(hz).unwrap();
+        tank.toggle_running();
+        while tank.time_seconds() < 1800.0 - 1e-6 {
+            tank.step(tank.step_size());
+        }
+        *tank.state()
+    };
+    let (coarse, fine) = (run(0.1), run(100.0));
+    assert!((coarse.temp_air - fine.temp_air).abs() < 0.01, "{} °C against {} °C", coarse.temp_air, fine.temp_air);
+    assert!((coarse.temp_water - fine.temp_water).abs() < 1e-4, "{} °C against {} °C", coarse.temp_water, fine.temp_water);
+}
+
+#[test]
+fn a_tick_rate_the_model_cannot_run_at_is_refused() {
+    let mut sim = Simulation::new();
+    for hz in [0.0, 0.00001, 1e6, f64::INFINITY, f64::NAN] {
+        assert!(sim.set_tick_rate(hz).is_err(), "{hz} Hz");
+        assert_eq!(sim.step_size(), FIXED_DT);
+    }
+    sim.set_tick_rate(0.01).unwrap();
+    assert_eq!(sim.step_size(), 100.0);
+
+    for value in ["nan", "inf", "0.00001"] {
+        let errors = Scenario::from_toml(&format!("tick_rate_hz = {value}\n")).unwrap_err();
+        assert_eq!((errors[0].line, errors[0].key.as_str()), (Some(1), "tick_rate_hz"), "{value}");
+    }
+}
+
+#[test]
+fn a_scenario_in_clock_time_starts_at_its_hour_and_the_sun_keeps_its_own() {
+    let source = "\
+start = \"08:30\"
+
+[outside]
+temp_c = 25.0
+sun_w_m2 = 800.0
+sun_from = \"09:00\"
+sun_until = \"17:00\"
+";
+    let mut sim = Simulation::new();
+    Scenario::from_toml(source).unwrap().apply(&mut sim);
+    sim.set_tick_rate(1.0).unwrap();
+    sim.toggle_running();
+    assert_eq!(sim.datetime().unwrap().to_string(), "Day 1 08:30:00");
+    // the sun is up from 09:00 to 17:00 by the run's clock, not its first eight hours
+    let mut sunny = Vec::new();
+    for _ in 0..10 {
+        sunny.push((sim.datetime().unwrap().hour_of_day(), sim.wall_exchange().solar > 0.0));
+        for _ in 0..3600 {
+            sim.step(sim.step_size());
+        }
+    }
+    let hours: Vec<f64> = sunny.iter().filter(|s| s.1).map(|s| s.0).collect();
+    assert_eq!(hours, [9.5, 10.5, 11.5, 12.5, 13.5, 14.5, 15.5, 16.5]);
+
+    for (source, key) in [("start = \"9 am\"\n", "start"), ("[outside]\nsun_from = \"09:00\"\nsun_until = \"17:00\"\n", "outside.sun_from")] {
+        let errors = Scenario::from_toml(source).unwrap_err();
+        assert_eq!(errors[0].key, key, "{errors:?}");
+    }
+}
+
+#[test]
+fn scenario_integrals_add_up_over_the_run() {
+    let source = "[initial]\nwater_kg = 0.5\nice_kg = 0.0\ncontents_c = 61.0\n\n[outside]\ntemp_c = 61.0\n\n[integrals]\nhours_above_c = [60.0]\npasteurization_units = true\n";
+    let scenario = Scenario::from_toml(source).unwrap();
+    assert_eq!(scenario.doses(), [DoseKind::HoursAbove(60.0), DoseKind::PasteurizationUnits]);
+
+    let mut sim = Simulation::new();
+    scenario.apply(&mut sim);
+    sim.toggle_running();
+    for _ in 0..60_000 {
+        sim.step(0.01);
+    }
+    // ten minutes just above 60 °C: a sixth of an hour and 1.393 units a minute
+    let totals: Vec<f64> = sim.doses().iter().map(|d| d.total()).collect();
+    assert!((totals[0] - 1.0 / 6.0).abs() < 1e-6, "{totals:?}");
+    assert!((totals[1] - 13.93).abs() < 0.05, "{totals:?}");
+
+    let errors = Scenario::from_toml("[integrals]\nhours_below_c = [-300.0]\n").unwrap_err();
+    assert_eq!((errors[0].line, errors[0].key.as_str()), (Some(2), "integrals.hours_below_c"));
+}
+
+#[test]
+fn the_first_hours_below_is_the_hold_threshold() {
+    let scenario = Scenario::from_toml("[integrals]\nhours_below_c = [5.0, 60.0]\n").unwrap();
+    assert_eq!(scenario.doses(), [DoseKind::HoursBelow(60.0)]);
+    let mut sim = Simulation::new();
+    scenario.apply(&mut sim);
+    assert_eq!(sim.hold_time().threshold, 5.0);
+    // the drink starts at 0 °C with ice, so it holds below 5 °C as long as it runs
+    sim.toggle_running();
+    for _ in 0..6000 {
+        sim.step(0.01);
+    }
+    assert!((sim.hold_time().hours() - 60.0 / 3600.0).abs() < 1e-9, "{} h", sim.hold_time().hours());
+
+    // without one the threshold stays as it was
+    let mut sim = Simulation::new();
+    Scenario::from_toml("[integrals]\nhours_above_c = [5.0]\n").unwrap().apply(&mut sim);
+    assert_eq!(sim.hold_time().threshold, 8.0);
+}
+
+#[test]
+fn problems_are_reported_with_their_lines() {
+    let source = "\
+duration_h = 2
+
+[initial]
+water_kg = -0.5
+
+[bottle]
+material = \"Cork\"
+
+[outside]
+profile = \"steps\"
+schedule = [[0, 20.0], [600, 4.0], [300, 10.0]]
+humidity = 1.5
+";
+    let errors = Scenario::from_toml(source).unwrap_err();
+    let found: Vec<(Option<usize>, &str)> = errors.iter().map(|e| (e.line, e.key.as_str())).collect();
+    assert_eq!(
+        found,
+        [
+            (Some(4), "initial.water_kg"),
+            (Some(7), "bottle.material"),
+            (Some(11), "outside.schedule"),
+            (Some(12), "outside.humidity"),
+        ]
+    );
+}
+
+#[test]
+fn unknown_keys_and_bad_syntax_point_at_their_line() {
+    let errors = Scenario::from_toml("[initial]\nwater_kg = 0.5\nicee_kg = 0.1\n").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, Some(3));
+    assert!(errors[0].message.contains("icee_kg"), "{}", errors[0].message);
+
+    let errors = Scenario::from_toml("name = \"unterminated\n").unwrap_err();
+    assert_eq!(errors[0].line, Some(1));
+}
+
+#[test]
+fn annotations_are_checked_and_reach_the_chart() {
+    let source = "\
+[[annotations]]
+label = \"plateau\"
+from_s = 600.0
+to_s = 300.0
+
+[[annotations]]
+low_c = 0.0
+high_c = 1.0
+
+[[annotations]]
+label = \"nothing to mark\"
+
+[target]
+low_c = 40.0
+high_c = 30.0
+";
+    let errors = Scenario::from_toml(source).unwrap_err();
+    let found: Vec<(Option<usize>, &str)> = errors.iter().map(|e| (e.line, e.key.as_str())).collect();
+    // the target's high_c, not the annotation's that comes first
+    assert_eq!(found, [(Some(15), "target.high_c"), (Some(1), "annotations"), (Some(10), "annotations")]);
+    assert!(errors[1].message.starts_with("entry 1 from_s"), "{}", errors[1].message);
+
+    let scenario = Scenario::from_toml(include_str!("../../scenarios/cold-bottle-room.toml")).unwrap();
+    let mut sim = Simulation::new();
+    scenario.apply(&mut sim);
+    assert_eq!(sim.annotations().len(), 2);
+    assert_eq!(sim.annotations()[0].temps(), Some((-0.5, 0.5)));
+}
+
+#[test]
+fn checkpoints_are_logged_as_the_run_passes_them() {
+    let source = "\
+duration_h = 0.5
+
+[checkpoints]
+times_h = [0.25, 0.75]
+every_h = 0.2
+";
+    let errors = Scenario::from_toml(source).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!((errors[0].line, errors[0].key.as_str()), (Some(4), "checkpoints.times_h"));
+
+    let scenario = Scenario::from_toml(&source.replace(", 0.75", "")).unwrap();
+    let mut sim = Simulation::new();
+    scenario.apply(&mut sim);
+    assert_eq!(sim.checkpoints().times(), [720.0, 900.0, 1440.0]);
+    sim.toggle_running();
+    while sim.is_running() {
+        sim.step(FIXED_DT);
+    }
+    let logged: Vec<f64> = sim.events().iter().filter(|e| e.kind == EventKind::Checkpoint).map(|e| e.time).collect();
+    assert_eq!(logged.len(), 3);
+    for (at, checkpoint) in logged.iter().zip(sim.checkpoints().times()) {
+        assert!(at - checkpoint >= 0.0 && at - checkpoint < FIXED_DT, "logged at {at} s for {checkpoint} s");
+    }
+}
+
+#[test]
+fn baby_bottle_comes_into_the_band_then_overshoots() {
+    let scenario = Scenario::from_toml(include_str!("../../scenarios/baby-bottle.toml")).unwrap();
+    let mut sim = Simulation::new();
+    scenario.apply(&mut sim);
+    assert!(sim.in_bath());
+    sim.toggle_running();
+    while sim.is_running() {
+        sim.step(FIXED_DT);
+    }
+    let band: Vec<(EventKind, f64)> = sim.events().iter().filter(|e| matches!(e.kind, EventKind::InBand | EventKind::Overshoot)).map(|e| (e.kind, e.time)).collect();
+    assert_eq!(band.iter().map(|b| b.0).collect::<Vec<_>>(), [EventKind::InBand, EventKind::Overshoot]);
+    // a few minutes in a 45 °C bath, as bottle warmers take
+    assert!((120.0..600.0).contains(&band[0].1), "{band:?}");
+    assert!(band[1].1 > band[0].1);
+}
+
+#[test]
+fn churned_ice_cream_gets_below_minus_three_sooner() {
+    let scenario = Scenario::from_toml(include_str!("../../scenarios/ice-cream-churn.toml")).unwrap();
+    // when it came into the band, and the ice frozen out of the mix by then
+    let run = |churn_every: Option<f64>| {
+        let mut sim = Simulation::new();
+        scenario.apply(&mut sim);
+        sim.toggle_running();
+        let mut churned = 0.0;
+        let mut done = None;
+        while sim.is_running() && done.is_none() {
+            sim.step(FIXED_DT);
+            if let Some(every) = churn_every.filter(|&every| sim.time_seconds() >= churned + every) {
+                sim.shake();
+                churned += every;
+            }
+            done = sim.events().iter().find(|e| e.kind == EventKind::InBand).map(|e| (e.time, sim.state().mass_ice));
+        }
+        // cooled into the band from above, which is not going past it
+        assert!(!sim.events().iter().any(|e| e.kind == EventKind::Overshoot));
+        done.expect("the mix gets below -3 °C")
+    };
+    let still = run(None);
+    let churned = run(Some(30.0));
+    assert!(churned.0 < 0.6 * still.0, "churned {churned:?}, still {still:?}");
+    // the freezing point only gets to -3 °C once ice has frozen out of a good share of the mix
+    for (_, ice) in [still, churned] {
+        assert!(ice > 0.3, "{ice} kg");
+    }
+}
+
+#[test]
+fn boiled_water_supercools_deeper_than_tap_water() {
+    // the lowest the water gets before it nucleates, and when that is
+    let undercooled = |source: &str| {
+        let mut sim = Simulation::new();
+        Scenario::from_toml(source).unwrap().apply(&mut sim);
+        sim.toggle_running();
+        let mut lowest = f64::INFINITY;
+        while sim.is_running() && !sim.events().iter().any(|e| e.kind == EventKind::Nucleation) {
+            lowest = lowest.min(sim.state().temp_water);
+            sim.step(FIXED_DT);
+        }
+        (lowest, sim.time_seconds())
+    };
+    let tap = undercooled(include_str!("../../scenarios/supercooling-tap.toml"));
+    let boiled = undercooled(include_str!("../../scenarios/supercooling-boiled.toml"));
+    assert!(tap.0 < -1.0, "{tap:?}");
+    assert!(boiled.0 < tap.0 && boiled.1 > tap.1, "boiled {boiled:?}, tap {tap:?}");
+
+    let errors = Scenario::from_toml("[initial]\ndissolved_air = 1.5\n").unwrap_err();
+    assert_eq!((errors[0].line, errors[0].key.as_str()), (Some(2), "initial.dissolved_air"));
+}
+
+#[test]
+fn a_cooler_holds_the_drink_cold_at_a_price() {
+    let scenario = Scenario::from_toml(include_str!("../../scenarios/ice-or-cooler.toml")).unwrap();
+    // hours below the hold threshold over the first three, and the electricity bill
+    let run = |name: &str| {
+        let mut sim = Simulation::new();
+        scenario.variant(scenario.variants.iter().position(|v| v.name == name).unwrap()).apply(&mut sim);
+        sim.set_tick_rate(10.0).unwrap();
+        sim.toggle_running();
+        while sim.time_seconds() < 3.0 * 3600.0 {
+            sim.step(sim.step_size());
+        }
+        (sim.hold_time().hours(), sim.electricity().kwh(), sim.electricity().cost(), -sim.heater_energy())
+    };
+    let ice = run("Twice the ice");
+    let cooler = run("Cooler, 10 W");
+    assert_eq!((ice.1, ice.2), (0.0, 0.0));
+    assert!(cooler.0 > ice.0, "cooler {cooler:?}, ice {ice:?}");
+    assert!((cooler.2 - cooler.1 * 0.30).abs() < 1e-12);
+    // pumping from a drink near 0 °C against 30 °C takes about a joule per joule
+    let cop = cooler.3 / (cooler.1 * 3.6e6);
+    assert!((0.7..1.5).contains(&cop), "COP {cop}");
+
+    let errors = Scenario::from_toml("[electricity]\ncarnot_fraction = 1.5\n").unwrap_err();
+    assert_eq!((errors[0].line, errors[0].key.as_str()), (Some(2), "electricity.carnot_fraction"));
+}
+
+#[test]
+fn without_convection_the_ice_lasts_longer() {
+    let scenario = Scenario::from_toml(include_str!("../../scenarios/conduction-only.toml")).unwrap();
+    // ice left after two hours, the heat let in, and how far the ledger is off
+    let run = |name: &str| {
+        let mut sim = Simulation::new();
+        scenario.variant(scenario.variants.iter().position(|v| v.name == name).unwrap()).apply(&mut sim);
+        sim.set_tick_rate(10.0).unwrap();
+        sim.toggle_running();
+        let start = sim.state().enthalpy();
+        while sim.time_seconds() < 2.0 * 3600.0 {
+            sim.step(sim.step_size());
+        }
+        let total = sim.energy().total;
+        let sources = total.sources();
+        (sim.state().mass_ice, total.wall, (sim.state().enthalpy() - start - sources).abs())
+    };
+    let convection = run("With convection");
+    let conduction = run("Conduction only");
+    assert!(conduction.0 > convection.0, "conduction {conduction:?}, convection {convection:?}");
+    // radiation still comes in, so it is slowed, not stopped
+    assert!(conduction.1 > 0.2 * convection.1 && conduction.1 < 0.8 * convection.1, "conduction {conduction:?}, convection {convection:?}");
+    assert!(convection.2 < 1e-6 && conduction.2 < 1e-6, "conduction {conduction:?}, convection {convection:?}");
+}
+
+#[test]
+fn a_bottle_sweating_in_humid_air_warms_faster() {
+    let scenario = Scenario::from_toml(include_str!("../../scenarios/sweating-bottle.toml")).unwrap();
+    // hours below 12 °C, what condensed on the wall and the humidity run at
+    let run = |name: &str| {
+        let mut sim = Simulation::new();
+        scenario.variant(scenario.variants.iter().position(|v| v.name == name).unwrap()).apply(&mut sim);
+        sim.set_tick_rate(10.0).unwrap();
+        sim.toggle_running();
+        while sim.is_running() {
+            sim.step(sim.step_size());
+        }
+        (sim.hold_time().hours(), sim.condensate() + sim.condensate_dripped(), sim.humidity())
+    };
+    let dry = run("Dry, 20 %");
+    let humid = run("Humid, 85 %");
+    // the variants change the outside air and nothing else
+    assert_eq!((dry.2, humid.2), (0.2, 0.85));
+    assert_eq!(dry.1, 0.0);
+    assert!(humid.1 > 0.001, "{} kg", humid.1);
+    assert!(humid.0 < 0.8 * dry.0, "humid {humid:?}, dry {dry:?}");
+}
+
+#[test]
+fn variants_change_only_what_they_set() {
+    let mpemba = Scenario::from_toml(include_str!("../../scenarios/mpemba.toml")).unwrap();
+    let cold_capped = mpemba.variants.iter().position(|v| v.name == "25 °C, capped").unwrap();
+    let mut sim = Simulation::new();
+    mpemba.variant(cold_capped).apply(&mut sim);
+    assert_eq!(sim.init().system_temp, 25.0);
+    assert_eq!(sim.init().water, 0.5);
+    assert!(sim.is_capped());
+    assert_eq!(sim.init().outside_temp, -18.0);
+
+    let source = "\
+[initial]
+water_kg = 0.5
+
+[[variants]]
+name = \"hot\"
+initial = { contents_c = -300.0 }
+
+[[variants]]
+name = \"hot\"
+";
+    let errors = Scenario::from_toml(source).unwrap_err();
+    let found: Vec<(Option<usize>, &str)> = errors.iter().map(|e| (e.line, e.message.as_str())).collect();
+    assert_eq!(
+        found,
+        [
+            (Some(4), "entry 1 initial.contents_c: -300 °C is below absolute zero"),
+            (Some(8), "entry 2 has the same name as an earlier one, 'hot'"),
+        ]
+    );
+}
+
+#[test]
+fn an_outline_turns_into_the_bottle_it_draws() {
+    // a 70 x 200 mm rectangle is the default bottle's cylinder
+    let box_outline = Geometry::from_svg_path("m10,10 h70 v200 h-70 z", None).unwrap();
+    let cylinder = Geometry::default();
+    for (of_outline, of_cylinder) in [(box_outline.volume(), cylinder.volume()), (box_outline.area(), cylinder.area()), (box_outline.projected_area(), cylinder.projected_area())] {
+        assert!((of_outline - of_cylinder).abs() < 1e-12 * of_cylinder, "{of_outline} {of_cylinder}");
+    }
+
+    // the wine bottle narrows into its neck: three quarters of it by volume is not much more than half its height
+    let wine = Scenario::from_toml(include_str!("../../scenarios/wine-bottle.toml")).unwrap().geometry().unwrap().unwrap();
+    let from_svg = Geometry::from_svg(include_str!("../../scenarios/wine-bottle.svg"), None).unwrap();
+    assert_eq!(wine, from_svg);
+    assert!((wine.height() - 0.3).abs() < 1e-12);
+    assert!(wine.volume() > 0.9e-3 && wine.volume() < 1.0e-3, "{}", wine.volume());
+    let level = wine.fill_fraction(0.75e-3);
+    assert!(level > 0.5 && level < 0.6, "{level}");
+
+    assert!(Geometry::from_svg_path("M0 0 A 5 5 0 0 1 10 10", None).unwrap_err().contains("arcs"));
+    assert!(Geometry::from_svg("<svg></svg>", None).is_err());
+}
+
+#[test]
+fn a_scenario_lists_the_fields_it_locks_and_the_app_checks_the_names() {
+    let source = "name = \"Exercise\"\nlocked = [\"water\", \"colour\"]\n";
+    let scenario = Scenario::from_toml(source).unwrap();
+    assert_eq!(scenario.locked, ["water", "colour"]);
+    let errors = scenario.check_locked(source, &["water", "ice"]);
+    let found: Vec<(Option<usize>, String)> = errors.iter().map(|e| (e.line, e.to_string())).collect();
+    assert_eq!(found, [(Some(2), "line 2: locked: 'colour' is not a field that can be locked".to_string())]);
+    assert!(Scenario::from_toml("").unwrap().locked.is_empty());
+}
@@ -0,0 +1,42 @@
+use icebottle_sim::{ControlMode, InitialConditions, Simulation, FIXED_DT};
+
+// Salty water with ice pieces, layers, the 2D field and a controlled heater,
+// so every part of the model has state worth saving.
+fn busy_run() -> Simulation {
+    let mut sim = Simulation::with_initial(InitialConditions {
+        water: 0.4,
+        ice: 0.2,
+        air: 0.01,
+        salt: 0.01,
+        system_temp: -2.0,
+        outside_temp: 28.0,
+    });
+    sim.set_water_layers(4);
+    sim.set_ice_pieces(4);
+    sim.set_field_mode(true);
+    sim.set_heater_power(-5.0);
+    sim.controller_mut().mode = ControlMode::Pid;
+    sim.experiment_mut().name = "Snapshot \"test\"".to_string();
+    sim.toggle_running();
+    sim
+}
+
+#[test]
+fn a_loaded_snapshot_carries_on_exactly_like_the_original() {
+    let mut original = busy_run();
+    for _ in 0..30_000 {
+        original.step(FIXED_DT);
+    }
+    let saved = original.to_json();
+    let mut resumed = Simulation::from_json(&saved).expect("snapshot loads");
+    assert_eq!(resumed.to_json(), saved);
+
+    for _ in 0..30_000 {
+        original.step(FIXED_DT);
+        resumed.step(FIXED_DT);
+    }
+    assert_eq!(resumed.fingerprint(), original.fingerprint());
+    assert_eq!(resumed.to_json(), original.to_json());
+
+    assert!(Simulation::from_json("{\"state\": 1}").is_err());
+}
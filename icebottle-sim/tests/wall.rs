@@ -0,0 +1,23 @@
+use icebottle_sim::{GapGas, InsulationPreset, Surroundings, WallConfig, WallMaterial};
+
+fn flask(gas: GapGas) -> WallConfig {
+    let mut wall = WallConfig::for_material(WallMaterial::StainlessSteel);
+    wall.layers = InsulationPreset::VacuumFlask.layers();
+    wall.set_gap_gas(gas);
+    wall
+}
+
+#[test]
+fn gap_gas_sets_how_well_a_flask_insulates() {
+    let warm_room = Surroundings::still_air(30.0);
+    let heat_in = |gas| flask(gas).exchange(0.0, &warm_room).conduction;
+    let (vacuum, argon, air) = (heat_in(GapGas::Vacuum), heat_in(GapGas::Argon), heat_in(GapGas::Air));
+    assert!(vacuum > 0.0 && vacuum * 10.0 < argon && argon < air, "{vacuum} {argon} {air}");
+    assert_eq!(InsulationPreset::of_layers(&flask(GapGas::Argon).layers), Some(InsulationPreset::VacuumFlask));
+
+    // radiation across an empty gap grows with the temperatures on either side
+    let vacuum = flask(GapGas::Vacuum);
+    let cool = vacuum.exchange(5.0, &Surroundings::still_air(20.0)).resistance;
+    let hot = vacuum.exchange(90.0, &Surroundings::still_air(20.0)).resistance;
+    assert!(hot < cool * 0.8, "{hot} {cool}");
+}
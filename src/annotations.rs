@@ -0,0 +1,80 @@
+// Marks a scenario's author put on the water temperature chart: lines at a
+// time or temperature, labelled points and shaded spans, such as where the
+// plateau is expected while the ice melts. Marks later than the run so far
+// appear once the chart reaches them.
+
+use icebottle_sim::Annotation;
+use macroquad::prelude::*;
+
+use crate::sparkline::Chart;
+use crate::theme::Palette;
+use crate::units::Units;
+
+const TEXT_SIZE: f32 = 12.0;
+
+/// Temperatures (in the unit shown) of the marks drawn once the chart spans
+/// `span` s, for the chart to be scaled to take in.
+pub fn temps(annotations: &[Annotation], span: f64, units: Units) -> Vec<f32> {
+    annotations
+        .iter()
+        .filter(|a| reached(a, span))
+        .flat_map(|a| [a.temp_c, a.low_c, a.high_c])
+        .flatten()
+        .map(|c| units.temp.of_celsius(c) as f32)
+        .collect()
+}
+
+/// Draws the marks into `chart`, whose series spans `span` s of the run.
+pub fn draw(chart: &Chart, annotations: &[Annotation], span: f64, units: Units, palette: &Palette) {
+    let (mark, shade) = (palette.highlight, Color { a: 0.18, ..palette.highlight });
+    let across = |t: f64| (t / span).clamp(0.0, 1.0) as f32;
+    let shown = |c: f64| units.temp.of_celsius(c) as f32;
+    for a in annotations.iter().filter(|a| reached(a, span)) {
+        let (x, y) = match (a.times(), a.temps()) {
+            (Some((from, to)), Some((low, high))) => {
+                let (left, right) = (chart.x_of(across(from)), chart.x_of(across(to)));
+                let top = chart.y_of(shown(high));
+                draw_rectangle(left, top, right - left, chart.y_of(shown(low)) - top, shade);
+                (left, top)
+            }
+            (Some((from, to)), None) => {
+                chart.shade_across(across(from), across(to), shade);
+                (chart.x_of(across(from)), chart.top())
+            }
+            (None, Some((low, high))) => {
+                chart.shade_between(shown(low), shown(high), shade);
+                (chart.x_of(0.0), chart.y_of(shown(high)))
+            }
+            (None, None) => match (a.time_s, a.temp_c) {
+                (Some(t), Some(c)) => {
+                    let (x, y) = (chart.x_of(across(t)), chart.y_of(shown(c)));
+                    draw_circle(x, y, 2.5, mark);
+                    (x + 2.0, y - TEXT_SIZE)
+                }
+                (Some(t), None) => {
+                    let x = chart.x_of(across(t));
+                    draw_line(x, chart.top() + 1.0, x, chart.bottom() - 1.0, 1.0, mark);
+                    (x, chart.top())
+                }
+                (None, Some(c)) => {
+                    let y = chart.y_of(shown(c));
+                    draw_line(chart.x_of(0.0), y, chart.x_of(1.0), y, 1.0, mark);
+                    (chart.x_of(0.0), y - TEXT_SIZE)
+                }
+                // the scenario check turns these away
+                (None, None) => continue,
+            },
+        };
+        if !a.label.is_empty() {
+            draw_text(&a.label, x + 2.0, y + TEXT_SIZE - 2.0, TEXT_SIZE, mark);
+        }
+    }
+}
+
+// Whether the chart has got as far as the mark's time; marks without one always are
+fn reached(a: &Annotation, span: f64) -> bool {
+    match a.time_s.or(a.from_s) {
+        Some(t) => span > 0.0 && t <= span,
+        None => true,
+    }
+}
@@ -0,0 +1,77 @@
+use icebottle_sim::Simulation;
+
+use crate::strings::tr;
+use crate::units::Units;
+
+// Real seconds between announcements, so a screen reader can keep up at any speed
+const ANNOUNCE_INTERVAL: f64 = 10.0;
+
+/// Accessibility mode: periodically prints a one-line status to stdout, e.g.
+/// "5 minutes: water 3.2 °C, 120.0 g ice", for screen readers or TTS pipes.
+pub struct Announcer {
+    enabled: bool,
+    last_announce: f64,
+}
+
+impl Announcer {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            last_announce: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// `now` is real (wall-clock) time in seconds, as for `update`; turning
+    /// announcements on says the status at once.
+    pub fn toggle(&mut self, sim: &Simulation, units: Units, now: f64) {
+        self.enabled = !self.enabled;
+        if self.enabled {
+            println!("{}", tr!("announce.on"));
+            self.last_announce = f64::NEG_INFINITY;
+            self.update(sim, units, now);
+        } else {
+            println!("{}", tr!("announce.off"));
+        }
+    }
+
+    /// `now` is real (wall-clock) time in seconds.
+    pub fn update(&mut self, sim: &Simulation, units: Units, now: f64) {
+        if !self.enabled || now - self.last_announce < ANNOUNCE_INTERVAL {
+            return;
+        }
+        self.last_announce = now;
+        println!("{}", status_line(sim, units));
+    }
+}
+
+fn status_line(sim: &Simulation, units: Units) -> String {
+    let state = sim.state();
+    let mut line = tr!("announce.water", spoken_duration(sim.time_seconds()), units.temp(state.temp_water, 1));
+    // the ice as a mass: ice dropped in during the run would take a share of the starting ice past 100 %
+    if state.mass_ice > 0.0 {
+        line += &tr!("announce.ice", units.mass.small_of_kg(state.mass_ice), units.mass.small_label());
+    } else if sim.init().ice > 0.0 {
+        line += &tr!("announce.melted");
+    }
+    if !sim.is_running() {
+        line += &tr!("announce.paused");
+    }
+    line
+}
+
+fn spoken_duration(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (h, m, s) = (total / 3600, (total / 60) % 60, total % 60);
+    // `announce.minute` for one, `announce.minutes` for any other number
+    let unit = |n: u64, word: &str| if n == 1 { tr!(&format!("announce.{word}")) } else { tr!(&format!("announce.{word}s"), n) };
+    match (h, m) {
+        (0, 0) => unit(s, "second"),
+        (0, _) => unit(m, "minute"),
+        (_, 0) => unit(h, "hour"),
+        _ => format!("{} {}", unit(h, "hour"), unit(m, "minute")),
+    }
+}
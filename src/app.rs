@@ -0,0 +1,87 @@
+// The states the window can be in, and what each lets the user do. It is in
+// exactly one at a time: setting a run up, running it, paused in it, at the
+// end of its duration, browsing scenarios, or in a lesson, a recorded session
+// playing back. The state is worked out afresh every frame from the run and
+// what is open; the keys, the controls panel and the cap ask it what they may
+// do, and the frame a state is entered is where its one-off work happens.
+
+use icebottle_sim::Simulation;
+
+use crate::keymap::Action;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppState {
+    /// At the start of a run, not yet started.
+    Setup,
+    Running,
+    /// Stopped partway through a run.
+    Paused,
+    /// Stopped at the run's duration; Start begins it again.
+    Finished,
+    /// The scenario browser is open and takes the keys.
+    Browsing,
+    /// A recorded session is playing back; the run is the recording's until it ends.
+    Lesson,
+}
+
+impl AppState {
+    /// The state for `sim`, with the browser open or a replay going taking
+    /// precedence over the run's own.
+    pub fn of(sim: &Simulation, browsing: bool, replaying: bool) -> Self {
+        if browsing {
+            AppState::Browsing
+        } else if replaying {
+            AppState::Lesson
+        } else if sim.is_running() {
+            AppState::Running
+        } else if sim.is_finished() {
+            AppState::Finished
+        } else if sim.time_seconds() > 0.0 {
+            AppState::Paused
+        } else {
+            AppState::Setup
+        }
+    }
+
+    /// Whether `action`'s key does anything in this state.
+    pub fn allows(self, action: Action) -> bool {
+        match self {
+            AppState::Browsing => matches!(
+                action,
+                Action::Browse | Action::Theme | Action::Language | Action::Render | Action::Screenshot | Action::Movie | Action::Fullscreen
+            ),
+            AppState::Lesson => !changes_run(action),
+            AppState::Setup | AppState::Running | AppState::Paused | AppState::Finished => true,
+        }
+    }
+
+    /// Whether the controls panel is shown and undo and redo work: a lesson's
+    /// run is the recording's, which would overwrite any change.
+    pub fn edits_run(self) -> bool {
+        self != AppState::Lesson
+    }
+}
+
+// Actions that change the simulation or swap it for another, which a replay
+// would overwrite with its next recorded change
+fn changes_run(action: Action) -> bool {
+    matches!(
+        action,
+        Action::StartPause
+            | Action::Reset
+            | Action::Integrator
+            | Action::LoadSnapshot
+            | Action::Shake
+            | Action::Cap
+            | Action::PourOut
+            | Action::Random
+            | Action::Presets
+            | Action::Name
+            | Action::Notes
+            | Action::Browse
+            | Action::NextField
+            | Action::PrevField
+            | Action::Increase
+            | Action::Decrease
+    )
+}
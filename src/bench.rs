@@ -0,0 +1,47 @@
+use icebottle_sim::{Integrator, Simulation};
+use std::time::Instant;
+
+// Water layers the 1D grid is timed with, the middle of the panel's choices
+const BENCH_LAYERS: usize = 8;
+
+/// Runs `steps` physics steps of `sim`, the run set up on the command line or
+/// by `--scenario`, on each grid the water can be solved on and with each
+/// integrator, as fast as possible, and prints the throughput.
+pub fn run(sim: &Simulation, steps: u64) {
+    let dt = sim.step_size();
+    println!("Benchmarking {steps} steps of {dt} s (release builds are much faster than debug)");
+    // name, water layers (0 lumped) and whether the 2D field is on
+    let grids = [("lumped", 0, false), ("1D layers", BENCH_LAYERS, false), ("2D field", 0, true)];
+    for (grid, layers, field) in grids {
+        let mut integrator = Integrator::default();
+        loop {
+            let mut sim = sim.clone();
+            sim.set_water_layers(layers);
+            sim.set_field_mode(field);
+            sim.set_integrator(integrator);
+            sim.toggle_running();
+
+            let start = Instant::now();
+            for _ in 0..steps {
+                sim.step(dt);
+            }
+            let elapsed = start.elapsed().as_secs_f64();
+            // keep the result observable so the loop isn't optimised away
+            let check = sim.fingerprint();
+
+            let per_step_ns = elapsed * 1e9 / steps.max(1) as f64;
+            println!(
+                "{grid:<10} {:<13} {:>10.0} steps/s  {:>8.1} ns/step  ({:.3} s, fingerprint {check:016x})",
+                integrator.label(),
+                steps as f64 / elapsed.max(1e-12),
+                per_step_ns,
+                elapsed,
+            );
+
+            integrator = integrator.next();
+            if integrator == Integrator::default() {
+                break;
+            }
+        }
+    }
+}
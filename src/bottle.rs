@@ -0,0 +1,274 @@
+// The bottle as drawn: the bath around it, the neck and cap, the wall in the
+// bottle's shape and the contents inside, with the ice, the heater rod and the
+// condensation on the wall, and the bubbles and droplets of `effects`. A `BottleView` draws one simulation and keeps what
+// it needs between frames, so the window can show several bottles side by side.
+
+use icebottle_sim::constants::CONDENSATE_FILM;
+use icebottle_sim::{CellKind, IceCubes, Simulation};
+use macroquad::prelude::*;
+
+use crate::effects::Effects;
+use crate::silhouette::Silhouette;
+use crate::strings::tr;
+use crate::theme::Palette;
+use crate::MAX_FRAME_TIME;
+
+// Cold blue, warm red, over -10..30 °C
+pub fn temperature_color(temp: f64) -> Color {
+    let f = ((temp as f32 + 10.0) / 40.0).clamp(0.0, 1.0);
+    Color::new(0.12 + 0.66 * f, 0.35 - 0.12 * f, 0.78 - 0.62 * f, 0.8)
+}
+
+// px the bottle sways by just after a shake, and how fast (rad/s)
+const WOBBLE_PX: f32 = 6.0;
+const WOBBLE_RATE: f64 = 30.0;
+
+pub struct BottleView {
+    prev_ice: f64,
+    field_texture: Option<Texture2D>,
+    effects: Effects,
+}
+
+impl BottleView {
+    pub fn new(sim: &Simulation) -> Self {
+        Self { prev_ice: sim.state().mass_ice, field_texture: None, effects: Effects::default() }
+    }
+
+    /// Starts over on `sim`, which replaces the run it was drawing.
+    pub fn reset(&mut self, sim: &Simulation) {
+        self.prev_ice = sim.state().mass_ice;
+        self.effects.clear();
+    }
+
+    /// Draws `sim`'s bottle with its body in `rect`, the neck and cap above
+    /// it, tinted `tint`. Called once a frame.
+    pub fn draw(&mut self, sim: &Simulation, rect: Rect, tint: [u8; 3], palette: &Palette) {
+        // rocks from side to side while a shake settles
+        let sway = WOBBLE_PX * sim.agitation() as f32 * (get_time() * WOBBLE_RATE).sin() as f32;
+        let rect = Rect { x: rect.x + sway, ..rect };
+        let (bottle_x, bottle_y, bottle_w, bottle_h) = (rect.x, rect.y, rect.w, rect.h);
+        let bottle_center_x = rect.x + rect.w / 2.0;
+        // brine is rejected into the water while ice is growing
+        let rejecting_brine = sim.state().mass_solute > 0.0 && sim.state().mass_ice > self.prev_ice;
+        self.prev_ice = sim.state().mass_ice;
+        self.effects.update(sim, get_frame_time().min(MAX_FRAME_TIME));
+
+        // A water bath around the lower part of the bottle, tinted by its temperature
+        if sim.in_bath() {
+            let mut bath = temperature_color(sim.outside_temp());
+            bath.a = 0.35;
+            let bath_y = bottle_y + bottle_h * 0.3;
+            draw_rectangle(bottle_x - 30.0, bath_y, bottle_w + 60.0, bottle_y + bottle_h + 8.0 - bath_y, bath);
+            draw_rectangle_lines(bottle_x - 30.0, bath_y - 20.0, bottle_w + 60.0, bottle_y + bottle_h + 28.0 - bath_y, 3.0, palette.glass);
+        }
+
+        // Draw bottle body
+        let top_center = vec2(bottle_center_x, bottle_y);
+        draw_rectangle(top_center.x - 45., top_center.y - 7., bottle_w * 0.38, 16., palette.glass);
+
+        // cap on the neck, or lying beside the bottle when off
+        if sim.is_capped() {
+            draw_rectangle(top_center.x - 49.0, top_center.y - 25.0, 92.0, 18.0, palette.cap);
+            let ridge = Color::new(palette.cap.r * 0.5, palette.cap.g * 0.65, palette.cap.b * 0.7, 1.0);
+            for i in 0..8 {
+                let ridge_x = top_center.x - 43.0 + i as f32 * 11.0;
+                draw_line(ridge_x, top_center.y - 23.0, ridge_x, top_center.y - 9.0, 1.0, ridge);
+            }
+        } else {
+            draw_rectangle(bottle_x + bottle_w + 10.0, bottle_y - 30.0, 18.0, 40.0, palette.cap);
+            // vapour leaving the neck, denser the faster the water evaporates or boils
+            let strength = ((sim.evaporation_rate() + sim.state().mass_vapor) * 1e6).clamp(0.0, 1.0) as f32;
+            if strength > 0.02 {
+                let t = get_time() as f32;
+                for i in 0..5 {
+                    let rise = (t * 20.0 + i as f32 * 13.0) % 60.0;
+                    let drift = (t * 2.0 + i as f32).sin() * 6.0;
+                    let alpha = strength * (1.0 - rise / 60.0) * 0.6;
+                    draw_circle(top_center.x - 4.0 + drift, top_center.y - 10.0 - rise, 4.0 + rise * 0.1, Color { a: alpha, ..palette.steam });
+                }
+            }
+        }
+
+        // a bottle from an outline is drawn in its shape, the others as a box
+        let geometry = sim.geometry();
+        let fill_px = bottle_h - 12.0;
+        let silhouette = Silhouette::new(&geometry, bottle_center_x, bottle_y + bottle_h - 6.0, fill_px, bottle_w - 8.0);
+        let [tint_r, tint_g, tint_b] = tint;
+        if let Some(shape) = &silhouette {
+            shape.fill(0.0, 1.0, Color::from_rgba(tint_r, tint_g, tint_b, 80));
+            shape.stroke(3.0, palette.glass);
+        } else {
+            draw_rectangle(bottle_x, bottle_y + 10.0, bottle_w, bottle_h - 10.0, Color::from_rgba(tint_r, tint_g, tint_b, 80));
+            draw_rectangle_lines(bottle_x, bottle_y + 10.0, bottle_w, bottle_h - 10.0, 3.0, palette.glass);
+        }
+
+        // compute liquid height from volume and the bottle shape, so freezing visibly raises the level
+        let pixels_per_m = fill_px / geometry.height() as f32;
+        let density = sim.state().liquid.properties().density;
+        let liquid_height_px = geometry.fill_fraction(sim.state().condensed_volume()) as f32 * fill_px;
+        let water_height_px = geometry.fill_fraction(sim.state().mass_water / density) as f32 * fill_px;
+        let ice_height_px = liquid_height_px - water_height_px;
+
+        let water_top = bottle_y + bottle_h - water_height_px - 6.0;
+        // the contents between two heights (px above the base), in the bottle's shape
+        let fill_band = |from: f32, to: f32, color: Color| match &silhouette {
+            Some(shape) => shape.fill(from / fill_px, to / fill_px, color),
+            None => draw_rectangle(bottle_x + 4.0, bottle_y + bottle_h - 6.0 - to, bottle_w - 8.0, to - from, color),
+        };
+        if sim.state().mass_water > 0.0 {
+            // water rectangle
+            fill_band(0.0, water_height_px.max(1.0), palette.water);
+            // water surface ellipse
+            fill_band(0.0, water_height_px.max(1.0), palette.water);
+            // the surface line spans the bottle's width at that height
+            let surface = silhouette.as_ref().map_or(bottle_w / 2.0 - 4.0, |shape| shape.half_width(water_height_px / fill_px));
+            draw_line(bottle_center_x - surface, water_top, bottle_center_x + surface, water_top, 2.0, palette.surface);
+
+            // stratified water: one band per layer, coloured by temperature
+            let layers = sim.water_layers();
+            if !layers.is_empty() {
+                let band_h = water_height_px / layers.len() as f32;
+                for (i, &t) in layers.iter().enumerate() {
+                    fill_band(i as f32 * band_h, (i + 1) as f32 * band_h, temperature_color(t));
+                }
+            }
+
+            // salty water is tinted green; sinking brine plumes while it freezes
+            let salt_tint = (sim.state().salinity() as f32 * 4.0).min(1.0);
+            if salt_tint > 0.0 {
+                fill_band(0.0, water_height_px.max(1.0), Color { a: palette.brine_tint.a * salt_tint, ..palette.brine_tint });
+            }
+            if rejecting_brine && water_height_px > 8.0 {
+                let t = get_time() as f32;
+                for i in 0..8 {
+                    let px = bottle_x + 20.0 + i as f32 * (bottle_w - 40.0) / 7.0;
+                    let fall = (t * 40.0 + i as f32 * 37.0) % water_height_px;
+                    draw_circle(px, water_top + fall, 2.5, palette.brine);
+                }
+            }
+            self.effects.draw_bubbles(Rect::new(bottle_center_x - surface, water_top, 2.0 * surface, water_height_px), palette);
+        }
+
+        let cubes = sim.ice_cubes().masses();
+        if let (true, Some(shape)) = (cubes.is_empty(), &silhouette) {
+            // ice fills the shape above the water
+            shape.fill(water_height_px / fill_px, liquid_height_px / fill_px, palette.ice);
+        } else if cubes.is_empty() {
+            // ice blocks drawn stacked above water
+            let mut ice_y = water_top - ice_height_px;
+            let mut remaining = ice_height_px;
+            while remaining > 0.0 {
+                let block_h = remaining.min(36.0);
+                draw_rectangle(bottle_x + 8.0, ice_y, bottle_w - 16.0, block_h.max(1.0), palette.ice);
+                draw_rectangle_lines(bottle_x + 8.0, ice_y, bottle_w - 16.0, block_h.max(1.0), 1.0, palette.ice_edge);
+                ice_y += block_h;
+                remaining -= block_h;
+            }
+        } else {
+            // separate cubes floating at the surface, sized by their edge length;
+            // further rows pile on top when a row is full
+            let mut cube_x = bottle_x + 8.0;
+            let mut base: Option<f32> = None; // bottom of the current row, None for the floating row
+            let mut row_top = water_top;
+            for &mass in cubes {
+                let side = (IceCubes::edge(mass) as f32 * pixels_per_m).max(2.0);
+                if cube_x + side > bottle_x + bottle_w - 8.0 && cube_x > bottle_x + 8.0 {
+                    cube_x = bottle_x + 8.0;
+                    base = Some(row_top - 2.0);
+                }
+                // floating ice shows about a tenth above the surface
+                let top = match base {
+                    Some(bottom) => bottom - side,
+                    None => water_top - side * 0.1,
+                };
+                draw_rectangle(cube_x, top, side, side, palette.ice);
+                draw_rectangle_lines(cube_x, top, side, side, 1.0, palette.ice_edge);
+                cube_x += side + 2.0;
+                row_top = row_top.min(top);
+            }
+        }
+
+        // 2D field mode: heatmap of the slice over the whole bottle interior
+        if let Some(field) = sim.field() {
+            let (nx, nz) = (field.nx(), field.nz());
+            let mut image = Image::gen_image_color(nx as u16, nz as u16, BLACK);
+            for j in 0..nz {
+                for i in 0..nx {
+                    let mut color = temperature_color(field.temperature(i, j));
+                    if field.kind(i, j) == CellKind::Contents {
+                        // ice shows as a whitening of the cell
+                        let ice = 1.0 - field.liquid_fraction(i, j) as f32;
+                        color = Color::new(
+                            color.r + (1.0 - color.r) * 0.7 * ice,
+                            color.g + (1.0 - color.g) * 0.7 * ice,
+                            color.b + (1.0 - color.b) * 0.7 * ice,
+                            1.0,
+                        );
+                    }
+                    image.set_pixel(i as u32, (nz - 1 - j) as u32, Color { a: 1.0, ..color });
+                }
+            }
+            let texture = self.field_texture.get_or_insert_with(|| {
+                let texture = Texture2D::from_image(&image);
+                texture.set_filter(FilterMode::Nearest);
+                texture
+            });
+            texture.update(&image);
+            draw_texture_ex(
+                texture,
+                bottle_x,
+                bottle_y + 10.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(bottle_w, bottle_h - 10.0)),
+                    ..Default::default()
+                },
+            );
+        }
+
+        // Immersion heater: a rod down the middle, glowing red when heating and
+        // blue when cooling
+        if sim.heater_power() != 0.0 {
+            let glow = (sim.heater_output().abs() / 200.0).min(1.0) as f32;
+            let color = if sim.heater_power() > 0.0 {
+                Color::new(0.5 + 0.5 * glow, 0.3 - 0.2 * glow, 0.2, 1.0)
+            } else {
+                Color::new(0.2, 0.4 + 0.3 * glow, 0.6 + 0.4 * glow, 1.0)
+            };
+            draw_line(bottle_center_x, bottle_y - 6.0, bottle_center_x, bottle_y + bottle_h - 24.0, 6.0, color);
+            draw_line(bottle_center_x, bottle_y - 6.0, bottle_center_x, bottle_y + bottle_h - 24.0, 1.0, palette.frame);
+        }
+
+        // Condensation: beads on both sides of the wall, more as the film builds up;
+        // once it is full some of them run down
+        let film = (sim.condensate() / (CONDENSATE_FILM * sim.wall().area)) as f32;
+        if film > 0.0 {
+            let drops = (film * 40.0).ceil() as usize;
+            let dripping = film >= 0.999 && sim.condensation_rate() > 0.0;
+            let t = get_time() as f32;
+            for i in 0..drops.min(40) {
+                // fixed pseudo-random spot per drop so they don't jump around between frames
+                let hash = (i as u32).wrapping_mul(2_654_435_761);
+                let side_x = if i % 2 == 0 { bottle_x - 3.0 } else { bottle_x + bottle_w + 3.0 };
+                let mut drop_y = bottle_y + 20.0 + (hash >> 8) as f32 / 16_777_216.0 * (bottle_h - 30.0);
+                if dripping && i % 5 == 0 {
+                    drop_y = bottle_y + 20.0 + (drop_y - bottle_y - 20.0 + t * 30.0) % (bottle_h - 30.0);
+                }
+                draw_circle(side_x, drop_y, 2.0 + (hash % 3) as f32 * 0.5, palette.droplet);
+            }
+        }
+        self.effects.draw_droplets(Rect::new(bottle_x, bottle_y + 20.0, bottle_w, bottle_h - 30.0), palette);
+
+        // Level the contents would have if they were all liquid
+        let all_liquid = (sim.state().mass_water + sim.state().mass_ice) / density;
+        if sim.state().mass_ice > 0.0 {
+            let level_y = bottle_y + bottle_h - 6.0 - geometry.fill_fraction(all_liquid) as f32 * fill_px;
+            let mut dash_x = bottle_x - 14.0;
+            while dash_x < bottle_x + bottle_w + 14.0 {
+                draw_line(dash_x, level_y, dash_x + 6.0, level_y, 1.0, palette.highlight);
+                dash_x += 12.0;
+            }
+            draw_text(tr!("bottle.if_melted"), bottle_x - 72.0, level_y + 4.0, 13.0, palette.highlight);
+        }
+    }
+}
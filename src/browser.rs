@@ -0,0 +1,93 @@
+// The scenario browser (O): the scenario files of a folder listed by name with
+// their notes, read afresh each time it opens, so files edited meanwhile show
+// as they are now. The one picked replaces the current run, set up as
+// `--scenario` would have; a file with problems is listed but can't be opened.
+
+use egui_macroquad::egui::{self, ScrollArea};
+use icebottle_sim::Scenario;
+use std::path::{Path, PathBuf};
+
+use crate::check;
+use crate::strings::tr;
+
+struct Entry {
+    path: PathBuf,
+    /// The scenario, or how many problems the check found in it.
+    scenario: Result<Scenario, usize>,
+}
+
+pub struct Browser {
+    dir: PathBuf,
+    pub open: bool,
+    entries: Vec<Entry>,
+}
+
+impl Browser {
+    pub fn new(dir: &Path) -> Self {
+        Self { dir: dir.to_path_buf(), open: false, entries: Vec::new() }
+    }
+
+    /// Opens the browser on the folder as it is now, or closes it.
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        if self.open {
+            self.entries = read(&self.dir);
+        }
+    }
+
+    /// Draws the list; returns the scenario picked, with the file it came from.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<(PathBuf, Scenario)> {
+        let mut picked = None;
+        let mut open = self.open;
+        egui::Window::new(tr!("browser.title", self.dir.display())).id(egui::Id::new("browser")).open(&mut open).default_width(420.0).show(ctx, |ui| {
+            if self.entries.is_empty() {
+                ui.label(tr!("browser.empty"));
+            }
+            ScrollArea::vertical().max_height(ctx.screen_rect().height() - 160.0).show(ui, |ui| {
+                for entry in &self.entries {
+                    let file = entry.path.file_name().unwrap_or_default().to_string_lossy();
+                    ui.group(|ui| match &entry.scenario {
+                        Ok(scenario) => {
+                            ui.horizontal(|ui| {
+                                ui.strong(if scenario.name.is_empty() { file.to_string() } else { scenario.name.clone() });
+                                if ui.button(tr!("browser.open")).clicked() {
+                                    picked = Some((entry.path.clone(), scenario.clone()));
+                                }
+                            });
+                            ui.small(file.to_string());
+                            if !scenario.notes.is_empty() {
+                                ui.label(&scenario.notes);
+                            }
+                        }
+                        Err(problems) => {
+                            ui.strong(file.to_string());
+                            ui.colored_label(ui.visuals().warn_fg_color, tr!("browser.problems", problems));
+                        }
+                    });
+                }
+            });
+        });
+        self.open = open && picked.is_none();
+        picked
+    }
+}
+
+// Every .toml in `dir`, in name order
+fn read(dir: &Path) -> Vec<Entry> {
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(files) => files.filter_map(|f| f.ok().map(|f| f.path())).filter(|p| p.extension().is_some_and(|e| e == "toml")).collect(),
+        Err(e) => {
+            eprintln!("Could not read {}: {e}", dir.display());
+            Vec::new()
+        }
+    };
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let source = std::fs::read_to_string(&path).ok()?;
+            let scenario = check::parse(&source).map_err(|errors| errors.len());
+            Some(Entry { path, scenario })
+        })
+        .collect()
+}
@@ -0,0 +1,50 @@
+// Scenario files on the command line: `--check` validates one, and one given
+// with `--scenario` is checked the same way before it is used.
+
+use icebottle_sim::{Scenario, ScenarioError};
+
+use crate::fields::KEYS;
+
+/// Validates a scenario file and prints its problems one per line, as
+/// `path:line: error: key: message` so editors and CI logs can link to them.
+/// Returns the exit code: 0 when it's fine, 1 when it has problems, 2 when it
+/// can't be read.
+pub fn run(path: &str) -> i32 {
+    match load(path) {
+        Ok(scenario) => {
+            let name = if scenario.name.is_empty() { String::new() } else { format!(" ({})", scenario.name) };
+            println!("{path}: ok{name}");
+            0
+        }
+        Err(code) => code,
+    }
+}
+
+/// The scenario in `path`, or the exit code once its problems are printed.
+pub fn load(path: &str) -> Result<Scenario, i32> {
+    let source = std::fs::read_to_string(path).map_err(|e| {
+        eprintln!("{path}: error: could not read: {e}");
+        2
+    })?;
+    parse(&source).map_err(|errors| {
+        for e in &errors {
+            let at = e.line.map(|line| format!(":{line}")).unwrap_or_default();
+            let key = if e.key.is_empty() { String::new() } else { format!("{}: ", e.key) };
+            println!("{path}{at}: error: {key}{}", e.message);
+        }
+        println!("{path}: {} problem{}", errors.len(), if errors.len() == 1 { "" } else { "s" });
+        1
+    })
+}
+
+/// Parses and checks a scenario as the library does, and its `locked` list
+/// against the app's field names.
+pub fn parse(source: &str) -> Result<Scenario, Vec<ScenarioError>> {
+    let scenario = Scenario::from_toml(source)?;
+    let errors = scenario.check_locked(source, &KEYS);
+    if errors.is_empty() {
+        Ok(scenario)
+    } else {
+        Err(errors)
+    }
+}
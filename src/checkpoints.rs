@@ -0,0 +1,57 @@
+// Exports at a scenario's checkpoints, so a run nobody is watching, such as a
+// headless one overnight, leaves its intermediate results behind: each
+// checkpoint writes the full state as a snapshot (`checkpoint-<t>s.json`, the
+// same JSON as F5 saves, so F9 picks the run up there once it is copied to
+// `snapshot.json`) and adds a summary row to `checkpoints.csv`, and in the
+// window a stamped screenshot when the scenario asks.
+
+use icebottle_sim::{write_checkpoint_header, write_checkpoint_row, EventKind, Simulation};
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+pub const CHECKPOINT_DIR: &str = "checkpoints";
+const SUMMARY_CSV: &str = "checkpoints.csv";
+
+pub struct Exporter {
+    dir: PathBuf,
+    // whether this session has started the summary CSV, which it then appends to
+    started: bool,
+}
+
+impl Exporter {
+    pub fn new(dir: &str) -> Self {
+        Self { dir: PathBuf::from(dir), started: false }
+    }
+
+    /// Exports for each checkpoint among `sim`'s events from index `from` on,
+    /// logged by the step just taken. Returns where the screenshot goes when
+    /// the scenario wants one.
+    pub fn after_step(&mut self, sim: &Simulation, from: usize) -> Option<PathBuf> {
+        let passed = sim.events().get(from..).unwrap_or_default().iter().filter(|e| e.kind == EventKind::Checkpoint).count();
+        if passed == 0 {
+            return None;
+        }
+        let name = format!("checkpoint-{:.0}s", sim.time_seconds());
+        if let Err(e) = self.export(sim, &name) {
+            eprintln!("Could not export the checkpoint at {:.0} s into {}: {e}", sim.time_seconds(), self.dir.display());
+            return None;
+        }
+        println!("Checkpoint at {:.0} s written to {}", sim.time_seconds(), self.dir.join(format!("{name}.json")).display());
+        sim.checkpoints().screenshot.then(|| self.dir.join(format!("{name}.png")))
+    }
+
+    fn export(&mut self, sim: &Simulation, name: &str) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.dir.join(format!("{name}.json")), sim.to_json())?;
+        let path = self.dir.join(SUMMARY_CSV);
+        let file = OpenOptions::new().create(true).write(true).append(self.started).truncate(!self.started).open(path)?;
+        let mut out = BufWriter::new(file);
+        if !self.started {
+            write_checkpoint_header(&mut out, sim)?;
+            self.started = true;
+        }
+        write_checkpoint_row(&mut out, sim)?;
+        out.flush()
+    }
+}
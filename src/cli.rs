@@ -0,0 +1,303 @@
+// Command-line options. The setup flags work the same with or without a
+// window; the ones under "Other modes" do something else and exit.
+
+use clap::{ArgGroup, Parser};
+use icebottle_sim::{Geometry, InsulationPreset, Liquid, Simulation, WallMaterial};
+
+use crate::checkpoints::CHECKPOINT_DIR;
+use crate::describe::FORMATS;
+use crate::fields::KEYS;
+use crate::movie::MovieFormat;
+use crate::sweep::Grid;
+use crate::theme::Theme;
+use crate::units::{MassUnit, TempUnit, Units};
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Ice melting in a bottle: heat flow, phase change and the bottle around them")]
+#[command(group(ArgGroup::new("writes_output").args(["headless", "sweep", "compare", "uncertainty", "describe_model"])))]
+pub struct Args {
+    /// Set up from a scenario file; the flags below override it
+    #[arg(long, value_name = "FILE")]
+    pub scenario: Option<String>,
+    /// Folder the scenario's checkpoints are exported into
+    #[arg(long, value_name = "DIR", default_value = CHECKPOINT_DIR)]
+    pub checkpoint_dir: String,
+    /// Initial water (kg)
+    #[arg(long, value_name = "KG", value_parser = non_negative)]
+    pub water: Option<f64>,
+    /// Initial ice (kg)
+    #[arg(long, value_name = "KG", value_parser = non_negative)]
+    pub ice: Option<f64>,
+    /// Initial headspace air (kg)
+    #[arg(long, value_name = "KG", value_parser = non_negative)]
+    pub air: Option<f64>,
+    /// Initial dissolved salt (kg)
+    #[arg(long, value_name = "KG", value_parser = non_negative)]
+    pub salt: Option<f64>,
+    /// What the bottle holds: Water, Saltwater, Milk, Juice or Ethanol
+    #[arg(long, value_parser = liquid)]
+    pub liquid: Option<Liquid>,
+    /// Initial temperature of the contents (°C)
+    #[arg(long, value_name = "C", allow_negative_numbers = true)]
+    pub contents: Option<f64>,
+    /// Outside air temperature (°C)
+    #[arg(long, value_name = "C", allow_negative_numbers = true)]
+    pub outside: Option<f64>,
+    /// Wall material: Glass, PET, Steel or Foam
+    #[arg(long, value_parser = material)]
+    pub material: Option<WallMaterial>,
+    /// Insulation: None, Sleeve, Vacuum or Vac+sleeve
+    #[arg(long, value_parser = insulation)]
+    pub insulation: Option<InsulationPreset>,
+    /// Bottle shape from the silhouette in an SVG file's first path, drawn in millimetres
+    #[arg(long, value_name = "SVG", value_parser = outline)]
+    pub outline: Option<Geometry>,
+    /// Heater power in the contents (W); negative for a Peltier cooler
+    #[arg(long, value_name = "W", allow_negative_numbers = true)]
+    pub heater: Option<f64>,
+    /// Take convection out of the model: heat only conducts, through still air and still contents
+    #[arg(long)]
+    pub conduction_only: bool,
+    /// Price of a kWh of the heater's electricity [default: 0.30]
+    #[arg(long, value_name = "PRICE", value_parser = non_negative)]
+    pub tariff: Option<f64>,
+    /// Stop after this many hours of simulated time
+    #[arg(long, value_name = "H", value_parser = positive)]
+    pub duration: Option<f64>,
+    /// Run name, written into every export
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Run notes, written into every export
+    #[arg(long)]
+    pub notes: Option<String>,
+    /// Physics steps per simulated second [default: 100]
+    #[arg(long, value_name = "HZ", value_parser = tick_rate)]
+    pub tick_rate: Option<f64>,
+    /// Open the window fullscreen (F11 switches)
+    #[arg(long)]
+    pub fullscreen: bool,
+    /// Enter the panel's values on an on-screen keypad, for touch screens (always on in the web build)
+    #[arg(long)]
+    pub keypad: bool,
+    /// Mass of an ice cube from the tray (kg); the mouse wheel over the tray changes it [default: 0.02]
+    #[arg(long, value_name = "KG", value_parser = positive)]
+    pub cube_mass: Option<f64>,
+    /// Temperature of an ice cube from the tray (°C), 0 at most; Shift and the mouse wheel change it [default: -18]
+    #[arg(long, value_name = "C", allow_negative_numbers = true)]
+    pub cube_temp: Option<f64>,
+    /// Mass of the liquid poured from the jug (kg); the mouse wheel over the jug changes it [default: 0.1]
+    #[arg(long, value_name = "KG", value_parser = positive)]
+    pub pour_mass: Option<f64>,
+    /// Temperature of the liquid poured from the jug (°C), 0 to 100; Shift and the mouse wheel change it [default: 80]
+    #[arg(long, value_name = "C", allow_negative_numbers = true)]
+    pub pour_temp: Option<f64>,
+    /// Frames drawn per second at most; the window sleeps in between [default: as fast as the display]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub fps: Option<u32>,
+    /// Simulated seconds between history samples [default: 1]
+    #[arg(long, value_name = "S", value_parser = positive)]
+    pub sample_every: Option<f64>,
+    /// Temperatures shown and typed in the app: C, F or K [default: C]
+    #[arg(long, value_name = "UNIT", value_parser = temp_unit)]
+    pub temp_unit: Option<TempUnit>,
+    /// Masses shown and typed in the app: kg or oz [default: kg]
+    #[arg(long, value_name = "UNIT", value_parser = mass_unit)]
+    pub mass_unit: Option<MassUnit>,
+    /// Colours of the window: dark, light or projector (high contrast); T switches [default: dark]
+    #[arg(long, value_name = "THEME", value_parser = theme)]
+    pub theme: Option<Theme>,
+    /// Language of the window: en, de or es, or a strings file (see lang/en.lang); F2 switches [default: $ICEBOTTLE_LANG or en]
+    #[arg(long, value_name = "CODE|FILE")]
+    pub lang: Option<String>,
+    /// What V records the window into: gif, or png for a folder of numbered frames [default: gif]
+    #[arg(long, value_name = "FORMAT", value_parser = movie_format)]
+    pub movie_format: Option<MovieFormat>,
+    /// Frames per second of real time V records
+    #[arg(long, value_name = "N", default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..=50))]
+    pub movie_fps: u32,
+    /// Seed of the first random draw (G)
+    #[arg(long, default_value_t = 1)]
+    pub seed: u64,
+    /// Fields shown but not editable, e.g. water,ice,material
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',', value_parser = clap::builder::PossibleValuesParser::new(KEYS))]
+    pub lock: Vec<String>,
+
+    /// Record every change made during the session into a replay file
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub record: Option<String>,
+    /// Play a recorded session back, in place of the setup above
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<String>,
+
+    /// Run to the end without a window and write the history as CSV
+    #[arg(long, help_heading = "Other modes")]
+    pub headless: bool,
+    /// Run every combination of the --sweep-* values in parallel and write a summary table as CSV
+    #[arg(long, help_heading = "Other modes")]
+    pub sweep: bool,
+    /// Ice masses to sweep (kg), e.g. 0.1,0.2,0.3
+    #[arg(long, value_name = "KG,...", value_delimiter = ',', value_parser = non_negative, requires = "sweep", help_heading = "Other modes")]
+    pub sweep_ice: Vec<f64>,
+    /// Insulations to sweep, e.g. none,sleeve,vacuum
+    #[arg(long, value_name = "NAMES", value_delimiter = ',', value_parser = insulation, requires = "sweep", help_heading = "Other modes")]
+    pub sweep_insulation: Vec<InsulationPreset>,
+    /// Outside temperatures to sweep (°C), e.g. 10,20,30
+    #[arg(long, value_name = "C,...", value_delimiter = ',', allow_negative_numbers = true, requires = "sweep", help_heading = "Other modes")]
+    pub sweep_outside: Vec<f64>,
+    /// Run the scenario's [[variants]] side by side and write a report on which freezes first, as Markdown
+    #[arg(long, requires = "scenario", help_heading = "Other modes")]
+    pub compare: bool,
+    /// Run the setup this many times with its inputs drawn from their likely spreads, in parallel, and write a report with confidence intervals, as Markdown
+    #[arg(long, value_name = "RUNS", value_parser = clap::value_parser!(u32).range(2..), help_heading = "Other modes")]
+    pub uncertainty: Option<u32>,
+    /// Show the scenario's [[variants]], two to four, side by side in the window on one clock
+    #[arg(long, requires = "scenario", conflicts_with_all = ["writes_output", "replay", "record"], help_heading = "Other modes")]
+    pub side_by_side: bool,
+    /// Describe the model as set up (nodes, parameters, equations, constants) and exit
+    #[arg(long, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "markdown", value_parser = clap::builder::PossibleValuesParser::new(FORMATS), help_heading = "Other modes")]
+    pub describe_model: Option<String>,
+    /// File for the --headless or --sweep CSV, the --compare or --uncertainty report or the --describe-model output [default: standard output]
+    #[arg(long, value_name = "FILE", requires = "writes_output", help_heading = "Other modes")]
+    pub output: Option<String>,
+    /// Validate a scenario file
+    #[arg(long, value_name = "FILE", help_heading = "Other modes")]
+    pub check: Option<String>,
+    /// Draw a PNG thumbnail of every scenario in a folder
+    #[arg(long, value_name = "DIR", help_heading = "Other modes")]
+    pub thumbnails: Option<String>,
+    /// Time this many physics steps with each integrator
+    #[arg(long, value_name = "N", help_heading = "Other modes")]
+    pub bench_steps: Option<u64>,
+}
+
+impl Args {
+    /// Applies the setup flags to `sim`, over whatever a scenario file set.
+    pub fn apply(&self, sim: &mut Simulation) {
+        let init = sim.init_mut();
+        for (value, target) in [
+            (self.water, &mut init.water),
+            (self.ice, &mut init.ice),
+            (self.air, &mut init.air),
+            (self.salt, &mut init.salt),
+            (self.contents, &mut init.system_temp),
+            (self.outside, &mut init.outside_temp),
+        ] {
+            if let Some(v) = value {
+                *target = v;
+            }
+        }
+        if let Some(liquid) = self.liquid {
+            sim.set_liquid(liquid);
+        }
+        if let Some(material) = self.material {
+            sim.wall_mut().set_material(material);
+        }
+        if let Some(insulation) = self.insulation {
+            sim.wall_mut().layers = insulation.layers();
+        }
+        if let Some(geometry) = self.outline {
+            sim.set_geometry(geometry);
+        }
+        if let Some(power) = self.heater {
+            sim.set_heater_power(power);
+        }
+        if self.conduction_only {
+            sim.set_conduction_only(true);
+        }
+        if let Some(tariff) = self.tariff {
+            sim.electricity_mut().tariff = tariff;
+        }
+        // in range, as the parser took it
+        if let Some(hz) = self.tick_rate {
+            let _ = sim.set_tick_rate(hz);
+        }
+        if let Some(hours) = self.duration {
+            sim.set_duration(Some(hours * 3600.0));
+        }
+        if let Some(name) = &self.name {
+            sim.experiment_mut().name = name.clone();
+        }
+        if let Some(notes) = &self.notes {
+            sim.experiment_mut().notes = notes.clone();
+        }
+        sim.apply_init();
+    }
+
+    /// The units to show, from --temp-unit and --mass-unit.
+    pub fn units(&self) -> Units {
+        Units {
+            temp: self.temp_unit.unwrap_or_default(),
+            mass: self.mass_unit.unwrap_or_default(),
+        }
+    }
+
+    /// The values given to the --sweep-* flags.
+    pub fn grid(&self) -> Grid {
+        Grid {
+            ice: self.sweep_ice.clone(),
+            insulation: self.sweep_insulation.clone(),
+            outside: self.sweep_outside.clone(),
+        }
+    }
+}
+
+fn positive(text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(v) if v > 0.0 => Ok(v),
+        _ => Err("expected a positive number".to_string()),
+    }
+}
+
+// A tick rate the model can run at
+fn tick_rate(text: &str) -> Result<f64, String> {
+    let hz = text.parse::<f64>().map_err(|e| e.to_string())?;
+    Simulation::check_tick_rate(hz).map(|()| hz)
+}
+
+fn non_negative(text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(v) if v >= 0.0 => Ok(v),
+        _ => Err("expected a number, 0 or more".to_string()),
+    }
+}
+
+// One of `all` by its label, in any case
+fn by_label<T: Copy>(all: &[T], label: fn(T) -> &'static str, text: &str) -> Result<T, String> {
+    match all.iter().find(|&&v| label(v).eq_ignore_ascii_case(text)) {
+        Some(&v) => Ok(v),
+        None => Err(format!("expected one of {}", all.iter().map(|&v| label(v)).collect::<Vec<_>>().join(", "))),
+    }
+}
+
+fn liquid(text: &str) -> Result<Liquid, String> {
+    by_label(&Liquid::ALL, Liquid::label, text)
+}
+
+fn material(text: &str) -> Result<WallMaterial, String> {
+    by_label(&WallMaterial::ALL, WallMaterial::label, text)
+}
+
+fn insulation(text: &str) -> Result<InsulationPreset, String> {
+    by_label(&InsulationPreset::ALL, InsulationPreset::label, text)
+}
+
+fn outline(path: &str) -> Result<Geometry, String> {
+    let svg = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Geometry::from_svg(&svg, None)
+}
+
+fn temp_unit(text: &str) -> Result<TempUnit, String> {
+    by_label(&TempUnit::ALL, TempUnit::key, text)
+}
+
+fn mass_unit(text: &str) -> Result<MassUnit, String> {
+    by_label(&MassUnit::ALL, MassUnit::label, text)
+}
+
+fn theme(text: &str) -> Result<Theme, String> {
+    by_label(&Theme::ALL, Theme::label, text)
+}
+
+fn movie_format(text: &str) -> Result<MovieFormat, String> {
+    by_label(&MovieFormat::ALL, MovieFormat::label, text)
+}
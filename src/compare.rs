@@ -0,0 +1,254 @@
+// Comparisons: a scenario's `[[variants]]` run side by side to see which
+// freezes first, as in the Mpemba question of whether hot water can freeze
+// before cold. Each variant is the scenario with a few settings changed; the
+// runs spread over all cores, and the report is Markdown with the table, the
+// winner of each group and the caveats that apply to what this model can say.
+
+use icebottle_sim::{Scenario, Simulation};
+use rayon::prelude::*;
+use std::io::{self, BufWriter, Write};
+use std::time::Instant;
+
+use crate::cli::Args;
+
+/// Frozen solid once the liquid is less than this share of the contents.
+const SOLID: f64 = 0.01;
+/// Times closer than this share of the later one count as a tie.
+const TIE: f64 = 0.02;
+
+/// What a run of one variant came to; times in s from the start.
+struct Outcome {
+    start_temp: f64, // °C
+    capped: bool,
+    supercooling: bool,
+    water: f64,             // kg at the start
+    at_freezing: Option<f64>,
+    first_ice: Option<f64>,
+    solid: Option<f64>,
+    coldest_water: f64,     // °C, while there was water
+    evaporated: f64,        // kg, over the whole run
+    evaporated_by_ice: f64, // kg, before the first ice
+    sweated: f64,           // kg condensed on the outside, on the wall or run off by the end
+    hold_threshold: f64,    // °C
+    hold_hours: f64,
+}
+
+/// Runs each of `scenario`'s variants, with the setup flags in `args` over
+/// them, and writes the report to `output` (standard output when `None`).
+/// Returns the exit code.
+pub fn run(scenario: &Scenario, args: &Args, output: Option<&str>) -> i32 {
+    if scenario.variants.is_empty() {
+        eprintln!("The scenario has no [[variants]] to compare");
+        return 2;
+    }
+    let sims: Vec<Simulation> = (0..scenario.variants.len())
+        .map(|i| {
+            let mut sim = Simulation::new();
+            scenario.variant(i).apply(&mut sim);
+            args.apply(&mut sim);
+            sim
+        })
+        .collect();
+    let span = sims[0].span();
+    eprintln!("Comparing {} variants over {:.1} h on {} threads", sims.len(), span / 3600.0, rayon::current_num_threads());
+    let start = Instant::now();
+    let outcomes: Vec<Outcome> = sims.into_par_iter().map(|sim| run_one(sim, span)).collect();
+    eprintln!("Done in {:.1} s", start.elapsed().as_secs_f64());
+
+    let written = match output {
+        Some(path) => std::fs::File::create(path).map(BufWriter::new).and_then(|mut f| {
+            write_report(&mut f, scenario, &outcomes, span)?;
+            f.flush()
+        }),
+        None => {
+            let mut out = BufWriter::new(io::stdout().lock());
+            write_report(&mut out, scenario, &outcomes, span).and_then(|()| out.flush())
+        }
+    };
+    match written {
+        Ok(()) => {
+            if let Some(path) = output {
+                println!("Wrote the comparison of {} variants to {path}", outcomes.len());
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("Could not write {}: {e}", output.unwrap_or("the report"));
+            1
+        }
+    }
+}
+
+fn run_one(mut sim: Simulation, span: f64) -> Outcome {
+    let state = *sim.state();
+    let mut outcome = Outcome {
+        start_temp: state.temp_water,
+        capped: sim.is_capped(),
+        supercooling: sim.nucleation().enabled,
+        water: state.mass_water,
+        at_freezing: None,
+        first_ice: (state.mass_ice > 0.0).then_some(0.0),
+        solid: None,
+        coldest_water: state.temp_water,
+        evaporated: 0.0,
+        evaporated_by_ice: 0.0,
+        sweated: 0.0,
+        hold_threshold: sim.hold_time().threshold,
+        hold_hours: 0.0,
+    };
+    sim.set_duration(Some(span));
+    sim.toggle_running();
+    while sim.is_running() && outcome.solid.is_none() {
+        sim.step(sim.step_size());
+        let (t, state) = (sim.time_seconds(), sim.state());
+        if state.mass_water > 0.0 {
+            outcome.coldest_water = outcome.coldest_water.min(state.temp_water);
+            if outcome.at_freezing.is_none() && state.temp_water <= state.freezing_point() {
+                outcome.at_freezing = Some(t);
+            }
+        }
+        if outcome.first_ice.is_none() && state.mass_ice > 0.0 {
+            outcome.first_ice = Some(t);
+            outcome.evaporated_by_ice = sim.vapor_lost();
+        }
+        if state.mass_water <= SOLID * (state.mass_water + state.mass_ice) {
+            outcome.solid = Some(t);
+        }
+    }
+    outcome.evaporated = sim.vapor_lost();
+    outcome.sweated = sim.condensate() + sim.condensate_dripped();
+    outcome.hold_hours = sim.hold_time().hours();
+    outcome
+}
+
+fn write_report<W: Write>(out: &mut W, scenario: &Scenario, outcomes: &[Outcome], span: f64) -> io::Result<()> {
+    let hours = |t: Option<f64>| t.map_or("—".to_string(), |t| format!("{:.2} h", t / 3600.0));
+    let title = if scenario.name.is_empty() { "Comparison" } else { &scenario.name };
+    writeln!(out, "# {title}")?;
+    writeln!(out)?;
+    if !scenario.notes.is_empty() {
+        writeln!(out, "{}", scenario.notes)?;
+        writeln!(out)?;
+    }
+    writeln!(out, "Each variant ran for up to {:.1} h, or until it froze solid (liquid under {:.0} % of the contents).", span / 3600.0, SOLID * 100.0)?;
+    writeln!(out)?;
+    writeln!(out, "| Variant | Group | Start | Cap | Reaches freezing point | First ice | Frozen solid | Coldest water | Evaporated | Sweated | Hold time |")?;
+    writeln!(out, "|---|---|---|---|---|---|---|---|---|---|---|")?;
+    for (variant, o) in scenario.variants.iter().zip(outcomes) {
+        writeln!(
+            out,
+            "| {} | {} | {:.1} °C | {} | {} | {} | {} | {:.2} °C | {:.1} g ({:.1} %) | {:.1} g | {:.2} h at ≤ {} °C |",
+            variant.name,
+            variant.group,
+            o.start_temp,
+            if o.capped { "capped" } else { "open" },
+            hours(o.at_freezing),
+            hours(o.first_ice),
+            hours(o.solid),
+            o.coldest_water,
+            o.evaporated * 1000.0,
+            o.evaporated / o.water * 100.0,
+            o.sweated * 1000.0,
+            o.hold_hours,
+            o.hold_threshold,
+        )?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "## First to freeze")?;
+    writeln!(out)?;
+    let mut groups: Vec<&str> = Vec::new();
+    for variant in &scenario.variants {
+        if !groups.contains(&variant.group.as_str()) {
+            groups.push(&variant.group);
+        }
+    }
+    let mut ties = Vec::new();
+    // groups where the hottest start froze solid first, of those where two or more did
+    let (mut hot_first, mut raced) = (Vec::new(), 0);
+    for group in &groups {
+        let members: Vec<usize> = (0..outcomes.len()).filter(|&i| scenario.variants[i].group == *group).collect();
+        let label = if group.is_empty() { "All variants".to_string() } else { format!("Group {group}") };
+        for what in ["first ice", "frozen solid"] {
+            let time = |o: &Outcome| if what == "first ice" { o.first_ice } else { o.solid };
+            let mut ranked: Vec<(usize, f64)> = members.iter().filter_map(|&i| time(&outcomes[i]).map(|t| (i, t))).collect();
+            ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let line = match ranked[..] {
+                [] => "no variant got there".to_string(),
+                [(i, t)] => format!("only {} got there, at {}", scenario.variants[i].name, hours(Some(t))),
+                [(i, t), (j, u), ..] => {
+                    if u - t <= TIE * u {
+                        ties.push(format!("{} and {} ({what})", scenario.variants[i].name, scenario.variants[j].name));
+                    }
+                    if what == "frozen solid" {
+                        raced += 1;
+                        if ranked.iter().all(|&(k, _)| outcomes[k].start_temp <= outcomes[i].start_temp) {
+                            hot_first.push(label.clone());
+                        }
+                    }
+                    format!("{} at {}, {:.0} min ahead of {}", scenario.variants[i].name, hours(Some(t)), (u - t) / 60.0, scenario.variants[j].name)
+                }
+            };
+            writeln!(out, "- {label}, {what}: {line}")?;
+        }
+    }
+
+    writeln!(out)?;
+    writeln!(out, "## Caveats")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "- The model is lumped: the water is well mixed (or a few layers), with no convection currents, no frost on or under the bottle, \
+         no gas driven out by the heat and no contact with a freezer shelf. Those are the usual explanations offered for the Mpemba effect, \
+         so a result here shows what conduction, radiation and evaporation do on their own, not whether the effect is real."
+    )?;
+    writeln!(
+        out,
+        "- The freezer holds its temperature whatever is put in it. A real one warms up when a hot bottle goes in, which slows everything in it, \
+         the cold bottle included when they share it."
+    )?;
+    let losses: Vec<String> = scenario
+        .variants
+        .iter()
+        .zip(outcomes)
+        .filter(|(_, o)| o.evaporated > 0.0)
+        .map(|(v, o)| format!("{:.1} g from {} ({:.1} g before the first ice)", o.evaporated * 1000.0, v.name, o.evaporated_by_ice * 1000.0))
+        .collect();
+    if !losses.is_empty() {
+        writeln!(
+            out,
+            "- Evaporation took {}. Less water is less to cool and freeze: this is the one way a hotter bottle can gain in this model, \
+             and how much it takes depends on the neck, the humidity and the wind as set.",
+            losses.join(", ")
+        )?;
+    }
+    match hot_first.len() {
+        _ if raced == 0 => {}
+        0 => writeln!(out, "- The hottest start froze solid first in no group: what evaporation took did not make up for the extra heat to remove.")?,
+        _ => writeln!(
+            out,
+            "- The hottest start froze solid first in {}. Check the evaporation above before reading it as the Mpemba effect: \
+             this model has no other way for it to happen.",
+            hot_first.join(", ")
+        )?,
+    }
+    if outcomes.iter().any(|o| o.supercooling) {
+        writeln!(
+            out,
+            "- With supercooling on, when the first ice forms is a random draw from a fixed seed, one per variant. A difference in first-ice \
+             times between supercooling variants can be the luck of the draw; the coldest water shows how deep each one went."
+        )?;
+    }
+    if !ties.is_empty() {
+        writeln!(
+            out,
+            "- Within {:.0} % of each other, which is closer than the model's accuracy: {}.",
+            TIE * 100.0,
+            ties.join(", ")
+        )?;
+    }
+    if outcomes.iter().all(|o| o.solid.is_none()) {
+        writeln!(out, "- No variant froze solid within {:.1} h; a longer --duration would settle it.", span / 3600.0)?;
+    }
+    Ok(())
+}
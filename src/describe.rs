@@ -0,0 +1,46 @@
+// The model description: what the model consists of as set up, with the
+// current values, for --describe-model and beside every CSV the app writes.
+
+use icebottle_sim::{ModelDescription, Simulation};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+pub const FORMATS: [&str; 2] = ["markdown", "json"];
+
+/// Writes the description of `sim` in `format` (one of `FORMATS`) to `output`
+/// (standard output when `None`). Returns the exit code.
+pub fn run(sim: &Simulation, format: &str, output: Option<&str>) -> i32 {
+    let description = ModelDescription::of(sim);
+    let write = |out: &mut dyn Write| -> io::Result<()> {
+        match format {
+            "json" => writeln!(out, "{}", description.to_json())?,
+            _ => description.write_markdown(out)?,
+        }
+        out.flush()
+    };
+    let written = match output {
+        Some(path) => std::fs::File::create(path).and_then(|f| write(&mut BufWriter::new(f))),
+        None => write(&mut io::stdout().lock()),
+    };
+    match written {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Could not write {}: {e}", output.unwrap_or("the description"));
+            1
+        }
+    }
+}
+
+/// Writes the Markdown description of `sim` next to the export at `path`, as
+/// `<name>.model.md`, and says where it went.
+pub fn describe_beside(sim: &Simulation, path: &str) {
+    let out = Path::new(path).with_extension("model.md");
+    let written = std::fs::File::create(&out).map(BufWriter::new).and_then(|mut f| {
+        ModelDescription::of(sim).write_markdown(&mut f)?;
+        f.flush()
+    });
+    match written {
+        Ok(()) => println!("Wrote the model description to {}", out.display()),
+        Err(e) => eprintln!("Could not write {}: {e}", out.display()),
+    }
+}
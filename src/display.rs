@@ -0,0 +1,104 @@
+// Number formatting for the status card: friendly rounding for classrooms or
+// raw full-precision values for debugging the solver.
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    Rounded,
+    Raw,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StatusField {
+    Time,
+    Water,
+    Ice,
+    TempWater,
+    TempIce,
+    TempAir,
+    Hold,
+}
+
+impl StatusField {
+    pub const ALL: [StatusField; 7] = [
+        StatusField::Time,
+        StatusField::Water,
+        StatusField::Ice,
+        StatusField::TempWater,
+        StatusField::TempIce,
+        StatusField::TempAir,
+        StatusField::Hold,
+    ];
+
+    /// Name used in the `ICEBOTTLE_PRECISION` override list.
+    fn key(self) -> &'static str {
+        match self {
+            StatusField::Time => "time",
+            StatusField::Water => "water",
+            StatusField::Ice => "ice",
+            StatusField::TempWater => "t_water",
+            StatusField::TempIce => "t_ice",
+            StatusField::TempAir => "t_air",
+            StatusField::Hold => "hold",
+        }
+    }
+
+    fn rounded_decimals(self) -> usize {
+        match self {
+            StatusField::Time => 1,
+            StatusField::Water | StatusField::Ice => 4,
+            StatusField::TempWater | StatusField::TempIce | StatusField::TempAir | StatusField::Hold => 2,
+        }
+    }
+}
+
+pub struct DisplaySettings {
+    pub precision: Precision,
+    overrides: [Option<usize>; StatusField::ALL.len()],
+}
+
+impl DisplaySettings {
+    pub fn new() -> Self {
+        Self {
+            precision: Precision::Rounded,
+            overrides: [None; StatusField::ALL.len()],
+        }
+    }
+
+    /// Reads per-field decimal overrides such as `t_water=4,ice=6`; unknown or
+    /// malformed entries are reported and skipped.
+    pub fn from_env() -> Self {
+        let mut settings = Self::new();
+        if let Ok(spec) = std::env::var("ICEBOTTLE_PRECISION") {
+            for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                let parsed = entry.split_once('=').and_then(|(key, digits)| {
+                    let field = StatusField::ALL.into_iter().find(|f| f.key() == key.trim())?;
+                    Some((field, digits.trim().parse::<usize>().ok()?))
+                });
+                match parsed {
+                    Some((field, digits)) => settings.set_override(field, Some(digits)),
+                    None => eprintln!("ICEBOTTLE_PRECISION: ignoring '{entry}'"),
+                }
+            }
+        }
+        settings
+    }
+
+    pub fn set_override(&mut self, field: StatusField, decimals: Option<usize>) {
+        self.overrides[field as usize] = decimals;
+    }
+
+    pub fn toggle(&mut self) {
+        self.precision = match self.precision {
+            Precision::Rounded => Precision::Raw,
+            Precision::Raw => Precision::Rounded,
+        };
+    }
+
+    pub fn format(&self, field: StatusField, value: f64) -> String {
+        match (self.overrides[field as usize], self.precision) {
+            (Some(decimals), _) => format!("{value:.decimals$}"),
+            (None, Precision::Raw) => format!("{value:e}"),
+            (None, Precision::Rounded) => format!("{value:.*}", field.rounded_decimals()),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+// Particles over the bottle, driven by its thermal state and only drawn:
+// bubbles rise through water getting hot, dissolved air first and then steam
+// as it nears boiling, and droplets bead up on a wall below the dew point
+// while water condenses on it. They hold still while the run is paused.
+// Positions are kept as fractions of the water and the wall, so a bottle
+// drawn at another size carries them along.
+
+use icebottle_sim::Simulation;
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+use crate::theme::Palette;
+
+// °C from which dissolved air starts coming out as bubbles; from there to the
+// boiling point they come faster and bigger, at most BUBBLE_RATE a second
+const BUBBLES_FROM: f64 = 50.0;
+const BUBBLE_RATE: f32 = 30.0;
+const MAX_BUBBLES: usize = 80;
+// Droplets a second per kg/(m^2*s) condensing, which puts a cold drink in
+// humid air at a few a second, and at most MAX_DROPLET_RATE
+const DROPLETS_PER_FLUX: f64 = 2e5;
+const MAX_DROPLET_RATE: f32 = 20.0;
+const MAX_DROPLETS: usize = 60;
+// s a droplet grows for, then sits before it fades into the film
+const GROW: f32 = 1.5;
+const SIT: f32 = 2.5;
+const FADE: f32 = 1.0;
+
+struct Bubble {
+    x: f32,     // across the water, 0..1
+    y: f32,     // up from the bottom to the surface, 0..1
+    speed: f32, // of the water's height a second
+    r: f32,     // px
+}
+
+struct Droplet {
+    right: bool, // which side of the wall
+    y: f32,      // up the wall, 0..1
+    size: f32,   // px once grown
+    age: f32,    // s
+}
+
+#[derive(Default)]
+pub struct Effects {
+    bubbles: Vec<Bubble>,
+    droplets: Vec<Droplet>,
+    // particles owed but not yet spawned, carried between frames
+    bubbles_due: f32,
+    droplets_due: f32,
+}
+
+impl Effects {
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Moves the particles on by `dt` s of real time and spawns new ones for
+    /// `sim` as it is now.
+    pub fn update(&mut self, sim: &Simulation, dt: f32) {
+        if !sim.is_running() {
+            return;
+        }
+        let state = sim.state();
+
+        // the warmest water bubbles: the bottom layer of a column heated from below
+        let hottest = sim.water_layers().iter().copied().fold(state.temp_water, f64::max);
+        let boiling = state.boiling_point();
+        let warmth = ((hottest - BUBBLES_FROM) / (boiling - BUBBLES_FROM)).clamp(0.0, 1.0) as f32;
+        if state.mass_water > 0.0 {
+            self.bubbles_due += BUBBLE_RATE * warmth * warmth * dt;
+        }
+        while self.bubbles_due >= 1.0 {
+            self.bubbles_due -= 1.0;
+            if self.bubbles.len() < MAX_BUBBLES {
+                let r = gen_range(1.0, 1.5 + 3.0 * warmth);
+                self.bubbles.push(Bubble { x: gen_range(0.1, 0.9), y: 0.0, speed: gen_range(0.25, 0.5), r });
+            }
+        }
+        for bubble in &mut self.bubbles {
+            bubble.y += bubble.speed * dt;
+            // steam bubbles swell as they rise, air ones hardly
+            bubble.r *= 1.0 + 0.3 * warmth * dt;
+            bubble.x += (bubble.y * 20.0).sin() * 0.02 * dt;
+        }
+        self.bubbles.retain(|b| b.y < 1.0);
+
+        let flux = sim.condensation_rate() / sim.wall().area;
+        self.droplets_due += ((flux * DROPLETS_PER_FLUX) as f32).min(MAX_DROPLET_RATE) * dt;
+        while self.droplets_due >= 1.0 {
+            self.droplets_due -= 1.0;
+            if self.droplets.len() < MAX_DROPLETS {
+                self.droplets.push(Droplet { right: gen_range(0, 2) == 1, y: gen_range(0.05, 0.95), size: gen_range(1.0, 2.5), age: 0.0 });
+            }
+        }
+        for droplet in &mut self.droplets {
+            droplet.age += dt;
+        }
+        self.droplets.retain(|d| d.age < GROW + SIT + FADE);
+    }
+
+    /// Draws the bubbles in `water`, the rectangle from the bottom of the
+    /// water to its surface.
+    pub fn draw_bubbles(&self, water: Rect, palette: &Palette) {
+        for bubble in &self.bubbles {
+            let (x, y) = (water.x + bubble.x * water.w, water.y + water.h * (1.0 - bubble.y));
+            draw_circle(x, y, bubble.r, Color { a: 0.3, ..palette.steam });
+            draw_circle_lines(x, y, bubble.r, 1.0, palette.surface);
+        }
+    }
+
+    /// Draws the droplets on the outside of `wall`, the bottle's body.
+    pub fn draw_droplets(&self, wall: Rect, palette: &Palette) {
+        for droplet in &self.droplets {
+            let x = if droplet.right { wall.x + wall.w + 3.0 } else { wall.x - 3.0 };
+            let y = wall.y + wall.h * (1.0 - droplet.y);
+            let grown = (droplet.age / GROW).min(1.0);
+            let fade = ((GROW + SIT + FADE - droplet.age) / FADE).min(1.0);
+            draw_circle(x, y, droplet.size * grown, Color { a: palette.droplet.a * fade, ..palette.droplet });
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// The energy-flow panel: where the contents' energy came from and where it went
+// over the last physics step, drawn Sankey-style as two stacked columns joined
+// by a band, with the totals since the reset underneath.
+
+use icebottle_sim::EnergyLedger;
+use macroquad::prelude::*;
+
+use crate::strings::{self, tr};
+use crate::theme::Palette;
+
+const W: f32 = 420.0;
+// px of the taller column
+const COLUMN_H: f32 = 180.0;
+// flows under this share of the total are left out
+const MIN_SHARE: f64 = 0.005;
+
+/// Draws the panel with its top-left corner at `x`, `y`.
+pub fn draw(ledger: &EnergyLedger, palette: &Palette, x: f32, y: f32) {
+    let entries = ledger.last.entries();
+    // a source going out (a wall losing heat) and a store giving energy up both
+    // change sides, so the two columns add up to the same flow
+    let mut from = Vec::new();
+    let mut to = Vec::new();
+    for (i, &(label, value)) in entries.iter().enumerate() {
+        let source = i < 5;
+        match (source, value > 0.0) {
+            (true, true) | (false, false) => from.push((i, label, value.abs())),
+            _ => to.push((i, label, value.abs())),
+        }
+    }
+    let sum = |side: &[(usize, &str, f64)]| side.iter().map(|e| e.2).sum::<f64>();
+    let total = sum(&from).max(sum(&to));
+    let shown = |side: Vec<(usize, &'static str, f64)>| side.into_iter().filter(|e| e.2 > total * MIN_SHARE).collect::<Vec<_>>();
+    let (from, to) = (shown(from), shown(to));
+
+    let totals: Vec<(usize, &str, f64)> = ledger.total.entries().iter().enumerate().filter(|(_, e)| e.1.abs() >= 1.0).map(|(i, e)| (i, e.0, e.1)).collect();
+    let h = 72.0 + COLUMN_H + 20.0 + totals.len().div_ceil(2) as f32 * 17.0;
+    draw_rectangle(x, y, W, h, palette.overlay);
+    draw_rectangle_lines(x, y, W, h, 2.0, palette.border);
+    draw_text(tr!("energy.title"), x + 10.0, y + 24.0, 18.0, palette.text);
+    draw_text(tr!("energy.from"), x + 60.0, y + 48.0, 15.0, palette.soft_text);
+    draw_text(tr!("energy.to"), x + 276.0, y + 48.0, 15.0, palette.soft_text);
+    let top = y + 60.0;
+    if total <= 0.0 {
+        draw_text(tr!("energy.idle"), x + 10.0, top + 20.0, 15.0, palette.faint_text);
+        return;
+    }
+
+    let (left_x, right_x, bar_w) = (x + 150.0, x + 254.0, 16.0);
+    let scale = COLUMN_H / total as f32;
+    let column = |side: &[(usize, &str, f64)], bar_x: f32, labels_left: bool| {
+        let mut bar_y = top;
+        for &(i, label, value) in side {
+            let bar_h = value as f32 * scale;
+            draw_rectangle(bar_x, bar_y, bar_w, bar_h.max(1.0), palette.flows[i]);
+            let text = format!("{} {value:.1} W", strings::name("flow", label));
+            let text_x = if labels_left { bar_x - 6.0 - measure_text(&text, None, 14, 1.0).width } else { bar_x + bar_w + 6.0 };
+            draw_text(&text, text_x, bar_y + bar_h / 2.0 + 5.0, 14.0, palette.flows[i]);
+            bar_y += bar_h.max(12.0);
+        }
+    };
+    column(&from, left_x, true);
+    column(&to, right_x, false);
+    // the band between the columns carries the whole flow
+    let band = palette.flow_band;
+    let (from_h, to_h) = (sum(&from) as f32 * scale, sum(&to) as f32 * scale);
+    let (a, b) = (vec2(left_x + bar_w, top), vec2(right_x, top));
+    draw_triangle(a, b, vec2(right_x, top + to_h), band);
+    draw_triangle(a, vec2(right_x, top + to_h), vec2(left_x + bar_w, top + from_h), band);
+
+    let totals_y = top + COLUMN_H + 30.0;
+    draw_text(tr!("energy.totals"), x + 10.0, totals_y - 4.0, 14.0, palette.soft_text);
+    for (n, &(i, label, value)) in totals.iter().enumerate() {
+        let (cx, cy) = (x + 10.0 + (n % 2) as f32 * 205.0, totals_y + 14.0 + (n / 2) as f32 * 17.0);
+        draw_text(format!("{} {:+.1} kJ", strings::name("flow", label), value / 1000.0), cx, cy, 14.0, palette.flows[i]);
+    }
+}
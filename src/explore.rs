@@ -0,0 +1,218 @@
+// The explore panel (H): what-if sliders over the run as it is set up. Moving
+// one runs the whole scenario again at once, headless and on a coarse step,
+// and draws the drink's temperature over it against the run as set up, so a
+// change can be felt out in a fraction of a second without touching the live
+// run. The wall's U value is changed by wrapping the bottle in as much foam as
+// takes it there.
+
+use egui_macroquad::egui::{self, Grid, Sense, Shape, Slider, Stroke, StrokeKind, Ui};
+use icebottle_sim::{InsulationLayer, Simulation};
+use macroquad::time::get_time;
+
+use crate::panel::{mass_slider, temperature_slider};
+use crate::strings::tr;
+use crate::units::Units;
+
+// Steps a re-run takes at most, about 20 ms, and the coarsest step (s) it may
+// take for them; the model holds to a few seconds
+const STEPS: f64 = 20_000.0;
+const MAX_STEP: f64 = 10.0;
+// Points of a curve
+const POINTS: usize = 240;
+// The foam the U value is reached with, W/(m·K); a twentieth of the bottle's is the lowest
+const FOAM: f64 = 0.035;
+const U_SPAN: f64 = 20.0;
+const ICE_RANGE: std::ops::RangeInclusive<f64> = 0.0..=2.0; // kg
+const CHART_H: f32 = 140.0;
+
+/// The values the sliders set, over the run as set up.
+#[derive(Clone, Copy, PartialEq)]
+struct Knobs {
+    u: f64, // W/(m^2·K)
+    outside: f64,
+    ice: f64,
+    contents: f64,
+}
+
+/// What a re-run came to: the drink's temperature (°C) at evenly spaced
+/// times over it, when the ice was gone, and the hours below the hold threshold.
+struct Outcome {
+    curve: Vec<f64>,
+    ice_gone: Option<f64>, // s
+    hold_hours: f64,
+}
+
+pub struct Explorer {
+    pub open: bool,
+    /// The run as set up when the panel opened, and its own knobs.
+    base: Option<(Simulation, Knobs)>,
+    knobs: Knobs,
+    baseline: Option<Outcome>,
+    what_if: Option<Outcome>,
+    took: f64, // ms of the last re-run
+}
+
+impl Explorer {
+    pub fn new() -> Self {
+        let knobs = Knobs { u: 0.0, outside: 0.0, ice: 0.0, contents: 0.0 };
+        Self { open: false, base: None, knobs, baseline: None, what_if: None, took: 0.0 }
+    }
+
+    /// Opens the panel on `sim` as it is set up now, or closes it.
+    pub fn toggle(&mut self, sim: &Simulation) {
+        self.open = !self.open;
+        if self.open {
+            self.take(sim);
+        }
+    }
+
+    // Starts over from `sim`: its knobs, its run and no what-if yet
+    fn take(&mut self, sim: &Simulation) {
+        let init = sim.init();
+        let knobs = Knobs { u: sim.wall().conductance() / sim.wall().area, outside: init.outside_temp, ice: init.ice, contents: init.system_temp };
+        let baseline = run(sim, knobs, knobs);
+        self.base = Some((sim.clone(), knobs));
+        self.knobs = knobs;
+        self.baseline = Some(baseline);
+        self.what_if = None;
+    }
+
+    /// Draws the panel; a slider moved runs the scenario again with it.
+    pub fn show(&mut self, ctx: &egui::Context, sim: &Simulation, units: Units) {
+        let mut open = self.open;
+        let mut retake = false;
+        egui::Window::new(tr!("explore.title")).id(egui::Id::new("explore")).open(&mut open).default_width(420.0).show(ctx, |ui| {
+            let Some((base, own)) = &self.base else { return };
+            let before = self.knobs;
+            let knobs = &mut self.knobs;
+            Grid::new("explore knobs").num_columns(2).show(ui, |ui| {
+                ui.label(tr!("explore.u"));
+                ui.add(Slider::new(&mut knobs.u, own.u / U_SPAN..=own.u).logarithmic(true).fixed_decimals(2));
+                ui.end_row();
+                ui.label(units.label(&tr!("field.outside_temp")));
+                temperature_slider(ui, units.temp, &mut knobs.outside);
+                ui.end_row();
+                ui.label(units.label(&tr!("field.ice")));
+                mass_slider(ui, units.mass, &mut knobs.ice, ICE_RANGE, 0.01);
+                ui.end_row();
+                ui.label(units.label(&tr!("field.system_temp")));
+                temperature_slider(ui, units.temp, &mut knobs.contents);
+                ui.end_row();
+            });
+            if *knobs != before {
+                let start = get_time();
+                self.what_if = Some(run(base, *own, *knobs));
+                self.took = (get_time() - start) * 1000.0;
+            }
+            if let Some(InsulationLayer::Solid { thickness, .. }) = sleeve(base, *own, self.knobs) {
+                ui.weak(tr!("explore.sleeve", thickness * 1000.0));
+            }
+
+            let span = base.span();
+            chart(ui, self.baseline.as_ref(), self.what_if.as_ref(), units);
+            ui.weak(tr!("explore.span", span / 3600.0));
+            let threshold = units.temp(base.hold_time().threshold, 0);
+            let hours = |t: Option<f64>| t.map_or_else(|| tr!("explore.never"), |t| format!("{:.2} h", t / 3600.0));
+            for (outcome, key) in [(self.baseline.as_ref(), "explore.as_set"), (self.what_if.as_ref(), "explore.what_if")] {
+                if let Some(outcome) = outcome {
+                    ui.label(tr!(key, hours(outcome.ice_gone), threshold, outcome.hold_hours));
+                }
+            }
+            if self.what_if.is_some() {
+                ui.weak(tr!("explore.took", self.took));
+            }
+            // the live run may have been changed since the panel opened
+            retake = ui.button(tr!("explore.retake")).clicked();
+        });
+        if retake {
+            self.take(sim);
+        }
+        self.open = open;
+    }
+}
+
+// The foam layer that takes the bottle from its own U value to `knobs.u`, if any
+fn sleeve(base: &Simulation, own: Knobs, knobs: Knobs) -> Option<InsulationLayer> {
+    let area = base.wall().area;
+    let resistance = 1.0 / (knobs.u * area) - 1.0 / (own.u * area);
+    (resistance > 0.0).then_some(InsulationLayer::Solid { thickness: resistance * FOAM * area, conductivity: FOAM })
+}
+
+// Runs `base` from its start to its end with `knobs` in place of `own`
+fn run(base: &Simulation, own: Knobs, knobs: Knobs) -> Outcome {
+    let mut sim = base.clone();
+    sim.init_mut().outside_temp = knobs.outside;
+    sim.init_mut().ice = knobs.ice;
+    sim.init_mut().system_temp = knobs.contents;
+    if let Some(layer) = sleeve(base, own, knobs) {
+        sim.wall_mut().layers.push(layer);
+    }
+    let span = base.span();
+    sim.set_duration(Some(span));
+    // never finer than the run's own step, even where that is over MAX_STEP;
+    // a span too short for that to be a rate the model takes keeps the run's own
+    let _ = sim.set_tick_rate(1.0 / (span / STEPS).min(MAX_STEP).max(base.step_size()));
+    sim.reset_from_init();
+    sim.toggle_running();
+
+    let mut curve = Vec::with_capacity(POINTS + 1);
+    let mut ice_gone = (sim.state().mass_ice <= 0.0).then_some(0.0);
+    curve.push(sim.state().drink_temperature());
+    let every = span / POINTS as f64;
+    while sim.is_running() {
+        sim.step(sim.step_size());
+        if ice_gone.is_none() && sim.state().mass_ice <= 0.0 {
+            ice_gone = Some(sim.time_seconds());
+        }
+        if sim.time_seconds() >= curve.len() as f64 * every - 1e-9 {
+            curve.push(sim.state().drink_temperature());
+        }
+    }
+    Outcome { curve, ice_gone, hold_hours: sim.hold_time().hours() }
+}
+
+// Both curves on one scale, the run as set up faint behind the what-if
+fn chart(ui: &mut Ui, baseline: Option<&Outcome>, what_if: Option<&Outcome>, units: Units) {
+    let (response, painter) = ui.allocate_painter(egui::vec2(ui.available_width(), CHART_H), Sense::hover());
+    let rect = response.rect;
+    let visuals = ui.visuals();
+    painter.rect_stroke(rect, 0.0, visuals.widgets.noninteractive.bg_stroke, StrokeKind::Inside);
+    let curves: Vec<(&[f64], egui::Color32)> = [(baseline, visuals.weak_text_color()), (what_if, visuals.hyperlink_color)]
+        .into_iter()
+        .filter_map(|(outcome, color)| outcome.map(|o| (o.curve.as_slice(), color)))
+        .collect();
+    let (lo, hi) = curves.iter().flat_map(|(c, _)| c.iter()).fold((f64::MAX, f64::MIN), |(lo, hi), &t| (lo.min(t), hi.max(t)));
+    if lo > hi {
+        return;
+    }
+    let range = (hi - lo).max(0.5);
+    for (curve, color) in &curves {
+        let points = curve
+            .iter()
+            .enumerate()
+            .map(|(i, &t)| egui::pos2(rect.left() + rect.width() * i as f32 / POINTS as f32, rect.bottom() - rect.height() * ((t - lo) / range) as f32))
+            .collect();
+        painter.add(Shape::line(points, Stroke::new(1.5, *color)));
+    }
+    let font = egui::FontId::proportional(12.0);
+    let weak = visuals.weak_text_color();
+    painter.text(rect.left_top() + egui::vec2(4.0, 2.0), egui::Align2::LEFT_TOP, units.temp(hi, 1), font.clone(), weak);
+    painter.text(rect.left_bottom() + egui::vec2(4.0, -2.0), egui::Align2::LEFT_BOTTOM, units.temp(lo, 1), font, weak);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_run_on_a_slow_tick_re_runs_at_its_own_step() {
+        // 0.05 Hz is a 20 s step, over MAX_STEP
+        let mut base = Simulation::new();
+        base.set_tick_rate(0.05).unwrap();
+        let init = base.init();
+        let knobs = Knobs { u: base.wall().conductance() / base.wall().area, outside: init.outside_temp, ice: init.ice, contents: init.system_temp };
+        let outcome = run(&base, knobs, knobs);
+        assert_eq!(outcome.curve.len(), POINTS + 1);
+        assert!(outcome.curve.iter().all(|t| t.is_finite()), "{:?}", outcome.curve);
+    }
+}
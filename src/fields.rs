@@ -0,0 +1,246 @@
+// Editable fields of the controls panel, with the names locks and files use for them.
+
+use icebottle_sim::{AmbientProfile, Geometry, InitialConditions, Simulation};
+
+pub const FIELD_COUNT: usize = 32;
+pub const FIELD_WATER: usize = 0;
+pub const FIELD_ICE: usize = 1;
+pub const FIELD_AIR: usize = 2;
+pub const FIELD_SYSTEM_TEMP: usize = 3;
+pub const FIELD_OUTSIDE_TEMP: usize = 4;
+pub const FIELD_MATERIAL: usize = 5;
+pub const FIELD_INSULATION: usize = 6;
+pub const FIELD_START_CLOCK: usize = 7;
+pub const FIELD_IRRADIANCE: usize = 8;
+pub const FIELD_SHADED: usize = 9;
+pub const FIELD_WIND: usize = 10;
+pub const FIELD_AMBIENT: usize = 11;
+pub const FIELD_SALT: usize = 12;
+pub const FIELD_SUPERCOOL: usize = 13;
+pub const FIELD_CAP: usize = 14;
+pub const FIELD_LAYERS: usize = 15;
+pub const FIELD_FIELD2D: usize = 16;
+pub const FIELD_ICE_PIECES: usize = 17;
+pub const FIELD_GEOMETRY: usize = 18;
+pub const FIELD_HUMIDITY: usize = 19;
+pub const FIELD_DURATION: usize = 20;
+pub const FIELD_HEATER: usize = 21;
+pub const FIELD_CONTROL: usize = 22;
+pub const FIELD_SETPOINT: usize = 23;
+pub const FIELD_KP: usize = 24;
+pub const FIELD_KI: usize = 25;
+pub const FIELD_KD: usize = 26;
+pub const FIELD_GAP_GAS: usize = 27;
+pub const FIELD_PRESSURE_MELT: usize = 28;
+pub const FIELD_DISSOLVED_AIR: usize = 29;
+pub const FIELD_LIQUID: usize = 30;
+pub const FIELD_CONDUCTION_ONLY: usize = 31;
+
+// Names used on the command line and in files; `field.<name>` is the panel's label
+pub const KEYS: [&str; FIELD_COUNT] = [
+    "water",
+    "ice",
+    "air",
+    "system_temp",
+    "outside_temp",
+    "material",
+    "insulation",
+    "start_clock",
+    "sun",
+    "shaded",
+    "wind",
+    "ambient",
+    "salt",
+    "supercool",
+    "cap",
+    "layers",
+    "field2d",
+    "ice_pieces",
+    "geometry",
+    "humidity",
+    "duration",
+    "heater",
+    "control",
+    "setpoint",
+    "kp",
+    "ki",
+    "kd",
+    "gap",
+    "pressure_melt",
+    "dissolved_air",
+    "liquid",
+    "conduction_only",
+];
+
+pub const AMBIENT_PRESET_COUNT: usize = 4;
+
+/// Choices for the outside-profile field, built around the edited outside temperature.
+pub fn ambient_preset(index: usize, outside: f64) -> AmbientProfile {
+    match index % AMBIENT_PRESET_COUNT {
+        0 => AmbientProfile::Constant,
+        1 => AmbientProfile::day_night(outside, 6.0),
+        // bottle moved into a 4 °C fridge after an hour
+        2 => AmbientProfile::Steps(vec![(0.0, outside), (3600.0, 4.0)]),
+        // parked car heating up and cooling back down
+        _ => AmbientProfile::Table(vec![(0.0, outside), (7200.0, outside + 20.0), (14_400.0, outside)]),
+    }
+}
+
+/// The outside-profile choice that gives `profile`, `None` for one that came
+/// from somewhere else (a scenario file or snapshot) and is kept as it is.
+pub fn ambient_preset_index(profile: &AmbientProfile, outside: f64) -> Option<usize> {
+    (0..AMBIENT_PRESET_COUNT).find(|&i| ambient_preset(i, outside) == *profile)
+}
+
+// Choices for the water-layers field; 1 keeps the water lumped
+pub const LAYER_CHOICES: [usize; 4] = [1, 4, 8, 16];
+
+// Choices for the ice-pieces field; 0 keeps the ice lumped
+pub const ICE_PIECE_CHOICES: [usize; 5] = [0, 1, 4, 12, 40];
+
+// Choices for the stop-after field, in hours of simulated time; 0 runs until paused
+pub const DURATION_CHOICES: [f64; 7] = [0.0, 0.5, 1.0, 2.0, 6.0, 12.0, 24.0];
+
+// Choices for the bottle-shape field
+pub const GEOMETRY_PRESETS: [(&str, Geometry); 4] = [
+    ("0.77 L bottle", Geometry::Cylinder { radius: 0.035, height: 0.2 }),
+    ("1.5 L bottle", Geometry::Cylinder { radius: 0.045, height: 0.24 }),
+    ("Round flask", Geometry::Sphere { radius: 0.06 }),
+    ("0.5 L pouch", Geometry::Custom { area: 0.06, volume: 0.0005 }),
+];
+
+pub fn index_of(key: &str) -> Option<usize> {
+    KEYS.iter().position(|&k| k == key)
+}
+
+/// Teacher lock: fields students can see but not edit, so an exercise only
+/// varies the intended parameter. There is deliberately no way to unlock from
+/// inside the app.
+pub struct FieldLocks {
+    locked: [bool; FIELD_COUNT],
+}
+
+impl FieldLocks {
+    pub fn none() -> Self {
+        Self {
+            locked: [false; FIELD_COUNT],
+        }
+    }
+
+    /// Locks the fields named in `keys`, as given to `--lock water,ice,...`.
+    pub fn from_keys(keys: &[String]) -> Self {
+        let mut locks = Self::none();
+        locks.lock(keys);
+        locks
+    }
+
+    /// Locks the fields named in `keys` as well, as a scenario's `locked` list
+    /// does when it is opened; nothing already locked is unlocked.
+    pub fn lock(&mut self, keys: &[String]) {
+        for i in keys.iter().filter_map(|key| index_of(key)) {
+            self.locked[i] = true;
+        }
+    }
+
+    pub fn is_locked(&self, field: usize) -> bool {
+        self.locked.get(field).copied().unwrap_or(false)
+    }
+
+    /// Copies the initial conditions from `random` into `init`, except for locked fields.
+    pub fn apply_unlocked(&self, init: &mut InitialConditions, random: InitialConditions) {
+        let fields: [(usize, &mut f64, f64); 6] = [
+            (FIELD_WATER, &mut init.water, random.water),
+            (FIELD_ICE, &mut init.ice, random.ice),
+            (FIELD_AIR, &mut init.air, random.air),
+            (FIELD_SALT, &mut init.salt, random.salt),
+            (FIELD_SYSTEM_TEMP, &mut init.system_temp, random.system_temp),
+            (FIELD_OUTSIDE_TEMP, &mut init.outside_temp, random.outside_temp),
+        ];
+        for (field, value, new) in fields {
+            if !self.is_locked(field) {
+                *value = new;
+            }
+        }
+    }
+
+    /// Gives `to`, the run taking the place of `from`, the locked fields' values
+    /// from `from`, so a snapshot or scenario loaded later can't change them.
+    pub fn keep(&self, from: &Simulation, to: &mut Simulation) {
+        for field in (0..FIELD_COUNT).filter(|&f| self.locked[f]) {
+            match field {
+                FIELD_WATER => to.init_mut().water = from.init().water,
+                FIELD_ICE => to.init_mut().ice = from.init().ice,
+                FIELD_AIR => to.init_mut().air = from.init().air,
+                FIELD_SALT => to.init_mut().salt = from.init().salt,
+                FIELD_SYSTEM_TEMP => to.init_mut().system_temp = from.init().system_temp,
+                FIELD_OUTSIDE_TEMP => to.init_mut().outside_temp = from.init().outside_temp,
+                FIELD_LIQUID => to.set_liquid(from.liquid()),
+                FIELD_SUPERCOOL => to.nucleation_mut().enabled = from.nucleation().enabled,
+                FIELD_DISSOLVED_AIR => to.nucleation_mut().dissolved_air = from.nucleation().dissolved_air,
+                FIELD_AMBIENT => to.set_ambient(from.ambient().clone()),
+                FIELD_START_CLOCK => to.set_start_datetime(from.start_datetime()),
+                FIELD_IRRADIANCE => to.solar_mut().irradiance = from.solar().irradiance,
+                FIELD_SHADED => to.solar_mut().shaded = from.solar().shaded,
+                FIELD_WIND => to.set_wind_speed(from.wind_speed()),
+                FIELD_HUMIDITY => to.set_humidity(from.humidity()),
+                FIELD_GEOMETRY => to.set_geometry(from.geometry()),
+                FIELD_MATERIAL => to.wall_mut().set_material(from.wall().material),
+                FIELD_INSULATION => to.wall_mut().layers = from.wall().layers.clone(),
+                // after the insulation, which brings its own gap
+                FIELD_GAP_GAS => {
+                    if let Some(gas) = from.wall().gap_gas() {
+                        to.wall_mut().set_gap_gas(gas);
+                    }
+                }
+                FIELD_CAP => to.set_capped(from.is_capped()),
+                // the model's grids start over only when they change, so a snapshot keeps its own
+                FIELD_LAYERS if to.water_layers().len() != from.water_layers().len() => to.set_water_layers(from.water_layers().len()),
+                FIELD_FIELD2D if to.field().is_some() != from.field().is_some() => to.set_field_mode(from.field().is_some()),
+                FIELD_ICE_PIECES if to.ice_pieces() != from.ice_pieces() => to.set_ice_pieces(from.ice_pieces()),
+                FIELD_PRESSURE_MELT => to.set_pressure_melting(from.pressure_melting()),
+                FIELD_CONDUCTION_ONLY => to.set_conduction_only(from.conduction_only()),
+                FIELD_DURATION => to.set_duration(from.duration()),
+                FIELD_HEATER => to.set_heater_power(from.heater_power()),
+                FIELD_CONTROL => to.controller_mut().mode = from.controller().mode,
+                FIELD_SETPOINT => to.controller_mut().setpoint = from.controller().setpoint,
+                FIELD_KP => to.controller_mut().kp = from.controller().kp,
+                FIELD_KI => to.controller_mut().ki = from.controller().ki,
+                FIELD_KD => to.controller_mut().kd = from.controller().kd,
+                _ => {}
+            }
+        }
+        // a run that hasn't started takes the kept contents up at once
+        if self.any() && to.time_seconds() == 0.0 {
+            to.apply_init();
+        }
+    }
+
+    pub fn any(&self) -> bool {
+        self.locked.iter().any(|&l| l)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icebottle_sim::WallMaterial;
+
+    #[test]
+    fn a_replaced_run_keeps_the_locked_values_and_takes_the_rest() {
+        let locks = FieldLocks::from_keys(&["water".to_string(), "material".to_string()]);
+        let mut current = Simulation::new();
+        current.init_mut().water = 0.3;
+        current.wall_mut().set_material(WallMaterial::StainlessSteel);
+        let mut loaded = Simulation::new();
+        loaded.init_mut().water = 1.2;
+        loaded.init_mut().ice = 0.4;
+        loaded.wall_mut().set_material(WallMaterial::Glass);
+        loaded.apply_init();
+        locks.keep(&current, &mut loaded);
+        assert_eq!(loaded.init().water, 0.3);
+        assert_eq!(loaded.wall().material, WallMaterial::StainlessSteel);
+        assert_eq!(loaded.init().ice, 0.4);
+        // not started yet, so the contents are the kept ones already
+        assert_eq!(loaded.state().mass_water, 0.3);
+    }
+}
@@ -0,0 +1,80 @@
+// Runs the model without a window, for scripts and servers where no display
+// can be opened: the run set up on the command line goes to its end and the
+// history comes out as CSV, the same rows X exports from the app. A recorded
+// session replays here too, to the step it ended on, and a scenario's
+// checkpoints are exported as they are passed.
+
+use icebottle_sim::{write_history_csv, History, Player, Simulation, SAMPLE_COLUMNS};
+use std::io::{self, BufWriter, Write};
+
+use crate::checkpoints::Exporter;
+use crate::describe::describe_beside;
+use crate::units::Units;
+use crate::{summary, HISTORY_RAM_SAMPLES};
+
+/// Runs `sim` to its duration, or with a `player` through the recorded
+/// session, sampling every `interval` simulated seconds, and writes the CSV to
+/// `output` (standard output when `None`), with the summary after a file in
+/// `units`. Checkpoints go into `checkpoint_dir`. Returns the exit code.
+pub fn run(mut sim: Simulation, mut player: Option<Player>, interval: f64, checkpoint_dir: &str, output: Option<&str>, units: Units) -> i32 {
+    // a recording starts and stops the run itself
+    if player.is_none() {
+        if sim.duration().is_none() {
+            eprintln!("No duration given (--duration or the scenario's duration_h): running {} h", sim.span() / 3600.0);
+            sim.set_duration(Some(sim.span()));
+        }
+        sim.toggle_running();
+    }
+
+    if sim.checkpoints().screenshot && !sim.checkpoints().is_empty() {
+        eprintln!("Checkpoint screenshots need the window: exporting the state and summary only");
+    }
+    let mut checkpoints = Exporter::new(checkpoint_dir);
+
+    // the same fixed steps and sampling as the app's loop, so the rows match its export
+    let mut history = History::new(SAMPLE_COLUMNS.len(), HISTORY_RAM_SAMPLES);
+    let mut next_sample = 0.0;
+    let mut steps = 0;
+    loop {
+        match &mut player {
+            Some(player) if steps < player.end() => player.apply(steps, &mut sim),
+            None if sim.is_running() => {}
+            _ => break,
+        }
+        let logged = sim.events().len();
+        sim.step(sim.step_size());
+        steps += 1;
+        checkpoints.after_step(&sim, logged);
+        if sim.is_running() && sim.time_seconds() >= next_sample {
+            if let Err(e) = history.push(&sim.sample()) {
+                eprintln!("History sample dropped: {e}");
+            }
+            next_sample += interval;
+        }
+    }
+
+    let written = match output {
+        Some(path) => std::fs::File::create(path).map(BufWriter::new).and_then(|mut f| {
+            write_history_csv(&mut f, sim.experiment(), &mut history)?;
+            f.flush()
+        }),
+        None => {
+            let mut out = BufWriter::new(io::stdout().lock());
+            write_history_csv(&mut out, sim.experiment(), &mut history).and_then(|()| out.flush())
+        }
+    };
+    match (written, output) {
+        (Ok(()), Some(path)) => {
+            // standard output is free for the summary when the CSV went to a file
+            println!("Wrote {} samples to {path}", history.len());
+            describe_beside(&sim, path);
+            summary::print(&sim, units);
+            0
+        }
+        (Ok(()), None) => 0,
+        (Err(e), _) => {
+            eprintln!("Could not write {}: {e}", output.unwrap_or("the CSV"));
+            1
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// The jug beside the bottle: liquid of the jug's mass and temperature is
+// poured into the running drink when the jug is dragged and let go over the
+// bottle, as hot water is added to iced water. The mouse wheel over the jug
+// sets the mass, with Shift the temperature. Pouring out (W) tips a quarter of
+// the liquid out of the bottle. Both go through the open neck only.
+
+use icebottle_sim::Simulation;
+use macroquad::prelude::*;
+
+use crate::bottle::temperature_color;
+use crate::strings::tr;
+use crate::theme::Palette;
+use crate::units::Units;
+
+pub const DEFAULT_MASS: f64 = 0.1; // kg
+pub const DEFAULT_TEMP: f64 = 80.0; // °C, from a kettle left a while
+/// Share of the liquid poured out at a time.
+pub const POUR_OUT: f64 = 0.25;
+
+// Steps of the mouse wheel, and how far they go
+const MASS_STEP: f64 = 0.01;
+const MASS_RANGE: (f64, f64) = (0.01, 0.5);
+const TEMP_STEP: f64 = 5.0;
+const TEMP_RANGE: (f64, f64) = (0.0, 100.0);
+
+pub struct Jug {
+    pub mass: f64, // kg
+    pub temp: f64, // °C
+    rect: Rect,
+    dragging: bool,
+}
+
+impl Jug {
+    pub fn new(mass: f64, temp: f64) -> Self {
+        Self { mass, temp: temp.clamp(TEMP_RANGE.0, TEMP_RANGE.1), rect: Rect::default(), dragging: false }
+    }
+
+    /// Places the jug to the left of `bottle`, above the ice tray.
+    pub fn place(&mut self, bottle: Rect) {
+        self.rect = Rect::new(bottle.x - 86.0, bottle.y + bottle.h - 290.0, 72.0, 72.0);
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Takes the mouse: the wheel over the jug and a drag from it onto
+    /// `bottle`. With `enabled` false a drag under way is dropped and the
+    /// wheel alone still works.
+    pub fn update(&mut self, sim: &mut Simulation, bottle: Rect, enabled: bool) {
+        let mouse = Vec2::from(mouse_position());
+        let wheel = mouse_wheel().1;
+        if wheel != 0.0 && self.rect.contains(mouse) {
+            let step = wheel.signum() as f64;
+            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+                self.temp = (self.temp + step * TEMP_STEP).clamp(TEMP_RANGE.0, TEMP_RANGE.1);
+            } else {
+                self.mass = (self.mass + step * MASS_STEP).clamp(MASS_RANGE.0, MASS_RANGE.1);
+            }
+        }
+        if !enabled || sim.is_capped() {
+            self.dragging = false;
+            return;
+        }
+        if is_mouse_button_pressed(MouseButton::Left) && self.rect.contains(mouse) {
+            self.dragging = true;
+        }
+        if self.dragging && is_mouse_button_released(MouseButton::Left) {
+            self.dragging = false;
+            if bottle.contains(mouse) && !sim.pour_in(self.mass, self.temp) {
+                println!("No room in the bottle for that much");
+            }
+        }
+    }
+
+    /// Draws the jug with what it holds, greyed while nothing can be poured.
+    pub fn draw(&self, sim: &Simulation, enabled: bool, units: Units, palette: &Palette) {
+        let r = self.rect;
+        let open = enabled && !sim.is_capped();
+        let alpha = if open { 1.0 } else { 0.4 };
+        draw_rectangle(r.x, r.y, r.w, r.h, Color { a: palette.card.a * alpha, ..palette.card });
+        draw_rectangle_lines(r.x, r.y, r.w, r.h, 2.0, Color { a: alpha, ..palette.frame });
+        if !self.dragging {
+            self.draw_jug(vec2(r.x + r.w / 2.0, r.y + r.h / 2.0), alpha, palette);
+        }
+        draw_text(tr!("jug.pour", units.mass(self.mass, 3)), r.x, r.y + r.h + 16.0, 14.0, palette.soft_text);
+        draw_text(units.temp(self.temp, 0), r.x, r.y + r.h + 32.0, 14.0, palette.soft_text);
+    }
+
+    /// Draws the jug being dragged under the mouse, over everything else.
+    pub fn draw_held(&self, palette: &Palette) {
+        if self.dragging {
+            self.draw_jug(Vec2::from(mouse_position()), 0.8, palette);
+        }
+    }
+
+    fn draw_jug(&self, center: Vec2, alpha: f32, palette: &Palette) {
+        let (w, h) = (30.0, 40.0);
+        let (x, y) = (center.x - w / 2.0, center.y - h / 2.0);
+        // fuller for more, tinted by its temperature
+        let level = h * (self.mass / MASS_RANGE.1).sqrt() as f32;
+        let liquid = temperature_color(self.temp);
+        draw_rectangle(x, y + h - level, w, level, Color { a: liquid.a * alpha, ..liquid });
+        let edge = Color { a: alpha, ..palette.frame };
+        draw_rectangle_lines(x, y, w, h, 2.0, edge);
+        draw_line(x, y, x - 6.0, y - 6.0, 2.0, edge);
+        draw_rectangle_lines(x + w, y + 8.0, 8.0, 20.0, 2.0, edge);
+    }
+}
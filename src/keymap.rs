@@ -0,0 +1,274 @@
+// Key bindings, read from a plain text file in the working directory so the
+// shortcuts can be moved off keys a laptop doesn't have or a window manager
+// takes. Each action lists the keys that trigger it; a file only needs the
+// lines it changes. Ctrl+Z / Ctrl+Y, Shift and Esc stay where they are.
+
+use egui_macroquad::egui::{self, Id, LayerId, Order};
+use macroquad::prelude::*;
+
+pub const KEYMAP_FILE: &str = "keymap.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    StartPause,
+    Reset,
+    Speed,
+    Integrator,
+    Announce,
+    RawValues,
+    Events,
+    History,
+    SaveSnapshot,
+    LoadSnapshot,
+    Shake,
+    Dsc,
+    Cap,
+    PourOut,
+    Random,
+    Summary,
+    Energy,
+    Presets,
+    Theme,
+    Language,
+    Render,
+    Screenshot,
+    Movie,
+    Fullscreen,
+    Name,
+    Notes,
+    Browse,
+    Explore,
+    Ensemble,
+    /// Focus the next control of the panel, as Tab does.
+    NextField,
+    PrevField,
+    /// Step the focused slider or value up, as the Right arrow does.
+    Increase,
+    Decrease,
+}
+
+impl Action {
+    pub const ALL: [Action; 33] = [
+        Action::StartPause,
+        Action::Reset,
+        Action::Speed,
+        Action::Integrator,
+        Action::Announce,
+        Action::RawValues,
+        Action::Events,
+        Action::History,
+        Action::SaveSnapshot,
+        Action::LoadSnapshot,
+        Action::Shake,
+        Action::Dsc,
+        Action::Cap,
+        Action::PourOut,
+        Action::Random,
+        Action::Summary,
+        Action::Energy,
+        Action::Presets,
+        Action::Theme,
+        Action::Language,
+        Action::Render,
+        Action::Screenshot,
+        Action::Movie,
+        Action::Fullscreen,
+        Action::Name,
+        Action::Notes,
+        Action::Browse,
+        Action::Explore,
+        Action::Ensemble,
+        Action::NextField,
+        Action::PrevField,
+        Action::Increase,
+        Action::Decrease,
+    ];
+
+    /// Also its name in the keymap file.
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::StartPause => "start_pause",
+            Action::Reset => "reset",
+            Action::Speed => "speed",
+            Action::Integrator => "integrator",
+            Action::Announce => "announce",
+            Action::RawValues => "raw_values",
+            Action::Events => "events",
+            Action::History => "history",
+            Action::SaveSnapshot => "save_snapshot",
+            Action::LoadSnapshot => "load_snapshot",
+            Action::Shake => "shake",
+            Action::Dsc => "dsc",
+            Action::Cap => "cap",
+            Action::PourOut => "pour_out",
+            Action::Random => "random",
+            Action::Summary => "summary",
+            Action::Energy => "energy",
+            Action::Presets => "presets",
+            Action::Theme => "theme",
+            Action::Language => "language",
+            Action::Render => "render",
+            Action::Screenshot => "screenshot",
+            Action::Movie => "movie",
+            Action::Fullscreen => "fullscreen",
+            Action::Name => "name",
+            Action::Notes => "notes",
+            Action::Browse => "browse",
+            Action::Explore => "explore",
+            Action::Ensemble => "ensemble",
+            Action::NextField => "next_field",
+            Action::PrevField => "prev_field",
+            Action::Increase => "increase",
+            Action::Decrease => "decrease",
+        }
+    }
+
+    /// The keys it has without a keymap file. Tab and the arrow keys move
+    /// through the panel whatever the file says; these are extra to them.
+    fn default_keys(self) -> &'static [KeyCode] {
+        match self {
+            Action::StartPause => &[KeyCode::Enter],
+            Action::Reset => &[KeyCode::R],
+            Action::Speed => &[KeyCode::S],
+            Action::Integrator => &[KeyCode::I],
+            Action::Announce => &[KeyCode::A],
+            Action::RawValues => &[KeyCode::P],
+            Action::Events => &[KeyCode::E],
+            Action::History => &[KeyCode::X],
+            Action::SaveSnapshot => &[KeyCode::F5],
+            Action::LoadSnapshot => &[KeyCode::F9],
+            Action::Shake => &[KeyCode::K],
+            Action::Dsc => &[KeyCode::D],
+            Action::Cap => &[KeyCode::C],
+            Action::PourOut => &[KeyCode::W],
+            Action::Random => &[KeyCode::G],
+            Action::Summary => &[KeyCode::U],
+            Action::Energy => &[KeyCode::L],
+            Action::Presets => &[KeyCode::B],
+            Action::Theme => &[KeyCode::T],
+            Action::Language => &[KeyCode::F2],
+            Action::Render => &[KeyCode::F3],
+            Action::Screenshot => &[KeyCode::F12],
+            Action::Movie => &[KeyCode::V],
+            Action::Fullscreen => &[KeyCode::F11],
+            Action::Name => &[KeyCode::N],
+            Action::Notes => &[KeyCode::M],
+            Action::Browse => &[KeyCode::O],
+            Action::Explore => &[KeyCode::H],
+            Action::Ensemble => &[KeyCode::J],
+            Action::NextField | Action::PrevField => &[],
+            Action::Increase => &[KeyCode::KpAdd],
+            Action::Decrease => &[KeyCode::KpSubtract],
+        }
+    }
+}
+
+// Key names in the file: macroquad's, matched without regard to case
+macro_rules! key_names {
+    ($($key:ident),* $(,)?) => {
+        const KEY_NAMES: &[(&str, KeyCode)] = &[$((stringify!($key), KeyCode::$key)),*];
+    };
+}
+
+key_names![
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Space, Enter, Tab, Backspace, Insert, Delete, Home, End, PageUp, PageDown, Left, Right, Up, Down,
+    Minus, Equal, LeftBracket, RightBracket, Backslash, Semicolon, Apostrophe, Comma, Period, Slash, GraveAccent,
+    Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9, KpAdd, KpSubtract, KpMultiply, KpDivide, KpDecimal, KpEnter,
+];
+
+fn key_name(key: KeyCode) -> &'static str {
+    KEY_NAMES.iter().find(|(_, k)| *k == key).map_or("?", |(name, _)| name)
+}
+
+/// The keys of every action, by its place in `Action::ALL`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+    keys: [Vec<KeyCode>; Action::ALL.len()],
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self { keys: Action::ALL.map(|a| a.default_keys().to_vec()) }
+    }
+}
+
+impl Keymap {
+    /// Loads the keymap file; a missing one means the keys in the README.
+    pub fn load() -> Self {
+        std::fs::read_to_string(KEYMAP_FILE).map(|text| Self::parse(&text)).unwrap_or_default()
+    }
+
+    /// Reads `action = key key ...` lines, each replacing the action's keys;
+    /// nothing after the `=` leaves it without one. Lines it can't make sense
+    /// of are reported and skipped, and so is a key given to two actions.
+    pub fn parse(text: &str) -> Self {
+        let mut keymap = Self::default();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = line.split_once('=').and_then(|(action, keys)| {
+                let at = Action::ALL.iter().position(|a| a.label() == action.trim())?;
+                let keys = keys.split_whitespace().map(|name| KEY_NAMES.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, key)| *key));
+                keymap.keys[at] = keys.collect::<Option<_>>()?;
+                Some(())
+            });
+            if parsed.is_none() {
+                eprintln!("{KEYMAP_FILE}:{}: skipped '{line}'", n + 1);
+            }
+        }
+        // the earlier action keeps a key given to two
+        for (i, action) in Action::ALL.iter().enumerate() {
+            let (before, after) = keymap.keys.split_at_mut(i);
+            after[0].retain(|key| match Action::ALL.iter().zip(before.iter()).find(|(_, keys)| keys.contains(key)) {
+                Some((other, _)) => {
+                    eprintln!("{KEYMAP_FILE}: {} is already {}, so not {}", key_name(*key), other.label(), action.label());
+                    false
+                }
+                None => true,
+            });
+        }
+        keymap
+    }
+
+    /// Whether one of `action`'s keys went down this frame.
+    pub fn pressed(&self, action: Action) -> bool {
+        self.keys[action as usize].iter().any(|&key| is_key_pressed(key))
+    }
+
+    /// Moves the focus through the controls panel and steps the focused value
+    /// for the navigation keys, the way egui does for Tab and the arrows.
+    pub fn steer_panel(&self, ctx: &egui::Context) {
+        let focused = ctx.memory(|m| m.focused());
+        for (action, step) in [(Action::NextField, 1), (Action::PrevField, -1)] {
+            if !self.pressed(action) {
+                continue;
+            }
+            // the panel's controls in the order they were laid out last frame
+            let controls: Vec<Id> = ctx.viewport(|v| {
+                let layer = LayerId::new(Order::Middle, Id::new("controls"));
+                v.prev_pass.widgets.get_layer(layer).filter(|w| w.enabled && w.sense.is_focusable()).map(|w| w.id).collect()
+            });
+            let Some(last) = controls.len().checked_sub(1) else { continue };
+            let next = match focused.and_then(|id| controls.iter().position(|&c| c == id)) {
+                Some(at) if step > 0 => if at == last { 0 } else { at + 1 },
+                Some(at) => at.checked_sub(1).unwrap_or(last),
+                None if step > 0 => 0,
+                None => last,
+            };
+            ctx.memory_mut(|m| m.request_focus(controls[next]));
+        }
+        if focused.is_some() {
+            for (action, key) in [(Action::Increase, egui::Key::ArrowRight), (Action::Decrease, egui::Key::ArrowLeft)] {
+                if self.pressed(action) {
+                    let event = egui::Event::Key { key, physical_key: None, pressed: true, repeat: false, modifiers: egui::Modifiers::NONE };
+                    ctx.input_mut(|i| i.events.push(event));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+// The on-screen keypad, for touch screens: they have no keys to step a value
+// with, and their own keyboard would cover the canvas. With it on, a value of
+// the controls panel that is tapped (or tabbed to) opens the keypad at the
+// bottom of the window instead of being typed into; OK writes the number in,
+// in the units the panel shows, and Cancel leaves the old one. What it is
+// entering is kept in egui's memory, so the panel's controls find it there.
+
+use egui_macroquad::egui::{self, Align2, Button, Event, Grid, Id, Key, Order, Response, RichText, Vec2};
+
+use crate::panel::parse_number;
+use crate::strings::tr;
+
+const BUTTON: Vec2 = Vec2::new(64.0, 48.0);
+
+#[derive(Clone, Default)]
+struct Keypad {
+    on: bool,
+    /// The control being entered, with its value as it was.
+    target: Option<(Id, String)>,
+    text: String,
+    /// A value taken with OK, until its control picks it up.
+    entered: Option<(Id, f64)>,
+}
+
+fn memory_id() -> Id {
+    Id::new("keypad")
+}
+
+fn load(ctx: &egui::Context) -> Keypad {
+    ctx.data(|d| d.get_temp(memory_id())).unwrap_or_default()
+}
+
+/// Whether the keypad is open; it then takes the keys, as a text box would.
+pub fn is_open(ctx: &egui::Context) -> bool {
+    load(ctx).target.is_some()
+}
+
+/// For the value control `id`, just added as `response` and showing `value`:
+/// opens the keypad on it when it took the focus, and returns the number
+/// entered for it, the frame after OK.
+pub fn entry(ctx: &egui::Context, id: Id, response: &Response, value: f64) -> Option<f64> {
+    let mut keypad = load(ctx);
+    let opens = keypad.on && response.has_focus();
+    let entered = keypad.entered.take_if(|(to, _)| *to == id).map(|(_, v)| v);
+    if !opens && entered.is_none() {
+        return None;
+    }
+    if opens {
+        // the keypad types, not the control
+        response.surrender_focus();
+        keypad.target = Some((id, shown(value)));
+        keypad.text.clear();
+    }
+    ctx.data_mut(|d| d.insert_temp(memory_id(), keypad));
+    entered
+}
+
+/// Draws the keypad while a value is being entered; with `on` false it stays
+/// closed and the panel's values are typed into as usual.
+pub fn show(ctx: &egui::Context, on: bool) {
+    let mut keypad = load(ctx);
+    keypad.on = on;
+    if !on {
+        keypad.target = None;
+    }
+    if let Some((id, was)) = keypad.target.clone() {
+        let decimal = tr!("keypad.decimal");
+        let (mut ok, mut cancel) = (false, false);
+        // a keyboard, where there is one, types into it too
+        ctx.input_mut(|i| {
+            i.events.retain(|event| match event {
+                Event::Text(text) => {
+                    for c in text.chars() {
+                        match c {
+                            '0'..='9' => keypad.text.push(c),
+                            '.' | ',' => press(&mut keypad.text, &decimal),
+                            '-' => press(&mut keypad.text, "-"),
+                            _ => {}
+                        }
+                    }
+                    false
+                }
+                Event::Key { key: Key::Backspace, pressed: true, .. } => {
+                    keypad.text.pop();
+                    false
+                }
+                Event::Key { key: Key::Enter, pressed: true, .. } => {
+                    ok = true;
+                    false
+                }
+                Event::Key { key: Key::Escape, pressed: true, .. } => {
+                    cancel = true;
+                    false
+                }
+                _ => true,
+            })
+        });
+        egui::Window::new(tr!("keypad.title"))
+            .id(Id::new("keypad_window"))
+            .anchor(Align2::CENTER_BOTTOM, [0.0, -12.0])
+            .order(Order::Foreground)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.weak(tr!("keypad.was", was.replace('.', &decimal)));
+                ui.label(RichText::new(format!("{} ", keypad.text)).heading().monospace());
+                Grid::new("keypad_keys").spacing([6.0, 6.0]).show(ui, |ui| {
+                    for row in [["7", "8", "9"], ["4", "5", "6"], ["1", "2", "3"], ["-", "0", decimal.as_str()]] {
+                        for key in row {
+                            // the sign key shows a real minus
+                            let label = if key == "-" { "\u{2212}" } else { key };
+                            if ui.add(Button::new(RichText::new(label).heading()).min_size(BUTTON)).clicked() {
+                                press(&mut keypad.text, key);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                    if ui.add(Button::new(RichText::new("\u{232b}").heading()).min_size(BUTTON)).clicked() {
+                        keypad.text.pop();
+                    }
+                    cancel |= ui.add(Button::new(tr!("keypad.cancel")).min_size(BUTTON)).clicked();
+                    ok |= ui.add_enabled(parse_number(&keypad.text).is_some(), Button::new(tr!("keypad.ok")).min_size(BUTTON)).clicked();
+                    ui.end_row();
+                });
+            });
+        if ok {
+            if let Some(value) = parse_number(&keypad.text) {
+                keypad.entered = Some((id, value));
+                keypad.target = None;
+            }
+        }
+        if cancel {
+            keypad.target = None;
+        }
+    }
+    ctx.data_mut(|d| d.insert_temp(memory_id(), keypad));
+}
+
+// A key of the pad: the sign key flips the sign, and there is one decimal sign at most
+fn press(text: &mut String, key: &str) {
+    match key {
+        "-" => {
+            if text.starts_with('-') {
+                text.remove(0);
+            } else {
+                text.insert(0, '-');
+            }
+        }
+        _ if key.chars().all(|c| c.is_ascii_digit()) => text.push_str(key),
+        _ => {
+            if !text.contains(['.', ',']) {
+                text.push_str(key);
+            }
+        }
+    }
+}
+
+// The value as it was, to four decimals at most
+fn shown(value: f64) -> String {
+    let text = format!("{value:.4}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_string() } else { text.to_string() }
+}
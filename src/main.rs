@@ -1,410 +1,982 @@
+use icebottle_sim::constants::ATMOSPHERIC_PRESSURE;
+use icebottle_sim::{
+    write_events_csv, write_history_csv, ControlMode, DscPoint, EventKind, DscSweep, History, InitialConditions, InsulationPreset, Player, Forecast, Prediction, Predictor, Recorder, Simulation, SAMPLE_COLUMNS,
+};
 use macroquad::prelude::*;
-
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+mod annotations;
+mod announce;
+mod app;
+mod bench;
+mod bottle;
+mod browser;
+mod check;
+mod checkpoints;
+mod cli;
+mod compare;
+mod describe;
+mod display;
+mod effects;
+mod energy;
+mod explore;
+mod fields;
+mod headless;
+mod jug;
+mod keymap;
+mod keypad;
+mod movie;
+mod notes;
+mod panel;
+mod presets;
+mod render;
+mod screenshot;
+mod shaker;
+mod side_by_side;
+mod silhouette;
+mod sparkline;
+mod summary;
+mod sweep;
+mod strings;
+mod theme;
+mod thumbnails;
+mod tips;
+mod tray;
+mod uncertainty;
+mod undo;
+mod units;
+mod widget;
+
+use announce::Announcer;
+use app::AppState;
+use bottle::BottleView;
+use browser::Browser;
+use checkpoints::Exporter;
+use clap::Parser;
+use cli::Args;
+use display::{DisplaySettings, StatusField};
+use explore::Explorer;
+use fields::*;
+use jug::Jug;
+use keymap::{Action, Keymap};
+use movie::Movie;
+use notes::NoteField;
+use presets::{PresetManager, TINTS};
+use render::RenderConfig;
+use shaker::Shaker;
+use side_by_side::Lineup;
+use strings::{tr, Strings};
+use tips::Tooltips;
+use tray::Tray;
+use sparkline::{draw_chart, draw_series, Chart, Sparkline};
+use uncertainty::Band;
+use undo::UndoHistory;
+use widget::Button;
+
+// Size the window opens at; it can be resized or made fullscreen (F11) and
+// the layout follows
 const WINDOW_W: f32 = 1024.0;
 const WINDOW_H: f32 = 768.0;
 
-// Physical constants
-const CP_WATER: f32 = 4186.0; // J/(kg*K)
-const CP_ICE: f32 = 2100.0;   // J/(kg*K)
-const LATENT_FUSION: f32 = 334_000.0; // J/kg
-const U_EFFECTIVE: f32 = 5.0; // overall heat transfer (tunable)
-
-// Visual mapping
-const PIXELS_PER_KG: f32 = 120.0; // visual scale from kg -> px height
-
-#[derive(Clone, Copy)]
-struct SystemState {
-    mass_water: f32,
-    mass_ice: f32,
-    mass_air: f32,
-    temp_water: f32, // Celsius
-    temp_ice: f32,   // Celsius
-}
+// Longest frame we try to catch up on; avoids a spiral of death after a stall
+const MAX_FRAME_TIME: f32 = 0.25;
 
-impl SystemState {
-    // fn total_mass(&self) -> f32 {
-    //     self.mass_water + self.mass_ice + self.mass_air
-    // }
-
-    fn system_temperature_equivalent(&self) -> f32 {
-        // sensible heat weighted temperature relative to 0 °C:
-        let sensible_ice = self.mass_ice * CP_ICE * self.temp_ice;
-        let sensible_water = self.mass_water * CP_WATER * self.temp_water;
-        let c_eff = self.mass_ice * CP_ICE + self.mass_water * CP_WATER;
-        if c_eff.abs() < 1e-9 {
-            0.0
-        } else {
-            (sensible_ice + sensible_water) / c_eff
-        }
+// Run history: one sample per simulated second by default (--sample-every),
+// older ones spill to a temp file
+const HISTORY_INTERVAL: f64 = 1.0;
+const HISTORY_RAM_SAMPLES: usize = 50_000;
+
+// Forecast steps run per frame; a whole 24 h forecast takes about 20 frames
+const FORECAST_STEPS_PER_FRAME: usize = 2000;
+
+// Written to the working directory by the E and X keys
+const EVENTS_CSV: &str = "events.csv";
+const HISTORY_CSV: &str = "history.csv";
+const SNAPSHOT_FILE: &str = "snapshot.json";
+// Folder the scenario browser opens on, unless --scenario names a file elsewhere
+const SCENARIO_DIR: &str = "scenarios";
+
+// Drawn at the screen's full resolution on HiDPI displays, in logical pixels
+fn window_conf(fullscreen: bool) -> Conf {
+    Conf {
+        window_title: "Bottle Thermal Simulation".to_string(),
+        window_width: WINDOW_W as i32,
+        window_height: WINDOW_H as i32,
+        window_resizable: true,
+        high_dpi: true,
+        fullscreen,
+        ..Default::default()
     }
 }
 
-struct Simulation {
-    state: SystemState,
-    outside_temp: f32,
-    time_seconds: f32,
-    running: bool,
-    time_scale: f32, // multiplier 1,2,5,10
-
-    // initial GUI-editable values
-    init_water: f32,
-    init_ice: f32,
-    init_air: f32,
-    init_system_temp: f32,
-    init_outside_temp: f32,
+// Time until a forecast event, for the status card
+fn format_eta(prediction: &Prediction, at: Option<f64>, now: f64, horizon: f64) -> String {
+    match at {
+        Some(t) if t - now < 60.0 => tr!("eta.now"),
+        Some(t) if t - now < 3600.0 => tr!("eta.minutes", (t - now) / 60.0),
+        Some(t) => tr!("eta.hours", (t - now) / 3600.0),
+        None => tr!("eta.beyond", (prediction.from + horizon - now) / 3600.0),
+    }
 }
 
-impl Simulation {
-    fn new() -> Self {
-        let init_water = 0.5;
-        let init_ice = 0.1;
-        let init_air = 0.02;
-        let init_temp = 5.0;
-        let out_temp = 25.0;
-        Self {
-            state: SystemState {
-                mass_water: init_water,
-                mass_ice: init_ice,
-                mass_air: init_air,
-                temp_water: init_temp,
-                temp_ice: init_temp.min(0.0),
-            },
-            outside_temp: out_temp,
-            time_seconds: 0.0,
-            running: false,
-            time_scale: 1.0,
-            init_water,
-            init_ice,
-            init_air,
-            init_system_temp: init_temp,
-            init_outside_temp: out_temp,
+fn main() {
+    let mut args = Args::parse();
+    if let Some(path) = &args.check {
+        std::process::exit(check::run(path));
+    }
+    if let Some(dir) = &args.thumbnails {
+        std::process::exit(thumbnails::run(dir));
+    }
+    if let Some(spec) = args.lang.clone().or_else(|| std::env::var("ICEBOTTLE_LANG").ok()) {
+        match Strings::load(&spec) {
+            Ok(strings) => strings::set(strings),
+            Err(e) => {
+                eprintln!("{spec}: error: {e}");
+                std::process::exit(2);
+            }
         }
     }
-
-    fn reset_from_init(&mut self) {
-        self.state.mass_water = self.init_water;
-        self.state.mass_ice = self.init_ice;
-        self.state.mass_air = self.init_air;
-        self.state.temp_water = self.init_system_temp;
-        self.state.temp_ice = self.init_system_temp.min(0.0);
-        self.outside_temp = self.init_outside_temp;
-        self.time_seconds = 0.0;
-        self.running = false;
-        self.time_scale = 1.0;
+    // set up before the window opens, so a broken scenario file is reported and nothing starts
+    let mut sim = Simulation::new();
+    let mut scenario = None;
+    if let Some(path) = &args.scenario {
+        match check::load(path) {
+            Ok(loaded) => {
+                loaded.apply(&mut sim);
+                // the scenario's locks join those given with --lock
+                args.lock.extend(loaded.locked.iter().cloned());
+                scenario = Some(loaded);
+            }
+            Err(code) => std::process::exit(code),
+        }
     }
-
-    fn step(&mut self, dt: f32) {
-        if !self.running {
-            return;
+    args.apply(&mut sim);
+    if let Some(scenario) = scenario.as_ref().filter(|_| args.compare) {
+        std::process::exit(compare::run(scenario, &args, args.output.as_deref()));
+    }
+    if let Some(scenario) = scenario.as_ref().filter(|_| args.side_by_side) {
+        match Lineup::new(scenario, &args) {
+            Ok(lineup) => macroquad::Window::from_config(window_conf(args.fullscreen), lineup.run(args)),
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(2);
+            }
         }
-        let dt = dt * self.time_scale;
-
-        // Equivalent system temp (sensible)
-        let sys_temp = self.state.system_temperature_equivalent();
-
-        // Heat flow from outside -> system (positive => heating)
-        let q_dot = U_EFFECTIVE * (self.outside_temp - sys_temp); // J/s
-        let mut q = q_dot * dt; // Joules delivered during dt
-
-        // HEATING (q > 0): raise ice temp to 0, melt, then heat water
-        if q > 0.0 {
-            // 1) warm ice to 0°C
-            if self.state.mass_ice > 0.0 && self.state.temp_ice < 0.0 {
-                let need = self.state.mass_ice * CP_ICE * (0.0 - self.state.temp_ice);
-                if q >= need {
-                    self.state.temp_ice = 0.0;
-                    q -= need;
-                } else {
-                    self.state.temp_ice += q / (self.state.mass_ice * CP_ICE);
-                    q = 0.0;
-                }
+        return;
+    }
+    if let Some(steps) = args.bench_steps {
+        bench::run(&sim, steps);
+        return;
+    }
+    if let Some(format) = &args.describe_model {
+        std::process::exit(describe::run(&sim, format, args.output.as_deref()));
+    }
+    let mut player = None;
+    if let Some(path) = &args.replay {
+        match std::fs::File::open(path).map_err(|e| e.to_string()).and_then(|f| Player::read(std::io::BufReader::new(f))) {
+            Ok((start, recorded)) => {
+                sim = start;
+                player = Some(recorded);
             }
-
-            // 2) melt ice at 0°C
-            if q > 0.0 && self.state.mass_ice > 0.0 {
-                let can_melt = q / LATENT_FUSION;
-                let melt_mass = can_melt.min(self.state.mass_ice);
-                self.state.mass_ice -= melt_mass;
-                self.state.mass_water += melt_mass;
-                q -= melt_mass * LATENT_FUSION;
-                // melted water enters at 0°C; we will mix below
+            Err(e) => {
+                eprintln!("{path}: error: {e}");
+                std::process::exit(2);
             }
+        }
+    }
+    let history_interval = args.sample_every.unwrap_or(HISTORY_INTERVAL);
+    if args.headless {
+        std::process::exit(headless::run(sim, player, history_interval, &args.checkpoint_dir, args.output.as_deref(), args.units()));
+    }
+    if args.sweep {
+        std::process::exit(sweep::run(&sim, &args.grid(), args.output.as_deref()));
+    }
+    if let Some(runs) = args.uncertainty {
+        std::process::exit(uncertainty::run(&sim, runs as usize, args.output.as_deref()));
+    }
+    macroquad::Window::from_config(window_conf(args.fullscreen), run(sim, player, history_interval, args));
+}
 
-            // 3) raise water temperature (mixed water)
-            if q > 0.0 && self.state.mass_water > 0.0 {
-                let delta_t = q / (self.state.mass_water * CP_WATER);
-                self.state.temp_water += delta_t;
-                // q = 0.0;
+async fn run(mut sim: Simulation, mut player: Option<Player>, history_interval: f64, args: Args) {
+    let mut accumulator: f64 = 0.0;
+    let mut water_spark = Sparkline::new();
+    let mut salinity_spark = Sparkline::new();
+    let mut power_spark = Sparkline::new();
+    let mut history = History::new(SAMPLE_COLUMNS.len(), HISTORY_RAM_SAMPLES);
+    let mut next_history_sample = 0.0;
+    let mut bottle_view = BottleView::new(&sim);
+    let mut announcer = Announcer::new();
+    let mut display = DisplaySettings::from_env();
+    let mut units = args.units();
+    let mut insulation = InsulationPreset::of_layers(&sim.wall().layers).unwrap_or_default();
+    let mut presets = PresetManager::load();
+    let predictor = Predictor::default();
+    let mut prediction = predictor.predict(&sim);
+    let mut next_prediction = 0.0;
+    let mut forecasting: Option<Forecast> = None;
+    // the ensemble band drawn on the water chart after J, and the thread working it out
+    let mut ensemble: Option<JoinHandle<Band>> = None;
+    let mut ensemble_band: Option<Band> = None;
+    let mut bottle_tint = TINTS[0];
+    let mut locks = FieldLocks::from_keys(&args.lock);
+    let mut ambient_choice = ambient_preset_index(sim.ambient(), sim.init().outside_temp);
+    let mut undo = UndoHistory::new(&sim, insulation, ambient_choice);
+    let dsc_sweep = DscSweep::default();
+    let mut dsc_trace: Option<Vec<DscPoint>> = None;
+    let mut show_summary = false;
+    let mut show_energy = false;
+    let mut state = AppState::of(&sim, false, player.is_some());
+    let scenario_dir = args.scenario.as_deref().and_then(|path| Path::new(path).parent()).filter(|dir| !dir.as_os_str().is_empty());
+    let mut browser = Browser::new(scenario_dir.unwrap_or(Path::new(SCENARIO_DIR)));
+    let mut events_seen = 0;
+    let mut checkpoints = Exporter::new(&args.checkpoint_dir);
+    // where the frame goes once drawn, and whether it is stamped, after F12 or a checkpoint
+    let mut screenshot: Option<(PathBuf, bool)> = None;
+    let mut movie = Movie::new(args.movie_format.unwrap_or_default(), args.movie_fps);
+    let mut editing: Option<NoteField> = None;
+    let mut next_seed = args.seed;
+    let mut last_seed: Option<u64> = None;
+    // physics steps since the start, which replay files are keyed by
+    let mut steps_done: u64 = 0;
+    let mut recorder = args.record.as_ref().and_then(|path| {
+        match std::fs::File::create(path).and_then(|f| Recorder::new(std::io::BufWriter::new(f), &sim)) {
+            Ok(recorder) => {
+                println!("Recording the session into {path}");
+                // closing the window has to finish the file first
+                prevent_quit();
+                Some(recorder)
             }
-        } else if q < 0.0 {
-            // COOLING: remove energy from water down to 0°C, freeze, then cool ice
-            let mut q_abs = -q;
-
-            // 1) cool water to 0°C
-            if self.state.mass_water > 0.0 && self.state.temp_water > 0.0 {
-                let need = self.state.mass_water * CP_WATER * (self.state.temp_water - 0.0);
-                let take = need.min(q_abs);
-                self.state.temp_water -= take / (self.state.mass_water * CP_WATER);
-                q_abs -= take;
+            Err(e) => {
+                eprintln!("Could not record into {path}: {e}");
+                None
             }
-
-            // 2) freeze some water at 0°C (latent)
-            if q_abs > 0.0 && self.state.mass_water > 0.0 && (self.state.temp_water - 0.0).abs() < 1e-3 {
-                let freeze_mass = (q_abs / LATENT_FUSION).min(self.state.mass_water);
-                self.state.mass_water -= freeze_mass;
-                self.state.mass_ice += freeze_mass;
-                q_abs -= freeze_mass * LATENT_FUSION;
+        }
+    });
+
+    let mut frame_rate = args.fps;
+    let mut fullscreen = args.fullscreen;
+    let mut theme = args.theme.unwrap_or_default();
+    let mut render = RenderConfig::load();
+    let keymap = Keymap::load();
+    // touch screens have no keys to step or type a value with
+    let use_keypad = args.keypad || cfg!(target_arch = "wasm32");
+    let mut tray = Tray::new(args.cube_mass.unwrap_or(tray::DEFAULT_MASS), args.cube_temp.unwrap_or(tray::DEFAULT_TEMP));
+    let mut explorer = Explorer::new();
+    let mut jug = Jug::new(args.pour_mass.unwrap_or(jug::DEFAULT_MASS), args.pour_temp.unwrap_or(jug::DEFAULT_TEMP));
+    let mut shaker = Shaker::default();
+    let mut show_render = false;
+    let mut frame_end = get_time();
+    loop {
+        let palette = &render.recolor(theme.palette().clone());
+        clear_background(palette.background);
+
+        // fixed-timestep physics, independent of the frame rate
+        accumulator += (get_frame_time().min(MAX_FRAME_TIME) as f64) * sim.time_scale();
+        // clock went backwards => the run was reset
+        if sim.time_seconds() + history_interval < next_history_sample {
+            history.clear();
+            next_history_sample = 0.0;
+        }
+        while accumulator >= sim.step_size() {
+            if let Some(recorded) = &mut player {
+                if steps_done < recorded.end() {
+                    recorded.apply(steps_done, &mut sim);
+                } else {
+                    // the recording is over: hold the run where it ended and hand it over
+                    if sim.is_running() {
+                        sim.toggle_running();
+                    }
+                    println!("Replay finished at {:.2} s ({steps_done} steps)", sim.time_seconds());
+                    player = None;
+                }
             }
-
-            // 3) lower ice temperature
-            if q_abs > 0.0 && self.state.mass_ice > 0.0 {
-                let delta_t = q_abs / (self.state.mass_ice * CP_ICE);
-                self.state.temp_ice -= delta_t;
-                // q_abs = 0.0;
+            let dt = sim.step_size();
+            let logged = sim.events().len();
+            sim.step(dt);
+            steps_done += 1;
+            screenshot = checkpoints.after_step(&sim, logged).map(|path| (path, true)).or(screenshot);
+            accumulator -= dt;
+            if sim.is_running() && sim.time_seconds() >= next_history_sample {
+                if let Err(e) = history.push(&sim.sample()) {
+                    eprintln!("History sample dropped: {e}");
+                }
+                next_history_sample += history_interval;
             }
-
-            // negative q handled, set q = 0 implicitly
         }
-
-        // Ensure temp bounds and mass sanity
-        if self.state.mass_ice > 0.0 {
-            self.state.temp_ice = self.state.temp_ice.min(0.0);
-        } else {
-            self.state.temp_ice = 0.0;
+        if let Some(recorder) = &mut recorder {
+            recorder.after_steps(&sim);
         }
-        if self.state.mass_water > 0.0 {
-            self.state.temp_water = self.state.temp_water.max(0.0);
-        } else {
-            // if no water, keep temp at 0 (degenerate)
-            self.state.temp_water = 0.0;
+        let now = AppState::of(&sim, browser.open, player.is_some());
+        // the run hit its duration: stop there and show how it went
+        if now != state && now == AppState::Finished {
+            show_summary = true;
+            summary::print(&sim, units);
+        }
+        state = now;
+        // target band alarm: a terminal bell and a line on stdout as the contents
+        // come into the band or go past it
+        events_seen = events_seen.min(sim.events().len());
+        for event in &sim.events()[events_seen..] {
+            let temp = sim.state().drink_temperature();
+            match event.kind {
+                EventKind::InBand => println!("\x07{}", tr!("alarm.in_range", event.time, units.temp(temp, 1))),
+                EventKind::Overshoot => println!("\x07{}", tr!("alarm.too_hot", event.time, units.temp(temp, 1))),
+                _ => {}
+            }
+        }
+        events_seen = sim.events().len();
+        water_spark.record(sim.time_seconds(), sim.state().temp_water as f32);
+        salinity_spark.record(sim.time_seconds(), (sim.state().salinity() * 1000.0) as f32);
+        power_spark.record(sim.time_seconds(), sim.heater_output() as f32);
+        announcer.update(&sim, units, get_time());
+        // the forecast reruns the model ahead, so a fresh one starts a second
+        // after the last finished and runs a share of its steps each frame
+        if forecasting.is_none() && get_time() >= next_prediction {
+            forecasting = Some(predictor.start(&sim));
+        }
+        if let Some(done) = forecasting.as_mut().and_then(|f| f.advance(FORECAST_STEPS_PER_FRAME)) {
+            prediction = done;
+            forecasting = None;
+            next_prediction = get_time() + 1.0;
+        }
+        if ensemble.as_ref().is_some_and(|e| e.is_finished()) {
+            match ensemble.take().map(JoinHandle::join) {
+                Some(Ok(band)) => {
+                    match band.save(uncertainty::BAND_CSV) {
+                        Ok(()) => println!("Wrote the band of {} runs to {}", band.runs, uncertainty::BAND_CSV),
+                        Err(e) => eprintln!("Could not write {}: {e}", uncertainty::BAND_CSV),
+                    }
+                    ensemble_band = Some(band);
+                }
+                _ => eprintln!("The ensemble failed"),
+            }
         }
 
-        self.time_seconds += dt;
-    }
-}
-
-fn window_conf() -> Conf {
-    Conf {
-        window_title: "Bottle Thermal Simulation".to_string(),
-        window_width: WINDOW_W as i32,
-        window_height: WINDOW_H as i32,
-        ..Default::default()
-    }
-}
-
-#[macroquad::main(window_conf)]
-async fn main() {
-
-    let mut sim = Simulation::new();
-    let mut selected_field: usize = 0;
-    let fields = [
-        "Init water (kg)",
-        "Init ice (kg)",
-        "Init air (kg)",
-        "Init system temp (C)",
-        "Outside temp (C)",
-    ];
-
-    loop {
-        clear_background(Color::from_rgba(18, 20, 28, 255));
-
-        let dt = get_frame_time();
-        sim.step(dt);
-
-        // Layout sizes
-        let left_card_x = 12.0;
-        let left_card_y = 12.0;
-        let left_card_w = 300.0;
-        let left_card_h = 160.0;
-
-        let right_card_w = 300.0;
-        let right_card_x = WINDOW_W - right_card_w - 12.0;
-        let right_card_y = 12.0;
+        // Layout sizes, from the window as it is this frame
+        let (screen_w, screen_h) = (screen_width(), screen_height());
+        let left_card_x = render.margin;
+        let left_card_y = render.margin;
+        let left_card_w = render.card_w;
+        let left_card_h = render.card_h;
 
         // Bottle position - centered between the UI cards
-        let bottle_center_x = WINDOW_W / 2.0;
-        let bottle_w = 220.0;
-        let bottle_h = 420.0;
+        let bottle_center_x = screen_w / 2.0;
+        let bottle_w = render.bottle_w;
+        let bottle_h = render.bottle_h;
         let bottle_x = bottle_center_x - bottle_w / 2.0;
-        let bottle_y = WINDOW_H / 2.0 - bottle_h / 2.0;
-
-        // Draw bottle body
-        let top_center = vec2(bottle_center_x, bottle_y);
-        draw_rectangle(top_center.x - 45., top_center.y - 7., bottle_w * 0.38, 16., GRAY);
+        let bottle_y = screen_h / 2.0 - bottle_h / 2.0;
+
+        // cap on the neck, or lying beside the bottle when off; click either to toggle (C)
+        let cap_button = Button::new(bottle_center_x - 50.0, bottle_y - 26.0, 100.0, 36.0).enabled(!locks.is_locked(FIELD_CAP) && state.allows(Action::Cap));
+        bottle_view.draw(&sim, Rect::new(bottle_x, bottle_y, bottle_w, bottle_h), bottle_tint, palette);
+        if cap_button.enabled && cap_button.hovered() {
+            let r = cap_button.rect;
+            draw_rectangle_lines(r.x, r.y, r.w, r.h, 1.0, Color { a: 0.4, ..palette.text });
+        }
+        // ice and liquid go in while the run goes on, as the cap comes off, and not through an overlay
+        let drops_ice = sim.is_running() && state.allows(Action::Cap) && !show_summary && !show_energy && !presets.open;
+        tray.place(Rect::new(bottle_x, bottle_y, bottle_w, bottle_h));
+        tray.draw(&sim, drops_ice, units, palette);
+        jug.place(Rect::new(bottle_x, bottle_y, bottle_w, bottle_h));
+        jug.draw(&sim, drops_ice, units, palette);
+
+        // Headspace pressure gauge, full scale at the material's burst pressure
+        let gauge = vec2(bottle_x + bottle_w + render.gauge_r + 22.0, bottle_y + 50.0);
+        let burst = sim.wall().material.burst_pressure();
+        let gauge_kpa = (sim.headspace_pressure() - ATMOSPHERIC_PRESSURE) / 1000.0;
+        let fraction = ((gauge_kpa * 1000.0 / burst) as f32).clamp(0.0, 1.0);
+        let angle = std::f32::consts::PI * (0.75 + 1.5 * fraction);
+        draw_circle(gauge.x, gauge.y, render.gauge_r, palette.card);
+        draw_circle_lines(gauge.x, gauge.y, render.gauge_r, 2.0, if sim.burst_risk() { palette.danger } else { palette.soft_text });
+        draw_line(gauge.x, gauge.y, gauge.x + (render.gauge_r - 6.0) * angle.cos(), gauge.y + (render.gauge_r - 6.0) * angle.sin(), 2.0, palette.warning);
+        draw_text(format!("{:+.1} kPa", gauge_kpa), gauge.x - 30.0, gauge.y + render.gauge_r + 16.0, 14.0, palette.soft_text);
+        if sim.pressure_melting() {
+            // why it is usually left out: hundredths of a kelvin at most
+            let shift = sim.state().pressure_shift;
+            draw_text(tr!("gauge.melt", shift), gauge.x - 30.0, gauge.y + render.gauge_r + 32.0, 14.0, palette.soft_text);
+            draw_text(tr!("gauge.latent", sim.state().latent_fusion() - sim.state().liquid.properties().latent_fusion), gauge.x - 30.0, gauge.y + render.gauge_r + 48.0, 14.0, palette.soft_text);
+        }
+        if sim.burst_risk() {
+            draw_text(tr!("gauge.burst"), gauge.x - 34.0, gauge.y - render.gauge_r - 8.0, 16.0, palette.danger);
+        }
 
-        draw_rectangle(bottle_x, bottle_y + 10.0, bottle_w, bottle_h - 10.0, Color::from_rgba(20, 30, 50, 80));
-        draw_rectangle_lines(bottle_x, bottle_y + 10.0, bottle_w, bottle_h - 10.0, 3.0, GRAY);
+        // Target band: a flashing banner over the bottle once the contents are in
+        // it, a warning once they have gone past it
+        if let Some(band) = sim.target_band() {
+            let temp = sim.state().drink_temperature();
+            let (text, color) = if band.contains(temp) {
+                let flash = if get_time() % 1.0 < 0.5 { 1.0 } else { 0.45 };
+                (tr!("target.in_range", units.temp(temp, 1)), Color { a: flash, ..palette.good })
+            } else if band.overshot() {
+                (tr!("target.too_hot", units.temp(temp, 1), units.temp(band.high, 0)), palette.danger)
+            } else {
+                let low = units.temp.of_celsius(band.low);
+                (tr!("target.heading", low, units.temp(band.high, 0), units.temp(temp, 1)), palette.soft_text)
+            };
+            let width = measure_text(&text, None, 22, 1.0).width;
+            draw_text(&text, bottle_center_x - width / 2.0, bottle_y - 46.0, 22.0, color);
+        }
 
-        // compute liquid height
-        let liquid_mass = sim.state.mass_water + sim.state.mass_ice;
-        let liquid_height_px = (liquid_mass * PIXELS_PER_KG).min(bottle_h - 12.0);
-        let water_fraction = if liquid_mass > 0.0 {
-            sim.state.mass_water / liquid_mass
-        } else {
-            0.0
+        // Whole-run water temperature strip under the bottle
+        let spark_y = bottle_y + bottle_h + 12.0;
+        let heading = match (&ensemble_band, &ensemble) {
+            (Some(band), _) => tr!("chart.water_band", band.runs),
+            (None, Some(_)) => tr!("chart.water_band_pending", uncertainty::BAND_RUNS),
+            (None, None) => tr!("chart.water"),
         };
-        let water_height_px = liquid_height_px * water_fraction;
-        let ice_height_px = liquid_height_px - water_height_px;
-
-        let water_top = bottle_y + bottle_h - water_height_px - 6.0;
-        if sim.state.mass_water > 0.0 {
-            // water rectangle
-            draw_rectangle(bottle_x + 4.0, water_top, bottle_w - 8.0, water_height_px.max(1.0), Color::from_rgba(30, 90, 200, 200));
-            // water surface ellipse
-            draw_rectangle(bottle_x + 4.0, water_top, bottle_w - 8.0, water_height_px.max(1.0), Color::from_rgba(30, 90, 200, 200));
-            draw_line(bottle_x + 4.0, water_top, bottle_x + bottle_w - 4.0, water_top, 2.0, Color::from_rgba(50, 140, 220, 200));
+        draw_text(heading, bottle_x - 40.0, spark_y - 2.0, 14.0, palette.faint_text);
+        let shown_temp = |celsius: f64| units.temp.of_celsius(celsius) as f32;
+        let water_temps: Vec<f32> = water_spark.values().iter().map(|&t| shown_temp(t as f64)).collect();
+        let band = sim.target_band().map(|band| (shown_temp(band.low), shown_temp(band.high)));
+        let mut extra = annotations::temps(sim.annotations(), water_spark.span(), units);
+        extra.extend(band.iter().flat_map(|&(low, high)| [low, high]));
+        // the ensemble's curves in the unit shown, over their share of the chart's time, which runs to the later of theirs and the run's
+        let whole = ensemble_band.as_ref().map_or(water_spark.span(), |b| b.span.max(water_spark.span()));
+        let shown_curve = |curve: &[f64]| curve.iter().map(|&t| shown_temp(t)).collect::<Vec<f32>>();
+        let ensemble_curves =
+            ensemble_band.as_ref().map(|b| (shown_curve(&b.median), shown_curve(&b.low), shown_curve(&b.high), (b.span / whole) as f32));
+        if let Some((_, low, high, _)) = &ensemble_curves {
+            extra.extend(low.iter().chain(high));
+        }
+        let reach = if whole > 0.0 { (water_spark.span() / whole) as f32 } else { 1.0 };
+        let chart = Chart::fit(&water_temps, &extra, bottle_x - 40.0, spark_y + 2.0, bottle_w + 80.0, render.chart_h).reaching(reach);
+        draw_chart(&chart, &water_temps, palette.water_line, palette, |chart| {
+            if let Some((low, high)) = band {
+                chart.shade_between(low, high, palette.band);
+            }
+            if let Some((median, low, high, band_reach)) = &ensemble_curves {
+                chart.shade_curves(low, high, *band_reach, palette.ensemble_band);
+                chart.curve(median, *band_reach, palette.ensemble);
+            }
+            annotations::draw(chart, sim.annotations(), water_spark.span(), units, palette);
+        });
+        if sim.state().mass_solute > 0.0 {
+            let salt_y = spark_y + render.chart_h + 22.0;
+            draw_text(
+                tr!("chart.salinity", sim.state().salinity() * 1000.0, sim.state().liquid_specific_heat()),
+                bottle_x - 40.0,
+                salt_y - 2.0,
+                14.0,
+                palette.faint_text,
+            );
+            salinity_spark.draw(bottle_x - 40.0, salt_y + 2.0, bottle_w + 80.0, 36.0, palette.salinity_line, palette);
+        }
+        if sim.controller().mode != ControlMode::Off {
+            let power_y = spark_y + render.chart_h + if sim.state().mass_solute > 0.0 { 78.0 } else { 22.0 };
+            draw_text(
+                tr!(
+                    "chart.heater",
+                    strings::name("control", sim.controller().mode.label()),
+                    units.temp(sim.controller().setpoint, 1),
+                    sim.heater_output()
+                ),
+                bottle_x - 40.0,
+                power_y - 2.0,
+                14.0,
+                palette.faint_text,
+            );
+            power_spark.draw(bottle_x - 40.0, power_y + 2.0, bottle_w + 80.0, 30.0, palette.power_line, palette);
         }
 
-        // ice blocks drawn stacked above water
-        let mut ice_y = water_top - ice_height_px;
-        let mut remaining = ice_height_px;
-        while remaining > 0.0 {
-            let block_h = remaining.min(36.0);
-            draw_rectangle(bottle_x + 8.0, ice_y, bottle_w - 16.0, block_h.max(1.0), Color::from_rgba(230, 245, 255, 230));
-            draw_rectangle_lines(bottle_x + 8.0, ice_y, bottle_w - 16.0, block_h.max(1.0), 1.0, Color::from_rgba(180, 200, 220, 200));
-            ice_y += block_h;
-            remaining -= block_h;
+        if show_summary {
+            summary::draw(&sim, units, palette, bottle_center_x - 180.0, bottle_y + 20.0);
+        }
+        if show_energy {
+            energy::draw(sim.energy(), palette, bottle_center_x - 210.0, bottle_y + 20.0);
+        }
+        // the Enter that ends a rename must not reach the keys below
+        let was_renaming = presets.is_renaming();
+        presets.update(vec2(bottle_center_x - 180.0, bottle_y + 20.0), palette, &mut sim, &mut insulation, &mut bottle_tint, &locks);
+
+        // DSC analysis panel over the bottle
+        if let Some(trace) = &dsc_trace {
+            let (px, py, pw, ph) = (bottle_x - 60.0, bottle_y + 40.0, bottle_w + 120.0, 240.0);
+            draw_rectangle(px - 8.0, py - 30.0, pw + 56.0, ph + 56.0, palette.overlay);
+            draw_text(
+                tr!("dsc.title", dsc_sweep.rate, units.mass.small_of_kg(dsc_sweep.sample_mass), units.mass.small_label()),
+                px,
+                py - 10.0,
+                16.0,
+                palette.text,
+            );
+            let flow: Vec<f32> = trace.iter().map(|p| p.heat_flow as f32).collect();
+            draw_series(&flow, px, py, pw, ph, palette.dsc_line, palette);
+            draw_text(units.temp(dsc_sweep.start_temp, 0), px, py + ph + 16.0, 14.0, palette.faint_text);
+            draw_text(tr!("dsc.axis"), px + pw / 2.0 - 110.0, py + ph + 16.0, 14.0, palette.faint_text);
+            draw_text(units.temp(dsc_sweep.end_temp, 0), px + pw - 30.0, py + ph + 16.0, 14.0, palette.faint_text);
         }
 
         // Top-left status card
-        draw_rectangle(left_card_x, left_card_y, left_card_w, left_card_h, Color::from_rgba(8, 8, 12, 220));
-        draw_rectangle_lines(left_card_x, left_card_y, left_card_w, left_card_h, 2.0, LIGHTGRAY);
-        draw_text(&format!("Time: {:.1} s", sim.time_seconds), left_card_x + 10.0, left_card_y + 28.0, 20.0, WHITE);
-        draw_text(&format!("Water: {:.4} kg", sim.state.mass_water), left_card_x + 10.0, left_card_y + 56.0, 18.0, WHITE);
-        draw_text(&format!("Ice:   {:.4} kg", sim.state.mass_ice), left_card_x + 10.0, left_card_y + 82.0, 18.0, WHITE);
-        draw_text(&format!("T_water: {:.2} °C", sim.state.temp_water), left_card_x + 10.0, left_card_y + 108.0, 18.0, WHITE);
-        draw_text(&format!("T_ice:   {:.2} °C", sim.state.temp_ice), left_card_x + 10.0, left_card_y + 134.0, 18.0, WHITE);
-
-        // Top-right controls card
-        let ctrl_h = 250.0;
-        draw_rectangle(right_card_x, right_card_y, right_card_w, ctrl_h, Color::from_rgba(8, 8, 12, 220));
-        draw_rectangle_lines(right_card_x, right_card_y, right_card_w, ctrl_h, 2.0, LIGHTGRAY);
+        draw_rectangle(left_card_x, left_card_y, left_card_w, left_card_h, palette.card);
+        draw_rectangle_lines(left_card_x, left_card_y, left_card_w, left_card_h, 2.0, palette.border);
+        let fmt = |field, value| display.format(field, value);
+        let mass = |field, kg| format!("{} {}", fmt(field, units.mass.of_kg(kg)), units.mass.label());
+        let temp = |field, celsius| format!("{} {}", fmt(field, units.temp.of_celsius(celsius)), units.temp.label());
+        draw_text(tr!("status.time", fmt(StatusField::Time, sim.time_seconds())), left_card_x + 10.0, left_card_y + 28.0, 20.0, palette.text);
+        if let Some(now) = sim.datetime() {
+            draw_text(now.to_string(), left_card_x + 170.0, left_card_y + 28.0, 16.0, palette.soft_text);
+        }
+        draw_text(tr!("status.water", mass(StatusField::Water, sim.state().mass_water)), left_card_x + 10.0, left_card_y + 56.0, 18.0, palette.text);
+        draw_text(tr!("status.ice", mass(StatusField::Ice, sim.state().mass_ice)), left_card_x + 10.0, left_card_y + 82.0, 18.0, palette.text);
+        draw_text(tr!("status.t_water", temp(StatusField::TempWater, sim.state().temp_water)), left_card_x + 10.0, left_card_y + 108.0, 18.0, palette.text);
+        draw_text(tr!("status.t_ice", temp(StatusField::TempIce, sim.state().temp_ice)), left_card_x + 10.0, left_card_y + 134.0, 18.0, palette.text);
+        draw_text(tr!("status.t_air", temp(StatusField::TempAir, sim.state().temp_air)), left_card_x + 10.0, left_card_y + 160.0, 18.0, palette.text);
+        // lowest..highest since the reset, right-aligned next to the value; column index into SAMPLE_COLUMNS
+        for (column, row_y, digits) in [(1, 56.0, 3), (2, 82.0, 3), (3, 108.0, 1), (4, 134.0, 1), (5, 160.0, 1), (7, 316.0, 1)] {
+            if let Some((lo, hi)) = sim.envelope().range(column) {
+                let (lo, hi) = (units.column(column, lo), units.column(column, hi));
+                let text = format!("{lo:.digits$}..{hi:.digits$}");
+                let width = measure_text(&text, None, 13, 1.0).width;
+                draw_text(&text, left_card_x + left_card_w - 8.0 - width, left_card_y + row_y, 13.0, palette.faint_text);
+            }
+        }
+        let hold = sim.hold_time();
+        draw_text(tr!("status.hold", units.temp(hold.threshold, 0), fmt(StatusField::Hold, hold.hours())), left_card_x + 10.0, left_card_y + 186.0, 18.0, palette.text);
+        draw_text(tr!("status.wall_ua", sim.wall().conductance()), left_card_x + 10.0, left_card_y + 212.0, 18.0, palette.text);
+        let exchange = sim.wall_exchange();
+        draw_text(tr!("status.convection", exchange.convection), left_card_x + 10.0, left_card_y + 238.0, 18.0, palette.text);
+        draw_text(tr!("status.radiation", exchange.radiation), left_card_x + 10.0, left_card_y + 264.0, 18.0, palette.text);
+        draw_text(tr!("status.solar", exchange.solar), left_card_x + 10.0, left_card_y + 290.0, 18.0, palette.text);
+        draw_text(tr!("status.conduction", exchange.conduction), left_card_x + 10.0, left_card_y + 316.0, 18.0, palette.text);
+        draw_text(tr!("status.freezing_point", units.temp(sim.state().freezing_point(), 2)), left_card_x + 10.0, left_card_y + 342.0, 18.0, palette.text);
+        if sim.is_supercooled() {
+            draw_text(tr!("status.supercooled"), left_card_x + 210.0, left_card_y + 342.0, 16.0, palette.accent);
+        }
+        let vapor_line = if sim.sublimation_rate() > 0.0 {
+            // frozen solid: freezer burn, slow enough to count by the day
+            let daily = units.mass.small_of_kg(sim.sublimation_rate() * 86400.0);
+            tr!("status.subliming", daily, units.mass.small_label(), units.mass(sim.ice_sublimed(), 4))
+        } else if sim.is_capped() {
+            tr!("status.steam", units.mass(sim.state().mass_vapor, 4), units.mass(sim.vapor_lost(), 4))
+        } else {
+            let hourly = units.mass.small_of_kg(sim.evaporation_rate() * 3600.0);
+            tr!("status.evaporating", hourly, units.mass.small_label(), units.mass(sim.vapor_lost(), 4))
+        };
         draw_text(
-            "Ctrls: Tab: field, +/-: change, Enter: Start/Pause",
-            right_card_x + 8.0,
-            right_card_y + 22.0,
-            13.0,
-            LIGHTGRAY,
+            vapor_line,
+            left_card_x + 10.0,
+            left_card_y + 368.0,
+            18.0,
+            palette.text,
         );
-
-        // editable fields listing (highlight selected)
-        let vals = [
-            sim.init_water,
-            sim.init_ice,
-            sim.init_air,
-            sim.init_system_temp,
-            sim.init_outside_temp,
-        ];
-        let mut fy = right_card_y + 46.0;
-        for i in 0..5 {
-            let is_sel = i == selected_field;
-            let bg = if is_sel { Color::from_rgba(36, 36, 50, 220) } else { Color::from_rgba(0, 0, 0, 0) };
-            draw_rectangle(right_card_x + 8.0, fy - 18.0, right_card_w - 16.0, 28.0, bg);
-            draw_text(&format!("{:20}: {:.3}", fields[i], vals[i]), right_card_x + 14.0, fy, 16.0, WHITE);
-            fy += 36.0;
-        }
-
-        // Buttons (Start, Reset, Speed)
-        let btn_y = right_card_y + ctrl_h - 40.0;
-        let btn_w = 87.0;
-        let btn_h = 34.0;
-        let start_label = if sim.running { "Pause" } else { "Start" };
-        draw_rectangle(right_card_x + 3.0, btn_y, btn_w, btn_h, Color::from_rgba(60, 120, 60, 220));
-        draw_text(start_label, right_card_x + 12.0 + 14.0, btn_y + 24.0, 18.0, WHITE);
-
-        draw_rectangle(right_card_x + 12.0 + btn_w + 8.0, btn_y, btn_w, btn_h, Color::from_rgba(150, 60, 60, 220));
-        draw_text("Reset", right_card_x + 12.0 + btn_w + 12.0 + 22.0, btn_y + 24.0, 18.0, WHITE);
-
-        draw_rectangle(right_card_x + 12.0 + 2.0 * (btn_w + 12.0), btn_y, btn_w, btn_h, Color::from_rgba(60, 60, 120, 220));
-        draw_text(&format!("Speed x{}", sim.time_scale as i32), right_card_x + 12.0 + 2.0 * (btn_w + 12.0) + 10.0, btn_y + 24.0, 16.0, WHITE);
-
-        // Mouse clicks for buttons
-        if is_mouse_button_pressed(MouseButton::Left) {
-            let (mx, my) = mouse_position();
-            // Start/Pause
-            if mx >= right_card_x + 12.0 && mx <= right_card_x + 12.0 + btn_w && my >= btn_y && my <= btn_y + btn_h {
-                // apply inits if paused
-                if !sim.running {
-                    sim.state.mass_water = sim.init_water;
-                    sim.state.mass_ice = sim.init_ice;
-                    sim.state.mass_air = sim.init_air;
-                    sim.state.temp_water = sim.init_system_temp;
-                    sim.state.temp_ice = sim.init_system_temp.min(0.0);
-                    sim.outside_temp = sim.init_outside_temp;
-                }
-                sim.running = !sim.running;
+        // against the level the contents would have if they were all liquid
+        let volume_l = (sim.state().condensed_volume() * 1000.0) as f32;
+        let density = sim.state().liquid.properties().density;
+        let all_liquid_l = ((sim.state().mass_water + sim.state().mass_ice) / density * 1000.0) as f32;
+        draw_text(
+            tr!("status.volume", volume_l * 1000.0, (volume_l - all_liquid_l) * 1000.0),
+            left_card_x + 10.0,
+            left_card_y + 394.0,
+            18.0,
+            palette.text,
+        );
+        draw_text(
+            tr!("status.dew_point", units.temp(sim.dew_point(), 1), units.mass.small_of_kg(sim.condensate()), units.mass.small_label()),
+            left_card_x + 10.0,
+            left_card_y + 420.0,
+            18.0,
+            palette.text,
+        );
+        let chiller = sim.chiller();
+        let heater_line = if chiller.flow > 0.0 {
+            tr!("status.chiller", chiller.power(sim.state().temp_water), chiller.coolant_used())
+        } else {
+            tr!("status.heater", sim.heater_output(), sim.electricity().kwh(), sim.electricity().cost())
+        };
+        draw_text(
+            heater_line,
+            left_card_x + 10.0,
+            left_card_y + 446.0,
+            18.0,
+            palette.text,
+        );
+        let now = sim.time_seconds();
+        let forecast = if sim.state().mass_ice > 0.0 {
+            tr!("status.ice_gone", format_eta(&prediction, prediction.ice_gone, now, predictor.horizon))
+        } else {
+            tr!("status.near_ambient", format_eta(&prediction, prediction.near_ambient, now, predictor.horizon))
+        };
+        draw_text(forecast, left_card_x + 10.0, left_card_y + 472.0, 18.0, palette.accent);
+        // every line of the card explains itself under the mouse
+        let mut tooltips = Tooltips::default();
+        let heater_tip = if chiller.flow > 0.0 { "chiller" } else { "heater" };
+        for (row_y, name) in [
+            (28.0, "time"),
+            (56.0, "water"),
+            (82.0, "ice"),
+            (108.0, "t_water"),
+            (134.0, "t_ice"),
+            (160.0, "t_air"),
+            (186.0, "hold"),
+            (212.0, "wall_ua"),
+            (238.0, "convection"),
+            (264.0, "radiation"),
+            (290.0, "solar"),
+            (316.0, "conduction"),
+            (342.0, "freezing_point"),
+            (368.0, "vapor"),
+            (394.0, "volume"),
+            (420.0, "dew_point"),
+            (446.0, heater_tip),
+            (472.0, "forecast"),
+        ] {
+            tooltips.region(Rect::new(left_card_x, left_card_y + row_y - 20.0, left_card_w, 26.0), || tips::status(name));
+        }
+        tooltips.region(tray.rect(), || tips::status("tray"));
+        tooltips.region(jug.rect(), || tips::status("jug"));
+
+        // Upcoming outside temperature, next 24 h
+        let profile_y = left_card_y + left_card_h + 24.0;
+        let upcoming: Vec<f32> = (0..=96)
+            .map(|i| units.temp.of_celsius(sim.ambient_temp_at(sim.time_seconds() + i as f64 * 900.0)) as f32)
+            .collect();
+        let profile_label = match sim.room() {
+            Some(room) => tr!("outside.room", units.temp(room.temp(), 2), strings::name("ambient", sim.ambient().label())),
+            None => tr!("outside.next", strings::name("ambient", sim.ambient().label())),
+        };
+        draw_text(profile_label, left_card_x, profile_y - 4.0, 14.0, palette.faint_text);
+        draw_series(&upcoming, left_card_x, profile_y, left_card_w, render.profile_h, palette.outside_line, palette);
+
+        // Top-right controls panel; it takes the keys while one of its controls has focus
+        let mut edited = None;
+        let mut panel_keys = false;
+        let mut panel_pointer = false;
+        let mut picked = None;
+        egui_macroquad::ui(|ctx| {
+            ctx.set_visuals(theme.visuals());
+            if editing.is_none() && !presets.is_renaming() && !keypad::is_open(ctx) && state.allows(Action::NextField) {
+                keymap.steer_panel(ctx);
+            }
+            if state.edits_run() {
+                edited = panel::show(ctx, &mut sim, &mut insulation, &mut ambient_choice, &mut frame_rate, &mut units, &locks);
+            }
+            keypad::show(ctx, use_keypad && state.edits_run());
+            if browser.open {
+                picked = browser.show(ctx);
             }
-            // Reset
-            if mx >= right_card_x + 12.0 + btn_w + 12.0 && mx <= right_card_x + 12.0 + 2.0 * btn_w + 12.0 && my >= btn_y && my <= btn_y + btn_h {
-                sim.reset_from_init();
+            if show_render {
+                render::debug_panel(ctx, &mut render, theme.palette(), &mut show_render);
             }
-            // Speed toggle
-            if mx >= right_card_x + 12.0 + 2.0 * (btn_w + 12.0) && mx <= right_card_x + 12.0 + 3.0 * btn_w + 24.0 && my >= btn_y && my <= btn_y + btn_h {
-                sim.time_scale = match sim.time_scale as i32 {
-                    1 => 2.0,
-                    2 => 5.0,
-                    5 => 10.0,
-                    _ => 1.0,
-                };
+            if explorer.open {
+                explorer.show(ctx, &sim, units);
             }
+            panel_keys = ctx.wants_keyboard_input() || keypad::is_open(ctx);
+            panel_pointer = ctx.is_pointer_over_area();
+        });
+
+        // Mouse click on the cap
+        if cap_button.clicked() {
+            sim.set_capped(!sim.is_capped());
+        }
+        // Ice from the tray and liquid from the jug; the neck is the cap's, so a click there is the cap's too
+        if !panel_pointer {
+            let body = Rect::new(bottle_x, bottle_y + 12.0, bottle_w, bottle_h - 12.0);
+            tray.update(&mut sim, body, drops_ice);
+            jug.update(&mut sim, body, drops_ice);
+            shaker.update(&mut sim, body, state.allows(Action::Shake) && !show_summary && !show_energy && !presets.open);
         }
 
-        // Keyboard input
-        if is_key_pressed(KeyCode::Tab) {
-            selected_field = (selected_field + 1) % 5;
+        // Keyboard input; while a note or preset name is being typed, keys go to the text only
+        let typing = editing.is_some() || was_renaming || presets.is_renaming() || panel_keys;
+        if let Some(field) = editing {
+            if !notes::type_into(sim.experiment_mut(), field) {
+                editing = None;
+            }
+        }
+        let down = |key| !typing && is_key_down(key);
+        let key_pressed = |key| !typing && is_key_pressed(key);
+        let shift = down(KeyCode::LeftShift) || down(KeyCode::RightShift);
+        let ctrl = down(KeyCode::LeftControl) || down(KeyCode::RightControl);
+        // Ctrl is kept for undo and redo, whatever the keymap binds
+        let pressed = |action| !typing && !ctrl && state.allows(action) && keymap.pressed(action);
+
+        // presets follow the edited outside temperature while paused
+        if let Some(choice) = ambient_choice.filter(|_| !sim.is_running()) {
+            let profile = ambient_preset(choice, sim.init().outside_temp);
+            if *sim.ambient() != profile {
+                sim.set_ambient(profile);
+            }
+        }
+
+        if pressed(Action::StartPause) {
+            sim.toggle_running();
+        }
+        if pressed(Action::Reset) {
+            sim.reset_from_init();
+        }
+        if ctrl && state.edits_run() && key_pressed(KeyCode::Z) && !shift && undo.undo(&mut sim, &mut insulation, &mut ambient_choice) {
+            println!("Undone ({} more to undo)", undo.undo_len());
+        }
+        if ctrl && state.edits_run() && (key_pressed(KeyCode::Y) || key_pressed(KeyCode::Z) && shift) && undo.redo(&mut sim, &mut insulation, &mut ambient_choice) {
+            println!("Redone ({} more to redo)", undo.redo_len());
+        }
+        if pressed(Action::Speed) {
+            sim.cycle_time_scale();
+        }
+        if pressed(Action::Integrator) {
+            sim.set_integrator(sim.integrator().next());
+        }
+        if pressed(Action::Announce) {
+            announcer.toggle(&sim, units, get_time());
         }
-        // Adjust selected field by small increments
-        let mut delta = 0.0;
-        if is_key_down(KeyCode::KpAdd) || is_key_down(KeyCode::Up) {
-            delta = 0.01;
-            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
-                delta = 0.1;
+        if pressed(Action::RawValues) {
+            display.toggle();
+        }
+        if pressed(Action::Events) {
+            let written = std::fs::File::create(EVENTS_CSV).and_then(|mut f| write_events_csv(&mut f, sim.experiment(), sim.events()));
+            match written {
+                Ok(()) => println!("Wrote {} events to {EVENTS_CSV}", sim.events().len()),
+                Err(e) => eprintln!("Could not write {EVENTS_CSV}: {e}"),
             }
         }
-        if is_key_down(KeyCode::KpSubtract) || is_key_down(KeyCode::Down) {
-            delta = -0.01;
-            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
-                delta = -0.1;
+        if pressed(Action::History) {
+            let written = std::fs::File::create(HISTORY_CSV)
+                .map(std::io::BufWriter::new)
+                .and_then(|mut f| {
+                    write_history_csv(&mut f, sim.experiment(), &mut history)?;
+                    std::io::Write::flush(&mut f)
+                });
+            match written {
+                Ok(()) => println!("Wrote {} samples to {HISTORY_CSV}", history.len()),
+                Err(e) => eprintln!("Could not write {HISTORY_CSV}: {e}"),
             }
+            describe::describe_beside(&sim, HISTORY_CSV);
         }
-        if delta != 0.0 {
-            match selected_field {
-                0 => sim.init_water = (sim.init_water + delta).max(0.0),
-                1 => sim.init_ice = (sim.init_ice + delta).max(0.0),
-                2 => sim.init_air = (sim.init_air + delta).max(0.0),
-                3 => sim.init_system_temp = sim.init_system_temp + delta * 5.0,
-                4 => sim.init_outside_temp = sim.init_outside_temp + delta * 5.0,
-                _ => {}
+        if pressed(Action::SaveSnapshot) {
+            match std::fs::write(SNAPSHOT_FILE, sim.to_json()) {
+                Ok(()) => println!("Saved a snapshot at {:.0} s to {SNAPSHOT_FILE}", sim.time_seconds()),
+                Err(e) => eprintln!("Could not write {SNAPSHOT_FILE}: {e}"),
             }
         }
-
-        if is_key_pressed(KeyCode::Enter) {
-            if !sim.running {
-                sim.state.mass_water = sim.init_water;
-                sim.state.mass_ice = sim.init_ice;
-                sim.state.mass_air = sim.init_air;
-                sim.state.temp_water = sim.init_system_temp;
-                sim.state.temp_ice = sim.init_system_temp.min(0.0);
-                sim.outside_temp = sim.init_outside_temp;
+        // a snapshot, or a scenario picked in the browser, takes the place of the run below
+        let mut replacement = picked.map(|(path, scenario)| {
+            let mut fresh = Simulation::new();
+            scenario.apply(&mut fresh);
+            // fields locked already keep their values, and the scenario's own locks join them
+            locks.keep(&sim, &mut fresh);
+            locks.lock(&scenario.locked);
+            println!("Loaded the scenario in {}", path.display());
+            fresh
+        });
+        if pressed(Action::LoadSnapshot) {
+            match std::fs::read_to_string(SNAPSHOT_FILE).map_err(|e| e.to_string()).and_then(|text| Simulation::from_json(&text)) {
+                Ok(mut loaded) => {
+                    locks.keep(&sim, &mut loaded);
+                    println!("Loaded the snapshot in {SNAPSHOT_FILE} at {:.0} s", loaded.time_seconds());
+                    replacement = Some(loaded);
+                }
+                Err(e) => eprintln!("Could not load {SNAPSHOT_FILE}: {e}"),
             }
-            sim.running = !sim.running;
         }
-        if is_key_pressed(KeyCode::R) {
-            sim.reset_from_init();
+        if pressed(Action::Shake) {
+            sim.shake();
         }
-        if is_key_pressed(KeyCode::S) {
-            sim.time_scale = match sim.time_scale as i32 {
-                1 => 2.0,
-                2 => 5.0,
-                5 => 10.0,
-                _ => 1.0,
+        if pressed(Action::Dsc) {
+            dsc_trace = match dsc_trace {
+                Some(_) => None,
+                None => Some(dsc_sweep.run(&sim)),
             };
         }
+        if pressed(Action::Cap) && !locks.is_locked(FIELD_CAP) {
+            sim.set_capped(!sim.is_capped());
+        }
+        if pressed(Action::PourOut) && !show_summary && !show_energy && !presets.open && !sim.pour_out(jug::POUR_OUT) {
+            println!("Nothing to pour out: the run is paused, the cap is on or the bottle holds no liquid");
+        }
+        if pressed(Action::Random) {
+            // surprise me: seeded, so `--seed N` repeats a draw
+            let random = InitialConditions::randomized(next_seed);
+            locks.apply_unlocked(sim.init_mut(), random);
+            println!(
+                "Random initial conditions, seed {next_seed}: water {}, ice {}, air {}, salt {}, contents {}, outside {}",
+                units.mass(random.water, 2),
+                units.mass(random.ice, 2),
+                units.mass(random.air, 3),
+                units.mass(random.salt, 3),
+                units.temp(random.system_temp, 1),
+                units.temp(random.outside_temp, 1)
+            );
+            last_seed = Some(next_seed);
+            next_seed = next_seed.wrapping_add(1);
+        }
+        if pressed(Action::Summary) {
+            show_summary = !show_summary;
+            if show_summary {
+                summary::print(&sim, units);
+            }
+        }
+        if pressed(Action::Energy) {
+            show_energy = !show_energy;
+        }
+        if pressed(Action::Theme) {
+            theme = theme.next();
+        }
+        if pressed(Action::Language) {
+            strings::next();
+        }
+        if pressed(Action::Render) {
+            show_render = !show_render;
+        }
+        // shift leaves the stamp off
+        if pressed(Action::Screenshot) {
+            screenshot = Some((screenshot::next_path(&sim), !shift));
+        }
+        if pressed(Action::Movie) {
+            movie.toggle(sim.time_seconds());
+            // closing the window has to finish the file first
+            prevent_quit();
+        }
+        if pressed(Action::Fullscreen) {
+            fullscreen = !fullscreen;
+            set_fullscreen(fullscreen);
+        }
+        if pressed(Action::Presets) {
+            presets.open = !presets.open;
+        }
+        if pressed(Action::Name) {
+            editing = Some(NoteField::Name);
+        }
+        if pressed(Action::Notes) {
+            editing = Some(NoteField::Notes);
+        }
+        if pressed(Action::Browse) {
+            browser.toggle();
+        }
+        if pressed(Action::Explore) {
+            explorer.toggle(&sim);
+        }
+        if pressed(Action::Ensemble) {
+            // a second press takes the band off, or gives up on one not yet worked out
+            if ensemble.is_some() || ensemble_band.is_some() {
+                ensemble = None;
+                ensemble_band = None;
+            } else {
+                println!("Running an ensemble of {} for the band on the water chart", uncertainty::BAND_RUNS);
+                ensemble = Some(Band::spawn(&sim, uncertainty::BAND_RUNS));
+            }
+        }
+        if let Some(loaded) = replacement {
+            sim = loaded;
+            // choices the app keeps outside the simulation follow the loaded one
+            insulation = InsulationPreset::of_layers(&sim.wall().layers).unwrap_or_default();
+            ambient_choice = ambient_preset_index(sim.ambient(), sim.init().outside_temp);
+            // the plots and history belong to the run that was replaced
+            accumulator = 0.0;
+            history.clear();
+            next_history_sample = sim.time_seconds();
+            water_spark.clear();
+            salinity_spark.clear();
+            power_spark.clear();
+            bottle_view.reset(&sim);
+            // a run loaded at its end doesn't count as just finished
+            state = AppState::of(&sim, browser.open, player.is_some());
+            events_seen = sim.events().len();
+            // the replaced run's forecast says nothing about this one
+            prediction = predictor.predict(&sim);
+            forecasting = None;
+            next_prediction = get_time() + 1.0;
+            ensemble = None;
+            ensemble_band = None;
+            dsc_trace = None;
+        }
+
+        // Run name and notes above the bottle; a caret marks the one being typed
+        let caret = |field| if editing == Some(field) { "_" } else { "" };
+        let experiment = sim.experiment();
+        // right of the left card, level with its top
+        let notes_x = left_card_x + left_card_w + render.margin;
+        if !experiment.name.is_empty() || editing.is_some() {
+            draw_text(tr!("notes.run", format!("{}{}", experiment.name, caret(NoteField::Name))), notes_x, left_card_y + 20.0, 20.0, palette.text);
+        }
+        let mut note_lines: Vec<&str> = experiment.notes.lines().collect();
+        if editing == Some(NoteField::Notes) && note_lines.is_empty() {
+            note_lines.push("");
+        }
+        for (i, line) in note_lines.iter().take(3).enumerate() {
+            let shown: String = line.chars().take(52).collect();
+            let last = i + 1 == note_lines.len().min(3);
+            let text = format!("{shown}{}", if last { caret(NoteField::Notes) } else { "" });
+            draw_text(text, notes_x, left_card_y + 40.0 + i as f32 * 16.0, 15.0, palette.soft_text);
+        }
 
         // Legend & FPS
-        draw_text("Model: simplified lumped heat + latent melt.", 12.0, WINDOW_H - 44.0, 16.0, LIGHTGRAY);
-        draw_text(&format!("FPS: {}", get_fps()), WINDOW_W - 96.0, WINDOW_H - 24.0, 16.0, LIGHTGRAY);
+        // each line's second item follows its first, however long the language makes that
+        let legend_gap = 24.0;
+        let model = tr!("legend.model");
+        let integrator = tr!("legend.integrator", strings::name("integrator", sim.integrator().label()));
+        draw_text(&model, 12.0, screen_h - 44.0, 16.0, palette.soft_text);
+        draw_text(&integrator, 12.0, screen_h - 24.0, 16.0, palette.soft_text);
+        draw_text(
+            tr!("legend.history", history.len(), history.spilled()),
+            12.0 + measure_text(&model, None, 16, 1.0).width + legend_gap,
+            screen_h - 44.0,
+            16.0,
+            palette.soft_text,
+        );
+        if let Some(seed) = last_seed {
+            draw_text(tr!("legend.seed", seed), screen_w - 300.0, screen_h - 44.0, 16.0, palette.soft_text);
+        }
+        if locks.any() {
+            draw_text(tr!("legend.locked"), screen_w - 300.0, screen_h - 24.0, 16.0, palette.warning);
+        }
+        if announcer.is_enabled() {
+            draw_text(tr!("legend.announcing"), 12.0 + measure_text(&integrator, None, 16, 1.0).width + legend_gap, screen_h - 24.0, 16.0, palette.soft_text);
+        }
+        draw_text(tr!("legend.fps", get_fps()), screen_w - 96.0, screen_h - 24.0, 16.0, palette.soft_text);
+        if let Some(recorded) = &player {
+            let text = tr!("replay.step", steps_done, recorded.end());
+            draw_text(&text, screen_w / 2.0 - measure_text(&text, None, 18, 1.0).width / 2.0, 24.0, 18.0, palette.accent);
+        } else if recorder.is_some() {
+            draw_text(tr!("replay.rec"), screen_w / 2.0 - 16.0, 24.0, 18.0, palette.danger);
+        }
+
+        egui_macroquad::draw();
+        if let Some((path, stamped)) = screenshot.take() {
+            if stamped {
+                screenshot::stamp(&sim, units, palette);
+            }
+            screenshot::save(&path);
+        }
+        // grabbed before the recording mark goes on
+        movie.capture(get_time());
+        if movie.is_recording() {
+            draw_text(tr!("movie.rec", movie.frames()), screen_w / 2.0 - 40.0, 44.0, 18.0, palette.danger);
+        }
+        tray.draw_held(palette);
+        jug.draw_held(palette);
+        if !panel_pointer {
+            tooltips.draw(palette);
+        }
 
+        undo.track(&sim, insulation, ambient_choice, edited, get_time());
+        // whatever the input above changed goes into the replay file
+        if let Some(active) = &mut recorder {
+            if let Err(e) = active.after_input(steps_done, &sim) {
+                eprintln!("Recording stopped: {e}");
+                recorder = None;
+            }
+        }
+        if is_quit_requested() {
+            movie.finish();
+            if let Some(active) = recorder.take() {
+                if let Err(e) = active.finish(steps_done) {
+                    eprintln!("Could not finish the recording: {e}");
+                }
+            }
+            return;
+        }
+
+        // a frame rate cap sleeps off the rest of the frame rather than drawing an
+        // unchanged scene again; a browser paces the frames itself and can't sleep
+        if let Some(fps) = frame_rate.filter(|_| cfg!(not(target_arch = "wasm32"))) {
+            let rest = 1.0 / fps as f64 - (get_time() - frame_end);
+            if rest > 0.0 {
+                std::thread::sleep(Duration::from_secs_f64(rest));
+            }
+        }
+        frame_end = get_time();
         next_frame().await;
     }
 }
@@ -0,0 +1,192 @@
+// Recording the window as a movie (V), for sharing a run the way it looks
+// sped up: frames are grabbed at a few per second of real time, shrunk, and
+// handed to a thread that encodes them into an animated GIF or writes them
+// out as numbered PNGs, which a video tool can turn into an MP4.
+
+use macroquad::prelude::*;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+
+/// Frames wider than this are shrunk by a whole factor, to keep GIFs shareable.
+const MAX_WIDTH: u16 = 800;
+/// Frames waiting for the encoder before new ones are dropped.
+const QUEUE: usize = 16;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MovieFormat {
+    #[default]
+    Gif,
+    /// A folder of `frame-00001.png`, ...
+    Png,
+}
+
+impl MovieFormat {
+    pub const ALL: [MovieFormat; 2] = [MovieFormat::Gif, MovieFormat::Png];
+
+    /// Also its name on the command line.
+    pub fn label(self) -> &'static str {
+        match self {
+            MovieFormat::Gif => "gif",
+            MovieFormat::Png => "png",
+        }
+    }
+}
+
+// One grabbed frame, rows bottom-up as the screen gives them
+struct Frame {
+    bytes: Vec<u8>,
+    width: u16,
+    height: u16,
+}
+
+struct Take {
+    path: PathBuf,
+    sender: SyncSender<Frame>,
+    worker: JoinHandle<io::Result<usize>>,
+    next_grab: f64,
+    grabbed: usize,
+    dropped: usize,
+}
+
+pub struct Movie {
+    format: MovieFormat,
+    fps: u32,
+    take: Option<Take>,
+}
+
+impl Movie {
+    pub fn new(format: MovieFormat, fps: u32) -> Self {
+        Self { format, fps, take: None }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.take.is_some()
+    }
+
+    /// Frames grabbed so far in this take.
+    pub fn frames(&self) -> usize {
+        self.take.as_ref().map_or(0, |take| take.grabbed)
+    }
+
+    /// Starts a take named after simulated time `now`, or finishes the one going.
+    pub fn toggle(&mut self, now: f64) {
+        if self.take.is_some() {
+            self.finish();
+            return;
+        }
+        let path = match self.format {
+            MovieFormat::Gif => PathBuf::from(format!("movie-{now:.0}s.gif")),
+            MovieFormat::Png => PathBuf::from(format!("movie-{now:.0}s")),
+        };
+        match self.start(&path) {
+            Ok((sender, worker)) => {
+                println!("Recording a movie into {} at {} frames/s (V to stop)", path.display(), self.fps);
+                self.take = Some(Take { path, sender, worker, next_grab: 0.0, grabbed: 0, dropped: 0 });
+            }
+            Err(e) => eprintln!("Could not write {}: {e}", path.display()),
+        }
+    }
+
+    // Opens `path` and starts the thread writing into it
+    fn start(&self, path: &Path) -> io::Result<(SyncSender<Frame>, JoinHandle<io::Result<usize>>)> {
+        let (sender, frames) = sync_channel::<Frame>(QUEUE);
+        let worker = match self.format {
+            MovieFormat::Gif => {
+                let out = BufWriter::new(File::create(path)?);
+                let delay = (100 / self.fps).max(1) as u16;
+                std::thread::spawn(move || encode_gif(out, frames.iter(), delay))
+            }
+            MovieFormat::Png => {
+                std::fs::create_dir_all(path)?;
+                let dir = path.to_path_buf();
+                std::thread::spawn(move || write_pngs(&dir, frames.iter()))
+            }
+        };
+        Ok((sender, worker))
+    }
+
+    /// Grabs the frame drawn so far when one is due; `clock` is real time (s).
+    pub fn capture(&mut self, clock: f64) {
+        let Some(take) = &mut self.take else { return };
+        if clock < take.next_grab {
+            return;
+        }
+        take.next_grab = clock + 1.0 / self.fps as f64;
+        let screen = get_screen_data();
+        match take.sender.try_send(shrink(screen.bytes, screen.width, screen.height)) {
+            Ok(()) => take.grabbed += 1,
+            Err(TrySendError::Full(_)) => take.dropped += 1,
+            // the encoder gave up; finish reports why
+            Err(TrySendError::Disconnected(_)) => self.finish(),
+        }
+    }
+
+    /// Waits for the frames still queued to be written and closes the file.
+    pub fn finish(&mut self) {
+        let Some(take) = self.take.take() else { return };
+        drop(take.sender);
+        match take.worker.join() {
+            Ok(Ok(written)) => {
+                println!("Wrote {written} frames to {}", take.path.display());
+                if take.dropped > 0 {
+                    println!("{} frames were skipped while the encoder caught up; try a lower --movie-fps", take.dropped);
+                }
+            }
+            Ok(Err(e)) => eprintln!("Could not write {}: {e}", take.path.display()),
+            Err(_) => eprintln!("The movie encoder stopped; {} is incomplete", take.path.display()),
+        }
+    }
+}
+
+// Every nth pixel of every nth row, n the smallest that fits MAX_WIDTH
+fn shrink(bytes: Vec<u8>, width: u16, height: u16) -> Frame {
+    let n = width.div_ceil(MAX_WIDTH).max(1) as usize;
+    if n == 1 {
+        return Frame { bytes, width, height };
+    }
+    let (w, h) = (width as usize / n, height as usize / n);
+    let mut small = Vec::with_capacity(w * h * 4);
+    for y in 0..h {
+        let row = y * n * width as usize;
+        for x in 0..w {
+            let at = (row + x * n) * 4;
+            small.extend_from_slice(&bytes[at..at + 4]);
+        }
+    }
+    Frame { bytes: small, width: w as u16, height: h as u16 }
+}
+
+// The GIF is as big as its first frame; frames of another size, grabbed after
+// the window was resized, are left out.
+fn encode_gif(out: BufWriter<File>, frames: impl Iterator<Item = Frame>, delay: u16) -> io::Result<usize> {
+    let mut frames = frames.peekable();
+    let Some(first) = frames.peek() else { return Ok(0) };
+    let (width, height) = (first.width, first.height);
+    let mut encoder = gif::Encoder::new(out, width, height, &[]).map_err(io::Error::other)?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(io::Error::other)?;
+    let mut written = 0;
+    for frame in frames.filter(|f| (f.width, f.height) == (width, height)) {
+        // GIF rows run top-down
+        let mut rgba: Vec<u8> = frame.bytes.chunks_exact(width as usize * 4).rev().flatten().copied().collect();
+        let mut gif_frame = gif::Frame::from_rgba_speed(width, height, &mut rgba, 10);
+        gif_frame.delay = delay;
+        encoder.write_frame(&gif_frame).map_err(io::Error::other)?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+fn write_pngs(dir: &Path, frames: impl Iterator<Item = Frame>) -> io::Result<usize> {
+    let mut written = 0;
+    for frame in frames {
+        let path = dir.join(format!("frame-{:05}.png", written + 1));
+        // export_png panics on a write error, so find out first
+        File::create(&path)?;
+        Image { bytes: frame.bytes, width: frame.width, height: frame.height }.export_png(&path.to_string_lossy());
+        written += 1;
+    }
+    Ok(written)
+}
@@ -0,0 +1,33 @@
+// Run name and notes: set with --name/--notes, or typed in the app (N / M).
+
+use icebottle_sim::Experiment;
+use macroquad::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoteField {
+    Name,
+    Notes,
+}
+
+/// Feeds this frame's typed characters into `field`. Returns false once Enter
+/// or Escape ends the edit.
+pub fn type_into(experiment: &mut Experiment, field: NoteField) -> bool {
+    let text = match field {
+        NoteField::Name => &mut experiment.name,
+        NoteField::Notes => &mut experiment.notes,
+    };
+    type_text(text)
+}
+
+/// Feeds this frame's typed characters into `text`, as for `type_into`.
+pub fn type_text(text: &mut String) -> bool {
+    while let Some(c) = get_char_pressed() {
+        if !c.is_control() {
+            text.push(c);
+        }
+    }
+    if is_key_pressed(KeyCode::Backspace) {
+        text.pop();
+    }
+    !(is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) || is_key_pressed(KeyCode::Escape))
+}
@@ -0,0 +1,543 @@
+// The controls panel: the editable fields in collapsible sections, then Start,
+// Reset and the speed. egui lays it out and handles the mouse and keyboard
+// (Tab moves between controls, the arrow keys step a value, a value can be
+// dragged or typed in), so what is drawn is always what is clicked. Values are
+// shown and typed in the chosen units and kept in °C and kg, and named in the
+// chosen language.
+
+use egui_macroquad::egui::{self, Align2, Color32, ComboBox, DragValue, Grid, Response, ScrollArea, Slider, SliderClamping, Ui};
+use icebottle_sim::constants::KELVIN;
+use icebottle_sim::{ControlMode, GapGas, Geometry, InsulationPreset, Liquid, SimDateTime, Simulation, WallMaterial, TIME_SCALES};
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+
+use crate::fields::*;
+use crate::keypad;
+use crate::strings::{self, tr, Strings, BUILT_IN};
+use crate::tips;
+use crate::units::{step_in, MassUnit, TempUnit, Units};
+
+const PANEL_W: f32 = 300.0;
+
+// Start clock choices are off, then every half hour of the day
+const CLOCK_STEP: f64 = 1800.0;
+const CLOCK_CHOICES: usize = 48;
+
+// Slider ranges of the initial conditions; a value typed in or loaded from a
+// file may lie outside them and is kept as it is
+const WATER_RANGE: RangeInclusive<f64> = 0.0..=2.0; // kg
+const ICE_RANGE: RangeInclusive<f64> = 0.0..=2.0; // kg
+const AIR_RANGE: RangeInclusive<f64> = 0.0..=0.05; // kg
+const SALT_RANGE: RangeInclusive<f64> = 0.0..=0.2; // kg
+const TEMP_RANGE: RangeInclusive<f64> = -40.0..=60.0; // °C
+// °C a temperature slider steps by, and a dragged setpoint moves per pixel
+const TEMP_STEP: f64 = 0.1;
+// What a value with no range of its own takes from the keypad
+const UNBOUNDED: RangeInclusive<f64> = f64::NEG_INFINITY..=f64::INFINITY;
+const PERCENT: RangeInclusive<f64> = 0.0..=100.0;
+
+// Physics steps per simulated second, and frame rate caps (none: as fast as the display)
+const TICK_RATES: [f64; 6] = [10.0, 20.0, 50.0, 100.0, 200.0, 1000.0];
+const FRAME_RATES: [Option<u32>; 4] = [Some(15), Some(30), Some(60), None];
+
+const CONTROL_MODES: [ControlMode; 3] = [ControlMode::Off, ControlMode::BangBang, ControlMode::Pid];
+
+/// Draws the panel in the top-right corner and applies what was changed in
+/// it. Returns the field edited this frame, if any.
+pub fn show(
+    ctx: &egui::Context,
+    sim: &mut Simulation,
+    insulation: &mut InsulationPreset,
+    ambient_choice: &mut Option<usize>,
+    frame_rate: &mut Option<u32>,
+    units: &mut Units,
+    locks: &FieldLocks,
+) -> Option<usize> {
+    let mut panel = Panel { locks, units: *units, edited: None };
+    // see-through like the cards on the dark theme; the light ones keep egui's fill
+    let style = ctx.style();
+    let frame = egui::Frame::window(&style);
+    let frame = if style.visuals.dark_mode { frame.fill(Color32::from_rgba_unmultiplied(8, 8, 12, 220)) } else { frame };
+    // by id, so the window stays put when its title changes language
+    egui::Window::new(tr!("panel.title"))
+        .id(egui::Id::new("controls"))
+        .anchor(Align2::RIGHT_TOP, [-12.0, 12.0])
+        .default_width(PANEL_W)
+        .resizable(false)
+        .collapsible(false)
+        .frame(frame)
+        .show(ctx, |ui| {
+            // the run buttons stay in view however short the window is
+            ScrollArea::vertical().max_height(ctx.screen_rect().height() - 120.0).show(ui, |ui| {
+                panel.contents(ui, sim);
+                panel.surroundings(ui, sim, ambient_choice);
+                panel.bottle(ui, sim, insulation);
+                panel.model(ui, sim);
+                panel.heater(ui, sim);
+                timing(ui, sim, frame_rate);
+                unit_choices(ui, units);
+            });
+            ui.separator();
+            run_buttons(ui, sim);
+        });
+    panel.edited
+}
+
+struct Panel<'a> {
+    locks: &'a FieldLocks,
+    units: Units,
+    edited: Option<usize>,
+}
+
+impl Panel<'_> {
+    /// One labelled row of a section's grid; `add` puts in the control and
+    /// says whether it changed. A locked field is shown greyed out.
+    fn row(&mut self, ui: &mut Ui, field: usize, add: impl FnOnce(&mut Ui) -> bool) {
+        let locked = self.locks.is_locked(field);
+        let label = ui.label(self.units.label(&tr!(&format!("field.{}", KEYS[field]))));
+        label.on_hover_ui(|ui| {
+            ui.set_max_width(320.0);
+            if locked {
+                ui.strong(tr!("panel.locked"));
+            }
+            ui.label(tips::field(KEYS[field]));
+        });
+        if ui.add_enabled_ui(!locked, add).inner {
+            self.edited = Some(field);
+        }
+        ui.end_row();
+    }
+
+    /// A collapsible section named by the `section.<key>` string.
+    fn section(&mut self, ui: &mut Ui, key: &str, open: bool, rows: impl FnOnce(&mut Self, &mut Ui)) {
+        collapsing(key, open).show(ui, |ui| {
+            Grid::new(key).num_columns(2).spacing([12.0, 4.0]).show(ui, |ui| rows(self, ui));
+        });
+    }
+
+    fn contents(&mut self, ui: &mut Ui, sim: &mut Simulation) {
+        self.section(ui, "contents", true, |panel, ui| {
+            let units = panel.units;
+            panel.row(ui, FIELD_LIQUID, |ui| {
+                let liquid = sim.liquid();
+                let current = Liquid::ALL.iter().position(|&l| l == liquid);
+                let picked = choice(ui, FIELD_LIQUID, strings::name("liquid", liquid.label()), current, Liquid::ALL.len(), |i| strings::name("liquid", Liquid::ALL[i].label()));
+                if let Some(i) = picked {
+                    sim.set_liquid(Liquid::ALL[i]);
+                }
+                picked.is_some()
+            });
+            let init = sim.init_mut();
+            panel.row(ui, FIELD_WATER, |ui| mass_slider(ui, units.mass, &mut init.water, WATER_RANGE, 0.01));
+            panel.row(ui, FIELD_ICE, |ui| mass_slider(ui, units.mass, &mut init.ice, ICE_RANGE, 0.01));
+            panel.row(ui, FIELD_AIR, |ui| mass_slider(ui, units.mass, &mut init.air, AIR_RANGE, 0.001));
+            panel.row(ui, FIELD_SALT, |ui| mass_slider(ui, units.mass, &mut init.salt, SALT_RANGE, 0.001));
+            panel.row(ui, FIELD_SYSTEM_TEMP, |ui| temperature_slider(ui, units.temp, &mut init.system_temp));
+            panel.row(ui, FIELD_SUPERCOOL, |ui| ui.checkbox(&mut sim.nucleation_mut().enabled, "").changed());
+            // boiling drives the air out, and with it much of what starts the ice
+            panel.row(ui, FIELD_DISSOLVED_AIR, |ui| {
+                let mut percent = sim.nucleation().dissolved_air * 100.0;
+                let changed = percent_slider(ui, &mut percent);
+                if changed {
+                    sim.nucleation_mut().dissolved_air = percent / 100.0;
+                }
+                changed
+            });
+        });
+    }
+
+    fn surroundings(&mut self, ui: &mut Ui, sim: &mut Simulation, ambient_choice: &mut Option<usize>) {
+        self.section(ui, "surroundings", true, |panel, ui| {
+            let units = panel.units;
+            panel.row(ui, FIELD_OUTSIDE_TEMP, |ui| temperature_slider(ui, units.temp, &mut sim.init_mut().outside_temp));
+            panel.row(ui, FIELD_AMBIENT, |ui| {
+                let outside = sim.init().outside_temp;
+                // a profile from a file has no preset and shows under its own name
+                let picked = choice(ui, FIELD_AMBIENT, strings::name("ambient", sim.ambient().label()), *ambient_choice, AMBIENT_PRESET_COUNT, |i| {
+                    strings::name("ambient", ambient_preset(i, outside).label())
+                });
+                *ambient_choice = picked.or(*ambient_choice);
+                picked.is_some()
+            });
+            panel.row(ui, FIELD_START_CLOCK, |ui| {
+                let current = sim.start_datetime().map(|t| (t.seconds_of_day / CLOCK_STEP) as usize + 1).unwrap_or(0);
+                let picked = choice(ui, FIELD_START_CLOCK, clock_label(current), Some(current), CLOCK_CHOICES + 1, clock_label);
+                if let Some(i) = picked {
+                    let start = (i > 0).then(|| SimDateTime { day: 1, seconds_of_day: (i - 1) as f64 * CLOCK_STEP });
+                    sim.set_start_datetime(start);
+                }
+                picked.is_some()
+            });
+            panel.row(ui, FIELD_IRRADIANCE, |ui| slider(ui, &mut sim.solar_mut().irradiance, 0.0..=1400.0, 10.0));
+            panel.row(ui, FIELD_SHADED, |ui| ui.checkbox(&mut sim.solar_mut().shaded, "").changed());
+            panel.row(ui, FIELD_WIND, |ui| {
+                let mut wind = sim.wind_speed();
+                let changed = keyed(ui, &mut wind, 0.0..=f64::INFINITY, |ui, v| ui.add(number(v).speed(0.1).range(0.0..=f64::INFINITY).fixed_decimals(1)));
+                sim.set_wind_speed(wind);
+                changed
+            });
+            panel.row(ui, FIELD_HUMIDITY, |ui| {
+                let mut percent = sim.humidity() * 100.0;
+                let changed = percent_slider(ui, &mut percent);
+                if changed {
+                    sim.set_humidity(percent / 100.0);
+                }
+                changed
+            });
+        });
+    }
+
+    fn bottle(&mut self, ui: &mut Ui, sim: &mut Simulation, insulation: &mut InsulationPreset) {
+        self.section(ui, "bottle", false, |panel, ui| {
+            panel.row(ui, FIELD_GEOMETRY, |ui| {
+                let current = GEOMETRY_PRESETS.iter().position(|(_, g)| *g == sim.geometry());
+                let other = if matches!(sim.geometry(), Geometry::Outline { .. }) { "outline" } else { "custom" };
+                let shown = current.map_or_else(|| tr!(&format!("panel.{other}")), |i| strings::name("shape", GEOMETRY_PRESETS[i].0));
+                let picked = choice(ui, FIELD_GEOMETRY, shown, current, GEOMETRY_PRESETS.len(), |i| strings::name("shape", GEOMETRY_PRESETS[i].0));
+                if let Some(i) = picked {
+                    sim.set_geometry(GEOMETRY_PRESETS[i].1);
+                }
+                picked.is_some()
+            });
+            panel.row(ui, FIELD_MATERIAL, |ui| {
+                let material = sim.wall().material;
+                let current = WallMaterial::ALL.iter().position(|&m| m == material);
+                let picked = choice(ui, FIELD_MATERIAL, strings::name("material", material.label()), current, WallMaterial::ALL.len(), |i| {
+                    strings::name("material", WallMaterial::ALL[i].label())
+                });
+                if let Some(i) = picked {
+                    sim.wall_mut().set_material(WallMaterial::ALL[i]);
+                }
+                picked.is_some()
+            });
+            panel.row(ui, FIELD_INSULATION, |ui| {
+                let current = InsulationPreset::ALL.iter().position(|&p| p == *insulation);
+                let picked = choice(ui, FIELD_INSULATION, strings::name("insulation", insulation.label()), current, InsulationPreset::ALL.len(), |i| {
+                    strings::name("insulation", InsulationPreset::ALL[i].label())
+                });
+                if let Some(i) = picked {
+                    *insulation = InsulationPreset::ALL[i];
+                    // keep the chosen gas when moving between double-walled presets
+                    let gas = sim.wall().gap_gas();
+                    sim.wall_mut().layers = insulation.layers();
+                    if let Some(gas) = gas {
+                        sim.wall_mut().set_gap_gas(gas);
+                    }
+                }
+                picked.is_some()
+            });
+            panel.row(ui, FIELD_GAP_GAS, |ui| match sim.wall().gap_gas() {
+                Some(gas) => {
+                    let current = GapGas::ALL.iter().position(|&g| g == gas);
+                    let picked = choice(ui, FIELD_GAP_GAS, strings::name("gap", gas.label()), current, GapGas::ALL.len(), |i| strings::name("gap", GapGas::ALL[i].label()));
+                    if let Some(i) = picked {
+                        sim.wall_mut().set_gap_gas(GapGas::ALL[i]);
+                    }
+                    picked.is_some()
+                }
+                None => {
+                    ui.weak(tr!("panel.no_gap"));
+                    false
+                }
+            });
+            panel.row(ui, FIELD_CAP, |ui| {
+                let mut capped = sim.is_capped();
+                let changed = ui.checkbox(&mut capped, "").changed();
+                if changed {
+                    sim.set_capped(capped);
+                }
+                changed
+            });
+        });
+    }
+
+    fn model(&mut self, ui: &mut Ui, sim: &mut Simulation) {
+        self.section(ui, "model", false, |panel, ui| {
+            panel.row(ui, FIELD_LAYERS, |ui| {
+                let current = LAYER_CHOICES.iter().position(|&n| n == sim.water_layers().len().max(1));
+                let label = |n: usize| if n == 1 { tr!("panel.lumped") } else { n.to_string() };
+                let shown = label(sim.water_layers().len().max(1));
+                let picked = choice(ui, FIELD_LAYERS, shown, current, LAYER_CHOICES.len(), |i| label(LAYER_CHOICES[i]));
+                if let Some(i) = picked {
+                    sim.set_water_layers(LAYER_CHOICES[i]);
+                }
+                picked.is_some()
+            });
+            panel.row(ui, FIELD_FIELD2D, |ui| {
+                let mut on = sim.field().is_some();
+                let changed = ui.checkbox(&mut on, "").changed();
+                if changed {
+                    sim.set_field_mode(on);
+                }
+                changed
+            });
+            panel.row(ui, FIELD_ICE_PIECES, |ui| {
+                let current = ICE_PIECE_CHOICES.iter().position(|&n| n == sim.ice_pieces());
+                let label = |n: usize| if n == 0 { tr!("panel.lumped") } else { n.to_string() };
+                let picked = choice(ui, FIELD_ICE_PIECES, label(sim.ice_pieces()), current, ICE_PIECE_CHOICES.len(), |i| label(ICE_PIECE_CHOICES[i]));
+                if let Some(i) = picked {
+                    sim.set_ice_pieces(ICE_PIECE_CHOICES[i]);
+                }
+                picked.is_some()
+            });
+            panel.row(ui, FIELD_PRESSURE_MELT, |ui| {
+                let mut on = sim.pressure_melting();
+                let changed = ui.checkbox(&mut on, "").changed();
+                if changed {
+                    sim.set_pressure_melting(on);
+                }
+                changed
+            });
+            panel.row(ui, FIELD_CONDUCTION_ONLY, |ui| {
+                let mut on = sim.conduction_only();
+                let changed = ui.checkbox(&mut on, "").changed();
+                if changed {
+                    sim.set_conduction_only(on);
+                }
+                changed
+            });
+            panel.row(ui, FIELD_DURATION, |ui| {
+                let hours = sim.duration().unwrap_or(0.0) / 3600.0;
+                let current = DURATION_CHOICES.iter().position(|&h| h == hours);
+                let label = |h: f64| if h == 0.0 { tr!("panel.off") } else { format!("{h} h") };
+                let picked = choice(ui, FIELD_DURATION, label(hours), current, DURATION_CHOICES.len(), |i| label(DURATION_CHOICES[i]));
+                if let Some(i) = picked {
+                    sim.set_duration(Some(DURATION_CHOICES[i] * 3600.0));
+                }
+                picked.is_some()
+            });
+        });
+    }
+
+    fn heater(&mut self, ui: &mut Ui, sim: &mut Simulation) {
+        self.section(ui, "heater", false, |panel, ui| {
+            let units = panel.units;
+            panel.row(ui, FIELD_HEATER, |ui| {
+                let mut power = sim.heater_power();
+                let changed = keyed(ui, &mut power, UNBOUNDED, |ui, v| ui.add(number(v).speed(1.0).fixed_decimals(0)));
+                sim.set_heater_power(power);
+                changed
+            });
+            let control = sim.controller_mut();
+            panel.row(ui, FIELD_CONTROL, |ui| {
+                let current = CONTROL_MODES.iter().position(|&m| m == control.mode);
+                let picked = choice(ui, FIELD_CONTROL, strings::name("control", control.mode.label()), current, CONTROL_MODES.len(), |i| {
+                    strings::name("control", CONTROL_MODES[i].label())
+                });
+                if let Some(i) = picked {
+                    control.mode = CONTROL_MODES[i];
+                }
+                picked.is_some()
+            });
+            panel.row(ui, FIELD_SETPOINT, |ui| temperature(ui, units.temp, &mut control.setpoint));
+            panel.row(ui, FIELD_KP, |ui| gain(ui, &mut control.kp, 1.0, 1));
+            panel.row(ui, FIELD_KI, |ui| gain(ui, &mut control.ki, 0.001, 3));
+            panel.row(ui, FIELD_KD, |ui| gain(ui, &mut control.kd, 1.0, 1));
+        });
+    }
+}
+
+// How often the model steps and the window redraws; not fields of the run
+// itself, so they aren't locked or undone
+fn timing(ui: &mut Ui, sim: &mut Simulation, frame_rate: &mut Option<u32>) {
+    collapsing("timing", false).show(ui, |ui| {
+        Grid::new("timing").num_columns(2).spacing([12.0, 4.0]).show(ui, |ui| {
+            ui.label(tr!("panel.tick"));
+            let current = TICK_RATES.iter().position(|&hz| hz == sim.tick_rate());
+            if let Some(i) = choice(ui, "tick", format!("{}", sim.tick_rate()), current, TICK_RATES.len(), |i| TICK_RATES[i].to_string()) {
+                let _ = sim.set_tick_rate(TICK_RATES[i]);
+            }
+            ui.end_row();
+            ui.label(tr!("panel.fps"));
+            let label = |fps: Option<u32>| fps.map_or_else(|| tr!("panel.display"), |n| n.to_string());
+            let current = FRAME_RATES.iter().position(|&fps| fps == *frame_rate);
+            if let Some(i) = choice(ui, "fps", label(*frame_rate), current, FRAME_RATES.len(), |i| label(FRAME_RATES[i])) {
+                *frame_rate = FRAME_RATES[i];
+            }
+            ui.end_row();
+        });
+    });
+}
+
+// Units and language the panel and the window show; the run itself is in °C and kg
+fn unit_choices(ui: &mut Ui, units: &mut Units) {
+    collapsing("units", false).show(ui, |ui| {
+        Grid::new("units").num_columns(2).spacing([12.0, 4.0]).show(ui, |ui| {
+            ui.label(tr!("panel.temperature"));
+            let current = TempUnit::ALL.iter().position(|&u| u == units.temp);
+            if let Some(i) = choice(ui, "temp unit", units.temp.label(), current, TempUnit::ALL.len(), |i| TempUnit::ALL[i].label().to_string()) {
+                units.temp = TempUnit::ALL[i];
+            }
+            ui.end_row();
+            ui.label(tr!("panel.mass"));
+            let current = MassUnit::ALL.iter().position(|&u| u == units.mass);
+            if let Some(i) = choice(ui, "mass unit", units.mass.label(), current, MassUnit::ALL.len(), |i| MassUnit::ALL[i].label().to_string()) {
+                units.mass = MassUnit::ALL[i];
+            }
+            ui.end_row();
+            ui.label(tr!("panel.language"));
+            // a language read from a file is shown but not in the list
+            let code = strings::code();
+            let current = BUILT_IN.iter().position(|(c, _)| *c == code);
+            let name = |i: usize| Strings::built_in(BUILT_IN[i].0).map_or_else(String::new, |s| s.name().to_string());
+            if let Some(i) = choice(ui, "language", strings::language(), current, BUILT_IN.len(), name) {
+                strings::set(Strings::built_in(BUILT_IN[i].0).expect("a built-in language"));
+            }
+            ui.end_row();
+        });
+    });
+}
+
+fn run_buttons(ui: &mut Ui, sim: &mut Simulation) {
+    ui.horizontal(|ui| {
+        let start = if sim.is_running() { tr!("panel.pause") } else { tr!("panel.start") };
+        if ui.button(start).clicked() {
+            // apply inits if paused
+            sim.toggle_running();
+        }
+        // nothing to reset before the first run
+        let can_reset = sim.is_running() || sim.time_seconds() > 0.0;
+        if ui.add_enabled(can_reset, egui::Button::new(tr!("panel.reset"))).clicked() {
+            sim.reset_from_init();
+        }
+        ui.label(tr!("panel.speed"));
+        let current = TIME_SCALES.iter().position(|&s| s == sim.time_scale());
+        if let Some(i) = choice(ui, "speed", format!("x{}", sim.time_scale()), current, TIME_SCALES.len(), |i| format!("x{}", TIME_SCALES[i])) {
+            while sim.time_scale() != TIME_SCALES[i] {
+                sim.cycle_time_scale();
+            }
+        }
+    });
+}
+
+/// A drop-down of `len` choices showing `shown`, with `current` ticked.
+/// Returns the one picked this frame.
+fn choice(ui: &mut Ui, id: impl Hash, shown: impl Into<egui::WidgetText>, current: Option<usize>, len: usize, label: impl Fn(usize) -> String) -> Option<usize> {
+    let mut picked = None;
+    ComboBox::from_id_salt(id).selected_text(shown).show_ui(ui, |ui| {
+        for i in 0..len {
+            if ui.selectable_label(current == Some(i), label(i)).clicked() && current != Some(i) {
+                picked = Some(i);
+            }
+        }
+    });
+    picked
+}
+
+fn clock_label(choice: usize) -> String {
+    match choice {
+        0 => tr!("panel.off"),
+        i => {
+            let minutes = (i - 1) * CLOCK_STEP as usize / 60;
+            format!("{:02}:{:02}", minutes / 60, minutes % 60)
+        }
+    }
+}
+
+// A collapsing header titled by the `section.<key>` string; it keeps its
+// open state by key when the language changes
+fn collapsing(key: &str, open: bool) -> egui::CollapsingHeader {
+    egui::CollapsingHeader::new(tr!(&format!("section.{key}"))).id_salt(key).default_open(open)
+}
+
+/// A value that can be dragged or typed in: clicking it, or tabbing to it,
+/// opens it for typing `-18` or `0,75`. The typed value is taken on Enter or
+/// when focus moves on; Esc leaves the old one.
+fn number(value: &mut f64) -> DragValue<'_> {
+    DragValue::new(value).update_while_editing(false).custom_parser(parse_number)
+}
+
+/// egui's own parser, but a decimal comma is read as a point, and `inf`,
+/// `NaN` and numbers too large for an f64 are not numbers to take.
+pub fn parse_number(text: &str) -> Option<f64> {
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            ',' => '.',
+            '\u{2212}' => '-',
+            c => c,
+        })
+        .collect();
+    text.parse().ok().filter(|v: &f64| v.is_finite())
+}
+
+/// A slider over `range` in steps of `step`, which the arrow keys nudge by
+/// when it has focus, with a box beside it to type a value into.
+fn slider(ui: &mut Ui, value: &mut f64, range: RangeInclusive<f64>, step: f64) -> bool {
+    keyed(ui, value, range.clone(), |ui, v| ui.add(Slider::new(v, range).step_by(step).clamping(SliderClamping::Edits).custom_parser(parse_number)))
+}
+
+fn percent_slider(ui: &mut Ui, percent: &mut f64) -> bool {
+    keyed(ui, percent, PERCENT, |ui, v| ui.add(Slider::new(v, PERCENT).fixed_decimals(0).custom_parser(parse_number)))
+}
+
+/// Adds a value's control with `add` and says whether it changed. With the
+/// on-screen keypad on, activating the control opens the keypad, and the
+/// number entered there is taken within `range`.
+fn keyed(ui: &mut Ui, value: &mut f64, range: RangeInclusive<f64>, add: impl FnOnce(&mut Ui, &mut f64) -> Response) -> bool {
+    let id = ui.next_auto_id();
+    let response = add(ui, value);
+    match keypad::entry(ui.ctx(), id, &response, *value) {
+        Some(entered) => {
+            *value = entered.clamp(*range.start(), *range.end());
+            true
+        }
+        None => response.changed(),
+    }
+}
+
+/// Runs `control` on `value` as shown through `to`; a changed value is read
+/// back through `from`, so one that is only looked at keeps every digit.
+fn converted(value: &mut f64, to: impl Fn(f64) -> f64, from: impl Fn(f64) -> f64, control: impl FnOnce(&mut f64) -> bool) -> bool {
+    let mut shown = to(*value);
+    let changed = control(&mut shown);
+    if changed {
+        *value = from(shown);
+    }
+    changed
+}
+
+// A slider over a kg range and step, shown in `unit`
+pub fn mass_slider(ui: &mut Ui, unit: MassUnit, kg: &mut f64, range: RangeInclusive<f64>, step: f64) -> bool {
+    let range = unit.of_kg(*range.start())..=unit.of_kg(*range.end());
+    let step = step_in(step, |kg| unit.of_kg(kg));
+    converted(kg, |kg| unit.of_kg(kg), |v| unit.to_kg(v), |v| slider(ui, v, range, step))
+}
+
+pub fn temperature_slider(ui: &mut Ui, unit: TempUnit, celsius: &mut f64) -> bool {
+    let range = unit.of_celsius(*TEMP_RANGE.start())..=unit.of_celsius(*TEMP_RANGE.end());
+    let step = step_in(TEMP_STEP, |c| unit.of_celsius(c));
+    converted(celsius, |c| unit.of_celsius(c), |v| unit.to_celsius(v), |v| slider(ui, v, range, step))
+}
+
+// A temperature typed or dragged in `unit`, held at absolute zero or above
+fn temperature(ui: &mut Ui, unit: TempUnit, celsius: &mut f64) -> bool {
+    let step = step_in(TEMP_STEP, |c| unit.of_celsius(c));
+    let range = unit.of_celsius(-KELVIN)..=f64::INFINITY;
+    converted(
+        celsius,
+        |c| unit.of_celsius(c),
+        // the unit's rounding can't take it below either
+        |v| unit.to_celsius(v).max(-KELVIN),
+        |v| keyed(ui, v, range.clone(), |ui, v| ui.add(number(v).speed(step).range(range.clone()).fixed_decimals(1))),
+    )
+}
+
+fn gain(ui: &mut Ui, value: &mut f64, speed: f64, decimals: usize) -> bool {
+    keyed(ui, value, 0.0..=f64::INFINITY, |ui, v| ui.add(number(v).speed(speed).range(0.0..=f64::INFINITY).fixed_decimals(decimals)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_finite_numbers_are_taken() {
+        assert_eq!(parse_number(" 0,75 "), Some(0.75));
+        assert_eq!(parse_number("\u{2212}18"), Some(-18.0));
+        for text in ["inf", "-inf", "infinity", "NaN", "1e999", ""] {
+            assert_eq!(parse_number(text), None, "{text}");
+        }
+    }
+}
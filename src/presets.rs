@@ -0,0 +1,309 @@
+// Bottle presets saved by the user (shape, wall, colour and cap), kept in a
+// plain text file in the working directory and managed from a small panel (B).
+
+use icebottle_sim::{GapGas, Geometry, InsulationPreset, Simulation, WallMaterial};
+use macroquad::prelude::*;
+
+use crate::fields::{FieldLocks, FIELD_CAP, FIELD_GAP_GAS, FIELD_GEOMETRY, FIELD_INSULATION, FIELD_MATERIAL};
+use crate::notes;
+use crate::strings::{self, tr};
+use crate::theme::Palette;
+use crate::widget::Button;
+
+pub const PRESETS_FILE: &str = "bottle-presets.txt";
+
+// Body tints the colour button steps through; the first is the plain bottle
+pub const TINTS: [[u8; 3]; 5] = [[20, 30, 50], [30, 90, 40], [30, 50, 120], [120, 80, 20], [120, 40, 80]];
+
+const PANEL_W: f32 = 360.0;
+const ROW_H: f32 = 22.0;
+const MAX_ROWS: usize = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BottlePreset {
+    pub name: String,
+    pub geometry: Geometry,
+    pub material: WallMaterial,
+    pub insulation: InsulationPreset,
+    pub gap_gas: GapGas,
+    pub tint: [u8; 3],
+    pub capped: bool,
+}
+
+impl BottlePreset {
+    /// The bottle as currently set up.
+    pub fn capture(name: String, sim: &Simulation, insulation: InsulationPreset, tint: [u8; 3]) -> Self {
+        Self {
+            name,
+            geometry: sim.geometry(),
+            material: sim.wall().material,
+            insulation,
+            gap_gas: sim.wall().gap_gas().unwrap_or_default(),
+            tint,
+            capped: sim.is_capped(),
+        }
+    }
+
+    /// Sets the bottle up like this preset, leaving locked fields alone.
+    pub fn apply(&self, sim: &mut Simulation, insulation: &mut InsulationPreset, tint: &mut [u8; 3], locks: &FieldLocks) {
+        if !locks.is_locked(FIELD_GEOMETRY) {
+            sim.set_geometry(self.geometry);
+        }
+        if !locks.is_locked(FIELD_MATERIAL) {
+            sim.wall_mut().set_material(self.material);
+        }
+        if !locks.is_locked(FIELD_INSULATION) {
+            let gas = sim.wall().gap_gas();
+            *insulation = self.insulation;
+            sim.wall_mut().layers = insulation.layers();
+            // a locked gap gas stays as it was
+            if let Some(gas) = gas.filter(|_| locks.is_locked(FIELD_GAP_GAS)) {
+                sim.wall_mut().set_gap_gas(gas);
+            }
+        }
+        if !locks.is_locked(FIELD_GAP_GAS) {
+            sim.wall_mut().set_gap_gas(self.gap_gas);
+        }
+        if !locks.is_locked(FIELD_CAP) {
+            sim.set_capped(self.capped);
+        }
+        *tint = self.tint;
+    }
+}
+
+fn format_geometry(geometry: Geometry) -> String {
+    match geometry {
+        Geometry::Cylinder { radius, height } => format!("cylinder {radius} {height}"),
+        Geometry::Sphere { radius } => format!("sphere {radius}"),
+        Geometry::Custom { area, volume } => format!("custom {area} {volume}"),
+        Geometry::Outline { radii, height } => {
+            let radii: Vec<String> = radii.iter().map(f64::to_string).collect();
+            format!("outline {height} {}", radii.join(" "))
+        }
+    }
+}
+
+fn parse_geometry(text: &str) -> Option<Geometry> {
+    let mut words = text.split_whitespace();
+    let kind = words.next()?;
+    let numbers: Vec<f64> = words.map(str::parse).collect::<Result<_, _>>().ok()?;
+    match (kind, numbers.as_slice()) {
+        ("cylinder", &[radius, height]) => Some(Geometry::Cylinder { radius, height }),
+        ("sphere", &[radius]) => Some(Geometry::Sphere { radius }),
+        ("custom", &[area, volume]) => Some(Geometry::Custom { area, volume }),
+        ("outline", [height, radii @ ..]) => Some(Geometry::Outline { radii: radii.try_into().ok()?, height: *height }),
+        _ => None,
+    }
+}
+
+/// Presets in the file format: a `[name]` line followed by `key = value` lines.
+pub fn format_presets(presets: &[BottlePreset]) -> String {
+    let mut out = String::from("# IceBottle bottle presets\n");
+    for p in presets {
+        out += &format!(
+            "\n[{}]\ngeometry = {}\nmaterial = {}\ninsulation = {}\ngap = {}\ntint = {} {} {}\ncap = {}\n",
+            p.name,
+            format_geometry(p.geometry),
+            p.material.label(),
+            p.insulation.label(),
+            p.gap_gas.label(),
+            p.tint[0],
+            p.tint[1],
+            p.tint[2],
+            if p.capped { "on" } else { "off" },
+        );
+    }
+    out
+}
+
+/// Reads presets written by `format_presets`; lines it can't make sense of are
+/// reported and skipped, missing keys keep the defaults.
+pub fn parse_presets(text: &str) -> Vec<BottlePreset> {
+    let mut presets: Vec<BottlePreset> = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            presets.push(BottlePreset {
+                name: name.to_string(),
+                geometry: Geometry::default(),
+                material: WallMaterial::default(),
+                insulation: InsulationPreset::default(),
+                gap_gas: GapGas::default(),
+                tint: TINTS[0],
+                capped: true,
+            });
+            continue;
+        }
+        let parsed = line.split_once('=').map(|(k, v)| (k.trim(), v.trim())).and_then(|(key, value)| {
+            let preset = presets.last_mut()?;
+            match key {
+                "geometry" => preset.geometry = parse_geometry(value)?,
+                "material" => preset.material = *WallMaterial::ALL.iter().find(|m| m.label() == value)?,
+                "insulation" => preset.insulation = *InsulationPreset::ALL.iter().find(|p| p.label() == value)?,
+                "gap" => preset.gap_gas = *GapGas::ALL.iter().find(|g| g.label() == value)?,
+                "tint" => {
+                    let rgb: Vec<u8> = value.split_whitespace().map(str::parse).collect::<Result<_, _>>().ok()?;
+                    preset.tint = rgb.try_into().ok()?;
+                }
+                "cap" => preset.capped = value == "on",
+                _ => return None,
+            }
+            Some(())
+        });
+        if parsed.is_none() {
+            eprintln!("{PRESETS_FILE}:{}: skipped '{line}'", n + 1);
+        }
+    }
+    presets
+}
+
+/// The presets panel: a list to pick from and buttons to manage it. Every
+/// change is written straight back to the file.
+pub struct PresetManager {
+    presets: Vec<BottlePreset>,
+    selected: usize,
+    renaming: bool,
+    pub open: bool,
+}
+
+impl PresetManager {
+    /// Loads the presets file; a missing one just means no presets yet.
+    pub fn load() -> Self {
+        let presets = std::fs::read_to_string(PRESETS_FILE).map(|text| parse_presets(&text)).unwrap_or_default();
+        Self {
+            presets,
+            selected: 0,
+            renaming: false,
+            open: false,
+        }
+    }
+
+    fn save(&self) {
+        if let Err(e) = std::fs::write(PRESETS_FILE, format_presets(&self.presets)) {
+            eprintln!("Could not write {PRESETS_FILE}: {e}");
+        }
+    }
+
+    /// True while a preset name is being typed, so other keys stay out of it.
+    pub fn is_renaming(&self) -> bool {
+        self.open && self.renaming
+    }
+
+    fn unused_name(&self, base: &str) -> String {
+        let taken = |name: &str| self.presets.iter().any(|p| p.name == name);
+        if !taken(base) {
+            return base.to_string();
+        }
+        (2..).map(|i| format!("{base} {i}")).find(|name| !taken(name)).unwrap_or_default()
+    }
+
+    /// Draws the panel with its top-left corner `at` and handles its clicks
+    /// and the name being typed.
+    pub fn update(&mut self, at: Vec2, palette: &Palette, sim: &mut Simulation, insulation: &mut InsulationPreset, tint: &mut [u8; 3], locks: &FieldLocks) {
+        if !self.open {
+            return;
+        }
+        let (x, y) = (at.x, at.y);
+        let list_h = MAX_ROWS as f32 * ROW_H;
+        let panel_h = 36.0 + list_h + 88.0;
+        draw_rectangle(x, y, PANEL_W, panel_h, palette.overlay);
+        draw_rectangle_lines(x, y, PANEL_W, panel_h, 2.0, palette.border);
+        draw_text(tr!("presets.title", PRESETS_FILE), x + 10.0, y + 24.0, 18.0, palette.text);
+
+        if self.renaming {
+            match self.presets.get_mut(self.selected) {
+                Some(preset) => {
+                    if !notes::type_text(&mut preset.name) {
+                        self.renaming = false;
+                        self.save();
+                    }
+                }
+                None => self.renaming = false,
+            }
+        }
+
+        if self.presets.is_empty() {
+            draw_text(tr!("presets.empty"), x + 10.0, y + 52.0, 16.0, palette.faint_text);
+        }
+        // the mouse wheel moves the selection; the list scrolls to keep it in view
+        let wheel = mouse_wheel().1;
+        if wheel != 0.0 && !self.renaming && !self.presets.is_empty() {
+            self.selected = if wheel > 0.0 { self.selected.saturating_sub(1) } else { (self.selected + 1).min(self.presets.len() - 1) };
+        }
+        let first = self.selected.saturating_sub(MAX_ROWS - 1);
+        for (i, preset) in self.presets.iter().enumerate().skip(first).take(MAX_ROWS) {
+            let row = Button::new(x + 8.0, y + 36.0 + (i - first) as f32 * ROW_H, PANEL_W - 16.0, ROW_H - 2.0);
+            if row.clicked() && !self.renaming {
+                self.selected = i;
+            }
+            if i == self.selected {
+                draw_rectangle(row.rect.x, row.rect.y, row.rect.w, row.rect.h, palette.row);
+                row.draw_focus(palette.highlight);
+            } else if row.hovered() {
+                draw_rectangle(row.rect.x, row.rect.y, row.rect.w, row.rect.h, Color { a: palette.row.a * 0.55, ..palette.row });
+            }
+            let [r, g, b] = preset.tint;
+            draw_rectangle(row.rect.x + 4.0, row.rect.y + 4.0, 12.0, 12.0, Color::from_rgba(r, g, b, 255));
+            let caret = if self.renaming && i == self.selected { "_" } else { "" };
+            let summary = format!(
+                "{}{caret}  ({}, {})",
+                preset.name,
+                strings::name("material", preset.material.label()),
+                strings::name("insulation", preset.insulation.label())
+            );
+            draw_text(summary, row.rect.x + 22.0, row.rect.y + 15.0, 16.0, palette.text);
+        }
+
+        // two rows of buttons under the list
+        let by = y + 36.0 + list_h + 8.0;
+        let bw = (PANEL_W - 16.0 - 2.0 * 8.0) / 3.0;
+        let button_at = |col: f32, row: f32| Button::new(x + 8.0 + col * (bw + 8.0), by + row * 40.0, bw, 32.0);
+        let has_selection = self.selected < self.presets.len() && !self.renaming;
+        let save = button_at(0.0, 0.0).enabled(!self.renaming);
+        let apply = button_at(1.0, 0.0).enabled(has_selection);
+        let tint_button = button_at(2.0, 0.0).enabled(!self.renaming);
+        let rename = button_at(0.0, 1.0).enabled(has_selection);
+        let duplicate = button_at(1.0, 1.0).enabled(has_selection);
+        let delete = button_at(2.0, 1.0).enabled(has_selection);
+        save.draw(&tr!("presets.save"), palette.button_save, 16.0, palette);
+        apply.draw(&tr!("presets.apply"), palette.button, 16.0, palette);
+        tint_button.draw(&tr!("presets.colour"), palette.button_plain, 16.0, palette);
+        rename.draw(&tr!("presets.rename"), palette.button, 16.0, palette);
+        duplicate.draw(&tr!("presets.duplicate"), palette.button, 16.0, palette);
+        delete.draw(&tr!("presets.delete"), palette.button_delete, 16.0, palette);
+
+        if save.clicked() {
+            let name = self.unused_name(&tr!("presets.new"));
+            self.presets.push(BottlePreset::capture(name, sim, *insulation, *tint));
+            self.selected = self.presets.len() - 1;
+            self.save();
+        }
+        if apply.clicked() {
+            self.presets[self.selected].apply(sim, insulation, tint, locks);
+        }
+        if tint_button.clicked() {
+            let i = TINTS.iter().position(|t| t == tint).unwrap_or(0);
+            *tint = TINTS[(i + 1) % TINTS.len()];
+        }
+        if rename.clicked() {
+            // drop keys typed before the click
+            while get_char_pressed().is_some() {}
+            self.renaming = true;
+        }
+        if duplicate.clicked() {
+            let mut copy = self.presets[self.selected].clone();
+            copy.name = self.unused_name(&tr!("presets.copy", copy.name));
+            self.presets.insert(self.selected + 1, copy);
+            self.selected += 1;
+            self.save();
+        }
+        if delete.clicked() {
+            self.presets.remove(self.selected);
+            self.selected = self.selected.min(self.presets.len().saturating_sub(1));
+            self.save();
+        }
+    }
+}
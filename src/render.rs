@@ -0,0 +1,181 @@
+// Sizes the window is laid out with and colours that override the theme's,
+// kept in a plain text file in the working directory so the drawing can be
+// tuned for a small screen, a projector or an embedded kiosk without
+// recompiling. The debug panel (F3) adjusts them live and saves them back.
+
+use egui_macroquad::egui::{self, Grid, Slider};
+use macroquad::prelude::*;
+use std::ops::RangeInclusive;
+
+use crate::strings::tr;
+use crate::theme::{Palette, COLOR_NAMES};
+
+pub const RENDER_FILE: &str = "render-config.txt";
+
+/// Layout sizes in logical pixels, and the palette colours changed from the theme's.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderConfig {
+    /// Gap between the window's edge and the status card.
+    pub margin: f32,
+    pub card_w: f32,
+    pub card_h: f32,
+    pub bottle_w: f32,
+    pub bottle_h: f32,
+    /// The water temperature chart under the bottle.
+    pub chart_h: f32,
+    /// The outside temperature chart under the status card.
+    pub profile_h: f32,
+    pub gauge_r: f32,
+    /// Named as in `COLOR_NAMES`, applied over whichever theme is shown.
+    pub colors: Vec<(&'static str, Color)>,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self {
+            margin: 12.0,
+            card_w: 300.0,
+            card_h: 498.0,
+            bottle_w: 220.0,
+            bottle_h: 420.0,
+            chart_h: 44.0,
+            profile_h: 60.0,
+            gauge_r: 28.0,
+            colors: Vec::new(),
+        }
+    }
+}
+
+impl RenderConfig {
+    /// Loads the render file; a missing one means the built-in layout.
+    pub fn load() -> Self {
+        std::fs::read_to_string(RENDER_FILE).map(|text| Self::parse(&text)).unwrap_or_default()
+    }
+
+    /// Reads `key = value` lines: a size in pixels, or `color.<name> = r g b [a]`
+    /// with 0-255 components. Lines it can't make sense of are reported and skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut config = Self::default();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = line.split_once('=').map(|(k, v)| (k.trim(), v.trim())).and_then(|(key, value)| match key.strip_prefix("color.") {
+                Some(name) => {
+                    let name = COLOR_NAMES.into_iter().find(|&c| c == name)?;
+                    config.set_color(name, parse_color(value)?);
+                    Some(())
+                }
+                None => {
+                    let (_, size, range) = config.sizes().into_iter().find(|(k, _, _)| *k == key)?;
+                    *size = value.parse::<f32>().ok().filter(|v| range.contains(v))?;
+                    Some(())
+                }
+            });
+            if parsed.is_none() {
+                eprintln!("{RENDER_FILE}:{}: skipped '{line}'", n + 1);
+            }
+        }
+        config
+    }
+
+    /// The file `parse` reads back.
+    pub fn format(&self) -> String {
+        let mut out = String::from("# IceBottle render settings: sizes in pixels, colours as r g b a (0-255)\n");
+        for (key, size, _) in self.clone().sizes() {
+            out += &format!("{key} = {size}\n");
+        }
+        for (name, c) in &self.colors {
+            let [r, g, b, a]: [u8; 4] = (*c).into();
+            out += &format!("color.{name} = {r} {g} {b} {a}\n");
+        }
+        out
+    }
+
+    fn save(&self) {
+        match std::fs::write(RENDER_FILE, self.format()) {
+            Ok(()) => println!("Saved the render settings to {RENDER_FILE}"),
+            Err(e) => eprintln!("Could not write {RENDER_FILE}: {e}"),
+        }
+    }
+
+    /// Each size with its key in the file and the range the debug panel offers.
+    fn sizes(&mut self) -> [(&'static str, &mut f32, RangeInclusive<f32>); 8] {
+        [
+            ("margin", &mut self.margin, 0.0..=60.0),
+            ("card_w", &mut self.card_w, 200.0..=600.0),
+            ("card_h", &mut self.card_h, 300.0..=900.0),
+            ("bottle_w", &mut self.bottle_w, 80.0..=600.0),
+            ("bottle_h", &mut self.bottle_h, 150.0..=1000.0),
+            ("chart_h", &mut self.chart_h, 20.0..=200.0),
+            ("profile_h", &mut self.profile_h, 20.0..=200.0),
+            ("gauge_r", &mut self.gauge_r, 10.0..=80.0),
+        ]
+    }
+
+    fn set_color(&mut self, name: &'static str, color: Color) {
+        match self.colors.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, c)) => *c = color,
+            None => self.colors.push((name, color)),
+        }
+    }
+
+    /// `palette` with the colours changed here.
+    pub fn recolor(&self, mut palette: Palette) -> Palette {
+        for &(name, color) in &self.colors {
+            if let Some(c) = palette.color_mut(name) {
+                *c = color;
+            }
+        }
+        palette
+    }
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    let parts: Vec<u8> = value.split_whitespace().map(str::parse).collect::<Result<_, _>>().ok()?;
+    match parts[..] {
+        [r, g, b] => Some(Color::from_rgba(r, g, b, 255)),
+        [r, g, b, a] => Some(Color::from_rgba(r, g, b, a)),
+        _ => None,
+    }
+}
+
+/// The debug panel: a slider per size and a colour button per palette entry,
+/// starting from `theme`'s colours, with buttons to save the file or go back
+/// to the built-in layout.
+pub fn debug_panel(ctx: &egui::Context, config: &mut RenderConfig, theme: &Palette, open: &mut bool) {
+    egui::Window::new(tr!("render.title")).id(egui::Id::new("render")).open(open).default_width(280.0).show(ctx, |ui| {
+        egui::ScrollArea::vertical().max_height(ctx.screen_rect().height() - 160.0).show(ui, |ui| {
+            Grid::new("render sizes").num_columns(2).show(ui, |ui| {
+                for (key, size, range) in config.sizes() {
+                    ui.label(key);
+                    ui.add(Slider::new(size, range).step_by(1.0));
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            let mut shown = config.recolor(theme.clone());
+            Grid::new("render colours").num_columns(2).show(ui, |ui| {
+                for name in COLOR_NAMES {
+                    let Some(c) = shown.color_mut(name) else { continue };
+                    let mut rgba = [c.r, c.g, c.b, c.a];
+                    ui.label(name);
+                    if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
+                        config.set_color(name, Color::new(rgba[0], rgba[1], rgba[2], rgba[3]));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button(tr!("render.save", RENDER_FILE)).clicked() {
+                config.save();
+            }
+            if ui.button(tr!("render.defaults")).clicked() {
+                *config = RenderConfig::default();
+            }
+        });
+    });
+}
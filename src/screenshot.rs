@@ -0,0 +1,69 @@
+// Screenshots of the window (F12), stamped in a corner with the simulated time
+// and the state of the bottle, for lab reports: a screenshot taken by the
+// operating system crops the window and misses the time the run is at.
+
+use icebottle_sim::Simulation;
+use macroquad::prelude::*;
+use std::path::{Path, PathBuf};
+
+use crate::strings::{self, tr};
+use crate::theme::Palette;
+use crate::units::Units;
+
+/// A file in the working directory named after the simulated time, not one
+/// already there.
+pub fn next_path(sim: &Simulation) -> PathBuf {
+    let name = format!("screenshot-{:.0}s", sim.time_seconds());
+    let mut path = PathBuf::from(format!("{name}.png"));
+    let mut n = 2;
+    while path.exists() {
+        path = PathBuf::from(format!("{name}-{n}.png"));
+        n += 1;
+    }
+    path
+}
+
+/// Saves the frame drawn so far to `path`.
+pub fn save(path: &Path) {
+    // export_png panics on a write error, so find out first
+    match std::fs::File::create(path) {
+        Ok(_) => {
+            get_screen_data().export_png(&path.to_string_lossy());
+            println!("Saved a screenshot to {}", path.display());
+        }
+        Err(e) => eprintln!("Could not write {}: {e}", path.display()),
+    }
+}
+
+/// Draws the stamp into the bottom-right corner: the run's name, the time it
+/// is at, and the contents and temperatures.
+pub fn stamp(sim: &Simulation, units: Units, palette: &Palette) {
+    let t = sim.time_seconds();
+    let state = sim.state();
+    let mut lines = Vec::new();
+    if !sim.experiment().name.is_empty() {
+        lines.push(sim.experiment().name.clone());
+    }
+    lines.push(tr!("stamp.time", clock(t), t));
+    lines.push(tr!("stamp.contents", units.mass(state.mass_water, 3), units.mass(state.mass_ice, 3)));
+    lines.push(tr!("stamp.temps", units.temp(state.temp_water, 2), units.temp(sim.outside_temp(), 1)));
+    lines.push(tr!(
+        "stamp.bottle",
+        strings::name("material", sim.wall().material.label()),
+        if sim.is_capped() { tr!("stamp.capped") } else { tr!("stamp.open") }
+    ));
+    let width = lines.iter().map(|line| measure_text(line, None, 16, 1.0).width).fold(0.0, f32::max) + 20.0;
+    let height = lines.len() as f32 * 20.0 + 12.0;
+    let (x, y) = (screen_width() - width - 12.0, screen_height() - height - 12.0);
+    draw_rectangle(x, y, width, height, palette.overlay);
+    draw_rectangle_lines(x, y, width, height, 2.0, palette.border);
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, x + 10.0, y + 24.0 + i as f32 * 20.0, 16.0, palette.text);
+    }
+}
+
+// h:mm:ss
+fn clock(seconds: f64) -> String {
+    let s = seconds.max(0.0) as u64;
+    format!("{}:{:02}:{:02}", s / 3600, s / 60 % 60, s % 60)
+}
@@ -0,0 +1,50 @@
+// Shaking the bottle with the mouse: a drag over it with the right button
+// held, back and forth, shakes it as K does once it has turned a few times in
+// quick succession. The left button is the tray's and the jug's.
+
+use icebottle_sim::Simulation;
+use macroquad::prelude::*;
+
+// Turns of the drag within TURN_WINDOW (s) that make a shake, and the px a
+// drag moves before its direction counts
+const TURNS: usize = 3;
+const TURN_WINDOW: f64 = 0.8;
+const MIN_MOVE: f32 = 6.0;
+
+#[derive(Default)]
+pub struct Shaker {
+    from: Option<f32>, // x the drag last moved from, while one is under way
+    direction: f32,
+    turns: Vec<f64>, // s of get_time() at each turn
+}
+
+impl Shaker {
+    /// Follows a right-button drag that starts on `bottle`; with `enabled`
+    /// false a drag under way is dropped.
+    pub fn update(&mut self, sim: &mut Simulation, bottle: Rect, enabled: bool) {
+        let mouse = Vec2::from(mouse_position());
+        if !enabled || !is_mouse_button_down(MouseButton::Right) {
+            *self = Self::default();
+            return;
+        }
+        if is_mouse_button_pressed(MouseButton::Right) && bottle.contains(mouse) {
+            self.from = Some(mouse.x);
+        }
+        let Some(from) = self.from else { return };
+        let moved = mouse.x - from;
+        if moved.abs() < MIN_MOVE {
+            return;
+        }
+        let now = get_time();
+        if self.direction != 0.0 && moved.signum() != self.direction {
+            self.turns.push(now);
+        }
+        self.direction = moved.signum();
+        self.from = Some(mouse.x);
+        self.turns.retain(|&t| now - t <= TURN_WINDOW);
+        if self.turns.len() >= TURNS {
+            sim.shake();
+            self.turns.clear();
+        }
+    }
+}
@@ -0,0 +1,214 @@
+// Side by side (--side-by-side): two to four of a scenario's `[[variants]]` in
+// the window at once, each bottle in a column of its own with its contents,
+// temperatures and water temperature chart. They step on one clock, so every
+// column is at the same simulated time, and the charts share a scale so the
+// curves can be read against each other. There is no controls panel: an edit
+// would change one bottle of the comparison, and the variants are where the
+// differences are set. --compare runs the same variants to the end for numbers.
+
+use icebottle_sim::{Scenario, Simulation};
+use macroquad::prelude::*;
+
+use crate::bottle::BottleView;
+use crate::cli::Args;
+use crate::keymap::{Action, Keymap};
+use crate::presets::TINTS;
+use crate::render::RenderConfig;
+use crate::screenshot;
+use crate::sparkline::{draw_chart, Chart, Sparkline};
+use crate::strings::{self, tr};
+use crate::MAX_FRAME_TIME;
+
+pub const MAX_BOTTLES: usize = 4;
+
+// Height of a column's name and status lines, above its bottle
+const HEADER_H: f32 = 210.0;
+
+struct Column {
+    name: String,
+    sim: Simulation,
+    view: BottleView,
+    spark: Sparkline,
+    /// s at which ice first formed, or the last of it melted.
+    first_ice: Option<f64>,
+    ice_gone: Option<f64>,
+}
+
+impl Column {
+    // Notes the first ice and the last melting, after a step
+    fn watch(&mut self, ice_before: f64) {
+        let (t, ice) = (self.sim.time_seconds(), self.sim.state().mass_ice);
+        if self.first_ice.is_none() && ice_before == 0.0 && ice > 0.0 {
+            self.first_ice = Some(t);
+        }
+        if self.ice_gone.is_none() && ice_before > 0.0 && ice == 0.0 {
+            self.ice_gone = Some(t);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.sim.reset_from_init();
+        self.view.reset(&self.sim);
+        self.spark.clear();
+        self.first_ice = None;
+        self.ice_gone = None;
+    }
+}
+
+/// The bottles of a side-by-side run.
+pub struct Lineup {
+    title: String,
+    columns: Vec<Column>,
+}
+
+impl Lineup {
+    /// Sets up a bottle for each of `scenario`'s variants, with the setup
+    /// flags in `args` over them, all on the first one's tick; an error says
+    /// why there aren't two to four.
+    pub fn new(scenario: &Scenario, args: &Args) -> Result<Self, String> {
+        let count = scenario.variants.len();
+        if !(2..=MAX_BOTTLES).contains(&count) {
+            return Err(format!("The scenario has {count} [[variants]]; side by side shows 2 to {MAX_BOTTLES}"));
+        }
+        let mut columns: Vec<Column> = (0..count)
+            .map(|i| {
+                let mut sim = Simulation::new();
+                scenario.variant(i).apply(&mut sim);
+                args.apply(&mut sim);
+                let view = BottleView::new(&sim);
+                Column { name: scenario.variants[i].name.clone(), sim, view, spark: Sparkline::new(), first_ice: None, ice_gone: None }
+            })
+            .collect();
+        let tick = columns[0].sim.tick_rate();
+        for column in &mut columns {
+            let _ = column.sim.set_tick_rate(tick);
+        }
+        Ok(Self { title: scenario.name.clone(), columns })
+    }
+
+    pub async fn run(mut self, args: Args) {
+        let keymap = Keymap::load();
+        let render = RenderConfig::load();
+        let units = args.units();
+        let mut theme = args.theme.unwrap_or_default();
+        let mut fullscreen = args.fullscreen;
+        let mut accumulator: f64 = 0.0;
+        loop {
+            let palette = &render.recolor(theme.palette().clone());
+            clear_background(palette.background);
+
+            // one clock: the first bottle's speed and tick step them all
+            let (scale, dt) = (self.columns[0].sim.time_scale(), self.columns[0].sim.step_size());
+            accumulator += (get_frame_time().min(MAX_FRAME_TIME) as f64) * scale;
+            while accumulator >= dt {
+                for column in &mut self.columns {
+                    let ice_before = column.sim.state().mass_ice;
+                    column.sim.step(dt);
+                    column.watch(ice_before);
+                }
+                accumulator -= dt;
+            }
+            for column in &mut self.columns {
+                column.spark.record(column.sim.time_seconds(), column.sim.state().temp_water as f32);
+            }
+
+            let (screen_w, screen_h) = (screen_width(), screen_height());
+            let clock = &self.columns[0].sim;
+            if !self.title.is_empty() {
+                let width = measure_text(&self.title, None, 22, 1.0).width;
+                draw_text(&self.title, screen_w / 2.0 - width / 2.0, 28.0, 22.0, palette.text);
+            }
+            let time = format!("{} ×{}", tr!("status.time", format!("{:.0}", clock.time_seconds())), clock.time_scale());
+            let width = measure_text(&time, None, 18, 1.0).width;
+            draw_text(&time, screen_w / 2.0 - width / 2.0, 52.0, 18.0, palette.soft_text);
+
+            // the earliest of each event is marked in every column that shares it
+            let earliest = |time: fn(&Column) -> Option<f64>| self.columns.iter().filter_map(time).min_by(f64::total_cmp);
+            let first_ice = earliest(|c| c.first_ice);
+            let ice_gone = earliest(|c| c.ice_gone);
+            // every chart on the scale of all of them
+            let shown: Vec<Vec<f32>> = self.columns.iter().map(|c| c.spark.values().iter().map(|&t| units.temp.of_celsius(t as f64) as f32).collect()).collect();
+            let all: Vec<f32> = shown.iter().flatten().copied().collect();
+
+            let col_w = screen_w / self.columns.len() as f32;
+            let chart_y = screen_h - render.chart_h - 48.0;
+            let room = (chart_y - 24.0 - (render.margin + HEADER_H)).max(40.0);
+            // bottles shrink to fit a narrow column or a short window, all alike
+            let fit = ((col_w - 80.0) / render.bottle_w).min(room / render.bottle_h).min(1.0);
+            let (bottle_w, bottle_h) = (render.bottle_w * fit, render.bottle_h * fit);
+            for (i, column) in self.columns.iter_mut().enumerate() {
+                let x = i as f32 * col_w;
+                let center = x + col_w / 2.0;
+                if i > 0 {
+                    draw_line(x, render.margin + 60.0, x, screen_h - 40.0, 1.0, palette.border);
+                }
+                let sim = &column.sim;
+                let width = measure_text(&column.name, None, 20, 1.0).width;
+                let top = render.margin + 80.0;
+                draw_text(&column.name, center - width / 2.0, top, 20.0, palette.text);
+                let left = x + 16.0;
+                let mut lines = vec![
+                    tr!("status.water", units.mass(sim.state().mass_water, 3)),
+                    tr!("status.ice", units.mass(sim.state().mass_ice, 3)),
+                    tr!("status.t_water", units.temp(sim.state().temp_water, 1)),
+                    tr!("status.t_ice", units.temp(sim.state().temp_ice, 1)),
+                ];
+                // what a heater or cooler costs, against the columns with more ice instead
+                if sim.heater_power() != 0.0 {
+                    lines.push(tr!("status.heater", sim.heater_output(), sim.electricity().kwh(), sim.electricity().cost()));
+                }
+                for (n, line) in lines.iter().enumerate() {
+                    draw_text(line, left, top + 26.0 + n as f32 * 20.0, 16.0, palette.text);
+                }
+                let hours = |t: f64| format!("{:.2} h", t / 3600.0);
+                let mut event_y = top + 26.0 + lines.len() as f32 * 20.0;
+                for (at, first, key) in [(column.first_ice, first_ice, "side.first_ice"), (column.ice_gone, ice_gone, "side.ice_gone")] {
+                    if let Some(t) = at {
+                        let (text, color) = if Some(t) == first { (tr!("side.first", tr!(key, hours(t))), palette.good) } else { (tr!(key, hours(t)), palette.soft_text) };
+                        draw_text(text, left, event_y, 16.0, color);
+                        event_y += 20.0;
+                    }
+                }
+
+                let bottle_y = render.margin + HEADER_H + (room - bottle_h) / 2.0;
+                column.view.draw(&column.sim, Rect::new(center - bottle_w / 2.0, bottle_y, bottle_w, bottle_h), TINTS[i % TINTS.len()], palette);
+
+                draw_text(tr!("chart.water"), left, chart_y - 4.0, 14.0, palette.faint_text);
+                let chart = Chart::fit(&shown[i], &all, left, chart_y, col_w - 64.0, render.chart_h);
+                draw_chart(&chart, &shown[i], palette.water_line, palette, |_| {});
+            }
+            draw_text(tr!("side.keys"), 12.0, screen_h - 16.0, 16.0, palette.soft_text);
+
+            if keymap.pressed(Action::StartPause) {
+                // all start or all stop, whichever ones had finished
+                let start = !self.columns.iter().any(|c| c.sim.is_running());
+                for column in &mut self.columns {
+                    if column.sim.is_running() != start {
+                        column.sim.toggle_running();
+                    }
+                }
+            }
+            if keymap.pressed(Action::Reset) {
+                self.columns.iter_mut().for_each(Column::reset);
+                accumulator = 0.0;
+            }
+            if keymap.pressed(Action::Speed) {
+                self.columns.iter_mut().for_each(|c| c.sim.cycle_time_scale());
+            }
+            if keymap.pressed(Action::Theme) {
+                theme = theme.next();
+            }
+            if keymap.pressed(Action::Language) {
+                strings::next();
+            }
+            if keymap.pressed(Action::Fullscreen) {
+                fullscreen = !fullscreen;
+                set_fullscreen(fullscreen);
+            }
+            if keymap.pressed(Action::Screenshot) {
+                screenshot::save(&screenshot::next_path(&self.columns[0].sim));
+            }
+            next_frame().await;
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// A bottle turned from an outline, drawn in its own shape: the body and the
+// contents are filled between the outline's samples, so the water narrows
+// into the neck as it rises.
+
+use icebottle_sim::{Geometry, OUTLINE_SAMPLES};
+use macroquad::prelude::*;
+
+pub struct Silhouette {
+    radii: [f64; OUTLINE_SAMPLES],
+    center_x: f32,
+    base_y: f32,
+    height_px: f32,
+    // px per m of radius
+    scale: f32,
+}
+
+impl Silhouette {
+    /// The drawing of `geometry` standing on `base_y`, `height_px` tall and
+    /// at most `width_px` wide; `None` for shapes drawn as a plain box.
+    pub fn new(geometry: &Geometry, center_x: f32, base_y: f32, height_px: f32, width_px: f32) -> Option<Self> {
+        let Geometry::Outline { radii, height } = *geometry else {
+            return None;
+        };
+        let widest = radii.iter().copied().fold(0.0, f64::max) as f32;
+        Some(Self {
+            radii,
+            center_x,
+            base_y,
+            height_px,
+            scale: (height_px / height as f32).min(width_px / (2.0 * widest)),
+        })
+    }
+
+    /// Half the width (px) at `fraction` of the height.
+    pub fn half_width(&self, fraction: f32) -> f32 {
+        let at = fraction.clamp(0.0, 1.0) * (OUTLINE_SAMPLES - 1) as f32;
+        let i = (at as usize).min(OUTLINE_SAMPLES - 2);
+        let t = at - i as f32;
+        (self.radii[i] as f32 + t * (self.radii[i + 1] - self.radii[i]) as f32) * self.scale
+    }
+
+    fn y(&self, fraction: f32) -> f32 {
+        self.base_y - fraction * self.height_px
+    }
+
+    /// Fills the inside between `from` and `to`, fractions of the height.
+    pub fn fill(&self, from: f32, to: f32, color: Color) {
+        let step = 1.0 / (OUTLINE_SAMPLES - 1) as f32;
+        // one trapezoid per piece between samples, where the outline bends
+        for i in 0..OUTLINE_SAMPLES - 1 {
+            let (low, high) = ((i as f32 * step).max(from), ((i + 1) as f32 * step).min(to));
+            if low >= high {
+                continue;
+            }
+            let (w0, w1) = (self.half_width(low), self.half_width(high));
+            let (y0, y1) = (self.y(low), self.y(high));
+            let cx = self.center_x;
+            draw_triangle(vec2(cx - w0, y0), vec2(cx + w0, y0), vec2(cx + w1, y1), color);
+            draw_triangle(vec2(cx - w0, y0), vec2(cx + w1, y1), vec2(cx - w1, y1), color);
+        }
+    }
+
+    /// Draws the outline itself: both sides and the base.
+    pub fn stroke(&self, thickness: f32, color: Color) {
+        let cx = self.center_x;
+        for i in 1..OUTLINE_SAMPLES {
+            let (f0, f1) = ((i - 1) as f32 / (OUTLINE_SAMPLES - 1) as f32, i as f32 / (OUTLINE_SAMPLES - 1) as f32);
+            let (w0, w1) = (self.half_width(f0), self.half_width(f1));
+            draw_line(cx - w0, self.y(f0), cx - w1, self.y(f1), thickness, color);
+            draw_line(cx + w0, self.y(f0), cx + w1, self.y(f1), thickness, color);
+        }
+        let base = self.half_width(0.0);
+        draw_line(cx - base, self.base_y, cx + base, self.base_y, thickness, color);
+    }
+}
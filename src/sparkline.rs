@@ -0,0 +1,179 @@
+use macroquad::prelude::*;
+
+use crate::theme::Palette;
+
+// Max points kept; when full, every other point is dropped and the interval doubles,
+// so the strip always spans the whole run with bounded memory.
+const CAPACITY: usize = 512;
+const INITIAL_INTERVAL: f64 = 1.0; // simulated seconds between samples
+
+pub struct Sparkline {
+    values: Vec<f32>,
+    interval: f64,
+    next_sample: f64,
+    last_time: f64,
+}
+
+impl Sparkline {
+    pub fn new() -> Self {
+        Self {
+            values: Vec::with_capacity(CAPACITY),
+            interval: INITIAL_INTERVAL,
+            next_sample: 0.0,
+            last_time: 0.0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn record(&mut self, time: f64, value: f32) {
+        // clock went backwards => the run was reset
+        if time < self.last_time {
+            self.clear();
+        }
+        self.last_time = time;
+        if time < self.next_sample {
+            return;
+        }
+        if self.values.len() == CAPACITY {
+            let kept: Vec<f32> = self.values.iter().step_by(2).copied().collect();
+            self.values = kept;
+            self.interval *= 2.0;
+        }
+        self.values.push(value);
+        self.next_sample = time + self.interval;
+    }
+
+    pub fn draw(&self, x: f32, y: f32, w: f32, h: f32, color: Color, palette: &Palette) {
+        draw_series(&self.values, x, y, w, h, color, palette);
+    }
+
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Simulated time (s) from the first point to the last.
+    pub fn span(&self) -> f64 {
+        self.interval * self.values.len().saturating_sub(1) as f64
+    }
+}
+
+const PAD: f32 = 4.0;
+
+/// The box a series is drawn in and the range of values it is scaled to.
+pub struct Chart {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+    lo: f32,
+    hi: f32,
+    /// Share of the width the series takes, the rest left for what comes after it.
+    reach: f32,
+}
+
+impl Chart {
+    /// A box scaled to take in `values` and `extra`, values drawn over the series.
+    pub fn fit(values: &[f32], extra: &[f32], x: f32, y: f32, w: f32, h: f32) -> Self {
+        let (lo, hi) = values.iter().chain(extra).fold((f32::MAX, f32::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        Self { x, y, w, h, lo, hi, reach: 1.0 }
+    }
+
+    /// The series over only the first `reach` of the width, as a run partway
+    /// through is drawn against curves of all of it.
+    pub fn reaching(self, reach: f32) -> Self {
+        Self { reach: reach.clamp(0.0, 1.0), ..self }
+    }
+
+    /// Screen x of `fraction` of the way across the series.
+    pub fn x_of(&self, fraction: f32) -> f32 {
+        self.x_across(fraction * self.reach)
+    }
+
+    // Screen x of `fraction` of the way across the whole width
+    fn x_across(&self, fraction: f32) -> f32 {
+        self.x + PAD + (self.w - 2.0 * PAD) * fraction
+    }
+
+    pub fn y_of(&self, value: f32) -> f32 {
+        self.y + self.h - PAD - (self.h - 2.0 * PAD) * (value - self.lo) / (self.hi - self.lo).max(0.5)
+    }
+
+    pub fn top(&self) -> f32 {
+        self.y
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.y + self.h
+    }
+
+    /// Shades the full height between the fractions `from` and `to` of the way across.
+    pub fn shade_across(&self, from: f32, to: f32, color: Color) {
+        let (left, right) = (self.x_of(from), self.x_of(to));
+        draw_rectangle(left, self.y + 1.0, right - left, self.h - 2.0, color);
+    }
+
+    /// Shades the full width between the values `low` and `high`.
+    pub fn shade_between(&self, low: f32, high: f32, color: Color) {
+        draw_rectangle(self.x + 1.0, self.y_of(high), self.w - 2.0, self.y_of(low) - self.y_of(high), color);
+    }
+
+    /// Shades between the curves `low` and `high`, each spread evenly over
+    /// the first `reach` of the width.
+    pub fn shade_curves(&self, low: &[f32], high: &[f32], reach: f32, color: Color) {
+        let n = low.len().min(high.len());
+        let at = |i: usize, v: f32| vec2(self.x_across(reach * i as f32 / (n - 1) as f32), self.y_of(v));
+        for i in 1..n {
+            let (a, b) = (at(i - 1, low[i - 1]), at(i, low[i]));
+            let (c, d) = (at(i, high[i]), at(i - 1, high[i - 1]));
+            draw_triangle(a, b, c, color);
+            draw_triangle(a, c, d, color);
+        }
+    }
+
+    /// A curve spread evenly over the first `reach` of the width, without the
+    /// series' labels.
+    pub fn curve(&self, values: &[f32], reach: f32, color: Color) {
+        let n = values.len();
+        let x = |i: usize| self.x_across(reach * i as f32 / (n - 1) as f32);
+        for i in 1..n {
+            draw_line(x(i - 1), self.y_of(values[i - 1]), x(i), self.y_of(values[i]), 1.5, color);
+        }
+    }
+
+    fn frame(&self, palette: &Palette) {
+        draw_rectangle(self.x, self.y, self.w, self.h, palette.card);
+        draw_rectangle_lines(self.x, self.y, self.w, self.h, 1.0, palette.frame);
+    }
+
+    fn series(&self, values: &[f32], color: Color, palette: &Palette) {
+        let n = values.len();
+        let to_px = |i: usize| (self.x_of(i as f32 / (n - 1) as f32), self.y_of(values[i]));
+        for i in 1..n {
+            let (x0, y0) = to_px(i - 1);
+            let (x1, y1) = to_px(i);
+            draw_line(x0, y0, x1, y1, 1.5, color);
+        }
+        draw_text(format!("{:.1}", self.hi), self.x + self.w + 4.0, self.y + 10.0, 14.0, palette.faint_text);
+        draw_text(format!("{:.1}", self.lo), self.x + self.w + 4.0, self.y + self.h, 14.0, palette.faint_text);
+    }
+}
+
+/// Line chart of `values` spread evenly across the box, auto-scaled, with the
+/// max/min labelled on the right.
+pub fn draw_series(values: &[f32], x: f32, y: f32, w: f32, h: f32, color: Color, palette: &Palette) {
+    draw_chart(&Chart::fit(values, &[], x, y, w, h), values, color, palette, |_| {});
+}
+
+/// Draws `values` in `chart`, with `behind` drawing into it first, even
+/// before there are two values to draw a line between.
+pub fn draw_chart(chart: &Chart, values: &[f32], color: Color, palette: &Palette, behind: impl FnOnce(&Chart)) {
+    chart.frame(palette);
+    behind(chart);
+    if values.len() < 2 {
+        return;
+    }
+    chart.series(values, color, palette);
+}
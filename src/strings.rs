@@ -0,0 +1,156 @@
+// The app's words, looked up by key in a string table so they can be
+// translated. English is built in from `lang/en.lang`, which is also the
+// template for a new language, and so are the other languages in `lang/`;
+// `--lang` or `ICEBOTTLE_LANG` picks one by its code or reads a file of your
+// own, and F2 moves through the built-in ones. A key a translation leaves out
+// is shown in English.
+//
+// Unlike the units and the palette the table isn't passed down: nearly every
+// line drawn or printed goes through it, so there is one for the whole app.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{LazyLock, RwLock};
+
+/// Languages shipped with the app, by code.
+pub const BUILT_IN: [(&str, &str); 3] = [
+    ("en", include_str!("../lang/en.lang")),
+    ("de", include_str!("../lang/de.lang")),
+    ("es", include_str!("../lang/es.lang")),
+];
+
+pub struct Strings {
+    code: String,
+    table: HashMap<String, String>,
+}
+
+impl Strings {
+    /// Reads a table of `key = text` lines; `#` starts a comment line. Lines
+    /// it can't make sense of are reported as `source:line` and skipped.
+    pub fn parse(code: &str, text: &str, source: &str) -> Self {
+        let mut table = HashMap::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once('=').map(|(k, v)| (k.trim(), v.trim())) {
+                Some((key, value)) if !key.is_empty() => {
+                    table.insert(key.to_string(), value.to_string());
+                }
+                _ => eprintln!("{source}:{}: skipped '{line}'", n + 1),
+            }
+        }
+        Self { code: code.to_string(), table }
+    }
+
+    pub fn built_in(code: &str) -> Option<Self> {
+        BUILT_IN.iter().find(|(c, _)| c.eq_ignore_ascii_case(code)).map(|(c, text)| Self::parse(c, text, &format!("lang/{c}.lang")))
+    }
+
+    /// A built-in language by its code, or else a table read from the file `spec`.
+    pub fn load(spec: &str) -> Result<Self, String> {
+        if let Some(strings) = Self::built_in(spec) {
+            return Ok(strings);
+        }
+        let text = std::fs::read_to_string(spec).map_err(|e| {
+            let codes: Vec<&str> = BUILT_IN.iter().map(|(c, _)| *c).collect();
+            format!("not a built-in language ({}) and can't be read: {e}", codes.join(", "))
+        })?;
+        let code = std::path::Path::new(spec).file_stem().and_then(|s| s.to_str()).unwrap_or(spec);
+        Ok(Self::parse(code, &text, spec))
+    }
+
+    /// The language's own name for itself, e.g. "Deutsch".
+    pub fn name(&self) -> &str {
+        self.table.get("language").map_or(&self.code, String::as_str)
+    }
+}
+
+static ENGLISH: LazyLock<Strings> = LazyLock::new(|| Strings::built_in("en").expect("English is built in"));
+// None until a language is chosen: English
+static CURRENT: RwLock<Option<Strings>> = RwLock::new(None);
+
+/// Shows everything in `strings` from now on.
+pub fn set(strings: Strings) {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = Some(strings);
+}
+
+// The text for `key` in the language shown, if it has one
+fn shown(key: &str) -> Option<String> {
+    CURRENT.read().unwrap_or_else(|e| e.into_inner()).as_ref().and_then(|s| s.table.get(key).cloned())
+}
+
+/// Code of the language shown.
+pub fn code() -> String {
+    CURRENT.read().unwrap_or_else(|e| e.into_inner()).as_ref().map_or("en".to_string(), |s| s.code.clone())
+}
+
+/// Name of the language shown, in that language.
+pub fn language() -> String {
+    CURRENT.read().unwrap_or_else(|e| e.into_inner()).as_ref().map_or(ENGLISH.name(), Strings::name).to_string()
+}
+
+/// Switches to the built-in language after the one shown, for the F2 key.
+pub fn next() {
+    let code = code();
+    let i = BUILT_IN.iter().position(|(c, _)| *c == code).map_or(0, |i| (i + 1) % BUILT_IN.len());
+    set(Strings::built_in(BUILT_IN[i].0).expect("a built-in language"));
+}
+
+/// The text for `key` in the language shown, in English if it has none, or
+/// the key itself if English has none either.
+pub fn get(key: &str) -> String {
+    shown(key).or_else(|| ENGLISH.table.get(key).cloned()).unwrap_or_else(|| key.to_string())
+}
+
+/// A name from the model, such as a wall material's `label`, in the language
+/// shown: `group.label` in the table, or the label as it is.
+pub fn name(group: &str, label: &str) -> String {
+    shown(&format!("{group}.{label}")).unwrap_or_else(|| label.to_string())
+}
+
+/// The text for `key` with `{0}`, `{1}`, ... filled in from `args`; a
+/// precision such as `{0:.1}` or `{0:+.2}` is applied to numbers.
+pub fn fill(key: &str, args: &[&dyn Display]) -> String {
+    let text = get(key);
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(open) = rest.find('{') {
+        out += &rest[..open];
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else {
+            rest = &rest[open..];
+            break;
+        };
+        let (index, spec) = after[..close].split_once(':').unwrap_or((&after[..close], ""));
+        match (index.parse::<usize>().ok().and_then(|i| args.get(i)), spec) {
+            (Some(arg), "") => out += &arg.to_string(),
+            (Some(arg), spec) => {
+                let (plus, precision) = (spec.starts_with('+'), spec.trim_start_matches('+').strip_prefix('.').and_then(|p| p.parse().ok()));
+                out += &match (plus, precision) {
+                    (true, Some(p)) => format!("{arg:+.p$}"),
+                    (false, Some(p)) => format!("{arg:.p$}"),
+                    (true, None) => format!("{arg:+}"),
+                    (false, None) => arg.to_string(),
+                };
+            }
+            // not a placeholder: keep it as written
+            (None, _) => out += &rest[open..open + close + 2],
+        }
+        rest = &after[close + 1..];
+    }
+    out + rest
+}
+
+/// `tr!("key")` is the text for a key, `tr!("key", a, b)` the text with
+/// `{0}` and `{1}` filled in.
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::strings::get($key)
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::strings::fill($key, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+pub(crate) use tr;
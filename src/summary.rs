@@ -0,0 +1,103 @@
+use icebottle_sim::{Simulation, SAMPLE_COLUMNS};
+use macroquad::prelude::*;
+
+use crate::strings::{self, tr};
+use crate::theme::Palette;
+use crate::units::Units;
+
+/// One quantity of the run summary: final value and the extremes reached.
+struct Row {
+    label: String,
+    last: f64,
+    min: f64,
+    max: f64,
+}
+
+fn rows(sim: &Simulation, units: Units) -> Vec<Row> {
+    let sample = sim.sample();
+    // the time column is left out: its range is just the run
+    (1..SAMPLE_COLUMNS.len())
+        .map(|i| {
+            let (min, max) = sim.envelope().range(i).unwrap_or((sample[i], sample[i]));
+            Row {
+                label: units.column_name(i),
+                last: units.column(i, sample[i]),
+                min: units.column(i, min),
+                max: units.column(i, max),
+            }
+        })
+        .collect()
+}
+
+fn heading(sim: &Simulation) -> String {
+    match sim.experiment().name.as_str() {
+        "" => tr!("summary.heading", sim.time_seconds()),
+        name => tr!("summary.heading_named", sim.time_seconds(), name),
+    }
+}
+
+fn footer(sim: &Simulation, units: Units) -> String {
+    let hold = sim.hold_time();
+    tr!("summary.footer", units.temp(hold.threshold, 0), hold.hours(), units.mass(sim.vapor_lost(), 4), sim.events().len())
+}
+
+// The chiller's result, the ice sublimed, the heater's electricity and the scenario's time-temperature integrals, one line each
+fn extra_lines(sim: &Simulation, units: Units) -> Vec<String> {
+    let mut lines = Vec::new();
+    let chiller = sim.chiller();
+    if chiller.flow > 0.0 || chiller.coolant_used() > 0.0 {
+        let coolant = chiller.coolant_used(); // kg, a litre each
+        lines.push(match chiller.chill_time() {
+            Some(t) => tr!("summary.chilled", units.temp(chiller.target, 0), t / 60.0, coolant),
+            None => tr!("summary.not_chilled", units.temp(chiller.target, 0), coolant),
+        });
+    }
+    if sim.ice_sublimed() > 0.0 {
+        lines.push(tr!("summary.sublimed", units.mass(sim.ice_sublimed(), 4)));
+    }
+    let electricity = sim.electricity();
+    if electricity.kwh() > 0.0 {
+        lines.push(tr!("summary.electricity", electricity.kwh(), electricity.cost(), electricity.tariff, sim.heater_energy() / 3600.0));
+    }
+    lines.extend(sim.doses().iter().map(|dose| format!("{}: {:.2}", strings::name("dose", &dose.kind.label()), dose.total())));
+    lines
+}
+
+/// Prints the summary as an aligned table on stdout.
+pub fn print(sim: &Simulation, units: Units) {
+    println!("{}", heading(sim));
+    println!("{:12} {:>12} {:>12} {:>12}", "", tr!("summary.final"), tr!("summary.min"), tr!("summary.max"));
+    for row in rows(sim, units) {
+        println!("{:12} {:>12.4} {:>12.4} {:>12.4}", row.label, row.last, row.min, row.max);
+    }
+    println!("{}", footer(sim, units));
+    for line in extra_lines(sim, units) {
+        println!("{line}");
+    }
+}
+
+/// Draws the summary as a panel with its top-left corner at `x`, `y`.
+pub fn draw(sim: &Simulation, units: Units, palette: &Palette, x: f32, y: f32) {
+    let rows = rows(sim, units);
+    let extra = extra_lines(sim, units);
+    let h = 96.0 + rows.len() as f32 * 20.0 + extra.len() as f32 * 18.0;
+    draw_rectangle(x, y, 360.0, h, palette.overlay);
+    draw_rectangle_lines(x, y, 360.0, h, 2.0, palette.border);
+    draw_text(heading(sim), x + 10.0, y + 24.0, 18.0, palette.text);
+    let columns = [x + 130.0, x + 205.0, x + 280.0];
+    for (label, cx) in [tr!("summary.final"), tr!("summary.min"), tr!("summary.max")].iter().zip(columns) {
+        draw_text(label, cx, y + 48.0, 15.0, palette.soft_text);
+    }
+    for (i, row) in rows.iter().enumerate() {
+        let ry = y + 68.0 + i as f32 * 20.0;
+        draw_text(&row.label, x + 10.0, ry, 15.0, palette.soft_text);
+        for (value, cx) in [row.last, row.min, row.max].iter().zip(columns) {
+            draw_text(format!("{value:.3}"), cx, ry, 15.0, palette.text);
+        }
+    }
+    let footer_y = y + h - 14.0 - extra.len() as f32 * 18.0;
+    draw_text(footer(sim, units), x + 10.0, footer_y, 14.0, palette.soft_text);
+    for (i, line) in extra.iter().enumerate() {
+        draw_text(line, x + 10.0, footer_y + (i + 1) as f32 * 18.0, 14.0, palette.soft_text);
+    }
+}
@@ -0,0 +1,134 @@
+// Parameter sweeps: the run set up on the command line is repeated for every
+// combination of ice mass, insulation and outside temperature, the runs spread
+// over all cores, and each one comes out as a row of a summary table.
+
+use icebottle_sim::{InsulationPreset, Simulation};
+use rayon::prelude::*;
+use std::io::{self, BufWriter, Write};
+use std::time::Instant;
+
+use crate::describe::describe_beside;
+
+/// The values each swept parameter takes; an empty axis keeps the base run's.
+pub struct Grid {
+    pub ice: Vec<f64>,
+    pub insulation: Vec<InsulationPreset>,
+    pub outside: Vec<f64>,
+}
+
+/// One combination of the grid.
+#[derive(Clone, Copy)]
+struct Point {
+    ice: f64,
+    insulation: Option<InsulationPreset>,
+    outside: f64,
+}
+
+/// What a run of one combination came to.
+struct Outcome {
+    ice_gone: Option<f64>, // s
+    final_temp: f64,       // °C
+    hold_hours: f64,
+}
+
+/// Runs `base` over every combination of `grid` in parallel, each to its
+/// duration, and writes the table as CSV to `output` (standard output when
+/// `None`). Returns the exit code.
+pub fn run(base: &Simulation, grid: &Grid, output: Option<&str>) -> i32 {
+    let points = points(base, grid);
+    let span = base.span();
+    eprintln!("Sweeping {} runs of {:.1} h on {} threads", points.len(), span / 3600.0, rayon::current_num_threads());
+    let start = Instant::now();
+    let outcomes: Vec<Outcome> = points.par_iter().map(|&point| run_one(base, point, span)).collect();
+    eprintln!("Done in {:.1} s", start.elapsed().as_secs_f64());
+
+    let written = match output {
+        Some(path) => std::fs::File::create(path).map(BufWriter::new).and_then(|mut f| {
+            write_table(&mut f, base, &points, &outcomes)?;
+            f.flush()
+        }),
+        None => {
+            let mut out = BufWriter::new(io::stdout().lock());
+            write_table(&mut out, base, &points, &outcomes).and_then(|()| out.flush())
+        }
+    };
+    match written {
+        Ok(()) => {
+            if let Some(path) = output {
+                println!("Wrote {} rows to {path}", points.len());
+                // the base run; the swept values are in the table
+                describe_beside(base, path);
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("Could not write {}: {e}", output.unwrap_or("the table"));
+            1
+        }
+    }
+}
+
+// Every combination, ice mass outermost
+fn points(base: &Simulation, grid: &Grid) -> Vec<Point> {
+    let or_base = |axis: &[f64], value: f64| if axis.is_empty() { vec![value] } else { axis.to_vec() };
+    let ice = or_base(&grid.ice, base.init().ice);
+    let outside = or_base(&grid.outside, base.init().outside_temp);
+    let insulation: Vec<Option<InsulationPreset>> = if grid.insulation.is_empty() {
+        vec![None]
+    } else {
+        grid.insulation.iter().copied().map(Some).collect()
+    };
+    let mut points = Vec::with_capacity(ice.len() * insulation.len() * outside.len());
+    for &ice in &ice {
+        for &insulation in &insulation {
+            for &outside in &outside {
+                points.push(Point { ice, insulation, outside });
+            }
+        }
+    }
+    points
+}
+
+fn run_one(base: &Simulation, point: Point, span: f64) -> Outcome {
+    let mut sim = base.clone();
+    sim.init_mut().ice = point.ice;
+    sim.init_mut().outside_temp = point.outside;
+    if let Some(insulation) = point.insulation {
+        sim.wall_mut().layers = insulation.layers();
+    }
+    sim.set_duration(Some(span));
+    sim.toggle_running();
+    let mut ice_gone = (sim.state().mass_ice <= 0.0).then_some(0.0);
+    while sim.is_running() {
+        sim.step(sim.step_size());
+        if ice_gone.is_none() && sim.state().mass_ice <= 0.0 {
+            ice_gone = Some(sim.time_seconds());
+        }
+    }
+    Outcome {
+        ice_gone,
+        final_temp: sim.state().system_temperature_equivalent(),
+        hold_hours: sim.hold_time().hours(),
+    }
+}
+
+fn write_table<W: Write>(out: &mut W, base: &Simulation, points: &[Point], outcomes: &[Outcome]) -> io::Result<()> {
+    base.experiment().write_header(out)?;
+    let threshold = base.hold_time().threshold;
+    writeln!(out, "ice_kg,insulation,outside_c,ice_gone_h,final_c,below_{threshold:.0}c_h")?;
+    let base_insulation = InsulationPreset::of_layers(&base.wall().layers).map_or("custom", |p| p.label());
+    for (point, outcome) in points.iter().zip(outcomes) {
+        // a run whose ice outlasts it has no melt time
+        let ice_gone = outcome.ice_gone.map(|t| format!("{:.3}", t / 3600.0)).unwrap_or_default();
+        writeln!(
+            out,
+            "{},{},{},{ice_gone},{:.2},{:.3}",
+            point.ice,
+            point.insulation.map_or(base_insulation, |p| p.label()),
+            point.outside,
+            outcome.final_temp,
+            outcome.hold_hours,
+        )?;
+    }
+    Ok(())
+}
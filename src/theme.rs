@@ -0,0 +1,353 @@
+// Colours the window is drawn in. Every drawing call takes its colours from
+// the palette of the chosen theme rather than naming them, so the dark
+// default can be swapped (T) for a light one or for the high-contrast
+// projector theme, which stays readable on a washed-out classroom projector.
+// Only colours that carry data, the temperature shading and the bottle's
+// tint, are left out of it.
+
+use egui_macroquad::egui;
+use macroquad::prelude::*;
+
+/// Named colours, by what they are used for.
+#[derive(Clone)]
+pub struct Palette {
+    pub background: Color,
+    /// Behind the status card, charts and the gauge.
+    pub card: Color,
+    /// Behind the summary, energy and DSC overlays.
+    pub overlay: Color,
+    /// A row in the presets manager.
+    pub row: Color,
+    /// Outline of cards and overlays.
+    pub border: Color,
+    /// Thin frames and axes in charts.
+    pub frame: Color,
+    /// The bottle's wall, neck and bath.
+    pub glass: Color,
+    pub text: Color,
+    /// Secondary text such as units and footers.
+    pub soft_text: Color,
+    /// Chart labels and hints.
+    pub faint_text: Color,
+    /// The forecast and other live notes.
+    pub accent: Color,
+    /// Focus outline, the melted level and authored chart marks.
+    pub highlight: Color,
+    pub warning: Color,
+    pub danger: Color,
+    /// In the target range.
+    pub good: Color,
+    /// The target band shaded on the water chart.
+    pub band: Color,
+    pub water: Color,
+    pub surface: Color,
+    pub ice: Color,
+    pub ice_edge: Color,
+    pub cap: Color,
+    pub droplet: Color,
+    /// Vapour over an open neck; its alpha is set as it is drawn.
+    pub steam: Color,
+    /// The water temperature line.
+    pub water_line: Color,
+    /// The ensemble's median water temperature and its 10-90 % band around it.
+    pub ensemble: Color,
+    pub ensemble_band: Color,
+    /// The salinity chart under the water chart.
+    pub salinity_line: Color,
+    /// The heater output chart.
+    pub power_line: Color,
+    /// The DSC heat-flow trace.
+    pub dsc_line: Color,
+    /// The next 24 hours of outside temperature under the status card.
+    pub outside_line: Color,
+    /// Sinking brine plumes.
+    pub brine: Color,
+    /// Salty water's tint at its strongest; fainter water is tinted less.
+    pub brine_tint: Color,
+    /// The band between the energy-flow columns.
+    pub flow_band: Color,
+    /// Fills of the presets manager's buttons: most of them, Save, Colour and Delete.
+    pub button: Color,
+    pub button_save: Color,
+    pub button_plain: Color,
+    pub button_delete: Color,
+    pub button_text: Color,
+    pub button_disabled_text: Color,
+    /// Outline of the button under the mouse.
+    pub button_hover: Color,
+    /// The energy-flow diagram's terms, in `EnergySplit::entries` order.
+    pub flows: [Color; 10],
+}
+
+/// The palette's colours by field name, as a render file names them; the
+/// energy-flow colours are left to the theme.
+pub const COLOR_NAMES: [&str; 40] = [
+    "background", "card", "overlay", "row", "border", "frame", "glass", "text", "soft_text", "faint_text", "accent", "highlight",
+    "warning", "danger", "good", "band", "water", "surface", "ice", "ice_edge", "cap", "droplet", "steam", "water_line",
+    "ensemble", "ensemble_band", "salinity_line", "power_line", "dsc_line", "outside_line", "brine", "brine_tint", "flow_band",
+    "button", "button_save", "button_plain", "button_delete", "button_text", "button_disabled_text", "button_hover",
+];
+
+impl Palette {
+    /// The colour named as in `COLOR_NAMES`.
+    pub fn color_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "background" => &mut self.background,
+            "card" => &mut self.card,
+            "overlay" => &mut self.overlay,
+            "row" => &mut self.row,
+            "border" => &mut self.border,
+            "frame" => &mut self.frame,
+            "glass" => &mut self.glass,
+            "text" => &mut self.text,
+            "soft_text" => &mut self.soft_text,
+            "faint_text" => &mut self.faint_text,
+            "accent" => &mut self.accent,
+            "highlight" => &mut self.highlight,
+            "warning" => &mut self.warning,
+            "danger" => &mut self.danger,
+            "good" => &mut self.good,
+            "band" => &mut self.band,
+            "water" => &mut self.water,
+            "surface" => &mut self.surface,
+            "ice" => &mut self.ice,
+            "ice_edge" => &mut self.ice_edge,
+            "cap" => &mut self.cap,
+            "droplet" => &mut self.droplet,
+            "steam" => &mut self.steam,
+            "water_line" => &mut self.water_line,
+            "ensemble" => &mut self.ensemble,
+            "ensemble_band" => &mut self.ensemble_band,
+            "salinity_line" => &mut self.salinity_line,
+            "power_line" => &mut self.power_line,
+            "dsc_line" => &mut self.dsc_line,
+            "outside_line" => &mut self.outside_line,
+            "brine" => &mut self.brine,
+            "brine_tint" => &mut self.brine_tint,
+            "flow_band" => &mut self.flow_band,
+            "button" => &mut self.button,
+            "button_save" => &mut self.button_save,
+            "button_plain" => &mut self.button_plain,
+            "button_delete" => &mut self.button_delete,
+            "button_text" => &mut self.button_text,
+            "button_disabled_text" => &mut self.button_disabled_text,
+            "button_hover" => &mut self.button_hover,
+            _ => return None,
+        })
+    }
+}
+
+const DARK: Palette = Palette {
+    background: Color::from_rgba(18, 20, 28, 255),
+    card: Color::from_rgba(8, 8, 12, 220),
+    overlay: Color::from_rgba(18, 20, 28, 240),
+    row: Color::from_rgba(36, 36, 50, 220),
+    border: LIGHTGRAY,
+    frame: DARKGRAY,
+    glass: GRAY,
+    text: WHITE,
+    soft_text: LIGHTGRAY,
+    faint_text: GRAY,
+    accent: SKYBLUE,
+    highlight: Color::new(1.0, 0.85, 0.2, 1.0),
+    warning: ORANGE,
+    danger: RED,
+    good: Color::new(0.35, 0.95, 0.45, 1.0),
+    band: Color::new(0.3, 0.9, 0.4, 0.25),
+    water: Color::from_rgba(30, 90, 200, 200),
+    surface: Color::from_rgba(50, 140, 220, 200),
+    ice: Color::from_rgba(230, 245, 255, 230),
+    ice_edge: Color::from_rgba(180, 200, 220, 200),
+    cap: Color::from_rgba(40, 110, 200, 255),
+    droplet: Color::from_rgba(170, 210, 255, 200),
+    steam: Color::new(0.9, 0.9, 0.95, 1.0),
+    water_line: Color::from_rgba(90, 170, 255, 255),
+    ensemble: Color::from_rgba(200, 200, 255, 255),
+    ensemble_band: Color::new(0.6, 0.6, 1.0, 0.22),
+    salinity_line: Color::from_rgba(60, 200, 150, 255),
+    power_line: Color::from_rgba(240, 120, 60, 255),
+    dsc_line: Color::from_rgba(255, 110, 90, 255),
+    outside_line: Color::from_rgba(255, 170, 80, 255),
+    brine: Color::from_rgba(20, 160, 120, 220),
+    brine_tint: Color::new(0.1, 0.7, 0.5, 0.35),
+    flow_band: Color::new(0.6, 0.7, 0.9, 0.25),
+    button: Color::from_rgba(60, 60, 120, 220),
+    button_save: Color::from_rgba(60, 120, 60, 220),
+    button_plain: Color::from_rgba(90, 90, 90, 220),
+    button_delete: Color::from_rgba(150, 60, 60, 220),
+    button_text: WHITE,
+    button_disabled_text: GRAY,
+    button_hover: Color::new(1.0, 1.0, 1.0, 0.5),
+    flows: [
+        Color::new(0.75, 0.75, 0.78, 1.0), // wall
+        Color::new(0.95, 0.45, 0.25, 1.0), // heater
+        Color::new(0.3, 0.6, 0.95, 1.0),   // chiller
+        Color::new(0.8, 0.6, 0.4, 1.0),    // contact
+        Color::new(0.85, 0.85, 0.5, 1.0),  // neck
+        Color::new(0.65, 0.88, 1.0, 1.0),  // ice, sensible
+        Color::new(0.95, 0.95, 1.0, 1.0),  // melting
+        Color::new(0.25, 0.55, 1.0, 1.0),  // water, sensible
+        Color::new(0.95, 0.7, 0.9, 1.0),   // steam
+        Color::new(0.55, 0.85, 0.6, 1.0),  // headspace air
+    ],
+};
+
+const LIGHT: Palette = Palette {
+    background: Color::from_rgba(236, 238, 242, 255),
+    card: Color::from_rgba(250, 250, 252, 235),
+    overlay: Color::from_rgba(255, 255, 255, 245),
+    row: Color::from_rgba(210, 216, 230, 220),
+    border: Color::from_rgba(90, 95, 110, 255),
+    frame: Color::from_rgba(150, 152, 160, 255),
+    glass: Color::from_rgba(100, 105, 115, 255),
+    text: Color::from_rgba(20, 22, 28, 255),
+    soft_text: Color::from_rgba(60, 64, 75, 255),
+    faint_text: Color::from_rgba(110, 115, 125, 255),
+    accent: Color::from_rgba(20, 110, 190, 255),
+    highlight: Color::from_rgba(190, 130, 0, 255),
+    warning: Color::from_rgba(210, 100, 0, 255),
+    danger: Color::from_rgba(200, 30, 30, 255),
+    good: Color::from_rgba(20, 140, 50, 255),
+    band: Color::new(0.2, 0.7, 0.3, 0.25),
+    water: Color::from_rgba(30, 90, 200, 200),
+    surface: Color::from_rgba(20, 70, 170, 220),
+    ice: Color::from_rgba(215, 235, 250, 240),
+    ice_edge: Color::from_rgba(90, 130, 170, 230),
+    cap: Color::from_rgba(40, 110, 200, 255),
+    droplet: Color::from_rgba(60, 120, 200, 200),
+    steam: Color::new(0.5, 0.55, 0.6, 1.0),
+    water_line: Color::from_rgba(20, 100, 210, 255),
+    ensemble: Color::from_rgba(90, 60, 170, 255),
+    ensemble_band: Color::new(0.4, 0.3, 0.8, 0.2),
+    salinity_line: Color::from_rgba(10, 140, 100, 255),
+    power_line: Color::from_rgba(200, 80, 20, 255),
+    dsc_line: Color::from_rgba(200, 50, 40, 255),
+    outside_line: Color::from_rgba(200, 120, 20, 255),
+    brine: Color::from_rgba(10, 120, 90, 220),
+    brine_tint: Color::new(0.05, 0.55, 0.4, 0.35),
+    flow_band: Color::new(0.3, 0.4, 0.7, 0.2),
+    button: Color::from_rgba(70, 90, 170, 230),
+    button_save: Color::from_rgba(50, 130, 60, 230),
+    button_plain: Color::from_rgba(110, 115, 125, 230),
+    button_delete: Color::from_rgba(180, 50, 50, 230),
+    button_text: WHITE,
+    button_disabled_text: Color::from_rgba(215, 218, 225, 255),
+    button_hover: Color::new(0.0, 0.0, 0.0, 0.5),
+    flows: [
+        Color::new(0.35, 0.35, 0.4, 1.0),
+        Color::new(0.85, 0.3, 0.1, 1.0),
+        Color::new(0.1, 0.4, 0.8, 1.0),
+        Color::new(0.6, 0.4, 0.2, 1.0),
+        Color::new(0.55, 0.5, 0.1, 1.0),
+        Color::new(0.2, 0.55, 0.75, 1.0),
+        Color::new(0.45, 0.45, 0.65, 1.0),
+        Color::new(0.1, 0.3, 0.85, 1.0),
+        Color::new(0.7, 0.3, 0.6, 1.0),
+        Color::new(0.2, 0.55, 0.25, 1.0),
+    ],
+};
+
+// black on white with the colours at full strength and nothing faint
+const PROJECTOR: Palette = Palette {
+    background: WHITE,
+    card: WHITE,
+    overlay: WHITE,
+    row: Color::from_rgba(200, 210, 235, 255),
+    border: BLACK,
+    frame: Color::from_rgba(60, 60, 60, 255),
+    glass: BLACK,
+    text: BLACK,
+    soft_text: BLACK,
+    faint_text: Color::from_rgba(40, 40, 40, 255),
+    accent: Color::from_rgba(0, 70, 200, 255),
+    highlight: Color::from_rgba(200, 100, 0, 255),
+    warning: Color::from_rgba(220, 90, 0, 255),
+    danger: Color::from_rgba(210, 0, 0, 255),
+    good: Color::from_rgba(0, 140, 0, 255),
+    band: Color::new(0.0, 0.7, 0.1, 0.3),
+    water: Color::from_rgba(0, 60, 220, 230),
+    surface: Color::from_rgba(0, 30, 150, 255),
+    ice: Color::from_rgba(200, 230, 255, 255),
+    ice_edge: Color::from_rgba(0, 60, 140, 255),
+    cap: Color::from_rgba(0, 60, 200, 255),
+    droplet: Color::from_rgba(0, 80, 220, 230),
+    steam: Color::new(0.3, 0.3, 0.35, 1.0),
+    water_line: Color::from_rgba(0, 60, 220, 255),
+    ensemble: Color::from_rgba(120, 0, 160, 255),
+    ensemble_band: Color::new(0.5, 0.0, 0.7, 0.2),
+    salinity_line: Color::from_rgba(0, 130, 80, 255),
+    power_line: Color::from_rgba(220, 70, 0, 255),
+    dsc_line: Color::from_rgba(210, 0, 0, 255),
+    outside_line: Color::from_rgba(200, 100, 0, 255),
+    brine: Color::from_rgba(0, 110, 70, 255),
+    brine_tint: Color::new(0.0, 0.5, 0.3, 0.4),
+    flow_band: Color::new(0.0, 0.2, 0.6, 0.25),
+    button: Color::from_rgba(0, 50, 170, 255),
+    button_save: Color::from_rgba(0, 120, 0, 255),
+    button_plain: Color::from_rgba(60, 60, 60, 255),
+    button_delete: Color::from_rgba(190, 0, 0, 255),
+    button_text: WHITE,
+    button_disabled_text: Color::from_rgba(230, 230, 230, 255),
+    button_hover: BLACK,
+    flows: [
+        Color::new(0.2, 0.2, 0.2, 1.0),
+        Color::new(0.85, 0.2, 0.0, 1.0),
+        Color::new(0.0, 0.35, 0.85, 1.0),
+        Color::new(0.55, 0.3, 0.1, 1.0),
+        Color::new(0.5, 0.45, 0.0, 1.0),
+        Color::new(0.0, 0.5, 0.7, 1.0),
+        Color::new(0.35, 0.2, 0.6, 1.0),
+        Color::new(0.0, 0.2, 0.8, 1.0),
+        Color::new(0.7, 0.0, 0.5, 1.0),
+        Color::new(0.0, 0.5, 0.1, 1.0),
+    ],
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    Projector,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::Projector];
+
+    /// Also its name on the command line.
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Dark => "dark",
+            Theme::Light => "light",
+            Theme::Projector => "projector",
+        }
+    }
+
+    pub fn palette(self) -> &'static Palette {
+        match self {
+            Theme::Dark => &DARK,
+            Theme::Light => &LIGHT,
+            Theme::Projector => &PROJECTOR,
+        }
+    }
+
+    /// The theme after this one, for the T key.
+    pub fn next(self) -> Theme {
+        Theme::ALL[(Theme::ALL.iter().position(|&t| t == self).unwrap_or(0) + 1) % Theme::ALL.len()]
+    }
+
+    /// The controls panel to go with it.
+    pub fn visuals(self) -> egui::Visuals {
+        match self {
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+            Theme::Projector => {
+                let mut visuals = egui::Visuals::light();
+                visuals.override_text_color = Some(egui::Color32::BLACK);
+                visuals.panel_fill = egui::Color32::WHITE;
+                visuals.window_fill = egui::Color32::WHITE;
+                visuals
+            }
+        }
+    }
+}
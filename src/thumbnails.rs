@@ -0,0 +1,126 @@
+// Thumbnails for a folder of scenario files: each scenario is run ahead and
+// drawn as a small chart (contents and outside temperature, ice left) into a
+// PNG next to its file, for the scenario browser and course pages to show.
+// Everything is drawn on the CPU, so no window is needed.
+
+use icebottle_sim::{Scenario, Simulation};
+use macroquad::prelude::*;
+use std::path::{Path, PathBuf};
+
+pub const THUMB_W: u16 = 320;
+pub const THUMB_H: u16 = 180;
+
+// s per step; coarse like the forecast's, only the shape of the curves shows
+const STEP: f64 = 2.0;
+
+const BACKGROUND: Color = Color::new(0.05, 0.05, 0.07, 1.0);
+const ICE: Color = Color::new(0.55, 0.8, 1.0, 0.35);
+const FREEZING: Color = Color::new(0.3, 0.3, 0.4, 1.0);
+const OUTSIDE: Color = Color::new(0.6, 0.6, 0.6, 1.0);
+const CONTENTS: Color = Color::new(0.35, 0.67, 1.0, 1.0);
+
+/// Renders `name.png` for every `name.toml` in `dir`. Scenarios that fail the
+/// check are skipped and reported. Returns the exit code: 0 when every
+/// thumbnail was written, 1 otherwise, 2 when the folder can't be read.
+pub fn run(dir: &str) -> i32 {
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|e| e.ok().map(|e| e.path())).filter(|p| p.extension().is_some_and(|x| x == "toml")).collect(),
+        Err(e) => {
+            eprintln!("{dir}: error: could not read: {e}");
+            return 2;
+        }
+    };
+    paths.sort();
+    if paths.is_empty() {
+        eprintln!("{dir}: no scenario files (*.toml)");
+        return 1;
+    }
+    let mut failed = 0;
+    for path in &paths {
+        match render_file(path) {
+            Ok(out) => println!("{}", out.display()),
+            Err(message) => {
+                eprintln!("{}: skipped: {message}", path.display());
+                failed += 1;
+            }
+        }
+    }
+    println!("{} of {} thumbnails written", paths.len() - failed, paths.len());
+    if failed > 0 { 1 } else { 0 }
+}
+
+fn render_file(path: &Path) -> Result<PathBuf, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("could not read: {e}"))?;
+    let scenario = Scenario::from_toml(&source).map_err(|errors| format!("{} problem(s), see --check", errors.len()))?;
+    let out = path.with_extension("png");
+    // export_png panics on a write error, so find out first
+    std::fs::File::create(&out).map_err(|e| format!("could not write {}: {e}", out.display()))?;
+    render(&scenario).export_png(&out.to_string_lossy());
+    Ok(out)
+}
+
+/// The scenario's run as a chart: ice left as a shaded area from the bottom,
+/// the outside temperature in grey, the contents in blue and 0 °C as a dim
+/// line. Time runs across the whole width, over the scenario's duration.
+pub fn render(scenario: &Scenario) -> Image {
+    let mut sim = Simulation::new();
+    scenario.apply(&mut sim);
+    let span = sim.span();
+    sim.set_duration(None);
+    sim.toggle_running();
+
+    let (w, h) = (THUMB_W as usize, THUMB_H as usize);
+    let mut columns = Vec::with_capacity(w);
+    for x in 0..w {
+        let target = span * x as f64 / (w - 1) as f64;
+        while sim.time_seconds() < target {
+            sim.step(STEP.min(target - sim.time_seconds()).max(1e-6));
+        }
+        let s = sim.state();
+        columns.push((s.system_temperature_equivalent(), sim.outside_temp(), s.mass_ice));
+    }
+
+    let temps = columns.iter().flat_map(|&(contents, outside, _)| [contents, outside]);
+    let (lo, hi) = temps.fold((0.0f64, 0.0f64), |(lo, hi), t| (lo.min(t), hi.max(t)));
+    let (lo, hi) = (lo - 2.0, hi + 2.0);
+    let row_of = |temp: f64| ((hi - temp) / (hi - lo) * (h - 1) as f64).round() as usize;
+    let max_ice = columns.iter().map(|c| c.2).fold(0.0, f64::max);
+
+    let mut image = Image::gen_image_color(THUMB_W, THUMB_H, BACKGROUND);
+    // export_png writes the rows bottom-up, as read back from the screen
+    let mut put = |x: usize, y: usize, color: Color| {
+        let y = (h - 1 - y.min(h - 1)) as u32;
+        let under = image.get_pixel(x as u32, y);
+        let a = color.a;
+        image.set_pixel(
+            x as u32,
+            y,
+            Color::new(under.r * (1.0 - a) + color.r * a, under.g * (1.0 - a) + color.g * a, under.b * (1.0 - a) + color.b * a, 1.0),
+        );
+    };
+    let freezing = row_of(0.0);
+    let mut prev: Option<(usize, usize)> = None;
+    for (x, &(contents, outside, ice)) in columns.iter().enumerate() {
+        if max_ice > 0.0 {
+            let top = h - (ice / max_ice * 0.4 * h as f64).round() as usize;
+            for y in top..h {
+                put(x, y, ICE);
+            }
+        }
+        if x % 6 < 3 {
+            put(x, freezing, FREEZING);
+        }
+        let (outside_row, contents_row) = (row_of(outside), row_of(contents));
+        let (outside_before, contents_before) = prev.unwrap_or((outside_row, contents_row));
+        // each line joins the previous column's row, so steep stretches have no
+        // gaps; the contents line is two pixels thick
+        for y in outside_row.min(outside_before)..=outside_row.max(outside_before) {
+            put(x, y, OUTSIDE);
+        }
+        for y in contents_row.min(contents_before)..=contents_row.max(contents_before) + 1 {
+            put(x, y, CONTENTS);
+        }
+        prev = Some((outside_row, contents_row));
+    }
+    image
+}
@@ -0,0 +1,120 @@
+// Tooltips: what a field or a status line is, its units, and the constant or
+// equation behind it. The text is in the strings table as `tip.field.<name>`
+// and `tip.status.<name>`; the model's constants are filled in here, so a
+// tooltip never quotes a value the model doesn't use. The controls panel shows
+// them through egui; the status card, which macroquad draws, registers the
+// rectangles of its lines with `Tooltips` and gets one drawn under the mouse.
+
+use icebottle_sim::constants::*;
+use icebottle_sim::Liquid;
+use macroquad::prelude::*;
+use std::fmt::Display;
+
+use crate::jug::POUR_OUT;
+use crate::strings;
+use crate::theme::Palette;
+
+const WIDTH: f32 = 340.0;
+const FONT_SIZE: f32 = 15.0;
+
+/// The tooltip of a panel field, by its name in `KEYS`.
+pub fn field(name: &str) -> String {
+    let sigma = format!("{STEFAN_BOLTZMANN:.4e}");
+    let rate = format!("{NUCLEATION_RATE:.0e}");
+    let slope = format!("{:.1e}", MELTING_SLOPE);
+    let milk = Liquid::Milk.properties();
+    let args: &[&dyn Display] = match name {
+        "water" => &[&CP_WATER, &DENSITY_WATER],
+        "ice" => &[&(LATENT_FUSION / 1000.0), &CP_ICE, &DENSITY_ICE],
+        "air" => &[&CV_AIR, &R_AIR],
+        "liquid" => &[&milk.cp, &(milk.latent_fusion / 1000.0), &milk.freezing_point],
+        "salt" => &[&(MOLAR_MASS_SALT * 1000.0), &VANT_HOFF_SALT, &CRYOSCOPIC_WATER, &EUTECTIC_TEMP],
+        "supercool" => &[&rate, &HOMOGENEOUS_UNDERCOOLING],
+        "dissolved_air" => &[&(DEGASSED_NUCLEATION * 100.0), &(BOILED_DISSOLVED_AIR * 100.0)],
+        "outside_temp" => &[&sigma, &RADIATION_REF_TEMP],
+        "wind" => &[&H_OUTSIDE, &H_WIND_PER_MS],
+        "layers" => &[&CONDUCTIVITY_WATER, &MIXING_TIME],
+        "field2d" => &[&(FIELD_CELL * 1000.0), &FIELD_WATER_CONDUCTIVITY, &CONDUCTIVITY_ICE],
+        "ice_pieces" => &[&H_ICE_WATER],
+        "humidity" => &[&(AMBIENT_HUMIDITY * 100.0), &(CONDENSATE_FILM * 1000.0)],
+        "cap" => &[&(NECK_AREA * 1e4), &MASS_TRANSFER_NECK],
+        "pressure_melt" => &[&slope],
+        "conduction_only" => &[&CONDUCTIVITY_AIR, &H_OUTSIDE, &H_INSIDE],
+        _ => &[],
+    };
+    strings::fill(&format!("tip.field.{name}"), args)
+}
+
+/// The tooltip of a line in the status card, by the key of its text.
+pub fn status(name: &str) -> String {
+    let sigma = format!("{STEFAN_BOLTZMANN:.4e}");
+    let args: &[&dyn Display] = match name {
+        "water" => &[&CP_WATER],
+        "ice" => &[&(LATENT_FUSION / 1000.0), &CP_ICE],
+        "t_water" => &[&H_INSIDE],
+        "t_ice" => &[&H_ICE_WATER],
+        "t_air" => &[&CV_AIR, &(AIR_WALL_FRACTION * 100.0), &UA_AIR_SURFACE],
+        "wall_ua" => &[&H_INSIDE, &H_OUTSIDE],
+        "convection" => &[&H_OUTSIDE, &H_WIND_PER_MS, &H_WATER_BATH],
+        "radiation" => &[&sigma, &RADIATION_REF_TEMP],
+        "freezing_point" => &[&CRYOSCOPIC_WATER, &VANT_HOFF_SALT, &EUTECTIC_TEMP],
+        "vapor" => &[&(LATENT_VAPORIZATION_0C / 1000.0), &MASS_TRANSFER_NECK, &(LATENT_SUBLIMATION / 1000.0), &(CAP_SEAL_LEAK * 100.0)],
+        "volume" => &[&DENSITY_WATER, &DENSITY_ICE],
+        "dew_point" => &[&(CONDENSATE_FILM * 1000.0)],
+        "heater" => &[&(PELTIER_CARNOT_FRACTION * 100.0), &PELTIER_MAX_COP],
+        "tray" => &[&CP_ICE, &(LATENT_FUSION / 1000.0)],
+        "jug" => &[&(POUR_OUT * 100.0)],
+        _ => &[],
+    };
+    strings::fill(&format!("tip.status.{name}"), args)
+}
+
+/// Regions of the screen with a tooltip, gathered while a frame is drawn and
+/// dropped with it.
+#[derive(Default)]
+pub struct Tooltips {
+    hovered: Option<String>,
+}
+
+impl Tooltips {
+    /// Notes that `rect` is explained by `text`, made only when the mouse is
+    /// over it; a later region over the same spot wins.
+    pub fn region(&mut self, rect: Rect, text: impl FnOnce() -> String) {
+        let (mx, my) = mouse_position();
+        if rect.contains(vec2(mx, my)) {
+            self.hovered = Some(text());
+        }
+    }
+
+    /// Draws the tooltip under the mouse, if any.
+    pub fn draw(self, palette: &Palette) {
+        let Some(text) = self.hovered else { return };
+        let lines = wrap(&text, WIDTH - 16.0);
+        let h = lines.len() as f32 * (FONT_SIZE + 3.0) + 12.0;
+        let (mx, my) = mouse_position();
+        // beside the pointer, kept on the screen
+        let x = (mx + 16.0).min(screen_width() - WIDTH - 4.0).max(4.0);
+        let y = if my + 20.0 + h > screen_height() { (my - h - 8.0).max(4.0) } else { my + 20.0 };
+        draw_rectangle(x, y, WIDTH, h, palette.overlay);
+        draw_rectangle_lines(x, y, WIDTH, h, 1.0, palette.border);
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(line, x + 8.0, y + 6.0 + FONT_SIZE + i as f32 * (FONT_SIZE + 3.0), FONT_SIZE, palette.text);
+        }
+    }
+}
+
+// Breaks `text` into lines no wider than `width` at spaces
+fn wrap(text: &str, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{line} {word}") };
+        if !line.is_empty() && measure_text(&candidate, None, FONT_SIZE as u16, 1.0).width > width {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+    lines.push(line);
+    lines
+}
@@ -0,0 +1,118 @@
+// The ice tray beside the bottle: a cube of the tray's mass and temperature
+// goes into the running drink when it is dragged from the tray and let go over
+// the bottle, or when the bottle is clicked. The mouse wheel over the tray sets
+// the mass, with Shift the temperature. Ice only goes in through the open neck,
+// so the tray says so while the cap is on.
+
+use icebottle_sim::Simulation;
+use macroquad::prelude::*;
+
+use crate::strings::tr;
+use crate::theme::Palette;
+use crate::units::Units;
+
+pub const DEFAULT_MASS: f64 = 0.02; // kg
+pub const DEFAULT_TEMP: f64 = -18.0; // °C, a freezer's
+
+// Steps of the mouse wheel, and how far they go
+const MASS_STEP: f64 = 0.005;
+const MASS_RANGE: (f64, f64) = (0.005, 0.1);
+const TEMP_STEP: f64 = 1.0;
+const TEMP_MIN: f64 = -40.0;
+
+// px of the cube drawn for 20 g; it grows with the cube root of the mass
+const CUBE_PX: f32 = 26.0;
+
+pub struct Tray {
+    pub mass: f64, // kg
+    pub temp: f64, // °C
+    rect: Rect,
+    dragging: bool,
+}
+
+impl Tray {
+    pub fn new(mass: f64, temp: f64) -> Self {
+        Self { mass, temp: temp.min(0.0), rect: Rect::default(), dragging: false }
+    }
+
+    /// Places the tray to the left of `bottle`, by its foot.
+    pub fn place(&mut self, bottle: Rect) {
+        self.rect = Rect::new(bottle.x - 86.0, bottle.y + bottle.h - 150.0, 72.0, 72.0);
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Takes the mouse: the wheel over the tray, a drag from it and a click on
+    /// `bottle`. With `enabled` false (the run paused, or not to be changed)
+    /// a drag under way is dropped and the wheel alone still works.
+    pub fn update(&mut self, sim: &mut Simulation, bottle: Rect, enabled: bool) {
+        let mouse = Vec2::from(mouse_position());
+        let wheel = mouse_wheel().1;
+        if wheel != 0.0 && self.rect.contains(mouse) {
+            let step = wheel.signum() as f64;
+            if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+                self.temp = (self.temp + step * TEMP_STEP).clamp(TEMP_MIN, 0.0);
+            } else {
+                self.mass = (self.mass + step * MASS_STEP).clamp(MASS_RANGE.0, MASS_RANGE.1);
+            }
+        }
+        if !enabled || sim.is_capped() {
+            self.dragging = false;
+            return;
+        }
+        let over_bottle = bottle.contains(mouse);
+        if is_mouse_button_pressed(MouseButton::Left) {
+            if self.rect.contains(mouse) {
+                self.dragging = true;
+            } else if over_bottle {
+                self.drop_into(sim);
+            }
+        }
+        if self.dragging && is_mouse_button_released(MouseButton::Left) {
+            self.dragging = false;
+            if over_bottle {
+                self.drop_into(sim);
+            }
+        }
+    }
+
+    fn drop_into(&self, sim: &mut Simulation) {
+        if !sim.drop_ice(self.mass, self.temp) {
+            println!("No room in the bottle for another cube");
+        }
+    }
+
+    /// Draws the tray with a cube in it and what it holds, greyed while no
+    /// cube can go in.
+    pub fn draw(&self, sim: &Simulation, enabled: bool, units: Units, palette: &Palette) {
+        let r = self.rect;
+        let open = enabled && !sim.is_capped();
+        let alpha = if open { 1.0 } else { 0.4 };
+        draw_rectangle(r.x, r.y, r.w, r.h, Color { a: palette.card.a * alpha, ..palette.card });
+        draw_rectangle_lines(r.x, r.y, r.w, r.h, 2.0, Color { a: alpha, ..palette.frame });
+        if !self.dragging {
+            self.draw_cube(vec2(r.x + r.w / 2.0, r.y + r.h / 2.0), alpha, palette);
+        }
+        draw_text(tr!("tray.cube", units.mass(self.mass, 3)), r.x, r.y + r.h + 16.0, 14.0, palette.soft_text);
+        draw_text(units.temp(self.temp, 0), r.x, r.y + r.h + 32.0, 14.0, palette.soft_text);
+        if enabled && sim.is_capped() {
+            draw_text(tr!("tray.capped"), r.x, r.y - 8.0, 14.0, palette.faint_text);
+        }
+    }
+
+    /// Draws the cube being dragged under the mouse, over everything else.
+    pub fn draw_held(&self, palette: &Palette) {
+        if self.dragging {
+            self.draw_cube(Vec2::from(mouse_position()), 0.8, palette);
+        }
+    }
+
+    fn draw_cube(&self, center: Vec2, alpha: f32, palette: &Palette) {
+        let side = CUBE_PX * (self.mass / DEFAULT_MASS).cbrt() as f32;
+        let (x, y) = (center.x - side / 2.0, center.y - side / 2.0);
+        draw_rectangle(x, y, side, side, Color { a: palette.ice.a * alpha, ..palette.ice });
+        draw_rectangle_lines(x, y, side, side, 2.0, Color { a: alpha, ..palette.ice_edge });
+    }
+}
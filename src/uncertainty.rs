@@ -0,0 +1,341 @@
+// Uncertainty (--uncertainty N): the run set up on the command line is
+// repeated N times with its inputs drawn from the spreads they are known to
+// (a thermometer's, a kitchen scale's, a room's draught), the runs spread over
+// all cores, and the report is Markdown stating each result with the interval
+// those spreads put around it ("ice lasts 3.1 ± 0.4 h"), with the
+// distributions drawn from and what the interval does not cover. In the window
+// J draws the same ensemble's water temperature on the chart as a median with
+// a 10-90 % band, worked out beside the window and exported as CSV.
+
+use icebottle_sim::{Rng, Simulation};
+use rayon::prelude::*;
+use std::io::{self, BufWriter, Write};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+// Each run draws from its own generator, seeded from this and its number, so
+// the ensemble repeats exactly however the runs are spread over threads
+const SEED: u64 = 0x1CE_B077;
+/// A normal distribution's central 95 % is within this many standard deviations.
+const Z95: f64 = 1.96;
+
+// The window's ensemble: runs, and points over the run of each curve
+pub const BAND_RUNS: usize = 40;
+const BAND_POINTS: usize = 240;
+// Steps a band run takes at most and the coarsest step (s) it may take, as the explore panel's re-runs
+const BAND_STEPS: f64 = 20_000.0;
+const BAND_MAX_STEP: f64 = 10.0;
+/// Where the band is written once it is worked out.
+pub const BAND_CSV: &str = "band.csv";
+
+/// The spread of each input drawn, as a standard deviation.
+const OUTSIDE_SD: f64 = 1.0; // K, a room thermometer and the day's drift
+const CONTENTS_SD: f64 = 0.5; // K, a kitchen thermometer in the drink
+const ICE_SD: f64 = 0.05; // share of the mass, a kitchen scale and what melts while weighing
+const THICKNESS_SD: f64 = 0.1; // share of the wall, from bottle to bottle
+/// m/s of draught at most, drawn evenly from none up to it, over the wind set.
+const DRAUGHT: f64 = 0.5;
+
+/// The inputs of one run, drawn around the base run's.
+#[derive(Clone, Copy)]
+struct Draw {
+    outside: f64,
+    contents: f64,
+    ice: f64,
+    thickness: f64,
+    wind: f64,
+}
+
+/// What one run came to.
+struct Outcome {
+    ice_gone: Option<f64>, // s
+    hold_hours: f64,
+    final_temp: f64, // °C, of the drink
+}
+
+/// Runs `runs` draws around `base` in parallel, each to its duration, and
+/// writes the report to `output` (standard output when `None`). Returns the
+/// exit code.
+pub fn run(base: &Simulation, runs: usize, output: Option<&str>) -> i32 {
+    let span = base.span();
+    eprintln!("Running an ensemble of {runs} over {:.1} h on {} threads", span / 3600.0, rayon::current_num_threads());
+    let start = Instant::now();
+    let outcomes: Vec<Outcome> = (0..runs).into_par_iter().map(|i| run_one(base, draw(base, i as u64), span)).collect();
+    eprintln!("Done in {:.1} s", start.elapsed().as_secs_f64());
+
+    let written = match output {
+        Some(path) => std::fs::File::create(path).map(BufWriter::new).and_then(|mut f| {
+            write_report(&mut f, base, &outcomes, span)?;
+            f.flush()
+        }),
+        None => {
+            let mut out = BufWriter::new(io::stdout().lock());
+            write_report(&mut out, base, &outcomes, span).and_then(|()| out.flush())
+        }
+    };
+    match written {
+        Ok(()) => {
+            if let Some(path) = output {
+                println!("Wrote the uncertainty report of {runs} runs to {path}");
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("Could not write {}: {e}", output.unwrap_or("the report"));
+            1
+        }
+    }
+}
+
+// The inputs of run `i`
+fn draw(base: &Simulation, i: u64) -> Draw {
+    // spread neighbouring run numbers over the seeds, so their first draws aren't alike
+    let mut rng = Rng::new(SEED.wrapping_add(i).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    let init = base.init();
+    Draw {
+        outside: init.outside_temp + OUTSIDE_SD * rng.normal(),
+        contents: init.system_temp + CONTENTS_SD * rng.normal(),
+        ice: (init.ice * (1.0 + ICE_SD * rng.normal())).max(0.0),
+        thickness: base.wall().thickness * (1.0 + THICKNESS_SD * rng.normal()).max(0.1),
+        wind: base.wind_speed() + rng.range(0.0, DRAUGHT),
+    }
+}
+
+// `base` with the inputs of `draw`, at its start and running to `span`
+fn set_up(base: &Simulation, draw: Draw, span: f64) -> Simulation {
+    let mut sim = base.clone();
+    sim.init_mut().outside_temp = draw.outside;
+    sim.init_mut().system_temp = draw.contents;
+    sim.init_mut().ice = draw.ice;
+    sim.wall_mut().thickness = draw.thickness;
+    sim.set_wind_speed(draw.wind);
+    sim.set_duration(Some(span));
+    sim
+}
+
+fn run_one(base: &Simulation, draw: Draw, span: f64) -> Outcome {
+    let mut sim = set_up(base, draw, span);
+    sim.reset_from_init();
+    sim.toggle_running();
+    let mut ice_gone = (sim.state().mass_ice <= 0.0).then_some(0.0);
+    while sim.is_running() {
+        sim.step(sim.step_size());
+        if ice_gone.is_none() && sim.state().mass_ice <= 0.0 {
+            ice_gone = Some(sim.time_seconds());
+        }
+    }
+    Outcome { ice_gone, hold_hours: sim.hold_time().hours(), final_temp: sim.state().drink_temperature() }
+}
+
+/// The water temperature (°C) of an ensemble at evenly spaced times over its
+/// run: the median and the 10th and 90th percentiles of the runs at each.
+pub struct Band {
+    pub runs: usize,
+    /// s from the first point to the last.
+    pub span: f64,
+    pub median: Vec<f64>,
+    pub low: Vec<f64>,
+    pub high: Vec<f64>,
+}
+
+impl Band {
+    /// Runs `runs` draws around `base` in parallel, on a coarse step like the
+    /// explore panel's, and takes the band of their water temperatures.
+    pub fn of(base: &Simulation, runs: usize) -> Self {
+        let span = base.span();
+        let curves: Vec<Vec<f64>> = (0..runs).into_par_iter().map(|i| water_curve(base, draw(base, i as u64), span)).collect();
+        let mut band = Band { runs, span, median: Vec::new(), low: Vec::new(), high: Vec::new() };
+        for point in 0..=BAND_POINTS {
+            let mut at: Vec<f64> = curves.iter().filter_map(|c| c.get(point).copied()).collect();
+            if at.is_empty() {
+                break;
+            }
+            at.sort_by(f64::total_cmp);
+            let quantile = |q: f64| at[((q * (at.len() - 1) as f64).round() as usize).min(at.len() - 1)];
+            band.median.push(quantile(0.5));
+            band.low.push(quantile(0.1));
+            band.high.push(quantile(0.9));
+        }
+        band
+    }
+
+    /// Worked out on a thread of its own, so the window keeps drawing meanwhile.
+    pub fn spawn(base: &Simulation, runs: usize) -> JoinHandle<Band> {
+        let base = base.clone();
+        std::thread::spawn(move || Band::of(&base, runs))
+    }
+
+    /// Writes the band to `path` as CSV.
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(std::fs::File::create(path)?);
+        self.write_csv(&mut out)?;
+        out.flush()
+    }
+
+    /// Writes the band as CSV: the time and the three temperatures, a row per point.
+    pub fn write_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "time_s,median_c,p10_c,p90_c")?;
+        let every = self.span / BAND_POINTS as f64;
+        for (i, ((median, low), high)) in self.median.iter().zip(&self.low).zip(&self.high).enumerate() {
+            writeln!(out, "{:.1},{median:.4},{low:.4},{high:.4}", i as f64 * every)?;
+        }
+        Ok(())
+    }
+}
+
+// The water temperature of one run of the band at its points
+fn water_curve(base: &Simulation, draw: Draw, span: f64) -> Vec<f64> {
+    let mut sim = set_up(base, draw, span);
+    // never finer than the run's own step; a span too short for that to be a rate the model takes keeps the run's own
+    let _ = sim.set_tick_rate(1.0 / (span / BAND_STEPS).min(BAND_MAX_STEP).max(base.step_size()));
+    sim.reset_from_init();
+    sim.toggle_running();
+    let every = span / BAND_POINTS as f64;
+    let mut curve = Vec::with_capacity(BAND_POINTS + 1);
+    curve.push(sim.state().temp_water);
+    while sim.is_running() {
+        sim.step(sim.step_size());
+        if sim.time_seconds() >= curve.len() as f64 * every - 1e-9 {
+            curve.push(sim.state().temp_water);
+        }
+    }
+    curve
+}
+
+/// Mean, standard deviation and the central 95 % of a sample.
+struct Spread {
+    mean: f64,
+    sd: f64,
+    low: f64,
+    high: f64,
+}
+
+impl Spread {
+    fn of(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let sd = if values.len() > 1 { (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt() } else { 0.0 };
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let at = |q: f64| sorted[((q * (n - 1.0)).round() as usize).min(sorted.len() - 1)];
+        Some(Self { mean, sd, low: at(0.025), high: at(0.975) })
+    }
+
+    // "**3.1 ± 0.4 h** (95 % of runs 2.7–3.5 h)"
+    fn describe(&self, decimals: usize, unit: &str) -> String {
+        format!(
+            "**{:.decimals$} ± {:.decimals$} {unit}** (95 % of runs {:.decimals$}–{:.decimals$} {unit})",
+            self.mean,
+            Z95 * self.sd,
+            self.low,
+            self.high
+        )
+    }
+}
+
+fn write_report<W: Write>(out: &mut W, base: &Simulation, outcomes: &[Outcome], span: f64) -> io::Result<()> {
+    let experiment = base.experiment();
+    let title = if experiment.name.is_empty() { "Uncertainty" } else { &experiment.name };
+    writeln!(out, "# {title}")?;
+    writeln!(out)?;
+    if !experiment.notes.is_empty() {
+        writeln!(out, "{}", experiment.notes)?;
+        writeln!(out)?;
+    }
+    let runs = outcomes.len();
+    writeln!(out, "The run as set up was repeated {runs} times for up to {:.1} h, each time with its inputs drawn from the distributions below.", span / 3600.0)?;
+    writeln!(out)?;
+
+    writeln!(out, "## Results")?;
+    writeln!(out)?;
+    let melted: Vec<f64> = outcomes.iter().filter_map(|o| o.ice_gone).map(|t| t / 3600.0).collect();
+    match Spread::of(&melted) {
+        // a bottle without ice has no melt time to give
+        _ if base.init().ice <= 0.0 => {}
+        None => writeln!(out, "- The ice outlasted the run every time.")?,
+        Some(spread) => {
+            writeln!(out, "- Ice lasts {}.", spread.describe(1, "h"))?;
+            if melted.len() < runs {
+                writeln!(out, "  In {} of the {runs} runs it outlasted the run, and those are left out of that.", runs - melted.len())?;
+            }
+        }
+    }
+    let threshold = base.hold_time().threshold;
+    let hold: Vec<f64> = outcomes.iter().map(|o| o.hold_hours).collect();
+    if let Some(spread) = Spread::of(&hold) {
+        writeln!(out, "- Below {threshold:.0} °C for {}.", spread.describe(1, "h"))?;
+    }
+    let last: Vec<f64> = outcomes.iter().map(|o| o.final_temp).collect();
+    if let Some(spread) = Spread::of(&last) {
+        writeln!(out, "- The drink is at {} at the end.", spread.describe(1, "°C"))?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "## Assumptions")?;
+    writeln!(out)?;
+    let init = base.init();
+    writeln!(out, "| Input | As set | Drawn from |")?;
+    writeln!(out, "|---|---|---|")?;
+    writeln!(out, "| Outside temperature | {:.1} °C | normal, σ = {OUTSIDE_SD} K: a room thermometer and the day's drift |", init.outside_temp)?;
+    writeln!(out, "| Contents temperature | {:.1} °C | normal, σ = {CONTENTS_SD} K: a kitchen thermometer in the drink |", init.system_temp)?;
+    writeln!(out, "| Ice | {:.3} kg | normal, σ = {:.0} % of the mass: a kitchen scale and what melts while weighing |", init.ice, ICE_SD * 100.0)?;
+    writeln!(out, "| Wall thickness | {:.2} mm | normal, σ = {:.0} %: from bottle to bottle |", base.wall().thickness * 1000.0, THICKNESS_SD * 100.0)?;
+    writeln!(out, "| Wind | {:.1} m/s | plus a draught even between 0 and {DRAUGHT} m/s: no room is quite still |", base.wind_speed())?;
+    writeln!(out)?;
+    writeln!(out, "The inputs are drawn independently of each other, from a fixed seed, so the same setup gives the same report.")?;
+
+    writeln!(out)?;
+    writeln!(out, "## Caveats")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "- The ± is {Z95} standard deviations of the runs, the spread these inputs give if they are normal. The range after it is the central \
+         95 % of the runs themselves, which says the same without that assumption; the two differ where a result is skewed."
+    )?;
+    writeln!(
+        out,
+        "- The spreads are assumed, not fitted to measurements of this bottle. Where an input is known better or worse than that, the \
+         interval narrows or widens with it."
+    )?;
+    writeln!(
+        out,
+        "- The interval only covers those inputs. The model's own simplifications (lumped or layered water, textbook film coefficients) \
+         are not in it: a measurement can fall outside it and still be right."
+    )?;
+    if let Some(spread) = Spread::of(&melted).filter(|_| base.init().ice > 0.0) {
+        writeln!(
+            out,
+            "- With {} runs the mean melt time is itself good to about ±{:.2} h; more runs narrow that, not the spread.",
+            melted.len(),
+            Z95 * spread.sd / (melted.len() as f64).sqrt()
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_band_is_ordered_at_every_point_and_written_a_row_each() {
+        let mut base = Simulation::new();
+        base.set_duration(Some(3600.0));
+        let band = Band::of(&base, 8);
+        assert_eq!(band.median.len(), BAND_POINTS + 1);
+        for i in 0..band.median.len() {
+            assert!(band.low[i] <= band.median[i] && band.median[i] <= band.high[i], "point {i}");
+        }
+        assert!(band.low.last() < band.high.last(), "the draws don't spread the runs");
+
+        let mut csv = Vec::new();
+        band.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().next(), Some("time_s,median_c,p10_c,p90_c"));
+        assert_eq!(csv.lines().count(), BAND_POINTS + 2);
+        assert!(csv.lines().last().unwrap().starts_with("3600.0,"));
+    }
+}
@@ -0,0 +1,188 @@
+// Undo/redo of the settings edited in the app: the initial contents, the
+// bottle and its surroundings, and the heater. The settings are compared
+// after each frame's input; a change is one step of history, and a run of
+// changes to the same field, as while a value is dragged, counts as one.
+
+use icebottle_sim::{AmbientProfile, ControlMode, Geometry, InitialConditions, InsulationPreset, Liquid, SimDateTime, Simulation, WallConfig};
+
+// Steps kept; the oldest is dropped past this
+const DEPTH: usize = 100;
+// s of real time between changes to a field that still count as one edit
+const MERGE_GAP: f64 = 1.0;
+
+/// What the edit panel changes, plus the panel's own choices that go with it.
+#[derive(Clone, PartialEq)]
+struct Settings {
+    init: InitialConditions,
+    liquid: Liquid,
+    wall: WallConfig,
+    insulation: InsulationPreset,
+    geometry: Geometry,
+    start: Option<SimDateTime>,
+    irradiance: f64,
+    shaded: bool,
+    wind: f64,
+    humidity: f64,
+    ambient: AmbientProfile,
+    ambient_choice: Option<usize>,
+    supercooling: bool,
+    dissolved_air: f64,
+    pressure_melting: bool,
+    conduction_only: bool,
+    capped: bool,
+    water_layers: usize,
+    field2d: bool,
+    ice_pieces: usize,
+    duration: Option<f64>,
+    heater: f64,
+    control: ControlMode,
+    setpoint: f64,
+    kp: f64,
+    ki: f64,
+    kd: f64,
+}
+
+impl Settings {
+    fn capture(sim: &Simulation, insulation: InsulationPreset, ambient_choice: Option<usize>) -> Self {
+        let control = sim.controller();
+        Self {
+            init: *sim.init(),
+            liquid: sim.liquid(),
+            wall: sim.wall().clone(),
+            insulation,
+            geometry: sim.geometry(),
+            start: sim.start_datetime(),
+            irradiance: sim.solar().irradiance,
+            shaded: sim.solar().shaded,
+            wind: sim.wind_speed(),
+            humidity: sim.humidity(),
+            ambient: sim.ambient().clone(),
+            ambient_choice,
+            supercooling: sim.nucleation().enabled,
+            dissolved_air: sim.nucleation().dissolved_air,
+            pressure_melting: sim.pressure_melting(),
+            conduction_only: sim.conduction_only(),
+            capped: sim.is_capped(),
+            water_layers: sim.water_layers().len(),
+            field2d: sim.field().is_some(),
+            ice_pieces: sim.ice_pieces(),
+            duration: sim.duration(),
+            heater: sim.heater_power(),
+            control: control.mode,
+            setpoint: control.setpoint,
+            kp: control.kp,
+            ki: control.ki,
+            kd: control.kd,
+        }
+    }
+
+    // The setters that rebuild part of the state only run when their setting changed
+    fn restore(&self, sim: &mut Simulation, insulation: &mut InsulationPreset, ambient_choice: &mut Option<usize>) {
+        *sim.init_mut() = self.init;
+        sim.set_liquid(self.liquid);
+        *sim.wall_mut() = self.wall.clone();
+        *insulation = self.insulation;
+        if sim.geometry() != self.geometry {
+            sim.set_geometry(self.geometry);
+        }
+        sim.set_start_datetime(self.start);
+        sim.solar_mut().irradiance = self.irradiance;
+        sim.solar_mut().shaded = self.shaded;
+        sim.set_wind_speed(self.wind);
+        sim.set_humidity(self.humidity);
+        sim.set_ambient(self.ambient.clone());
+        *ambient_choice = self.ambient_choice;
+        sim.nucleation_mut().enabled = self.supercooling;
+        sim.nucleation_mut().dissolved_air = self.dissolved_air;
+        sim.set_pressure_melting(self.pressure_melting);
+        sim.set_conduction_only(self.conduction_only);
+        sim.set_capped(self.capped);
+        if sim.water_layers().len() != self.water_layers {
+            sim.set_water_layers(self.water_layers);
+        }
+        if sim.field().is_some() != self.field2d {
+            sim.set_field_mode(self.field2d);
+        }
+        if sim.ice_pieces() != self.ice_pieces {
+            sim.set_ice_pieces(self.ice_pieces);
+        }
+        sim.set_duration(self.duration);
+        sim.set_heater_power(self.heater);
+        let control = sim.controller_mut();
+        control.mode = self.control;
+        control.setpoint = self.setpoint;
+        control.kp = self.kp;
+        control.ki = self.ki;
+        control.kd = self.kd;
+    }
+}
+
+pub struct UndoHistory {
+    undo: Vec<Settings>,
+    redo: Vec<Settings>,
+    current: Settings,
+    // field and time of the last change, for merging a drag into one step
+    last_edit: Option<(Option<usize>, f64)>,
+}
+
+impl UndoHistory {
+    pub fn new(sim: &Simulation, insulation: InsulationPreset, ambient_choice: Option<usize>) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            current: Settings::capture(sim, insulation, ambient_choice),
+            last_edit: None,
+        }
+    }
+
+    /// Takes the settings as this frame's input left them, with `field` the
+    /// panel field edited this frame (`None` for a key or button) and `now`
+    /// the real time (s).
+    pub fn track(&mut self, sim: &Simulation, insulation: InsulationPreset, ambient_choice: Option<usize>, field: Option<usize>, now: f64) {
+        let settings = Settings::capture(sim, insulation, ambient_choice);
+        if settings == self.current {
+            return;
+        }
+        let same_edit = field.is_some() && self.last_edit.is_some_and(|(last, at)| last == field && now - at < MERGE_GAP);
+        let previous = std::mem::replace(&mut self.current, settings);
+        if !same_edit {
+            self.undo.push(previous);
+            if self.undo.len() > DEPTH {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.last_edit = Some((field, now));
+    }
+
+    /// Puts the settings back as they were before the last edit. Returns
+    /// false when there is nothing to undo.
+    pub fn undo(&mut self, sim: &mut Simulation, insulation: &mut InsulationPreset, ambient_choice: &mut Option<usize>) -> bool {
+        let Some(settings) = self.undo.pop() else {
+            return false;
+        };
+        settings.restore(sim, insulation, ambient_choice);
+        self.redo.push(std::mem::replace(&mut self.current, settings));
+        self.last_edit = None;
+        true
+    }
+
+    /// Makes the last undone edit again. Returns false when there is none.
+    pub fn redo(&mut self, sim: &mut Simulation, insulation: &mut InsulationPreset, ambient_choice: &mut Option<usize>) -> bool {
+        let Some(settings) = self.redo.pop() else {
+            return false;
+        };
+        settings.restore(sim, insulation, ambient_choice);
+        self.undo.push(std::mem::replace(&mut self.current, settings));
+        self.last_edit = None;
+        true
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+}
@@ -0,0 +1,158 @@
+// Units the app shows temperatures and masses in and reads typed values in.
+// The model, scenario files, exports and the setup flags stay in °C and kg;
+// only what is drawn, printed or typed into the panel is converted.
+
+use icebottle_sim::constants::KELVIN;
+use icebottle_sim::SAMPLE_COLUMNS;
+
+const KG_PER_OUNCE: f64 = 0.028_349_523_125;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TempUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl TempUnit {
+    pub const ALL: [TempUnit; 3] = [TempUnit::Celsius, TempUnit::Fahrenheit, TempUnit::Kelvin];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TempUnit::Celsius => "°C",
+            TempUnit::Fahrenheit => "°F",
+            TempUnit::Kelvin => "K",
+        }
+    }
+
+    /// Name on the command line.
+    pub fn key(self) -> &'static str {
+        match self {
+            TempUnit::Celsius => "C",
+            TempUnit::Fahrenheit => "F",
+            TempUnit::Kelvin => "K",
+        }
+    }
+
+    pub fn of_celsius(self, celsius: f64) -> f64 {
+        match self {
+            TempUnit::Celsius => celsius,
+            TempUnit::Fahrenheit => celsius * 1.8 + 32.0,
+            TempUnit::Kelvin => celsius + KELVIN,
+        }
+    }
+
+    pub fn to_celsius(self, value: f64) -> f64 {
+        match self {
+            TempUnit::Celsius => value,
+            TempUnit::Fahrenheit => (value - 32.0) / 1.8,
+            TempUnit::Kelvin => value - KELVIN,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MassUnit {
+    #[default]
+    Kilogram,
+    Ounce,
+}
+
+impl MassUnit {
+    pub const ALL: [MassUnit; 2] = [MassUnit::Kilogram, MassUnit::Ounce];
+
+    /// Also its name on the command line.
+    pub fn label(self) -> &'static str {
+        match self {
+            MassUnit::Kilogram => "kg",
+            MassUnit::Ounce => "oz",
+        }
+    }
+
+    pub fn of_kg(self, kg: f64) -> f64 {
+        match self {
+            MassUnit::Kilogram => kg,
+            MassUnit::Ounce => kg / KG_PER_OUNCE,
+        }
+    }
+
+    pub fn to_kg(self, value: f64) -> f64 {
+        match self {
+            MassUnit::Kilogram => value,
+            MassUnit::Ounce => value * KG_PER_OUNCE,
+        }
+    }
+
+    /// Unit for small amounts such as a condensate film: grams, or still ounces.
+    pub fn small_label(self) -> &'static str {
+        match self {
+            MassUnit::Kilogram => "g",
+            MassUnit::Ounce => "oz",
+        }
+    }
+
+    pub fn small_of_kg(self, kg: f64) -> f64 {
+        match self {
+            MassUnit::Kilogram => kg * 1000.0,
+            MassUnit::Ounce => kg / KG_PER_OUNCE,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Units {
+    pub temp: TempUnit,
+    pub mass: MassUnit,
+}
+
+impl Units {
+    /// A temperature given in °C, e.g. "38.5 °F".
+    pub fn temp(self, celsius: f64, decimals: usize) -> String {
+        format!("{:.decimals$} {}", self.temp.of_celsius(celsius), self.temp.label())
+    }
+
+    /// A mass given in kg, e.g. "7.05 oz".
+    pub fn mass(self, kg: f64, decimals: usize) -> String {
+        format!("{:.decimals$} {}", self.mass.of_kg(kg), self.mass.label())
+    }
+
+    /// A label naming an SI unit, "Init water (kg)" or "Setpoint (C)", with
+    /// the unit shown instead.
+    pub fn label(self, label: &str) -> String {
+        label
+            .replace("(kg)", &format!("({})", self.mass.label()))
+            .replace("(C)", &format!("({})", self.temp.label()))
+    }
+
+    /// Name of a `SAMPLE_COLUMNS` column, its unit suffix changed to the one shown.
+    pub fn column_name(self, column: usize) -> String {
+        let name = SAMPLE_COLUMNS[column];
+        if let Some(stem) = name.strip_suffix("_kg") {
+            format!("{stem}_{}", self.mass.label())
+        } else if let Some(stem) = name.strip_suffix("_c") {
+            format!("{stem}_{}", self.temp.key().to_lowercase())
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// A value of a `SAMPLE_COLUMNS` column in the unit shown.
+    pub fn column(self, column: usize, value: f64) -> f64 {
+        let name = SAMPLE_COLUMNS[column];
+        if name.ends_with("_kg") {
+            self.mass.of_kg(value)
+        } else if name.ends_with("_c") {
+            self.temp.of_celsius(value)
+        } else {
+            value
+        }
+    }
+}
+
+/// About `step` of a quantity in the unit converted by `convert`, rounded
+/// down to a power of ten, as the step of a slider or drag.
+pub fn step_in(step: f64, convert: impl Fn(f64) -> f64) -> f64 {
+    let converted = (convert(step) - convert(0.0)).abs();
+    10f64.powf(converted.log10().floor())
+}
@@ -0,0 +1,75 @@
+// Clickable screen regions that draw themselves, so the drawn rectangle and
+// the hit test can't drift apart.
+
+use macroquad::prelude::*;
+
+use crate::theme::Palette;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Button {
+    pub rect: Rect,
+    /// A disabled button is drawn greyed out and ignores clicks.
+    pub enabled: bool,
+}
+
+impl Button {
+    pub fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Self {
+            rect: Rect::new(x, y, w, h),
+            enabled: true,
+        }
+    }
+
+    pub fn enabled(self, enabled: bool) -> Self {
+        Self { enabled, ..self }
+    }
+
+    pub fn hovered(&self) -> bool {
+        let (mx, my) = mouse_position();
+        self.rect.contains(vec2(mx, my))
+    }
+
+    /// Left button pressed over the button this frame.
+    pub fn clicked(&self) -> bool {
+        self.enabled && is_mouse_button_pressed(MouseButton::Left) && self.hovered()
+    }
+
+    /// Held down under the mouse.
+    pub fn pressed(&self) -> bool {
+        self.enabled && is_mouse_button_down(MouseButton::Left) && self.hovered()
+    }
+
+    /// Filled rectangle with the label centred in it: lighter under the mouse,
+    /// darker and nudged down while held, grey when disabled. The text and
+    /// outline come from `palette`.
+    pub fn draw(&self, label: &str, fill: Color, font_size: f32, palette: &Palette) {
+        let mut r = self.rect;
+        let (fill, text) = if !self.enabled {
+            let grey = (fill.r + fill.g + fill.b) / 3.0 * 0.6;
+            (Color::new(grey, grey, grey, fill.a), palette.button_disabled_text)
+        } else if self.pressed() {
+            r.y += 1.0;
+            (shade(fill, 0.7), palette.button_text)
+        } else if self.hovered() {
+            (shade(fill, 1.3), palette.button_text)
+        } else {
+            (fill, palette.button_text)
+        };
+        draw_rectangle(r.x, r.y, r.w, r.h, fill);
+        if self.enabled && self.hovered() {
+            draw_rectangle_lines(r.x, r.y, r.w, r.h, 1.0, palette.button_hover);
+        }
+        let size = measure_text(label, None, font_size as u16, 1.0);
+        draw_text(label, r.x + (r.w - size.width) / 2.0, r.y + (r.h + size.offset_y) / 2.0, font_size, text);
+    }
+
+    /// Outline of the keyboard-focused button, in `color`.
+    pub fn draw_focus(&self, color: Color) {
+        let r = self.rect;
+        draw_rectangle_lines(r.x - 2.0, r.y - 2.0, r.w + 4.0, r.h + 4.0, 2.0, color);
+    }
+}
+
+fn shade(c: Color, factor: f32) -> Color {
+    Color::new((c.r * factor).min(1.0), (c.g * factor).min(1.0), (c.b * factor).min(1.0), c.a)
+}